@@ -0,0 +1,82 @@
+//! Insert and query throughput against the in-memory backend.
+//!
+//! Run with `cargo bench`; Criterion keeps the previous results and reports
+//! any regression against them.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::parser::{Column, SelectItem, WhereClause};
+use rust_dbms::storage::MEMORY_PATH;
+
+const ROW_COUNTS: [usize; 3] = [100, 500, 1_000];
+
+fn create_users() -> QueryEngine {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    engine
+        .execute_create_table(
+            "users".to_string(),
+            vec![
+                Column::new("id", "INTEGER"),
+                Column::new("name", "TEXT"),
+            ],
+        )
+        .unwrap();
+    engine
+}
+
+fn insert_users(engine: &mut QueryEngine, rows: usize) {
+    for i in 0..rows {
+        engine
+            .execute_insert("users".to_string(), vec![Some(i.to_string()), Some(format!("user {}", i))])
+            .unwrap();
+    }
+}
+
+fn populated(rows: usize) -> QueryEngine {
+    let mut engine = create_users();
+    insert_users(&mut engine, rows);
+    engine
+}
+
+fn id_equals(id: usize) -> WhereClause {
+    WhereClause { column: "id".to_string(), operator: "=".to_string(), value: Some(id.to_string()), quoted: false }
+}
+
+fn bench_insert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("insert");
+    group.sample_size(10);
+    for rows in ROW_COUNTS {
+        group.bench_with_input(BenchmarkId::from_parameter(rows), &rows, |b, &rows| {
+            b.iter_batched(create_users, |mut engine| insert_users(&mut engine, rows), BatchSize::LargeInput);
+        });
+    }
+    group.finish();
+}
+
+fn bench_scan(c: &mut Criterion) {
+    let mut group = c.benchmark_group("full_scan");
+    for rows in ROW_COUNTS {
+        let engine = populated(rows);
+        group.bench_with_input(BenchmarkId::from_parameter(rows), &rows, |b, _| {
+            b.iter(|| engine.execute_select("users".to_string(), vec![SelectItem::Wildcard], None).unwrap());
+        });
+    }
+    group.finish();
+}
+
+fn bench_point_lookup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("point_lookup");
+    for rows in ROW_COUNTS {
+        let engine = populated(rows);
+        group.bench_with_input(BenchmarkId::from_parameter(rows), &rows, |b, &rows| {
+            b.iter(|| {
+                let clause = id_equals(black_box(rows / 2));
+                engine.execute_select("users".to_string(), vec![SelectItem::Wildcard], Some(clause.into())).unwrap()
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_insert, bench_scan, bench_point_lookup);
+criterion_main!(benches);
@@ -0,0 +1,63 @@
+// src/audit.rs
+
+use crate::error::IsentaError;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// An append-only record of the statements run against a database, kept in
+/// `<database>.audit` next to the database file. Unlike the WAL it isn't
+/// used for recovery; it only says who-ran-what for later review.
+///
+/// Each statement is one line of tab-separated fields:
+/// `<sequence>\t<unix time>\t<database path>\t<ok|error>\t<statement>`.
+/// Sequence numbers continue from the last entry when the log is reopened.
+/// Tabs, newlines and backslashes in the path and statement are escaped.
+pub struct AuditLog {
+    file: File,
+    database_path: String,
+    sequence: u64,
+}
+
+impl AuditLog {
+    /// Opens the audit log of the database at `database_path`, creating it
+    /// if it doesn't exist.
+    pub fn open(database_path: &str) -> Result<Self, IsentaError> {
+        let path = audit_path(database_path);
+        let open_error = |e: std::io::Error| IsentaError::Io(format!("Failed to open audit log '{}': {}", path, e));
+        let file = OpenOptions::new().create(true).append(true).read(true).open(&path).map_err(open_error)?;
+
+        let mut sequence = 0;
+        for line in BufReader::new(&file).lines() {
+            let line = line.map_err(open_error)?;
+            if let Some(Ok(number)) = line.split('\t').next().map(str::parse::<u64>) {
+                sequence = sequence.max(number);
+            }
+        }
+        Ok(AuditLog { file, database_path: database_path.to_string(), sequence })
+    }
+
+    /// Appends an entry for `statement`, returning its sequence number.
+    pub fn record(&mut self, statement: &str, succeeded: bool) -> Result<u64, IsentaError> {
+        let time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let entry = format!(
+            "{}\t{}.{:03}\t{}\t{}\t{}\n",
+            self.sequence + 1,
+            time.as_secs(),
+            time.subsec_millis(),
+            crate::escape_value(&self.database_path),
+            if succeeded { "ok" } else { "error" },
+            crate::escape_value(statement)
+        );
+        self.file
+            .write_all(entry.as_bytes())
+            .map_err(|e| IsentaError::Io(format!("Failed to write audit log: {}", e)))?;
+        self.sequence += 1;
+        Ok(self.sequence)
+    }
+}
+
+/// Where the audit log of the database at `database_path` is kept.
+pub fn audit_path(database_path: &str) -> String {
+    format!("{}.audit", database_path)
+}
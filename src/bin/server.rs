@@ -7,7 +7,7 @@ use std::thread;
 // The server uses the same core logic from the library crate.
 use rust_dbms::{
     parser::Parser,
-    engine::QueryEngine,
+    engine::{ExportAccess, QueryEngine},
     execute_line,
 };
 
@@ -19,11 +19,22 @@ use rust_dbms::{
 ///
 /// # Arguments
 /// * `stream` - A mutable `TcpStream` representing the client connection.
-fn handle_client(mut stream: TcpStream) {
+/// * `export_access` - Where the client's `EXPORT` statements may write.
+fn handle_client(mut stream: TcpStream, export_access: ExportAccess) {
     // Each connection gets its own isolated database engine and parser.
     // This is crucial for preventing data races and ensuring session state
     // is not shared between concurrent users.
-    let mut query_engine = QueryEngine::new();
+    let mut query_engine = match QueryEngine::try_new() {
+        Ok(engine) => engine,
+        Err(e) => {
+            // Tell the client why instead of dropping the connection on a panic.
+            eprintln!("Failed to open database: {}", e);
+            let _ = stream.write_all(format!("Error: {}", e).as_bytes());
+            return;
+        }
+    };
+    // Clients must not be able to write files anywhere the server can
+    query_engine.set_export_access(export_access);
     let parser = Parser::new();
 
     let mut buffer = [0; 4096]; // Increased buffer size for potentially larger queries
@@ -56,11 +67,27 @@ fn handle_client(mut stream: TcpStream) {
             eprintln!("An error occurred reading from connection: {}", e);
         }
     }
+
+    // Flush the session's changes before the connection is reported as done.
+    if let Err(e) = query_engine.close() {
+        eprintln!("Failed to close database: {}", e);
+    }
     // The `stream` goes out of scope here, and the connection is automatically closed.
     // This is what signals EOF to the client's `read_to_end()` call.
 }
 
 fn main() {
+    // EXPORT is refused unless `--export-dir <dir>` names a directory for it
+    let mut args = std::env::args().skip(1);
+    let export_access = match (args.next().as_deref(), args.next()) {
+        (None, _) => ExportAccess::Disabled,
+        (Some("--export-dir"), Some(dir)) => ExportAccess::Within(dir.into()),
+        _ => {
+            eprintln!("Usage: isenta_db_server [--export-dir <dir>]");
+            std::process::exit(2);
+        }
+    };
+
     let address = "127.0.0.1:5555";
     let listener = TcpListener::bind(address).expect("Failed to bind to address");
     println!("Server listening on {}", address);
@@ -71,8 +98,9 @@ fn main() {
         match stream {
             Ok(stream) => {
                 println!("New connection: {}", stream.peer_addr().unwrap());
+                let export_access = export_access.clone();
                 thread::spawn(move || {
-                    handle_client(stream);
+                    handle_client(stream, export_access);
                 });
             }
             Err(e) => {
@@ -1,32 +1,291 @@
-use crate::engine::{Catalog, Row, Table};
+use crate::engine::{blob_bytes, blob_literal, is_blob_type, is_integer_type, names_match, Catalog, Row, Table};
+use crate::error::IsentaError;
+use crate::lock::TableLocks;
+use crate::log::{self, Logger};
 use crate::parser::Column;
-use crate::storage::{Page, StorageEngine};
+use crate::storage::{Durability, Page, StorageEngine, PAGE_SIZE};
+use std::collections::{BTreeSet, HashSet, VecDeque};
+use std::time::Duration;
 
 // Database file format constants
 const MAGIC_NUMBER: u64 = 0x4953454E54414442; // "ISENTADB" in hex
-const DB_VERSION: u32 = 1;
+/// The file format version new databases are written in; files with an
+/// older version keep their format, except that those from before rowids
+/// are brought up to `ROWID_VERSION` when first written.
+pub const DB_VERSION: u32 = 10;
 const HEADER_PAGE_ID: u64 = 0;
 
+// First format version whose data pages carry a string dictionary
+const DICTIONARY_VERSION: u32 = 2;
+// First format version that prefixes each row with its encoded length
+const ROW_LENGTH_VERSION: u32 = 3;
+// First format version whose schema pages store created/updated timestamps
+const TIMESTAMP_VERSION: u32 = 4;
+// First format version that stores a rowid with each row
+const ROWID_VERSION: u32 = 5;
+// First format version that tags every page with its type
+const PAGE_TYPE_VERSION: u32 = 6;
+// First format version whose schema pages count the writes to each table
+const CHANGE_COUNT_VERSION: u32 = 7;
+// First format version that stores NOT NULL and DEFAULT with each column
+const COLUMN_CONSTRAINTS_VERSION: u32 = 8;
+// First format version that keeps a list of free pages, referenced from the header
+const FREE_LIST_VERSION: u32 = 9;
+// First format version that stores BLOB values as raw bytes
+const BLOB_VERSION: u32 = 10;
+
+// Column constraint flags (version 8+)
+const COLUMN_NOT_NULL: u8 = 1;
+const COLUMN_HAS_DEFAULT: u8 = 2;
+
+// The page type tag is the last byte of every page, so it sits at the same
+// offset whatever the page holds
+const PAGE_TYPE_OFFSET: usize = PAGE_SIZE - 1;
+
+// Longest table name, in bytes, a schema page may hold
+const MAX_TABLE_NAME_LEN: usize = 255;
+
+// Page fill heuristics: once a row doesn't fit into a data page, later rows
+// are still tried until the page is MIN_PAGE_FILL percent full or
+// MAX_FILL_ATTEMPTS rows have failed to fit
+const MIN_PAGE_FILL: usize = 90;
+const MAX_FILL_ATTEMPTS: usize = 64;
+
 // Value type tags for binary encoding
 const TYPE_NULL: u8 = 0;
 const TYPE_INT: u8 = 1;
 const TYPE_TEXT: u8 = 2;
+const TYPE_TEXT_REF: u8 = 3;
+const TYPE_BLOB: u8 = 4;
 
 // Header page layout (Page 0):
 // Offset 0-7:   Magic number (u64)
 // Offset 8-11:  Version (u32)
 // Offset 12-19: Schema root page ID (u64)
 // Offset 20-23: Number of tables (u32)
+// Offset 24-31: First free-list page ID (u64, 0 = none, version 9+ only)
 // Rest: Reserved
 
+// Every page (version 6+ only):
+// Last byte: Page type tag, see `PageType`
+
+// Schema page layout (one page per table):
+// Table name: length (u32) + UTF-8 bytes
+// Number of columns (u32), then per column: name length (u32) + name, type length (u32) + type,
+//   constraint flags (u8, version 8+ only) and, with a default, its length (u32) + value
+// First data page ID (u64)
+// Next schema page ID (u64, 0 = end of chain)
+// Created at, updated at: Unix epoch seconds (u64 each, version 4+ only)
+// Next rowid (u64, version 5+ only)
+// Change count: writes to the table so far (u64, version 7+ only)
+
+// Data page layout:
+// Offset 0-3:   Number of rows (u32)
+// Offset 4-7:   Number of dictionary entries (u32, version 2+ only)
+// Dictionary entries: length (u32) + UTF-8 bytes each
+// Rows: row length (u16, version 3+ only), rowid (u64, version 5+ only),
+//       then one tagged value per column
+// Last 8 bytes after the rows: Next data page ID (u64, 0 = end of chain)
+
+// Free page (version 6+ only): zeroed apart from its type tag; preallocated
+// for a table or left over when a table's data shrank
+
+// Free-list page layout (version 9+ only):
+// Offset 0-7:   Next free-list page ID (u64, 0 = end of chain)
+// Offset 8-11:  Number of page IDs in this page (u32)
+// Offset 12-:   Free page IDs (u64 each)
+const FREE_LIST_IDS_OFFSET: usize = 12;
+
+/// What a page holds, recorded in its type tag so that a pointer to the
+/// wrong kind of page is caught instead of being decoded as garbage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PageType {
+    Header = 1,
+    Schema = 2,
+    Data = 3,
+    Free = 4,
+    FreeList = 5,
+}
+
+impl PageType {
+    fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            1 => Some(PageType::Header),
+            2 => Some(PageType::Schema),
+            3 => Some(PageType::Data),
+            4 => Some(PageType::Free),
+            5 => Some(PageType::FreeList),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            PageType::Header => "header",
+            PageType::Schema => "schema",
+            PageType::Data => "data",
+            PageType::Free => "free",
+            PageType::FreeList => "free list",
+        }
+    }
+}
+
+/// What a database file's header says about it, see `Database::file_info`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileInfo {
+    pub magic: u64,
+    pub version: u32,
+    pub table_count: u32,
+    pub page_count: u64,
+}
+
+/// A problem with the file found while loading the catalog, which was
+/// worked around rather than failing the load. Each is also sent to the
+/// logger; see `Database::take_warnings`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadWarning {
+    /// The free-page list breaks off at a page of another type, given by the
+    /// message; the free pages it goes on to list aren't reused
+    FreeListUnreadable(String),
+    /// The schema chain leads back to a page it has already passed
+    CircularSchemaChain { page_id: u64 },
+    /// A page of the schema chain isn't tagged as a schema page
+    WrongPageType(String),
+    /// A page of the schema chain couldn't be read as a table; the tables
+    /// after it aren't loaded
+    InvalidTablePage { page_id: u64 },
+    /// Rows of a table that couldn't be decoded were left out
+    SkippedRows { table: String, count: usize },
+    /// Text values of a table that couldn't be decoded were replaced with
+    /// `UNREADABLE_TEXT`, keeping the rows they are in
+    UnreadableText { table: String, count: usize },
+    /// A table's chain of data pages breaks off at a page of another type;
+    /// the rows after it aren't loaded
+    DataChainBroken(String),
+    /// The header's table count didn't match the tables found, and was
+    /// corrected to the number loaded
+    TableCountRepaired { expected: u32, loaded: u32 },
+}
+
+impl std::fmt::Display for LoadWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadWarning::FreeListUnreadable(message) => write!(f, "{}; the free pages it lists won't be reused", message),
+            LoadWarning::CircularSchemaChain { page_id } => {
+                write!(f, "Circular reference detected in schema chain at page {}", page_id)
+            }
+            LoadWarning::WrongPageType(message) => write!(f, "{}", message),
+            LoadWarning::InvalidTablePage { page_id } => write!(f, "Invalid table page at {}", page_id),
+            LoadWarning::SkippedRows { table, count } => write!(f, "Skipped {} unreadable rows in table '{}'", count, table),
+            LoadWarning::UnreadableText { table, count } => {
+                write!(f, "Replaced {} unreadable text values in table '{}' with '{}'", count, table, UNREADABLE_TEXT)
+            }
+            LoadWarning::DataChainBroken(message) => write!(f, "{}; later rows of the table are unreachable", message),
+            LoadWarning::TableCountRepaired { expected, loaded } => {
+                write!(f, "Expected {} tables but only loaded {}. Repairing database...", expected, loaded)
+            }
+        }
+    }
+}
+
+/// Stands in for a stored text value that couldn't be decoded when its
+/// table was loaded; see `LoadWarning::UnreadableText`.
+pub const UNREADABLE_TEXT: &str = "\u{FFFD}";
+
+/// What couldn't be read back of a table's rows; see
+/// `Database::load_rows_from_pages`.
+#[derive(Debug, Clone, Copy, Default)]
+struct RowDamage {
+    /// Rows that couldn't be decoded and were left out
+    skipped_rows: usize,
+    /// Text values that were replaced with `UNREADABLE_TEXT`
+    unreadable_values: usize,
+}
+
+/// How a table's data pages are used; see `Database::table_stats`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableStats {
+    pub schema_page: u64,
+    /// The pages of the table's data chain, the first included even when
+    /// the table is empty
+    pub data_pages: u64,
+    pub rows: u64,
+    /// Bytes the data pages hold: row counts, dictionaries, rows and links
+    pub used_bytes: u64,
+    /// Bytes the data pages could hold
+    pub capacity_bytes: u64,
+}
+
+impl TableStats {
+    /// The bytes per row, including each row's share of its page's count,
+    /// dictionary and link; 0 for an empty table.
+    pub fn average_row_size(&self) -> u64 {
+        self.used_bytes.checked_div(self.rows).unwrap_or(0)
+    }
+
+    /// Capacity of the data pages that holds no data, such as the space
+    /// left by deleted rows or at the end of each page.
+    pub fn wasted_bytes(&self) -> u64 {
+        self.capacity_bytes.saturating_sub(self.used_bytes)
+    }
+}
+
+/// The contents of one data page, as read by `Database::decode_data_page`.
+struct DataPage {
+    rows: Vec<Row>,
+    /// How many rows couldn't be decoded
+    skipped: usize,
+    /// How many text values were replaced with `UNREADABLE_TEXT`
+    unreadable: usize,
+    /// The next page of the chain, `None` when the chain ends here or can't
+    /// be followed past this page
+    next: Option<u64>,
+    /// The bytes up to the end of the link to the next page, or of what could
+    /// be read
+    used: usize,
+}
+
 pub struct Database {
     storage: StorageEngine,
+    version: u32,
+    data_page_preallocation: u64,
+    /// Pages that are no longer used and are handed out again before the
+    /// file grows; persisted from `FREE_LIST_VERSION` on
+    free_pages: BTreeSet<u64>,
+    /// The free pages as they were when the open transaction began
+    transaction_free_pages: Option<BTreeSet<u64>>,
+    /// The format version when the open transaction began, which an
+    /// upgrade inside it may have changed
+    transaction_version: u32,
+    /// The tables the open transaction has written, and those of other
+    /// connections to the same file
+    locks: TableLocks,
+    logger: Logger,
+    /// Problems worked around since the catalog was last loaded
+    warnings: Vec<LoadWarning>,
 }
 
 impl Database {
-    pub fn new(path: &str) -> Result<Self, String> {
-        let storage = StorageEngine::new(path);
-        let mut db = Database { storage };
+    pub fn new(path: &str) -> Result<Self, IsentaError> {
+        Self::with_logger(path, log::stderr())
+    }
+
+    /// Opens the database like `new`, sending warnings to `logger` from the
+    /// start, including those about a damaged header.
+    pub fn with_logger(path: &str, logger: Logger) -> Result<Self, IsentaError> {
+        let mut storage = StorageEngine::new(path)?;
+        storage.set_logger(logger.clone());
+        let mut db = Database {
+            storage,
+            version: DB_VERSION,
+            data_page_preallocation: 1,
+            free_pages: BTreeSet::new(),
+            transaction_free_pages: None,
+            transaction_version: DB_VERSION,
+            locks: TableLocks::new(path),
+            logger,
+            warnings: Vec::new(),
+        };
 
         // Initialize database if it's new
         db.initialize_if_needed()?;
@@ -34,11 +293,183 @@ impl Database {
         Ok(db)
     }
 
-    fn initialize_if_needed(&mut self) -> Result<(), String> {
+    /// Sets when written pages reach the database file; see `Durability`.
+    pub fn set_durability(&mut self, durability: Durability) -> Result<(), IsentaError> {
+        self.storage.set_durability(durability)
+    }
+
+    /// Sets the write-ahead log size, in pages, that triggers a checkpoint;
+    /// see `StorageEngine::set_wal_autocheckpoint`.
+    pub fn set_wal_autocheckpoint(&mut self, pages: Option<u64>) {
+        self.storage.set_wal_autocheckpoint(pages);
+    }
+
+    pub fn wal_autocheckpoint(&self) -> Option<u64> {
+        self.storage.wal_autocheckpoint()
+    }
+
+    /// Sets the time between write-ahead log checkpoints; see
+    /// `StorageEngine::set_wal_checkpoint_interval`.
+    pub fn set_wal_checkpoint_interval(&mut self, interval: Option<Duration>) {
+        self.storage.set_wal_checkpoint_interval(interval);
+    }
+
+    pub fn wal_checkpoint_interval(&self) -> Option<Duration> {
+        self.storage.wal_checkpoint_interval()
+    }
+
+    /// Writes any pages held back by deferred durability to the file.
+    pub fn flush(&mut self) -> Result<(), IsentaError> {
+        self.storage.flush()
+    }
+
+    /// Holds every page written from now on in memory until the transaction
+    /// is committed or rolled back; see `StorageEngine::begin_transaction`.
+    pub fn begin_transaction(&mut self) -> Result<(), IsentaError> {
+        self.storage.begin_transaction()?;
+        self.transaction_free_pages = Some(self.free_pages.clone());
+        self.transaction_version = self.version;
+        Ok(())
+    }
+
+    /// Keeps the changes made since `begin_transaction`, writing them to the
+    /// file unless durability is deferred.
+    pub fn commit_transaction(&mut self) -> Result<(), IsentaError> {
+        self.transaction_free_pages = None;
+        let result = self.storage.commit_transaction();
+        self.locks.release();
+        result
+    }
+
+    /// Discards every page written since `begin_transaction` and reads the
+    /// tables back as they were.
+    pub fn rollback_transaction(&mut self) -> Result<Catalog, IsentaError> {
+        self.storage.rollback_transaction();
+        self.locks.release();
+        self.version = self.transaction_version;
+        let catalog = self.load_catalog()?;
+        // Files without a persisted free list only know their free pages here
+        if let Some(free_pages) = self.transaction_free_pages.take() {
+            self.free_pages = free_pages;
+        }
+        Ok(catalog)
+    }
+
+    /// Fails with `IsentaError::TableLocked` while another connection's open
+    /// transaction has written `table`. Inside a transaction, the table is
+    /// locked until the transaction ends. Every write to a table checks this
+    /// first.
+    pub fn lock_table(&mut self, table: &str) -> Result<(), IsentaError> {
+        self.locks.check(table, self.storage.in_transaction())
+    }
+
+    /// Sets where warnings about damaged or repaired data are sent.
+    pub fn set_logger(&mut self, logger: Logger) {
+        self.storage.set_logger(logger.clone());
+        self.logger = logger;
+    }
+
+    fn warn(&self, message: &str) {
+        (self.logger)(message);
+    }
+
+    /// Logs a problem found while loading and keeps it for `take_warnings`.
+    fn report(&mut self, warning: LoadWarning) {
+        self.warn(&warning.to_string());
+        self.warnings.push(warning);
+    }
+
+    /// Returns the problems that the latest `load_catalog`, and any reads of
+    /// table data since, found and worked around, such as a schema chain that
+    /// was cut short or rows that couldn't be decoded. Each is returned once.
+    pub fn take_warnings(&mut self) -> Vec<LoadWarning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Sets how many contiguous data pages a new table gets when it is
+    /// created (at least 1). Pages beyond the first are tagged free and filled
+    /// as the table grows, so large sequential inserts stay contiguous instead
+    /// of interleaving with other tables' pages. Files from before
+    /// `PAGE_TYPE_VERSION` can't mark pages free and only get the first page.
+    pub fn set_data_page_preallocation(&mut self, pages: u64) {
+        self.data_page_preallocation = pages.max(1);
+    }
+
+    /// How many data pages a new table gets when it is created.
+    pub fn data_page_preallocation(&self) -> u64 {
+        self.data_page_preallocation
+    }
+
+    /// Reads the header fields and size of the database file.
+    pub fn file_info(&mut self) -> Result<FileInfo, IsentaError> {
+        let header = self.storage.read_page(HEADER_PAGE_ID)?;
+        Ok(FileInfo {
+            magic: read_u64(&header.data, 0),
+            version: u32::from_le_bytes(header.data[8..12].try_into().unwrap()),
+            table_count: u32::from_le_bytes(header.data[20..24].try_into().unwrap()),
+            page_count: self.storage.file_len()? / PAGE_SIZE as u64,
+        })
+    }
+
+    /// Where page contents must end: before the type tag on tagged files.
+    fn page_body_end(&self) -> usize {
+        if self.version >= PAGE_TYPE_VERSION {
+            PAGE_TYPE_OFFSET
+        } else {
+            PAGE_SIZE
+        }
+    }
+
+    fn set_page_type(&self, page: &mut Page, page_type: PageType) {
+        if self.version >= PAGE_TYPE_VERSION {
+            page.data[PAGE_TYPE_OFFSET] = page_type as u8;
+        }
+    }
+
+    /// Returns the offset just past a column's constraint flags and default,
+    /// given the offset where they start; files from before
+    /// `COLUMN_CONSTRAINTS_VERSION` store neither.
+    fn skip_column_constraints(&self, data: &[u8], offset: usize) -> usize {
+        if self.version < COLUMN_CONSTRAINTS_VERSION {
+            return offset;
+        }
+        match data.get(offset) {
+            Some(flags) if flags & COLUMN_HAS_DEFAULT != 0 => {
+                let default_len = data
+                    .get(offset + 1..offset + 5)
+                    .map_or(0, |bytes| u32::from_le_bytes(bytes.try_into().unwrap()) as usize);
+                offset + 5 + default_len
+            }
+            _ => offset + 1,
+        }
+    }
+
+    /// Checks that a page is tagged as `expected`, describing the mismatch if
+    /// not. Files from before `PAGE_TYPE_VERSION` have no tags to check.
+    fn check_page_type(&self, page: &Page, expected: PageType) -> Result<(), String> {
+        if self.version < PAGE_TYPE_VERSION {
+            return Ok(());
+        }
+        match PageType::from_tag(page.data[PAGE_TYPE_OFFSET]) {
+            Some(page_type) if page_type == expected => Ok(()),
+            Some(page_type) => Err(format!(
+                "page {} is a {} page, not a {} page",
+                page.id,
+                page_type.name(),
+                expected.name()
+            )),
+            None => Err(format!(
+                "page {} has unknown page type {}, expected a {} page",
+                page.id,
+                page.data[PAGE_TYPE_OFFSET],
+                expected.name()
+            )),
+        }
+    }
+
+    fn initialize_if_needed(&mut self) -> Result<(), IsentaError> {
         // Check if database file exists and has content
-        let file_len = self.storage.file().metadata()
-            .map_err(|e| format!("Failed to get file metadata: {}", e))?
-            .len();
+        let file_len = self.storage.file_len()?;
         
         // If file is empty or doesn't exist, initialize it
         if file_len == 0 {
@@ -52,26 +483,27 @@ impl Database {
             header.data[12..20].copy_from_slice(&0u64.to_le_bytes());
             // Write table count (0 initially)
             header.data[20..24].copy_from_slice(&0u32.to_le_bytes());
+            self.set_page_type(&mut header, PageType::Header);
 
-            self.storage.write_page(&header);
+            self.storage.write_page(&header)?;
             return Ok(());
         }
 
         // File exists - verify it's a valid database file
-        let header = self.storage.read_page(HEADER_PAGE_ID);
+        let header = self.storage.read_page(HEADER_PAGE_ID)?;
         let magic = u64::from_le_bytes(
             header.data[0..8]
                 .try_into()
-                .map_err(|_| "Failed to read magic number")?,
+                .map_err(|_| corrupt("Failed to read magic number"))?,
         );
 
         // Only overwrite if magic number is completely wrong (not just zero)
         // If magic is 0 but file has content, it might be corrupted - but don't auto-fix
         if magic != 0 && magic != MAGIC_NUMBER {
-            return Err(format!(
+            return Err(IsentaError::Corruption(format!(
                 "Invalid database file: expected magic number 0x{:016X}, got 0x{:016X}. File may be corrupted or not a database file.",
                 MAGIC_NUMBER, magic
-            ));
+            )));
         }
 
         // If magic is 0 but file has content, it's likely corrupted
@@ -79,24 +511,185 @@ impl Database {
         if magic == 0 && file_len > 0 {
             // File exists but has no valid header - this is suspicious
             // Don't overwrite, but log a warning
-            eprintln!("Warning: Database file exists but has invalid header. Attempting to load anyway...");
+            self.warn("Database file exists but has invalid header. Attempting to load anyway...");
+            self.version = 1;
+            return Ok(());
+        }
+
+        // Files written by older versions keep their on-disk format, see `upgrade_to_rowids`
+        let version = u32::from_le_bytes(
+            header.data[8..12]
+                .try_into()
+                .map_err(|_| corrupt("Failed to read version"))?,
+        );
+        if version > DB_VERSION {
+            return Err(IsentaError::Corruption(format!(
+                "Unsupported database version {} (this build supports up to {})",
+                version, DB_VERSION
+            )));
+        }
+        self.version = version.max(1);
+
+        self.check_page_type(&header, PageType::Header)
+            .map_err(|message| IsentaError::Corruption(format!("Invalid database file: {}", message)))?;
+
+        self.load_free_list()
+    }
+
+    /// Reads the free-page list from the chain of pages the header points
+    /// to, replacing the list in memory. Files from before
+    /// `FREE_LIST_VERSION` keep no list, so pages they free are only reused
+    /// until the database is closed.
+    fn load_free_list(&mut self) -> Result<(), IsentaError> {
+        self.free_pages.clear();
+        if self.version < FREE_LIST_VERSION {
+            return Ok(());
+        }
+        let page_count = self.storage.file_len()? / PAGE_SIZE as u64;
+        let capacity = self.free_list_capacity();
+        let mut page_id = read_u64(&self.storage.read_page(HEADER_PAGE_ID)?.data, 24);
+        let mut visited = HashSet::new();
+        while page_id != 0 && visited.insert(page_id) {
+            let page = self.storage.read_page(page_id)?;
+            if let Err(message) = self.check_page_type(&page, PageType::FreeList) {
+                self.report(LoadWarning::FreeListUnreadable(message));
+                break;
+            }
+            let count = (u32::from_le_bytes(page.data[8..12].try_into().unwrap()) as usize).min(capacity);
+            for index in 0..count {
+                let free_page = read_u64(&page.data, FREE_LIST_IDS_OFFSET + index * 8);
+                // The header and pages past the end of the file are never free
+                if free_page != HEADER_PAGE_ID && free_page < page_count {
+                    self.free_pages.insert(free_page);
+                }
+            }
+            page_id = read_u64(&page.data, 0);
+        }
+        Ok(())
+    }
+
+    /// Writes the free-page list to its chain of pages. The chain grows at
+    /// the end of the file when the list no longer fits and never shrinks;
+    /// pages it no longer needs are left empty.
+    fn save_free_list(&mut self) -> Result<(), IsentaError> {
+        if self.version < FREE_LIST_VERSION {
+            return Ok(());
+        }
+        let mut header = self.storage.read_page(HEADER_PAGE_ID)?;
+        let mut page_id = read_u64(&header.data, 24);
+        if page_id == 0 {
+            if self.free_pages.is_empty() {
+                return Ok(());
+            }
+            page_id = self.extend_file()?.id;
+            header.data[24..32].copy_from_slice(&page_id.to_le_bytes());
+            self.storage.write_page(&header)?;
+        }
+
+        let ids: Vec<u64> = self.free_pages.iter().copied().collect();
+        let mut chunks = ids.chunks(self.free_list_capacity());
+        let mut visited = HashSet::new();
+        loop {
+            visited.insert(page_id);
+            let existing = self.storage.read_page(page_id)?;
+            let existing_next = match self.check_page_type(&existing, PageType::FreeList) {
+                Ok(()) => read_u64(&existing.data, 0),
+                Err(_) => 0,
+            };
+            let chunk = chunks.next().unwrap_or(&[]);
+            let next = if existing_next != 0 && !visited.contains(&existing_next) {
+                existing_next
+            } else if chunks.len() > 0 {
+                self.extend_file()?.id
+            } else {
+                0
+            };
+
+            let mut page = Page::new(page_id);
+            self.set_page_type(&mut page, PageType::FreeList);
+            page.data[0..8].copy_from_slice(&next.to_le_bytes());
+            page.data[8..12].copy_from_slice(&(chunk.len() as u32).to_le_bytes());
+            for (index, free_page) in chunk.iter().enumerate() {
+                let offset = FREE_LIST_IDS_OFFSET + index * 8;
+                page.data[offset..offset + 8].copy_from_slice(&free_page.to_le_bytes());
+            }
+            self.storage.write_page(&page)?;
+
+            if next == 0 {
+                return Ok(());
+            }
+            page_id = next;
+        }
+    }
+
+    /// How many page IDs one free-list page holds.
+    fn free_list_capacity(&self) -> usize {
+        (self.page_body_end() - FREE_LIST_IDS_OFFSET) / 8
+    }
+
+    /// The pages currently on the free-page list, lowest first.
+    pub fn free_pages(&self) -> Vec<u64> {
+        self.free_pages.iter().copied().collect()
+    }
+
+    /// Allocates an empty page, taking the lowest page from the free-page
+    /// list before extending the file. The page is written zeroed, so a
+    /// reused page doesn't keep its old contents.
+    pub fn allocate_page(&mut self) -> Result<Page, IsentaError> {
+        let Some(page_id) = self.free_pages.pop_first() else {
+            return self.extend_file();
+        };
+        // The list is saved before the page is used, so a crash can leak the
+        // page but never hand it out twice
+        self.save_free_list()?;
+        let page = Page::new(page_id);
+        self.storage.write_page(&page)?;
+        Ok(page)
+    }
+
+    /// Appends an empty page to the end of the file. Page 0 is the header and
+    /// is never handed out, even when the file is too short to hold it.
+    fn extend_file(&mut self) -> Result<Page, IsentaError> {
+        if self.storage.file_len()? < PAGE_SIZE as u64 {
+            let page = Page::new(HEADER_PAGE_ID + 1);
+            self.storage.write_page(&page)?;
+            return Ok(page);
         }
+        let page = self.storage.allocate_page()?;
+        debug_assert_ne!(page.id, HEADER_PAGE_ID, "page 0 is reserved for the header");
+        Ok(page)
+    }
 
+    /// Clears pages that are no longer used, tags them free and adds them to
+    /// the free-page list.
+    fn release_pages(&mut self, page_ids: impl IntoIterator<Item = u64>) -> Result<(), IsentaError> {
+        let mut released = false;
+        for page_id in page_ids {
+            self.write_free_page(page_id)?;
+            released |= self.free_pages.insert(page_id);
+        }
+        if released {
+            self.save_free_list()?;
+        }
         Ok(())
     }
 
-    pub fn load_catalog(&mut self) -> Result<Catalog, String> {
-        let mut header = self.storage.read_page(HEADER_PAGE_ID);
+    pub fn load_catalog(&mut self) -> Result<Catalog, IsentaError> {
+        self.warnings.clear();
+        // Another connection may have freed or reused pages since
+        self.load_free_list()?;
+
+        let mut header = self.storage.read_page(HEADER_PAGE_ID)?;
         let num_tables = u32::from_le_bytes(
             header.data[20..24]
                 .try_into()
-                .map_err(|_| "Failed to read table count")?,
+                .map_err(|_| corrupt("Failed to read table count"))?,
         );
 
         let schema_root = u64::from_le_bytes(
             header.data[12..20]
                 .try_into()
-                .map_err(|_| "Failed to read schema root")?,
+                .map_err(|_| corrupt("Failed to read schema root"))?,
         );
 
         // Validate and repair inconsistencies
@@ -104,7 +697,7 @@ impl Database {
             // If table_count is 0, schema_root should also be 0
             if schema_root != 0 {
                 header.data[12..20].copy_from_slice(&0u64.to_le_bytes());
-                self.storage.write_page(&header);
+                self.storage.write_page(&header)?;
             }
             return Ok(Catalog::new());
         }
@@ -113,7 +706,7 @@ impl Database {
             // If schema_root is 0 but table_count > 0, reset table_count
             if num_tables > 0 {
                 header.data[20..24].copy_from_slice(&0u32.to_le_bytes());
-                self.storage.write_page(&header);
+                self.storage.write_page(&header)?;
             }
             return Ok(Catalog::new());
         }
@@ -128,20 +721,27 @@ impl Database {
         while tables_loaded < num_tables && current_page_id != 0 {
             // Prevent infinite loops
             if pages_visited.contains(&current_page_id) {
-                eprintln!("Warning: Circular reference detected in schema chain at page {}", current_page_id);
+                self.report(LoadWarning::CircularSchemaChain { page_id: current_page_id });
                 break;
             }
             pages_visited.insert(current_page_id);
 
             match self.read_table_from_page(current_page_id)? {
-                Some((table, next_page)) => {
+                Some((table, next_page, damage)) => {
+                    if damage.skipped_rows > 0 {
+                        self.report(LoadWarning::SkippedRows { table: table.name.clone(), count: damage.skipped_rows });
+                    }
+                    if damage.unreadable_values > 0 {
+                        let count = damage.unreadable_values;
+                        self.report(LoadWarning::UnreadableText { table: table.name.clone(), count });
+                    }
                     tables.push(table);
                     tables_loaded += 1;
                     current_page_id = next_page;
                 }
                 None => {
                     // Invalid page - stop loading
-                    eprintln!("Warning: Invalid table page at {}", current_page_id);
+                    self.report(LoadWarning::InvalidTablePage { page_id: current_page_id });
                     break;
                 }
             }
@@ -149,9 +749,9 @@ impl Database {
 
         // If we loaded fewer tables than expected, update the count
         if tables_loaded != num_tables {
-            eprintln!("Warning: Expected {} tables but only loaded {}. Repairing database...", num_tables, tables_loaded);
-            header.data[20..24].copy_from_slice(&(tables_loaded as u32).to_le_bytes());
-            self.storage.write_page(&header);
+            self.report(LoadWarning::TableCountRepaired { expected: num_tables, loaded: tables_loaded });
+            header.data[20..24].copy_from_slice(&tables_loaded.to_le_bytes());
+            self.storage.write_page(&header)?;
         }
 
         let mut catalog = Catalog::new();
@@ -162,34 +762,30 @@ impl Database {
         Ok(catalog)
     }
 
-    fn read_table_from_page(&mut self, page_id: u64) -> Result<Option<(Table, u64)>, String> {
-        let page = self.storage.read_page(page_id);
+    /// Reads the table stored in a schema page.
+    ///
+    /// Returns the table, the next schema page ID, and what of its rows
+    /// couldn't be decoded.
+    fn read_table_from_page(&mut self, page_id: u64) -> Result<Option<(Table, u64, RowDamage)>, IsentaError> {
+        let page = self.storage.read_page(page_id)?;
 
         // Check if page is empty (all zeros)
         if page.data.iter().all(|&b| b == 0) {
             return Ok(None);
         }
 
-        let mut offset = 0;
-
-        // Read table name length and name
-        if offset + 4 > page.data.len() {
-            return Ok(None);
-        }
-        let name_len = u32::from_le_bytes(
-            page.data[offset..offset + 4]
-                .try_into()
-                .map_err(|_| "Failed to read table name length")?,
-        ) as usize;
-        offset += 4;
-
-        if name_len == 0 || name_len > 255 || offset + name_len > page.data.len() {
+        if let Err(message) = self.check_page_type(&page, PageType::Schema) {
+            self.report(LoadWarning::WrongPageType(message));
             return Ok(None);
         }
 
-        let name = String::from_utf8(page.data[offset..offset + name_len].to_vec())
-            .map_err(|_| "Invalid table name encoding")?;
-        offset += name_len;
+        // Read table name length and name
+        let name_bytes = match read_table_name(&page.data) {
+            Some(name_bytes) => name_bytes,
+            None => return Ok(None),
+        };
+        let mut offset = 4 + name_bytes.len();
+        let name = String::from_utf8(name_bytes.to_vec()).map_err(|_| corrupt("Invalid table name encoding"))?;
 
         // Read number of columns
         if offset + 4 > page.data.len() {
@@ -198,7 +794,7 @@ impl Database {
         let num_cols = u32::from_le_bytes(
             page.data[offset..offset + 4]
                 .try_into()
-                .map_err(|_| "Failed to read column count")?,
+                .map_err(|_| corrupt("Failed to read column count"))?,
         );
         offset += 4;
 
@@ -212,7 +808,7 @@ impl Database {
             let col_name_len = u32::from_le_bytes(
                 page.data[offset..offset + 4]
                     .try_into()
-                    .map_err(|_| "Failed to read column name length")?,
+                    .map_err(|_| corrupt("Failed to read column name length"))?,
             ) as usize;
             offset += 4;
 
@@ -220,7 +816,7 @@ impl Database {
                 return Ok(None);
             }
             let col_name = String::from_utf8(page.data[offset..offset + col_name_len].to_vec())
-                .map_err(|_| "Invalid column name encoding")?;
+                .map_err(|_| corrupt("Invalid column name encoding"))?;
             offset += col_name_len;
 
             // Data type length and type
@@ -230,7 +826,7 @@ impl Database {
             let type_len = u32::from_le_bytes(
                 page.data[offset..offset + 4]
                     .try_into()
-                    .map_err(|_| "Failed to read data type length")?,
+                    .map_err(|_| corrupt("Failed to read data type length"))?,
             ) as usize;
             offset += 4;
 
@@ -238,13 +834,22 @@ impl Database {
                 return Ok(None);
             }
             let data_type = String::from_utf8(page.data[offset..offset + type_len].to_vec())
-                .map_err(|_| "Invalid data type encoding")?;
+                .map_err(|_| corrupt("Invalid data type encoding"))?;
             offset += type_len;
 
-            columns.push(Column {
-                name: col_name,
-                data_type,
-            });
+            let mut column = Column::new(&col_name, &data_type);
+            if self.version >= COLUMN_CONSTRAINTS_VERSION {
+                if offset + 1 > page.data.len() {
+                    return Ok(None);
+                }
+                let flags = page.data[offset];
+                offset += 1;
+                column.not_null = flags & COLUMN_NOT_NULL != 0;
+                if flags & COLUMN_HAS_DEFAULT != 0 {
+                    column.default = Some(read_text(&page.data, &mut offset)?);
+                }
+            }
+            columns.push(column);
         }
 
         // Read data page ID (where rows are stored)
@@ -254,7 +859,7 @@ impl Database {
         let data_page_id = u64::from_le_bytes(
             page.data[offset..offset + 8]
                 .try_into()
-                .map_err(|_| "Failed to read data page ID")?,
+                .map_err(|_| corrupt("Failed to read data page ID"))?,
         );
         offset += 8;
 
@@ -265,14 +870,43 @@ impl Database {
         let next_page = u64::from_le_bytes(
             page.data[offset..offset + 8]
                 .try_into()
-                .map_err(|_| "Failed to read next page ID")?,
+                .map_err(|_| corrupt("Failed to read next page ID"))?,
         );
+        offset += 8;
+
+        // Older files have no timestamps, which read as 0
+        let (created_at, updated_at) = if self.version >= TIMESTAMP_VERSION && offset + 16 <= page.data.len() {
+            (read_u64(&page.data, offset), read_u64(&page.data, offset + 8))
+        } else {
+            (0, 0)
+        };
 
         // Load rows from data pages
-        let rows = if data_page_id > 0 {
-            self.load_rows_from_pages(data_page_id, &columns)?
+        let (mut rows, damage) = if data_page_id > 0 {
+            let (rows, damage, _) = self.load_rows_from_pages(data_page_id, &columns)?;
+            (rows, damage)
+        } else {
+            (Vec::new(), RowDamage::default())
+        };
+
+        let change_count = if self.version >= CHANGE_COUNT_VERSION && offset + 32 <= page.data.len() {
+            read_u64(&page.data, offset + 24)
+        } else {
+            0
+        };
+
+        let next_rowid = if self.version >= ROWID_VERSION && offset + 24 <= page.data.len() {
+            // Page packing may store rows out of order; rowids restore insertion order
+            rows.sort_by_key(|row| row.rowid);
+            // A counter behind the stored rowids would hand them out again
+            let stored = read_u64(&page.data, offset + 16);
+            rows.last().map_or(stored, |row| stored.max(row.rowid.saturating_add(1)))
         } else {
-            Vec::new()
+            // Older files have no rowids, so number the rows as they are read
+            for (index, row) in rows.iter_mut().enumerate() {
+                row.rowid = index as u64 + 1;
+            }
+            rows.len() as u64 + 1
         };
 
         Ok(Some((
@@ -280,156 +914,185 @@ impl Database {
                 name,
                 columns,
                 rows,
+                created_at,
+                updated_at,
+                next_rowid,
+                change_count,
             },
             next_page,
+            damage,
         )))
     }
 
+    /// Loads all rows of a data page chain.
+    ///
+    /// A row that fails to decode is skipped rather than aborting the load.
+    /// Pages written with row lengths (version 3+) let the loader step over a
+    /// bad row and carry on; in older pages a bad row makes the rest of that
+    /// page, and the pages chained after it, unreachable. A text value that
+    /// isn't valid UTF-8 or whose length runs past its row is replaced with
+    /// `UNREADABLE_TEXT` instead, keeping the row. Returns the rows that could
+    /// be read, what couldn't be, and the IDs of the chain's pages in order.
     fn load_rows_from_pages(
         &mut self,
         start_page_id: u64,
         columns: &[Column],
-    ) -> Result<Vec<Row>, String> {
+    ) -> Result<(Vec<Row>, RowDamage, Vec<u64>), IsentaError> {
         let mut rows = Vec::new();
+        let mut damage = RowDamage::default();
+        let mut chain = Vec::new();
         let mut current_page_id = start_page_id;
+        let mut pages_visited = std::collections::HashSet::new();
 
         loop {
-            let page = self.storage.read_page(current_page_id);
+            if !pages_visited.insert(current_page_id) {
+                break;
+            }
+            let page = self.storage.read_page(current_page_id)?;
 
             // Check if page is empty
             if page.data.iter().all(|&b| b == 0) {
                 break;
             }
 
-            let mut offset = 0;
-
-            // Read number of rows in this page
-            if offset + 4 > page.data.len() {
+            // The rest of the chain can't be found without this page
+            if let Err(message) = self.check_page_type(&page, PageType::Data) {
+                self.report(LoadWarning::DataChainBroken(message));
                 break;
             }
-            let num_rows = u32::from_le_bytes(
+            chain.push(current_page_id);
+
+            let data_page = self.decode_data_page(&page, columns)?;
+            rows.extend(data_page.rows);
+            damage.skipped_rows += data_page.skipped;
+            damage.unreadable_values += data_page.unreadable;
+            match data_page.next {
+                Some(next_page) => current_page_id = next_page,
+                None => break,
+            }
+        }
+
+        Ok((rows, damage, chain))
+    }
+
+    /// Decodes the rows of one data page.
+    fn decode_data_page(&self, page: &Page, columns: &[Column]) -> Result<DataPage, IsentaError> {
+        let mut rows = Vec::new();
+        let mut skipped = 0;
+        let mut unreadable = 0;
+        let mut offset = 0;
+
+        // Read number of rows in this page
+        let num_rows = u32::from_le_bytes(
+            page.data[offset..offset + 4]
+                .try_into()
+                .map_err(|_| corrupt("Failed to read row count"))?,
+        ) as usize;
+        offset += 4;
+
+        if num_rows == 0 {
+            return Ok(DataPage { rows, skipped, unreadable, next: None, used: offset });
+        }
+
+        // Read the page's string dictionary
+        let mut dictionary = Vec::new();
+        if self.version >= DICTIONARY_VERSION {
+            let num_entries = u32::from_le_bytes(
                 page.data[offset..offset + 4]
                     .try_into()
-                    .map_err(|_| "Failed to read row count")?,
+                    .map_err(|_| corrupt("Failed to read dictionary size"))?,
             );
             offset += 4;
 
-            if num_rows == 0 {
-                break;
+            for _ in 0..num_entries {
+                // A damaged dictionary leaves references unresolvable; they are
+                // reported as bad rows below
+                match read_text(&page.data, &mut offset) {
+                    Ok(entry) => dictionary.push(entry),
+                    Err(_) => break,
+                }
             }
+        }
 
-            // Read rows
-            for _ in 0..num_rows {
-                let mut row_values = Vec::new();
+        // Read rows
+        let mut position_lost = false;
+        for row_index in 0..num_rows {
+            if self.version >= ROW_LENGTH_VERSION {
+                if offset + 2 > page.data.len() {
+                    skipped += num_rows - row_index;
+                    position_lost = true;
+                    break;
+                }
+                let row_len = u16::from_le_bytes([page.data[offset], page.data[offset + 1]]) as usize;
+                offset += 2;
 
-                for _col in columns.iter() {
-                    // Read value type tag
-                    if offset + 1 > page.data.len() {
-                        break;
-                    }
-                    let value_type = page.data[offset];
-                    offset += 1;
+                let row_end = offset + row_len;
+                if row_end > page.data.len() {
+                    skipped += num_rows - row_index;
+                    position_lost = true;
+                    break;
+                }
 
-                    match value_type {
-                        TYPE_NULL => {
-                            row_values.push(String::new());
-                        }
-                        TYPE_INT => {
-                            // Read 8-byte integer
-                            if offset + 8 > page.data.len() {
-                                break;
-                            }
-                            let int_val = i64::from_le_bytes(
-                                page.data[offset..offset + 8]
-                                    .try_into()
-                                    .map_err(|_| "Failed to read integer value")?,
-                            );
-                            offset += 8;
-                            row_values.push(int_val.to_string());
-                        }
-                        TYPE_TEXT => {
-                            // Read text length and value
-                            if offset + 4 > page.data.len() {
-                                break;
-                            }
-                            let text_len = u32::from_le_bytes(
-                                page.data[offset..offset + 4]
-                                    .try_into()
-                                    .map_err(|_| "Failed to read text length")?,
-                            ) as usize;
-                            offset += 4;
-
-                            if text_len == 0 {
-                                row_values.push(String::new());
-                                continue;
-                            }
-
-                            if offset + text_len > page.data.len() {
-                                break;
-                            }
-                            let value = String::from_utf8(page.data[offset..offset + text_len].to_vec())
-                                .map_err(|_| "Invalid text encoding")?;
-                            offset += text_len;
-                            row_values.push(value);
-                        }
-                        _ => {
-                            // Unknown type - try to read as legacy string format for backward compatibility
-                            if offset + 4 > page.data.len() {
-                                break;
-                            }
-                            let val_len = u32::from_le_bytes(
-                                page.data[offset..offset + 4]
-                                    .try_into()
-                                    .map_err(|_| "Failed to read value length")?,
-                            ) as usize;
-                            offset += 4;
-
-                            if val_len == 0 {
-                                row_values.push(String::new());
-                                continue;
-                            }
-
-                            if offset + val_len > page.data.len() {
-                                break;
-                            }
-                            let value = String::from_utf8(page.data[offset..offset + val_len].to_vec())
-                                .map_err(|_| "Invalid value encoding")?;
-                            offset += val_len;
-                            row_values.push(value);
+                let mut row_offset = offset;
+                let mut replaced = 0;
+                let decoded = if self.version >= ROWID_VERSION {
+                    decode_rowid(&page.data[..row_end], &mut row_offset).and_then(|rowid| {
+                        let mut row =
+                            decode_row_to_end(&page.data[..row_end], &mut row_offset, &dictionary, &mut replaced)?;
+                        row.rowid = rowid;
+                        Ok(row)
+                    })
+                } else {
+                    decode_row_to_end(&page.data[..row_end], &mut row_offset, &dictionary, &mut replaced)
+                };
+                match decoded {
+                    Ok(mut row) => {
+                        // A bad text length hides the values after it
+                        if replaced > 0 && row.values.len() < columns.len() {
+                            row.values.resize(columns.len(), None);
                         }
+                        unreadable += replaced;
+                        rows.push(row);
                     }
+                    Err(_) => skipped += 1,
                 }
-
-                if row_values.len() == columns.len() {
-                    rows.push(Row { values: row_values });
+                offset = row_end;
+            } else {
+                match decode_row(&page.data, &mut offset, columns, &dictionary, &mut unreadable) {
+                    Ok(row) => rows.push(row),
+                    Err(_) => {
+                        skipped += num_rows - row_index;
+                        position_lost = true;
+                        break;
+                    }
                 }
             }
+        }
 
-            // Read next data page ID
-            if offset + 8 > page.data.len() {
-                break;
-            }
-            let next_page = u64::from_le_bytes(
-                page.data[offset..offset + 8]
-                    .try_into()
-                    .map_err(|_| "Failed to read next page ID")?,
-            );
+        // Without the end of the rows there's no way to find the next page
+        if position_lost {
+            return Ok(DataPage { rows, skipped, unreadable, next: None, used: offset });
+        }
 
-            if next_page == 0 {
-                break;
-            }
-            current_page_id = next_page;
+        // Read next data page ID
+        if offset + 8 > page.data.len() {
+            return Ok(DataPage { rows, skipped, unreadable, next: None, used: offset });
         }
+        let next_page = u64::from_le_bytes(
+            page.data[offset..offset + 8]
+                .try_into()
+                .map_err(|_| corrupt("Failed to read next page ID"))?,
+        );
 
-        Ok(rows)
+        Ok(DataPage { rows, skipped, unreadable, next: (next_page != 0).then_some(next_page), used: offset + 8 })
     }
 
-    fn find_table_schema_page(&mut self, table_name: &str) -> Result<Option<u64>, String> {
-        let header = self.storage.read_page(HEADER_PAGE_ID);
+    fn find_table_schema_page(&mut self, table_name: &str) -> Result<Option<u64>, IsentaError> {
+        let header = self.storage.read_page(HEADER_PAGE_ID)?;
         let schema_root = u64::from_le_bytes(
             header.data[12..20]
                 .try_into()
-                .map_err(|_| "Failed to read schema root")?,
+                .map_err(|_| corrupt("Failed to read schema root"))?,
         );
 
         if schema_root == 0 {
@@ -439,46 +1102,33 @@ impl Database {
         let mut current_page_id = schema_root;
 
         loop {
-            let page = self.storage.read_page(current_page_id);
+            let page = self.storage.read_page(current_page_id)?;
 
-            if page.data.iter().all(|&b| b == 0) {
+            if page.data.iter().all(|&b| b == 0) || self.check_page_type(&page, PageType::Schema).is_err() {
                 break;
             }
 
-            let mut offset = 0;
-
             // Read table name
-            if offset + 4 > page.data.len() {
-                break;
-            }
-            let name_len = u32::from_le_bytes(
-                page.data[offset..offset + 4]
-                    .try_into()
-                    .map_err(|_| "Failed to read table name length")?,
-            ) as usize;
-            offset += 4;
-
-            if name_len > 255 || offset + name_len > page.data.len() {
-                break;
-            }
-
-            let name = String::from_utf8(page.data[offset..offset + name_len].to_vec())
-                .map_err(|_| "Invalid table name encoding")?;
+            let name_bytes = match read_table_name(&page.data) {
+                Some(name_bytes) => name_bytes,
+                None => break,
+            };
+            let name = String::from_utf8(name_bytes.to_vec()).map_err(|_| corrupt("Invalid table name encoding"))?;
 
-            if name.to_lowercase() == table_name.to_lowercase() {
+            if names_match(&name, table_name) {
                 return Ok(Some(current_page_id));
             }
 
             // Skip to next page pointer
             // We need to skip: columns count, all columns, and data page ID
-            offset += name_len;
+            let mut offset = 4 + name_bytes.len();
             if offset + 4 > page.data.len() {
                 break;
             }
             let num_cols = u32::from_le_bytes(
                 page.data[offset..offset + 4]
                     .try_into()
-                    .map_err(|_| "Failed to read column count")?,
+                    .map_err(|_| corrupt("Failed to read column count"))?,
             );
             offset += 4;
 
@@ -491,7 +1141,7 @@ impl Database {
                 let col_name_len = u32::from_le_bytes(
                     page.data[offset..offset + 4]
                         .try_into()
-                        .map_err(|_| "Failed to read column name length")?,
+                        .map_err(|_| corrupt("Failed to read column name length"))?,
                 ) as usize;
                 offset += 4;
                 if offset + col_name_len > page.data.len() {
@@ -506,13 +1156,13 @@ impl Database {
                 let type_len = u32::from_le_bytes(
                     page.data[offset..offset + 4]
                         .try_into()
-                        .map_err(|_| "Failed to read data type length")?,
+                        .map_err(|_| corrupt("Failed to read data type length"))?,
                 ) as usize;
                 offset += 4;
                 if offset + type_len > page.data.len() {
                     break;
                 }
-                offset += type_len;
+                offset = self.skip_column_constraints(&page.data, offset + type_len);
             }
 
             // Skip data page ID
@@ -525,7 +1175,7 @@ impl Database {
             let next_page = u64::from_le_bytes(
                 page.data[offset..offset + 8]
                     .try_into()
-                    .map_err(|_| "Failed to read next page ID")?,
+                    .map_err(|_| corrupt("Failed to read next page ID"))?,
             );
 
             if next_page == 0 {
@@ -537,80 +1187,70 @@ impl Database {
         Ok(None)
     }
 
-    pub fn save_table(&mut self, table: &Table, is_new: bool) -> Result<(), String> {
+    pub fn save_table(&mut self, table: &Table, is_new: bool) -> Result<(), IsentaError> {
+        self.lock_table(&table.name)?;
+        self.upgrade_to_rowids()?;
+        // Check the name before any page is allocated
+        if is_new && self.find_table_schema_page(&table.name)?.is_some() {
+            return Err(IsentaError::TableExists(table.name.clone()));
+        }
+        check_table_name(&table.name)?;
+        self.check_schema_size(&table.name, &table.columns)?;
+
         // Save the table schema and data to pages
-        let schema_page = self.storage.allocate_page();
+        let schema_page = self.allocate_page()?;
         let mut page = Page::new(schema_page.id);
-        let mut offset = 0;
+        let mut offset = self.encode_schema(&mut page, table)?;
+        let body_end = self.page_body_end();
 
-        // Write table name
-        let name_bytes = table.name.as_bytes();
-        if offset + 4 + name_bytes.len() > page.data.len() {
-            return Err("Table name too long".to_string());
+        // Allocate data page for rows
+        let data_page = if !table.rows.is_empty() {
+            let rows: Vec<&Row> = table.rows.iter().collect();
+            self.save_rows_to_pages(&rows, &table.columns, None, &mut VecDeque::new())?
+        } else {
+            self.allocate_page()?
+        };
+        if is_new {
+            self.reserve_free_pages(self.data_page_preallocation - 1)?;
         }
-        page.data[offset..offset + 4].copy_from_slice(&(name_bytes.len() as u32).to_le_bytes());
-        offset += 4;
-        page.data[offset..offset + name_bytes.len()].copy_from_slice(name_bytes);
-        offset += name_bytes.len();
 
-        // Write number of columns
-        if offset + 4 > page.data.len() {
-            return Err("Page overflow".to_string());
+        // Write data page ID
+        if offset + 8 > body_end {
+            return Err(IsentaError::Constraint("Page overflow".to_string()));
         }
-        page.data[offset..offset + 4].copy_from_slice(&(table.columns.len() as u32).to_le_bytes());
-        offset += 4;
+        page.data[offset..offset + 8].copy_from_slice(&data_page.id.to_le_bytes());
+        offset += 8;
 
-        // Write columns
-        for col in &table.columns {
-            let col_name_bytes = col.name.as_bytes();
-            if offset + 4 + col_name_bytes.len() > page.data.len() {
-                return Err("Column name too long".to_string());
+        // Write timestamps and the next rowid after the next schema page ID
+        if self.version >= TIMESTAMP_VERSION {
+            if offset + 40 > body_end {
+                return Err(IsentaError::Constraint("Page overflow".to_string()));
             }
-            page.data[offset..offset + 4]
-                .copy_from_slice(&(col_name_bytes.len() as u32).to_le_bytes());
-            offset += 4;
-            page.data[offset..offset + col_name_bytes.len()].copy_from_slice(col_name_bytes);
-            offset += col_name_bytes.len();
-
-            let type_bytes = col.data_type.as_bytes();
-            if offset + 4 + type_bytes.len() > page.data.len() {
-                return Err("Data type too long".to_string());
+            page.data[offset + 8..offset + 16].copy_from_slice(&table.created_at.to_le_bytes());
+            page.data[offset + 16..offset + 24].copy_from_slice(&table.updated_at.to_le_bytes());
+            if self.version >= ROWID_VERSION {
+                page.data[offset + 24..offset + 32].copy_from_slice(&table.next_rowid.to_le_bytes());
+            }
+            if self.version >= CHANGE_COUNT_VERSION {
+                page.data[offset + 32..offset + 40].copy_from_slice(&table.change_count.to_le_bytes());
             }
-            page.data[offset..offset + 4].copy_from_slice(&(type_bytes.len() as u32).to_le_bytes());
-            offset += 4;
-            page.data[offset..offset + type_bytes.len()].copy_from_slice(type_bytes);
-            offset += type_bytes.len();
-        }
-
-        // Allocate data page for rows
-        let data_page = if !table.rows.is_empty() {
-            self.save_rows_to_pages(&table.rows, &table.columns, None)?
-        } else {
-            self.storage.allocate_page()
-        };
-
-        // Write data page ID
-        if offset + 8 > page.data.len() {
-            return Err("Page overflow".to_string());
         }
-        page.data[offset..offset + 8].copy_from_slice(&data_page.id.to_le_bytes());
-        offset += 8;
 
         // If this is not a new table, we need to update the existing schema chain
         if !is_new {
             // For now, we'll just save the table with no next page
             // In a real implementation, you'd want to update the existing chain
             page.data[offset..offset + 8].copy_from_slice(&0u64.to_le_bytes());
-            self.storage.write_page(&page);
+            self.storage.write_page(&page)?;
             return Ok(());
         }
 
         // For new tables, we need to update the schema chain
-        let mut header = self.storage.read_page(HEADER_PAGE_ID);
+        let mut header = self.storage.read_page(HEADER_PAGE_ID)?;
         let schema_root = u64::from_le_bytes(
             header.data[12..20]
                 .try_into()
-                .map_err(|_| "Failed to read schema root")?,
+                .map_err(|_| corrupt("Failed to read schema root"))?,
         );
 
         // If this is the first table, update the schema root
@@ -618,18 +1258,21 @@ impl Database {
             // This is the first table, update the header
             header.data[12..20].copy_from_slice(&schema_page.id.to_le_bytes());
             // Write header immediately to persist the schema_root
-            self.storage.write_page(&header);
+            self.storage.write_page(&header)?;
         } else {
             // Find the last table in the chain and update its next pointer
             let mut current_page_id = schema_root;
             loop {
-                let mut current_page = self.storage.read_page(current_page_id);
+                let mut current_page = self.storage.read_page(current_page_id)?;
+                self.check_page_type(&current_page, PageType::Schema).map_err(|message| corrupt(&message))?;
     
                 // Parse the current page to find the offset of the next_page pointer
                 let mut next_page_offset = 0;
     
                 // Read table name
-                let name_len = u32::from_le_bytes(current_page.data[next_page_offset..next_page_offset + 4].try_into().unwrap()) as usize;
+                let name_len = read_table_name(&current_page.data)
+                    .ok_or_else(|| corrupt(&format!("Invalid table name in schema page {}", current_page_id)))?
+                    .len();
                 next_page_offset += 4 + name_len;
     
                 // Read columns
@@ -641,7 +1284,7 @@ impl Database {
                     next_page_offset += 4 + col_name_len;
     
                     let type_len = u32::from_le_bytes(current_page.data[next_page_offset..next_page_offset + 4].try_into().unwrap()) as usize;
-                    next_page_offset += 4 + type_len;
+                    next_page_offset = self.skip_column_constraints(&current_page.data, next_page_offset + 4 + type_len);
                 }
     
                 // Read data page ID
@@ -653,7 +1296,7 @@ impl Database {
                 if next_page_id == 0 {
                     // This is the last page, update its next pointer
                     current_page.data[next_page_offset..next_page_offset + 8].copy_from_slice(&schema_page.id.to_le_bytes());
-                    self.storage.write_page(&current_page);
+                    self.storage.write_page(&current_page)?;
                     break;
                 }
                 current_page_id = next_page_id;
@@ -664,208 +1307,1288 @@ impl Database {
         page.data[offset..offset + 8].copy_from_slice(&0u64.to_le_bytes());
 
         // Save the schema page
-        self.storage.write_page(&page);
+        self.storage.write_page(&page)?;
 
         // Update the table count (re-read header in case it was modified)
-        let mut header = self.storage.read_page(HEADER_PAGE_ID);
+        let mut header = self.storage.read_page(HEADER_PAGE_ID)?;
         let table_count = u32::from_le_bytes(
             header.data[20..24]
                 .try_into()
-                .map_err(|_| "Failed to read table count")?,
+                .map_err(|_| corrupt("Failed to read table count"))?,
         );
         header.data[20..24].copy_from_slice(&(table_count + 1).to_le_bytes());
         // Also ensure schema_root is set correctly if this was the first table
         let current_schema_root = u64::from_le_bytes(
             header.data[12..20]
                 .try_into()
-                .map_err(|_| "Failed to read schema root")?,
+                .map_err(|_| corrupt("Failed to read schema root"))?,
         );
         if current_schema_root == 0 {
             header.data[12..20].copy_from_slice(&schema_page.id.to_le_bytes());
         }
-        self.storage.write_page(&header);
+        self.storage.write_page(&header)?;
+
+        Ok(())
+    }
+
+    /// The bytes a schema page needs for a table with this name and these
+    /// columns: what `encode_schema` writes, then the data page ID, the next
+    /// schema page ID and the bookkeeping fields after it.
+    pub fn schema_size(&self, name: &str, columns: &[Column]) -> usize {
+        let columns_size: usize = columns
+            .iter()
+            .map(|column| {
+                let constraints = if self.version >= COLUMN_CONSTRAINTS_VERSION {
+                    1 + column.default.as_ref().map_or(0, |default| 4 + default.len())
+                } else {
+                    0
+                };
+                4 + column.name.len() + 4 + column.data_type.len() + constraints
+            })
+            .sum();
+        let fields = if self.version >= TIMESTAMP_VERSION { 40 } else { 8 };
+        4 + name.len() + 4 + columns_size + 8 + fields
+    }
 
+    /// Rejects a table whose schema doesn't fit in its one schema page, before
+    /// anything is written.
+    pub fn check_schema_size(&self, name: &str, columns: &[Column]) -> Result<(), IsentaError> {
+        let size = self.schema_size(name, columns);
+        let max = self.page_body_end();
+        if size > max {
+            return Err(IsentaError::Constraint(format!(
+                "schema too large to fit in one page ({} bytes, max {})",
+                size, max
+            )));
+        }
         Ok(())
     }
 
+    /// Writes the page type, the name and the columns of `table` to a schema
+    /// page, returning the offset of the first data page ID that follows.
+    fn encode_schema(&self, page: &mut Page, table: &Table) -> Result<usize, IsentaError> {
+        let name_bytes = table.name.as_bytes();
+        self.set_page_type(page, PageType::Schema);
+        let body_end = self.page_body_end();
+        let mut offset = 0;
+
+        // Write table name
+        page.data[offset..offset + 4].copy_from_slice(&(name_bytes.len() as u32).to_le_bytes());
+        offset += 4;
+        page.data[offset..offset + name_bytes.len()].copy_from_slice(name_bytes);
+        offset += name_bytes.len();
+
+        // Write number of columns
+        if offset + 4 > body_end {
+            return Err(IsentaError::Constraint("Page overflow".to_string()));
+        }
+        page.data[offset..offset + 4].copy_from_slice(&(table.columns.len() as u32).to_le_bytes());
+        offset += 4;
+
+        // Write columns
+        for col in &table.columns {
+            let col_name_bytes = col.name.as_bytes();
+            if offset + 4 + col_name_bytes.len() > body_end {
+                return Err(IsentaError::Constraint("Column name too long".to_string()));
+            }
+            page.data[offset..offset + 4]
+                .copy_from_slice(&(col_name_bytes.len() as u32).to_le_bytes());
+            offset += 4;
+            page.data[offset..offset + col_name_bytes.len()].copy_from_slice(col_name_bytes);
+            offset += col_name_bytes.len();
+
+            let type_bytes = col.data_type.as_bytes();
+            if offset + 4 + type_bytes.len() > body_end {
+                return Err(IsentaError::Constraint("Data type too long".to_string()));
+            }
+            page.data[offset..offset + 4].copy_from_slice(&(type_bytes.len() as u32).to_le_bytes());
+            offset += 4;
+            page.data[offset..offset + type_bytes.len()].copy_from_slice(type_bytes);
+            offset += type_bytes.len();
+
+            if self.version >= COLUMN_CONSTRAINTS_VERSION {
+                let default_bytes = col.default.as_deref().map(str::as_bytes);
+                let default_len = default_bytes.map_or(0, |bytes| 4 + bytes.len());
+                if offset + 1 + default_len > body_end {
+                    return Err(IsentaError::Constraint("Column default too long".to_string()));
+                }
+                let mut flags = 0;
+                if col.not_null {
+                    flags |= COLUMN_NOT_NULL;
+                }
+                if default_bytes.is_some() {
+                    flags |= COLUMN_HAS_DEFAULT;
+                }
+                page.data[offset] = flags;
+                offset += 1;
+                if let Some(bytes) = default_bytes {
+                    page.data[offset..offset + 4].copy_from_slice(&(bytes.len() as u32).to_le_bytes());
+                    offset += 4;
+                    page.data[offset..offset + bytes.len()].copy_from_slice(bytes);
+                    offset += bytes.len();
+                }
+            } else if col.not_null || col.default.is_some() {
+                return Err(IsentaError::Constraint(format!(
+                    "Column constraints need database format version {}; this file uses version {}",
+                    COLUMN_CONSTRAINTS_VERSION, self.version
+                )));
+            }
+        }
+
+        Ok(offset)
+    }
+
+    /// Writes `rows` to a chain of data pages starting at `start_page_id`, or
+    /// at a new page when it is `None`. Pages the chain continues onto are
+    /// taken from `reusable` first, see `next_data_page_id`.
     fn save_rows_to_pages(
         &mut self,
-        rows: &[Row],
+        rows: &[&Row],
         columns: &[Column],
         start_page_id: Option<u64>,
-    ) -> Result<Page, String> {
+        reusable: &mut VecDeque<u64>,
+    ) -> Result<Page, IsentaError> {
         let page_id = if let Some(id) = start_page_id {
             id
         } else {
-            self.storage.allocate_page().id
+            self.allocate_page()?.id
         };
 
         let mut page = Page::new(page_id);
-        let mut offset = 0;
-
-        // Calculate how many rows fit in a page
-        // We'll write rows until we run out of space
-        let mut rows_written = 0;
+        self.set_page_type(&mut page, PageType::Data);
+        let body_end = self.page_body_end();
+
+        // Encode as many rows as fit into this page
+        let col_types: Vec<String> = columns.iter().map(|c| c.data_type.to_uppercase()).collect();
+        let (placed, body) = self.encode_page_rows(rows, &col_types);
+
+        if placed.is_empty() && !rows.is_empty() {
+            if rows[0].values.len() != columns.len() {
+                return Err(IsentaError::Constraint(format!(
+                    "Row has {} values but table has {} columns",
+                    rows[0].values.len(),
+                    columns.len()
+                )));
+            }
+            return Err(IsentaError::Constraint("Row is too large to fit in a single page".to_string()));
+        }
 
-        // Write number of rows (we'll update this later)
-        let row_count_offset = offset;
-        offset += 4;
+        page.data[..body.len()].copy_from_slice(&body);
+        let offset = body.len();
 
-        // Write rows
-        for row in rows {
-            let row_start_offset = offset;
+        // Rows that were skipped for this page go to the next one, in their original order
+        let remaining: Vec<&Row> = rows
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !placed.contains(index))
+            .map(|(_, row)| *row)
+            .collect();
 
-            // Try to write the row
-            for (value, col) in row.values.iter().zip(columns.iter()) {
-                let col_type = col.data_type.to_uppercase();
+        // If there are more rows, allocate next page and chain
+        if !remaining.is_empty() {
+            let next_page_id = self.next_data_page_id(page_id, reusable)?;
+            let next_page = self.save_rows_to_pages(&remaining, columns, Some(next_page_id), reusable)?;
+            if offset + 8 > body_end {
+                return Err(IsentaError::Constraint("Page overflow".to_string()));
+            }
+            page.data[offset..offset + 8].copy_from_slice(&next_page.id.to_le_bytes());
+        } else {
+            if offset + 8 > body_end {
+                return Err(IsentaError::Constraint("Page overflow".to_string()));
+            }
+            page.data[offset..offset + 8].copy_from_slice(&0u64.to_le_bytes());
+        }
 
-                // Write value type tag
-                if offset + 1 > page.data.len() {
-                    break;
-                }
+        self.storage.write_page(&page)?;
+        Ok(page)
+    }
 
-                if value.is_empty() {
-                    page.data[offset] = TYPE_NULL;
-                    offset += 1;
-                } else if col_type == "INT" || col_type == "INTEGER" {
-                    // Parse and write as integer
-                    match value.parse::<i64>() {
-                        Ok(int_val) => {
-                            page.data[offset] = TYPE_INT;
-                            offset += 1;
-                            if offset + 8 > page.data.len() {
-                                break;
-                            }
-                            page.data[offset..offset + 8].copy_from_slice(&int_val.to_le_bytes());
-                            offset += 8;
-                        }
-                        Err(_) => {
-                            // Fallback to text if parsing fails
-                            page.data[offset] = TYPE_TEXT;
-                            offset += 1;
-                            let val_bytes = value.as_bytes();
-                            if offset + 4 + val_bytes.len() > page.data.len() {
-                                break;
-                            }
-                            page.data[offset..offset + 4]
-                                .copy_from_slice(&(val_bytes.len() as u32).to_le_bytes());
-                            offset += 4;
-                            page.data[offset..offset + val_bytes.len()].copy_from_slice(val_bytes);
-                            offset += val_bytes.len();
-                        }
-                    }
-                } else {
-                    // Write as text
-                    page.data[offset] = TYPE_TEXT;
-                    offset += 1;
-                    let val_bytes = value.as_bytes();
-                    if offset + 4 + val_bytes.len() > page.data.len() {
-                        break;
-                    }
-                    page.data[offset..offset + 4]
-                        .copy_from_slice(&(val_bytes.len() as u32).to_le_bytes());
-                    offset += 4;
-                    page.data[offset..offset + val_bytes.len()].copy_from_slice(val_bytes);
-                    offset += val_bytes.len();
+    /// Picks the page a data chain continues onto after `page_id`: one of the
+    /// chain's previous pages if any are left, else the following page when
+    /// it is free (such as a page preallocated for the table), else a page
+    /// from the free-page list or a new one at the end of the file.
+    fn next_data_page_id(&mut self, page_id: u64, reusable: &mut VecDeque<u64>) -> Result<u64, IsentaError> {
+        if let Some(id) = reusable.pop_front() {
+            return Ok(id);
+        }
+        let page_count = self.storage.file_len()? / PAGE_SIZE as u64;
+        if self.version >= PAGE_TYPE_VERSION && page_id + 1 < page_count {
+            let next = self.storage.read_page(page_id + 1)?;
+            if next.data[PAGE_TYPE_OFFSET] == PageType::Free as u8 {
+                if self.free_pages.remove(&next.id) {
+                    self.save_free_list()?;
                 }
+                return Ok(next.id);
             }
+        }
+        Ok(self.allocate_page()?.id)
+    }
 
-            // Check if we successfully wrote the entire row
-            if row.values.len() == columns.len() && offset <= page.data.len() - 8 {
-                rows_written += 1;
-            } else {
-                // Row didn't fit, rollback
-                offset = row_start_offset;
+    /// Appends `count` pages tagged free to the end of the file. They stay off
+    /// the free-page list, reserved for the data chain they follow. Files
+    /// from before `PAGE_TYPE_VERSION` can't tell free pages apart, so nothing
+    /// is reserved on them.
+    fn reserve_free_pages(&mut self, count: u64) -> Result<(), IsentaError> {
+        if self.version < PAGE_TYPE_VERSION {
+            return Ok(());
+        }
+        for _ in 0..count {
+            let page = self.extend_file()?;
+            self.write_free_page(page.id)?;
+        }
+        Ok(())
+    }
+
+    /// Clears a page and tags it free, so a neighbouring data chain may
+    /// continue onto it.
+    fn write_free_page(&mut self, page_id: u64) -> Result<(), IsentaError> {
+        let mut page = Page::new(page_id);
+        self.set_page_type(&mut page, PageType::Free);
+        self.storage.write_page(&page)
+    }
+
+    /// Selects and encodes the rows that go into one data page.
+    ///
+    /// Rows are taken in order; when one doesn't fit, later (smaller) rows are
+    /// still tried until the page is reasonably full, so a single large row
+    /// doesn't leave the page half empty. Files from before `ROWID_VERSION`
+    /// can't restore the order of rows stored out of it, so there the page
+    /// ends at the first row that doesn't fit. Returns the indices of the rows
+    /// that were placed and the page body (everything before the next page
+    /// pointer).
+    /// On files with dictionary support both the inline and the dictionary
+    /// encoding are tried and the denser one is kept, so pages of mostly
+    /// distinct values fall back to plain inline values.
+    fn encode_page_rows(&self, rows: &[&Row], col_types: &[String]) -> (HashSet<usize>, Vec<u8>) {
+        let use_dictionary = self.version >= DICTIONARY_VERSION;
+        let header_len = if use_dictionary { 8 } else { 4 };
+        // Leave room for the next page pointer
+        let capacity = self.page_body_end() - 8 - header_len;
+
+        let row_lengths = self.version >= ROW_LENGTH_VERSION;
+        let rowids = self.version >= ROWID_VERSION;
+        let blobs = self.version >= BLOB_VERSION;
+        let mut inline = PageEncoder::new(false, row_lengths, rowids, blobs);
+        let mut dict = PageEncoder::new(use_dictionary, row_lengths, rowids, blobs);
+        dict.done = !use_dictionary;
+
+        for (index, row) in rows.iter().enumerate() {
+            inline.try_add(index, row, col_types, capacity);
+            dict.try_add(index, row, col_types, capacity);
+
+            if inline.done && dict.done {
                 break;
             }
         }
 
-        // Write actual row count
-        page.data[row_count_offset..row_count_offset + 4]
-            .copy_from_slice(&(rows_written as u32).to_le_bytes());
-
-        // If there are more rows, allocate next page and chain
-        if rows.len() > rows_written {
-            let next_page = self.save_rows_to_pages(&rows[rows_written..], columns, None)?;
-            if offset + 8 > page.data.len() {
-                return Err("Page overflow".to_string());
+        let use_dict_rows = use_dictionary
+            && (dict.placed.len() > inline.placed.len()
+                || (dict.placed.len() == inline.placed.len() && dict.used() < inline.used()));
+        let chosen = if use_dict_rows { dict } else { inline };
+
+        let mut body = Vec::with_capacity(PAGE_SIZE);
+        body.extend_from_slice(&(chosen.placed.len() as u32).to_le_bytes());
+        if use_dictionary {
+            let dictionary = chosen.dictionary.unwrap_or_default();
+            body.extend_from_slice(&(dictionary.len() as u32).to_le_bytes());
+            for entry in &dictionary {
+                body.extend_from_slice(&(entry.len() as u32).to_le_bytes());
+                body.extend_from_slice(entry.as_bytes());
             }
-            page.data[offset..offset + 8].copy_from_slice(&next_page.id.to_le_bytes());
+        }
+        body.extend_from_slice(&chosen.rows);
+        (chosen.placed, body)
+    }
+
+    /// Describes a page for debugging: its type, the first fields decoded
+    /// according to that type, and a hex dump of its bytes.
+    pub fn dump_page(&mut self, page_id: u64) -> Result<String, IsentaError> {
+        let page_count = self.storage.file_len()? / PAGE_SIZE as u64;
+        if page_id >= page_count {
+            return Err(IsentaError::InvalidQuery(format!(
+                "Page {} is past the end of the file, which has {} pages",
+                page_id, page_count
+            )));
+        }
+        let page = self.storage.read_page(page_id)?;
+
+        let page_type = if self.version >= PAGE_TYPE_VERSION {
+            PageType::from_tag(page.data[PAGE_TYPE_OFFSET])
+        } else if page_id == HEADER_PAGE_ID {
+            Some(PageType::Header)
         } else {
-            if offset + 8 > page.data.len() {
-                return Err("Page overflow".to_string());
+            None
+        };
+        let type_name = match page_type {
+            Some(page_type) => page_type.name().to_string(),
+            None if self.version >= PAGE_TYPE_VERSION => format!("unknown type tag {}", page.data[PAGE_TYPE_OFFSET]),
+            None => format!("untagged, format version {}", self.version),
+        };
+
+        let mut output = format!("Page {} of {} ({})\n", page_id, page_count, type_name);
+        match page_type {
+            Some(PageType::Header) => {
+                let magic = read_u64(&page.data, 0);
+                output.push_str(&format!(
+                    "Magic number: 0x{:016X}{}\n",
+                    magic,
+                    if magic == MAGIC_NUMBER { "" } else { " (invalid)" }
+                ));
+                output.push_str(&format!("Version: {}\n", u32::from_le_bytes(page.data[8..12].try_into().unwrap())));
+                output.push_str(&format!("Schema root: {}\n", read_u64(&page.data, 12)));
+                output.push_str(&format!("Tables: {}\n", u32::from_le_bytes(page.data[20..24].try_into().unwrap())));
+                if self.version >= FREE_LIST_VERSION {
+                    output.push_str(&format!("Free list: {}\n", read_u64(&page.data, 24)));
+                }
             }
-            page.data[offset..offset + 8].copy_from_slice(&0u64.to_le_bytes());
+            Some(PageType::Schema) => match read_table_name(&page.data) {
+                Some(name) => {
+                    output.push_str(&format!("Table name: {}\n", String::from_utf8_lossy(name)));
+                    let offset = 4 + name.len();
+                    let columns = u32::from_le_bytes(page.data[offset..offset + 4].try_into().unwrap());
+                    output.push_str(&format!("Columns: {}\n", columns));
+                }
+                None => output.push_str("Table name: invalid length\n"),
+            },
+            Some(PageType::Data) => {
+                output.push_str(&format!("Rows: {}\n", u32::from_le_bytes(page.data[0..4].try_into().unwrap())));
+                if self.version >= DICTIONARY_VERSION {
+                    let entries = u32::from_le_bytes(page.data[4..8].try_into().unwrap());
+                    output.push_str(&format!("Dictionary entries: {}\n", entries));
+                }
+            }
+            Some(PageType::FreeList) => {
+                output.push_str(&format!("Next free-list page: {}\n", read_u64(&page.data, 0)));
+                output.push_str(&format!("Free pages listed: {}\n", u32::from_le_bytes(page.data[8..12].try_into().unwrap())));
+            }
+            Some(PageType::Free) | None => {}
         }
 
-        self.storage.write_page(&page);
-        Ok(page)
+        output.push('\n');
+        output.push_str(&hex_dump(&page.data));
+        Ok(output)
     }
 
-    pub fn update_table_data(&mut self, table: &Table) -> Result<(), String> {
+    /// Rebuilds the schema chain from the pages tagged as schema pages, for
+    /// when the header's schema root or a next pointer is damaged but the
+    /// schema pages themselves are intact. The pages are linked in page
+    /// order and the header's schema root and table count are rewritten; a
+    /// second page for an already linked table name is left out. Returns
+    /// the number of tables linked.
+    pub fn rebuild_schema_chain(&mut self) -> Result<usize, IsentaError> {
+        if self.version < PAGE_TYPE_VERSION {
+            return Err(IsentaError::InvalidQuery(format!(
+                "Schema pages can only be found in files with page type tags (format version {}+); this file uses version {}",
+                PAGE_TYPE_VERSION, self.version
+            )));
+        }
+
+        let page_count = self.storage.file_len()? / PAGE_SIZE as u64;
+        let mut schema_pages: Vec<(Page, usize)> = Vec::new();
+        let mut names: Vec<String> = Vec::new();
+        for page_id in HEADER_PAGE_ID + 1..page_count {
+            let page = self.storage.read_page(page_id)?;
+            if self.check_page_type(&page, PageType::Schema).is_err() {
+                continue;
+            }
+            let (name, next_offset) = match (read_table_name(&page.data), self.next_schema_page_offset(&page.data)) {
+                (Some(name), Some(next_offset)) => (String::from_utf8_lossy(name).into_owned(), next_offset),
+                _ => {
+                    self.warn(&format!("Skipping unreadable schema page {}", page_id));
+                    continue;
+                }
+            };
+            if names.iter().any(|linked| names_match(linked, &name)) {
+                self.warn(&format!("Skipping schema page {} for table '{}', which is already linked", page_id, name));
+                continue;
+            }
+            names.push(name);
+            schema_pages.push((page, next_offset));
+        }
+
+        let next_ids: Vec<u64> = schema_pages.iter().skip(1).map(|(page, _)| page.id).chain([0]).collect();
+        for ((page, next_offset), next_id) in schema_pages.iter_mut().zip(next_ids) {
+            page.data[*next_offset..*next_offset + 8].copy_from_slice(&next_id.to_le_bytes());
+            self.storage.write_page(page)?;
+        }
+
+        let mut header = self.storage.read_page(HEADER_PAGE_ID)?;
+        let schema_root = schema_pages.first().map_or(0, |(page, _)| page.id);
+        header.data[12..20].copy_from_slice(&schema_root.to_le_bytes());
+        header.data[20..24].copy_from_slice(&(schema_pages.len() as u32).to_le_bytes());
+        self.storage.write_page(&header)?;
+
+        Ok(schema_pages.len())
+    }
+
+    /// Finds where the next schema page ID is stored in a schema page, after
+    /// the name, the columns and the first data page ID. Returns `None` when
+    /// a length runs past the page.
+    fn next_schema_page_offset(&self, data: &[u8]) -> Option<usize> {
+        let read_len = |offset: usize| -> Option<usize> {
+            Some(u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?) as usize)
+        };
+
+        let mut offset = 4 + read_table_name(data)?.len();
+        let num_cols = read_len(offset)?;
+        offset += 4;
+        for _ in 0..num_cols {
+            offset += 4 + read_len(offset)?;
+            offset += 4 + read_len(offset)?;
+            offset = self.skip_column_constraints(data, offset);
+        }
+        // Skip the data page ID
+        offset += 8;
+        (offset + 8 <= self.page_body_end()).then_some(offset)
+    }
+
+    /// Writes a table's rows and bookkeeping fields to its existing pages.
+    ///
+    /// On files that count changes, the write is refused with
+    /// `IsentaError::ConcurrentChange` when the table on disk has been written
+    /// since `table` was loaded, e.g. by another process, instead of
+    /// overwriting those changes. A successful write stores
+    /// `table.change_count + 1`; the caller keeps its copy in step.
+    pub fn update_table_data(&mut self, table: &Table) -> Result<(), IsentaError> {
+        self.upgrade_to_rowids()?;
         // Find the existing schema page for this table
-        if let Some(schema_page_id) = self.find_table_schema_page(&table.name)? {
-            // Read the existing schema page to get the data page ID
-            let schema_page = self.storage.read_page(schema_page_id);
-            
-            // Parse to find data page ID
-            let mut offset = 0;
-            
-            // Skip table name
-            let name_len = u32::from_le_bytes(
+        let (schema_page, offset) = match self.schema_page_for_write(table)? {
+            Some(found) => found,
+            // Table not found, create it as new
+            None => return self.save_table(table, true),
+        };
+        let existing_data_page_id = read_u64(&schema_page.data, offset);
+
+        // Update data pages, reusing the pages of the existing chain; any
+        // it no longer needs are freed
+        let rows: Vec<&Row> = table.rows.iter().collect();
+        let first_data_page = if existing_data_page_id > 0 {
+            let (_, _, chain) = self.load_rows_from_pages(existing_data_page_id, &table.columns)?;
+            let mut reusable: VecDeque<u64> = chain.into_iter().skip(1).collect();
+            let first_data_page =
+                self.save_rows_to_pages(&rows, &table.columns, Some(existing_data_page_id), &mut reusable)?;
+            self.release_pages(reusable)?;
+            first_data_page
+        } else {
+            self.save_rows_to_pages(&rows, &table.columns, None, &mut VecDeque::new())?
+        };
+
+        self.write_table_fields(schema_page, offset, first_data_page.id, table)
+    }
+
+    /// Removes the rows whose rowid is in `deleted` from a table's data
+    /// pages. Only the pages that held those rows are rewritten; pages left
+    /// empty are unlinked from the chain and freed. `table` holds the rows
+    /// that remain, and its bookkeeping fields are written as by
+    /// `update_table_data`, including the check for concurrent changes.
+    pub fn delete_table_rows(&mut self, table: &Table, deleted: &HashSet<u64>) -> Result<(), IsentaError> {
+        self.upgrade_to_rowids()?;
+        let (schema_page, offset) = match self.schema_page_for_write(table)? {
+            Some(found) => found,
+            None => return self.save_table(table, true),
+        };
+        let first_data_page = read_u64(&schema_page.data, offset);
+        if first_data_page == 0 {
+            return self.update_table_data(table);
+        }
+
+        // The pages that keep rows, each with its rows, whether they changed,
+        // and the page it originally linked to
+        let mut kept: Vec<(u64, Vec<Row>, bool, Option<u64>)> = Vec::new();
+        let mut emptied = Vec::new();
+        let mut current = Some(first_data_page);
+        let mut visited = HashSet::new();
+        while let Some(page_id) = current.filter(|&page_id| visited.insert(page_id)) {
+            let page = self.storage.read_page(page_id)?;
+            if page.data.iter().all(|&b| b == 0) || self.check_page_type(&page, PageType::Data).is_err() {
+                break;
+            }
+            let DataPage { mut rows, next: next_page, .. } = self.decode_data_page(&page, &table.columns)?;
+            let before = rows.len();
+            rows.retain(|row| !deleted.contains(&row.rowid));
+            if rows.is_empty() && before > 0 {
+                emptied.push(page_id);
+            } else {
+                let changed = rows.len() != before;
+                kept.push((page_id, rows, changed, next_page));
+            }
+            current = next_page;
+        }
+        // An emptied table keeps its first page, holding no rows
+        if kept.is_empty() {
+            emptied.retain(|&page_id| page_id != first_data_page);
+            kept.push((first_data_page, Vec::new(), true, None));
+        }
+
+        // Relink the pages that are left, rewriting those whose rows or
+        // next page changed; their rows fit, being a subset of what they held
+        let col_types: Vec<String> = table.columns.iter().map(|c| c.data_type.to_uppercase()).collect();
+        let next_ids: Vec<u64> = kept.iter().skip(1).map(|(page_id, ..)| *page_id).chain([0]).collect();
+        for ((page_id, rows, changed, old_next), next_id) in kept.iter().zip(next_ids) {
+            if !changed && old_next.unwrap_or(0) == next_id {
+                continue;
+            }
+            let rows: Vec<&Row> = rows.iter().collect();
+            let (placed, body) = self.encode_page_rows(&rows, &col_types);
+            if placed.len() != rows.len() {
+                // Not expected; fall back to rewriting the whole chain
+                return self.update_table_data(table);
+            }
+            let mut page = Page::new(*page_id);
+            self.set_page_type(&mut page, PageType::Data);
+            page.data[..body.len()].copy_from_slice(&body);
+            page.data[body.len()..body.len() + 8].copy_from_slice(&next_id.to_le_bytes());
+            self.storage.write_page(&page)?;
+        }
+        self.release_pages(emptied)?;
+
+        let first_data_page = kept[0].0;
+        self.write_table_fields(schema_page, offset, first_data_page, table)
+    }
+
+    /// Finds the schema page of `table` and the offset of its first data
+    /// page ID, which the next schema page ID and the bookkeeping fields
+    /// follow. Fails with `IsentaError::ConcurrentChange` if the table was
+    /// written since `table` was loaded, or `IsentaError::TableLocked` if
+    /// another connection's transaction has written it; `None` if it isn't
+    /// stored.
+    fn schema_page_for_write(&mut self, table: &Table) -> Result<Option<(Page, usize)>, IsentaError> {
+        self.lock_table(&table.name)?;
+        let schema_page_id = match self.find_table_schema_page(&table.name)? {
+            Some(schema_page_id) => schema_page_id,
+            None => return Ok(None),
+        };
+        let schema_page = self.storage.read_page(schema_page_id)?;
+        let offset = self.data_page_id_offset(&schema_page)?;
+
+        // The change count follows the data page ID, next schema page ID,
+        // timestamps and next rowid
+        let counts_changes = self.version >= CHANGE_COUNT_VERSION && offset + 48 <= self.page_body_end();
+        if counts_changes && read_u64(&schema_page.data, offset + 40) != table.change_count {
+            return Err(IsentaError::ConcurrentChange(table.name.clone()));
+        }
+        Ok(Some((schema_page, offset)))
+    }
+
+    /// The offset of the first data page ID in a schema page, just past the
+    /// table name and columns.
+    fn data_page_id_offset(&self, schema_page: &Page) -> Result<usize, IsentaError> {
+        let mut offset = 0;
+
+        // Skip table name
+        let name_len = u32::from_le_bytes(
+            schema_page.data[offset..offset + 4]
+                .try_into()
+                .map_err(|_| corrupt("Failed to read table name length"))?,
+        ) as usize;
+        offset += 4 + name_len;
+
+        // Skip columns
+        let num_cols = u32::from_le_bytes(
+            schema_page.data[offset..offset + 4]
+                .try_into()
+                .map_err(|_| corrupt("Failed to read column count"))?,
+        );
+        offset += 4;
+
+        for _ in 0..num_cols {
+            let col_name_len = u32::from_le_bytes(
                 schema_page.data[offset..offset + 4]
                     .try_into()
-                    .map_err(|_| "Failed to read table name length")?,
+                    .map_err(|_| corrupt("Failed to read column name length"))?,
             ) as usize;
-            offset += 4 + name_len;
-            
-            // Skip columns
-            let num_cols = u32::from_le_bytes(
+            offset += 4 + col_name_len;
+
+            let type_len = u32::from_le_bytes(
                 schema_page.data[offset..offset + 4]
                     .try_into()
-                    .map_err(|_| "Failed to read column count")?,
-            );
-            offset += 4;
-            
-            for _ in 0..num_cols {
-                let col_name_len = u32::from_le_bytes(
-                    schema_page.data[offset..offset + 4]
-                        .try_into()
-                        .map_err(|_| "Failed to read column name length")?,
-                ) as usize;
-                offset += 4 + col_name_len;
-                
-                let type_len = u32::from_le_bytes(
-                    schema_page.data[offset..offset + 4]
-                        .try_into()
-                        .map_err(|_| "Failed to read data type length")?,
-                ) as usize;
-                offset += 4 + type_len;
+                    .map_err(|_| corrupt("Failed to read data type length"))?,
+            ) as usize;
+            offset = self.skip_column_constraints(&schema_page.data, offset + 4 + type_len);
+        }
+        if offset + 8 > schema_page.data.len() {
+            return Err(corrupt("Failed to read data page ID"));
+        }
+        Ok(offset)
+    }
+
+    /// The first page of the data-page chain of the table named `table`, for
+    /// reading its rows a page at a time with `read_data_page`. `None` if the
+    /// table isn't stored or has no data pages.
+    pub fn first_data_page(&mut self, table: &str) -> Result<Option<u64>, IsentaError> {
+        let schema_page_id = match self.find_table_schema_page(table)? {
+            Some(schema_page_id) => schema_page_id,
+            None => return Ok(None),
+        };
+        let schema_page = self.storage.read_page(schema_page_id)?;
+        let offset = self.data_page_id_offset(&schema_page)?;
+        Ok(Some(read_u64(&schema_page.data, offset)).filter(|&page_id| page_id > 0))
+    }
+
+    /// Reads the rows stored in one data page of a table with `columns`, and
+    /// the page its chain continues on. Rows of files from before
+    /// `ROWID_VERSION` have rowid 0, being numbered by their position in the
+    /// whole chain.
+    pub fn read_data_page(&mut self, page_id: u64, columns: &[Column]) -> Result<(Vec<Row>, Option<u64>), IsentaError> {
+        let page = self.storage.read_page(page_id)?;
+        if page.data.iter().all(|&b| b == 0) {
+            return Ok((Vec::new(), None));
+        }
+        self.check_page_type(&page, PageType::Data).map_err(|message| corrupt(&message))?;
+        let data_page = self.decode_data_page(&page, columns)?;
+        Ok((data_page.rows, data_page.next))
+    }
+
+    /// Walks the data-page chain of `table` and reports how full its pages
+    /// are, such as to see how much space deletes and updates left behind.
+    /// `None` if the table isn't stored.
+    pub fn table_stats(&mut self, table: &Table) -> Result<Option<TableStats>, IsentaError> {
+        let schema_page = match self.find_table_schema_page(&table.name)? {
+            Some(schema_page) => schema_page,
+            None => return Ok(None),
+        };
+        let page = self.storage.read_page(schema_page)?;
+        let first_data_page = read_u64(&page.data, self.data_page_id_offset(&page)?);
+
+        let mut stats = TableStats { schema_page, data_pages: 0, rows: 0, used_bytes: 0, capacity_bytes: 0 };
+        let mut current = (first_data_page != 0).then_some(first_data_page);
+        let mut visited = HashSet::new();
+        while let Some(page_id) = current.filter(|&page_id| visited.insert(page_id)) {
+            let page = self.storage.read_page(page_id)?;
+            // A new table's first page stays zeroed until rows are written
+            if page.data.iter().all(|&b| b == 0) {
+                if page_id == first_data_page {
+                    stats.data_pages += 1;
+                    stats.capacity_bytes += self.page_body_end() as u64;
+                }
+                break;
             }
-            
-            // Read existing data page ID
-            let existing_data_page_id = u64::from_le_bytes(
-                schema_page.data[offset..offset + 8]
-                    .try_into()
-                    .map_err(|_| "Failed to read data page ID")?,
-            );
-            
-            // Update data pages, reusing the first page if possible
-            let first_data_page = if existing_data_page_id > 0 {
-                self.save_rows_to_pages(&table.rows, &table.columns, Some(existing_data_page_id))?
+            if self.check_page_type(&page, PageType::Data).is_err() {
+                break;
+            }
+            let data_page = self.decode_data_page(&page, &table.columns)?;
+            stats.data_pages += 1;
+            stats.rows += data_page.rows.len() as u64;
+            stats.used_bytes += data_page.used as u64;
+            stats.capacity_bytes += self.page_body_end() as u64;
+            current = data_page.next;
+        }
+        Ok(Some(stats))
+    }
+
+    /// Updates a schema page found by `schema_page_for_write` with the
+    /// table's first data page, modification time, next rowid and change
+    /// count.
+    fn write_table_fields(
+        &mut self,
+        mut schema_page: Page,
+        offset: usize,
+        first_data_page: u64,
+        table: &Table,
+    ) -> Result<(), IsentaError> {
+        schema_page.data[offset..offset + 8].copy_from_slice(&first_data_page.to_le_bytes());
+        // Update the modification time, which follows the next schema page ID
+        if self.version >= TIMESTAMP_VERSION && offset + 40 <= schema_page.data.len() {
+            schema_page.data[offset + 24..offset + 32].copy_from_slice(&table.updated_at.to_le_bytes());
+            if self.version >= ROWID_VERSION {
+                schema_page.data[offset + 32..offset + 40].copy_from_slice(&table.next_rowid.to_le_bytes());
+            }
+        }
+        if self.version >= CHANGE_COUNT_VERSION && offset + 48 <= self.page_body_end() {
+            schema_page.data[offset + 40..offset + 48].copy_from_slice(&(table.change_count + 1).to_le_bytes());
+        }
+        self.storage.write_page(&schema_page)
+    }
+
+    /// Rewrites a file from before `ROWID_VERSION` in that version's format
+    /// ahead of its first write. Such files store no rowids, so rows are
+    /// numbered by position each time they are loaded, and a row would come
+    /// back with another rowid once a row before it was deleted. Each row is
+    /// stored with the rowid it was loaded with, and the header records the
+    /// new version.
+    fn upgrade_to_rowids(&mut self) -> Result<(), IsentaError> {
+        if self.version >= ROWID_VERSION {
+            return Ok(());
+        }
+
+        // Read every table and the pages it uses while the old format applies
+        let mut header = self.storage.read_page(HEADER_PAGE_ID)?;
+        let mut tables = Vec::new();
+        let mut schema_page_id = read_u64(&header.data, 12);
+        let mut visited = HashSet::new();
+        while schema_page_id != 0 && visited.insert(schema_page_id) {
+            let Some((table, next_schema_page, _)) = self.read_table_from_page(schema_page_id)? else {
+                break;
+            };
+            self.lock_table(&table.name)?;
+            let schema_page = self.storage.read_page(schema_page_id)?;
+            let offset = self.data_page_id_offset(&schema_page)?;
+            let first_data_page = read_u64(&schema_page.data, offset);
+            let chain = if first_data_page > 0 {
+                self.load_rows_from_pages(first_data_page, &table.columns)?.2
             } else {
-                self.save_rows_to_pages(&table.rows, &table.columns, None)?
+                Vec::new()
             };
-            
-            // Update the schema page with the new data page ID
-            let mut updated_schema_page = schema_page;
-            updated_schema_page.data[offset..offset + 8].copy_from_slice(&first_data_page.id.to_le_bytes());
-            self.storage.write_page(&updated_schema_page);
-            
-            Ok(())
+            tables.push((table, schema_page, offset, chain));
+            schema_page_id = next_schema_page;
+        }
+
+        self.version = ROWID_VERSION;
+        header.data[8..12].copy_from_slice(&ROWID_VERSION.to_le_bytes());
+        self.storage.write_page(&header)?;
+        for (table, schema_page, offset, chain) in tables {
+            let rows: Vec<&Row> = table.rows.iter().collect();
+            let mut reusable: VecDeque<u64> = chain.iter().skip(1).copied().collect();
+            let first_data_page = self.save_rows_to_pages(&rows, &table.columns, chain.first().copied(), &mut reusable)?;
+            self.release_pages(reusable)?;
+            self.write_table_fields(schema_page, offset, first_data_page.id, &table)?;
+        }
+        Ok(())
+    }
+
+    /// Rewrites the schema page of the table stored as `old_name` with the
+    /// name and columns of `table`, for a rename. The page keeps its place in
+    /// the schema chain, its data pages and its bookkeeping fields; rows are
+    /// stored by position, so they are unaffected.
+    ///
+    /// Like `update_table_data`, the write is refused with
+    /// `IsentaError::ConcurrentChange` when the table has been written since
+    /// `table` was loaded, and a successful one stores
+    /// `table.change_count + 1`.
+    pub fn rewrite_table_schema(&mut self, old_name: &str, table: &Table) -> Result<(), IsentaError> {
+        self.lock_table(old_name)?;
+        self.lock_table(&table.name)?;
+        self.upgrade_to_rowids()?;
+        let schema_page_id = self
+            .find_table_schema_page(old_name)?
+            .ok_or_else(|| IsentaError::TableNotFound(old_name.to_string()))?;
+        if !names_match(old_name, &table.name) && self.find_table_schema_page(&table.name)?.is_some() {
+            return Err(IsentaError::TableExists(table.name.clone()));
+        }
+        check_table_name(&table.name)?;
+        self.check_schema_size(&table.name, &table.columns)?;
+
+        let old_page = self.storage.read_page(schema_page_id)?;
+        let old_next_offset = self
+            .next_schema_page_offset(&old_page.data)
+            .ok_or_else(|| corrupt(&format!("Invalid schema page {} for table '{}'", schema_page_id, old_name)))?;
+        // The data page ID, then the next schema page ID, timestamps, next
+        // rowid and change count
+        let body_end = self.page_body_end();
+        let fields = old_page.data[old_next_offset - 8..body_end.min(old_next_offset + 40)].to_vec();
+
+        let counts_changes = self.version >= CHANGE_COUNT_VERSION && fields.len() == 48;
+        if counts_changes && read_u64(&fields, 40) != table.change_count {
+            return Err(IsentaError::ConcurrentChange(old_name.to_string()));
+        }
+
+        let mut page = Page::new(schema_page_id);
+        let offset = self.encode_schema(&mut page, table)?;
+        if offset + fields.len() > body_end {
+            return Err(IsentaError::Constraint("Page overflow".to_string()));
+        }
+        page.data[offset..offset + fields.len()].copy_from_slice(&fields);
+        if counts_changes {
+            page.data[offset + 40..offset + 48].copy_from_slice(&(table.change_count + 1).to_le_bytes());
+        }
+        self.storage.write_page(&page)
+    }
+
+    /// Removes `table` from the file: its schema page is unlinked from the
+    /// schema chain, the header's table count goes down by one, and the
+    /// schema page and data pages are released for reuse.
+    ///
+    /// Like `update_table_data`, the drop is refused with
+    /// `IsentaError::ConcurrentChange` when the table has been written since
+    /// `table` was loaded.
+    pub fn drop_table(&mut self, table: &Table) -> Result<(), IsentaError> {
+        self.upgrade_to_rowids()?;
+        let (schema_page, offset) = self
+            .schema_page_for_write(table)?
+            .ok_or_else(|| IsentaError::TableNotFound(table.name.clone()))?;
+        let first_data_page = read_u64(&schema_page.data, offset);
+        let next_schema_page = read_u64(&schema_page.data, offset + 8);
+
+        // Point whatever referred to the schema page at the one after it
+        let mut header = self.storage.read_page(HEADER_PAGE_ID)?;
+        let schema_root = read_u64(&header.data, 12);
+        if schema_root == schema_page.id {
+            header.data[12..20].copy_from_slice(&next_schema_page.to_le_bytes());
         } else {
-            // Table not found, create it as new
-            self.save_table(table, true)
+            let mut current_page_id = schema_root;
+            let mut visited = HashSet::new();
+            loop {
+                if current_page_id == 0 || !visited.insert(current_page_id) {
+                    return Err(corrupt(&format!("Schema page {} of table '{}' is not in the schema chain", schema_page.id, table.name)));
+                }
+                let mut page = self.storage.read_page(current_page_id)?;
+                let next_offset = self
+                    .next_schema_page_offset(&page.data)
+                    .ok_or_else(|| corrupt(&format!("Invalid schema page {}", current_page_id)))?;
+                let next_page_id = read_u64(&page.data, next_offset);
+                if next_page_id == schema_page.id {
+                    page.data[next_offset..next_offset + 8].copy_from_slice(&next_schema_page.to_le_bytes());
+                    self.storage.write_page(&page)?;
+                    break;
+                }
+                current_page_id = next_page_id;
+            }
+        }
+        let num_tables = u32::from_le_bytes(
+            header.data[20..24]
+                .try_into()
+                .map_err(|_| corrupt("Failed to read table count"))?,
+        );
+        header.data[20..24].copy_from_slice(&num_tables.saturating_sub(1).to_le_bytes());
+        self.storage.write_page(&header)?;
+
+        let mut pages = Vec::new();
+        if first_data_page != 0 {
+            let (_, _, chain) = self.load_rows_from_pages(first_data_page, &table.columns)?;
+            pages = chain;
+            // A new table's first page stays zeroed until rows are written
+            if !pages.contains(&first_data_page) {
+                pages.push(first_data_page);
+            }
+        }
+        pages.push(schema_page.id);
+        self.release_pages(pages)
+    }
+}
+
+/// Rejects a table name that can't be stored in a schema page.
+fn check_table_name(name: &str) -> Result<(), IsentaError> {
+    if name.is_empty() {
+        return Err(IsentaError::Constraint("Table name cannot be empty".to_string()));
+    }
+    if name.len() > MAX_TABLE_NAME_LEN {
+        return Err(IsentaError::Constraint(format!(
+            "Table name too long: {} bytes, at most {} allowed",
+            name.len(),
+            MAX_TABLE_NAME_LEN
+        )));
+    }
+    Ok(())
+}
+
+/// Reads the length-prefixed table name at the start of a schema page.
+/// Returns `None` when the length is zero, over `MAX_TABLE_NAME_LEN` or
+/// runs past the page.
+fn read_table_name(data: &[u8]) -> Option<&[u8]> {
+    let name_len = u32::from_le_bytes(data.get(0..4)?.try_into().ok()?) as usize;
+    if name_len == 0 || name_len > MAX_TABLE_NAME_LEN {
+        return None;
+    }
+    data.get(4..4 + name_len)
+}
+
+/// Formats bytes as offset, hex and ASCII columns, 16 bytes per line. Runs
+/// of identical lines are collapsed into a single `*`.
+fn hex_dump(data: &[u8]) -> String {
+    let mut output = String::new();
+    let mut previous: Option<&[u8]> = None;
+    let mut collapsed = false;
+    let last_line = (data.len() - 1) / 16 * 16;
+
+    for (index, line) in data.chunks(16).enumerate() {
+        let offset = index * 16;
+        if previous == Some(line) && offset != last_line {
+            if !collapsed {
+                output.push_str("*\n");
+                collapsed = true;
+            }
+            continue;
+        }
+        previous = Some(line);
+        collapsed = false;
+
+        let hex: Vec<String> = line.iter().map(|byte| format!("{:02x}", byte)).collect();
+        let ascii: String = line
+            .iter()
+            .map(|&byte| if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' })
+            .collect();
+        output.push_str(&format!("{:04x}: {:<47}  |{}|\n", offset, hex.join(" "), ascii));
+    }
+
+    output.trim_end().to_string()
+}
+
+/// An error for data in the file that can't be decoded.
+fn corrupt(message: &str) -> IsentaError {
+    IsentaError::Corruption(message.to_string())
+}
+
+/// Reads a little-endian u64 at `offset`; the caller checks the bounds.
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&data[offset..offset + 8]);
+    u64::from_le_bytes(bytes)
+}
+
+/// Decodes one row starting at `offset`, advancing it past the row.
+fn decode_row(
+    data: &[u8],
+    offset: &mut usize,
+    columns: &[Column],
+    dictionary: &[String],
+    unreadable: &mut usize,
+) -> Result<Row, IsentaError> {
+    let mut values = Vec::with_capacity(columns.len());
+    for _ in columns {
+        values.push(decode_value(data, offset, dictionary, unreadable)?);
+    }
+    Ok(Row { rowid: 0, values })
+}
+
+/// Decodes every value up to the end of `data`, however many the schema expects.
+/// Used for rows with a length prefix, so rows written under a different
+/// column count still load and can be reconciled by the engine.
+fn decode_row_to_end(
+    data: &[u8],
+    offset: &mut usize,
+    dictionary: &[String],
+    unreadable: &mut usize,
+) -> Result<Row, IsentaError> {
+    let mut values = Vec::new();
+    while *offset < data.len() {
+        values.push(decode_value(data, offset, dictionary, unreadable)?);
+    }
+    Ok(Row { rowid: 0, values })
+}
+
+/// Reads the rowid at the start of a version 5+ row, advancing `offset` past it.
+fn decode_rowid(data: &[u8], offset: &mut usize) -> Result<u64, IsentaError> {
+    if *offset + 8 > data.len() {
+        return Err(corrupt("Truncated rowid"));
+    }
+    let rowid = read_u64(data, *offset);
+    *offset += 8;
+    Ok(rowid)
+}
+
+/// Decodes one tagged value starting at `offset`, advancing it past the value.
+/// Text that can't be decoded is replaced with `UNREADABLE_TEXT` and counted
+/// in `unreadable`; see `read_text_value`.
+fn decode_value(
+    data: &[u8],
+    offset: &mut usize,
+    dictionary: &[String],
+    unreadable: &mut usize,
+) -> Result<Option<String>, IsentaError> {
+    let value_type = *data.get(*offset).ok_or_else(|| corrupt("Unexpected end of row"))?;
+    *offset += 1;
+
+    match value_type {
+        TYPE_NULL => Ok(None),
+        TYPE_INT => {
+            let bytes = data.get(*offset..*offset + 8).ok_or_else(|| corrupt("Truncated integer value"))?;
+            let int_val = i64::from_le_bytes(bytes.try_into().map_err(|_| corrupt("Truncated integer value"))?);
+            *offset += 8;
+            Ok(Some(int_val.to_string()))
+        }
+        TYPE_TEXT_REF => {
+            let bytes = data.get(*offset..*offset + 2).ok_or_else(|| corrupt("Truncated dictionary index"))?;
+            let index = u16::from_le_bytes([bytes[0], bytes[1]]) as usize;
+            *offset += 2;
+            let value = dictionary.get(index).ok_or_else(|| corrupt("Dictionary index out of range"))?;
+            Ok(Some(value.clone()))
+        }
+        TYPE_BLOB => {
+            let len_bytes = data.get(*offset..*offset + 4).ok_or_else(|| corrupt("Truncated blob length"))?;
+            let len = u32::from_le_bytes(len_bytes.try_into().map_err(|_| corrupt("Truncated blob length"))?) as usize;
+            let bytes = data.get(*offset + 4..*offset + 4 + len).ok_or_else(|| corrupt("Truncated blob value"))?;
+            *offset += 4 + len;
+            Ok(Some(blob_literal(bytes)))
+        }
+        // TYPE_TEXT, and unknown tags read as the legacy length-prefixed string format
+        _ => Ok(Some(read_text_value(data, offset, unreadable)?)),
+    }
+}
+
+/// Reads a length-prefixed text value like `read_text`, but replaces one
+/// that isn't valid UTF-8 or whose length runs past the end of `data` with
+/// `UNREADABLE_TEXT`, counting it in `unreadable`. After a bad length the
+/// values that follow can't be found, so `offset` is left at the end.
+fn read_text_value(data: &[u8], offset: &mut usize, unreadable: &mut usize) -> Result<String, IsentaError> {
+    let len_bytes = data.get(*offset..*offset + 4).ok_or_else(|| corrupt("Truncated text length"))?;
+    let len = u32::from_le_bytes(len_bytes.try_into().map_err(|_| corrupt("Truncated text length"))?) as usize;
+    *offset += 4;
+
+    let value = match data.get(*offset..*offset + len) {
+        Some(bytes) => {
+            *offset += len;
+            String::from_utf8(bytes.to_vec()).ok()
+        }
+        None => {
+            *offset = data.len();
+            None
+        }
+    };
+    Ok(value.unwrap_or_else(|| {
+        *unreadable += 1;
+        UNREADABLE_TEXT.to_string()
+    }))
+}
+
+/// Reads a length-prefixed UTF-8 string starting at `offset`, advancing it past the string.
+fn read_text(data: &[u8], offset: &mut usize) -> Result<String, IsentaError> {
+    let len_bytes = data.get(*offset..*offset + 4).ok_or_else(|| corrupt("Truncated text length"))?;
+    let len = u32::from_le_bytes(len_bytes.try_into().map_err(|_| corrupt("Truncated text length"))?) as usize;
+    *offset += 4;
+
+    let bytes = data.get(*offset..*offset + len).ok_or_else(|| corrupt("Text length out of range"))?;
+    let value = String::from_utf8(bytes.to_vec()).map_err(|_| corrupt("Invalid text encoding"))?;
+    *offset += len;
+    Ok(value)
+}
+
+/// Accumulates the rows of one data page in a single encoding.
+struct PageEncoder {
+    /// Distinct text values, when encoding with a dictionary
+    dictionary: Option<Vec<String>>,
+    dictionary_len: usize,
+    /// Whether each row is prefixed with its encoded length
+    row_lengths: bool,
+    /// Whether each row stores its rowid before its values
+    rowids: bool,
+    /// Whether BLOB values are stored as raw bytes rather than hex text
+    blobs: bool,
+    /// Whether later rows may still be tried after one doesn't fit, which
+    /// stores rows out of order, so only when rowids can restore it
+    skip_ahead: bool,
+    rows: Vec<u8>,
+    placed: HashSet<usize>,
+    misses: usize,
+    done: bool,
+}
+
+impl PageEncoder {
+    fn new(use_dictionary: bool, row_lengths: bool, rowids: bool, blobs: bool) -> Self {
+        PageEncoder {
+            dictionary: if use_dictionary { Some(Vec::new()) } else { None },
+            dictionary_len: 0,
+            row_lengths,
+            rowids,
+            blobs,
+            skip_ahead: rowids,
+            rows: Vec::new(),
+            placed: HashSet::new(),
+            misses: 0,
+            done: false,
+        }
+    }
+
+    /// Bytes used by the dictionary and the encoded rows
+    fn used(&self) -> usize {
+        self.dictionary_len + self.rows.len()
+    }
+
+    /// Adds the row if it fits in `capacity`, otherwise records a miss and
+    /// decides whether to keep trying later rows.
+    fn try_add(&mut self, index: usize, row: &Row, col_types: &[String], capacity: usize) {
+        if self.done {
+            return;
+        }
+
+        if row.values.len() == col_types.len() {
+            let entries_before = self.dictionary.as_ref().map_or(0, |d| d.len());
+            let mut encoded = Vec::new();
+            if self.row_lengths {
+                // Patched with the actual length below
+                encoded.extend_from_slice(&0u16.to_le_bytes());
+            }
+            if self.rowids {
+                encoded.extend_from_slice(&row.rowid.to_le_bytes());
+            }
+            for (value, col_type) in row.values.iter().zip(col_types) {
+                encode_value(value.as_deref(), col_type, self.blobs, self.dictionary.as_mut(), &mut encoded);
+            }
+            if self.row_lengths {
+                let row_len = (encoded.len() - 2) as u16;
+                encoded[..2].copy_from_slice(&row_len.to_le_bytes());
+            }
+
+            let (entries, added_len) = match &self.dictionary {
+                Some(dictionary) => (
+                    dictionary.len(),
+                    dictionary[entries_before..].iter().map(|v| 4 + v.len()).sum(),
+                ),
+                None => (0, 0),
+            };
+
+            if entries <= u16::MAX as usize + 1 && self.used() + added_len + encoded.len() <= capacity {
+                self.dictionary_len += added_len;
+                self.rows.extend_from_slice(&encoded);
+                self.placed.insert(index);
+                return;
+            }
+
+            if let Some(dictionary) = self.dictionary.as_mut() {
+                dictionary.truncate(entries_before);
+            }
+        }
+
+        self.misses += 1;
+        if !self.skip_ahead || self.used() * 100 >= capacity * MIN_PAGE_FILL || self.misses >= MAX_FILL_ATTEMPTS {
+            self.done = true;
+        }
+    }
+}
+
+/// Appends the tagged binary encoding of a single value to `out`.
+///
+/// When a dictionary is supplied, text values are added to it (once) and
+/// written as a reference to their dictionary index instead of inline. With
+/// `blobs` set, the values of BLOB columns are written as their raw bytes.
+fn encode_value(
+    value: Option<&str>,
+    col_type: &str,
+    blobs: bool,
+    dictionary: Option<&mut Vec<String>>,
+    out: &mut Vec<u8>,
+) {
+    let value = match value {
+        Some(value) => value,
+        None => {
+            out.push(TYPE_NULL);
+            return;
+        }
+    };
+
+    if is_integer_type(col_type) {
+        // Values that don't parse as integers fall back to text
+        if let Ok(int_val) = value.parse::<i64>() {
+            out.push(TYPE_INT);
+            out.extend_from_slice(&int_val.to_le_bytes());
+            return;
+        }
+    }
+
+    if blobs && is_blob_type(col_type) {
+        // Values that aren't hex literals fall back to text
+        if let Ok(bytes) = blob_bytes(value) {
+            out.push(TYPE_BLOB);
+            out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+            out.extend_from_slice(&bytes);
+            return;
+        }
+    }
+
+    match dictionary {
+        Some(dictionary) => {
+            let index = match dictionary.iter().position(|entry| entry == value) {
+                Some(index) => index,
+                None => {
+                    dictionary.push(value.to_string());
+                    dictionary.len() - 1
+                }
+            };
+            out.push(TYPE_TEXT_REF);
+            out.extend_from_slice(&(index as u16).to_le_bytes());
+        }
+        None => {
+            out.push(TYPE_TEXT);
+            out.extend_from_slice(&(value.len() as u32).to_le_bytes());
+            out.extend_from_slice(value.as_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encoded(value: Option<&str>, col_type: &str) -> Vec<u8> {
+        let mut out = Vec::new();
+        encode_value(value, col_type, true, None, &mut out);
+        out
+    }
+
+    #[test]
+    fn values_round_trip_through_their_encoding() {
+        for (value, col_type) in [
+            (Some("42"), "INTEGER"),
+            (Some("-7"), "INT"),
+            (Some("hello"), "TEXT"),
+            (None, "TEXT"),
+            (Some("X'00FF10'"), "BLOB"),
+            (Some("X''"), "BLOB"),
+        ] {
+            let bytes = encoded(value, col_type);
+            let mut offset = 0;
+            assert_eq!(decode_value(&bytes, &mut offset, &[], &mut 0).unwrap().as_deref(), value);
+            assert_eq!(offset, bytes.len());
+        }
+    }
+
+    #[test]
+    fn non_numeric_integer_values_are_stored_as_text() {
+        assert_eq!(encoded(Some("abc"), "INTEGER")[0], TYPE_TEXT);
+    }
+
+    #[test]
+    fn blobs_are_stored_as_raw_bytes() {
+        assert_eq!(encoded(Some("X'4869'"), "BLOB"), vec![TYPE_BLOB, 2, 0, 0, 0, 0x48, 0x69]);
+        let mut out = Vec::new();
+        encode_value(Some("X'4869'"), "BLOB", false, None, &mut out);
+        assert_eq!(out[0], TYPE_TEXT);
+    }
+
+    #[test]
+    fn dictionary_values_are_stored_once() {
+        let mut dictionary = Vec::new();
+        let mut out = Vec::new();
+        encode_value(Some("pending"), "TEXT", true, Some(&mut dictionary), &mut out);
+        encode_value(Some("pending"), "TEXT", true, Some(&mut dictionary), &mut out);
+        assert_eq!(dictionary, vec!["pending".to_string()]);
+
+        let mut offset = 0;
+        for _ in 0..2 {
+            assert_eq!(decode_value(&out, &mut offset, &dictionary, &mut 0).unwrap().as_deref(), Some("pending"));
+        }
+    }
+
+    #[test]
+    fn truncated_values_are_errors() {
+        let bytes = encoded(Some("12345"), "INTEGER");
+        let mut offset = 0;
+        assert!(decode_value(&bytes[..4], &mut offset, &[], &mut 0).is_err());
+        assert!(decode_value(&[TYPE_TEXT_REF, 0, 0], &mut 0, &[], &mut 0).is_err());
+    }
+
+    #[test]
+    fn undecodable_text_is_replaced() {
+        let mut bytes = encoded(Some("ok"), "TEXT");
+        bytes[5] = 0xff;
+        let (mut offset, mut unreadable) = (0, 0);
+        assert_eq!(decode_value(&bytes, &mut offset, &[], &mut unreadable).unwrap().as_deref(), Some(UNREADABLE_TEXT));
+        assert_eq!((offset, unreadable), (bytes.len(), 1));
+
+        // A length past the end leaves nothing more to read
+        bytes[1..5].copy_from_slice(&100u32.to_le_bytes());
+        let mut offset = 0;
+        assert_eq!(read_text_value(&bytes[1..], &mut offset, &mut unreadable).unwrap(), UNREADABLE_TEXT);
+        assert_eq!((offset, unreadable), (bytes.len() - 1, 2));
+    }
+
+    #[test]
+    fn table_names_must_have_a_valid_length() {
+        let mut data = vec![0u8; 16];
+        data[..4].copy_from_slice(&3u32.to_le_bytes());
+        data[4..7].copy_from_slice(b"abc");
+        assert_eq!(read_table_name(&data), Some(&b"abc"[..]));
+
+        data[..4].copy_from_slice(&0u32.to_le_bytes());
+        assert_eq!(read_table_name(&data), None);
+        data[..4].copy_from_slice(&20u32.to_le_bytes());
+        assert_eq!(read_table_name(&data), None);
+    }
+
+    #[test]
+    fn page_type_tags_round_trip() {
+        for page_type in [PageType::Header, PageType::Schema, PageType::Data, PageType::Free, PageType::FreeList] {
+            assert_eq!(PageType::from_tag(page_type as u8), Some(page_type));
         }
+        assert_eq!(PageType::from_tag(0), None);
     }
 }
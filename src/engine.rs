@@ -1,23 +1,243 @@
-use crate::parser::{Column, WhereClause};
+use crate::audit::AuditLog;
+use crate::error::IsentaError;
+use crate::json::{self, PathStep};
+use crate::log::{self, Logger};
+use crate::parser::{is_quoted, split_top_level, Aggregate, AggregateFunction, Column, Expr, Limit, OrderBy, Parser, SelectItem, WhereClause, WhereExpr};
+use crate::database::TableStats;
+use crate::storage::{Durability, MEMORY_PATH};
 use regex::Regex;
-use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Table {
     pub name: String,
     pub columns: Vec<Column>,
     pub rows: Vec<Row>,
+    /// When the table was created, in Unix epoch seconds (0 if unknown)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub created_at: u64,
+    /// When the table was last modified, in Unix epoch seconds (0 if unknown)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub updated_at: u64,
+    /// The rowid the next inserted row receives
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub next_rowid: u64,
+    /// How many times the table has been written, used to detect a write by
+    /// another process since the table was loaded. Bookkeeping for the
+    /// database file, so it isn't serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub change_count: u64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+impl Table {
+    /// The CREATE TABLE statement for the table's schema in a canonical
+    /// form: names with ASCII letters in lowercase, as `names_match` folds
+    /// them, types and constraints as `Column` writes them, columns in order.
+    pub fn schema_ddl(&self) -> String {
+        let columns: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| format!("{} {}", column.name.to_ascii_lowercase(), column.type_definition()))
+            .collect();
+        format!("CREATE TABLE {} ({})", self.name.to_ascii_lowercase(), columns.join(", "))
+    }
+
+    /// Takes `count` rowids from the counter, returning the first. Fails
+    /// without taking any if the counter would run past `u64::MAX`, rather
+    /// than wrapping round to rowids already in use.
+    fn allocate_rowids(&mut self, count: usize) -> Result<u64, IsentaError> {
+        let first = self.next_rowid;
+        self.next_rowid = u64::try_from(count)
+            .ok()
+            .and_then(|count| first.checked_add(count))
+            .ok_or_else(|| IsentaError::Constraint(format!("auto-increment counter exhausted for table '{}'", self.name)))?;
+        Ok(first)
+    }
+
+    /// Takes a rowid for each new row: the one given for it, which moves the
+    /// counter past it so that no later row is given it again, or else the
+    /// next from the counter. Fails without moving the counter if it would
+    /// run past `u64::MAX`, as `allocate_rowids` does.
+    fn assign_rowids(&mut self, given: &[Option<u64>]) -> Result<Vec<u64>, IsentaError> {
+        let mut next = self.next_rowid;
+        let mut rowids = Vec::with_capacity(given.len());
+        for rowid in given {
+            let rowid = rowid.unwrap_or(next);
+            next = match rowid.checked_add(1) {
+                Some(after) => next.max(after),
+                None => {
+                    return Err(IsentaError::Constraint(format!("auto-increment counter exhausted for table '{}'", self.name)));
+                }
+            };
+            rowids.push(rowid);
+        }
+        self.next_rowid = next;
+        Ok(rowids)
+    }
+
+    /// A fingerprint of the table's schema that doesn't depend on its rows.
+    /// Tables with the same `schema_ddl` have the same hash in every build
+    /// and on every platform, so it can be compared across databases.
+    pub fn schema_hash(&self) -> u64 {
+        // 64-bit FNV-1a, which unlike `DefaultHasher` is fixed
+        self.schema_ddl().bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Row {
-    pub values: Vec<String>,
+    /// Identifies the row within its table; assigned on insert and never reused.
+    pub rowid: u64,
+    /// One value per column, `None` for NULL.
+    pub values: Vec<Option<String>>,
+}
+
+/// Name of the virtual column that exposes each row's rowid.
+pub const ROWID_COLUMN: &str = "_rowid";
+
+/// How many row combinations a join may produce before a warning is printed.
+pub const CROSS_JOIN_WARNING_ROWS: usize = 100_000;
+
+/// The size of the buffer CSV exports are written through.
+pub const EXPORT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// The outcome of a query, as returned by the library API.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum QueryResult {
+    /// Rows produced by a query, with the names of their columns.
+    Rows { columns: Vec<String>, rows: Vec<Row> },
+}
+
+impl QueryResult {
+    /// The names of the result columns.
+    pub fn columns(&self) -> &[String] {
+        match self {
+            QueryResult::Rows { columns, .. } => columns,
+        }
+    }
+
+    /// Iterates over the result rows, each paired with the column names.
+    pub fn rows(&self) -> impl Iterator<Item = ResultRow<'_>> {
+        match self {
+            QueryResult::Rows { columns, rows } => rows.iter().map(move |row| ResultRow { columns, row }),
+        }
+    }
+
+    /// The number of result rows.
+    pub fn len(&self) -> usize {
+        match self {
+            QueryResult::Rows { rows, .. } => rows.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops the first `offset` rows, or every row if there are fewer.
+    pub fn offset(&mut self, offset: usize) {
+        match self {
+            QueryResult::Rows { rows, .. } => {
+                rows.drain(..offset.min(rows.len()));
+            }
+        }
+    }
+
+    /// Keeps only the first rows `limit` allows.
+    pub fn limit(&mut self, limit: Limit) {
+        match self {
+            QueryResult::Rows { rows, .. } => rows.truncate(limit.rows(rows.len())),
+        }
+    }
+}
+
+/// A result row with access to its values by column name.
+#[derive(Debug, Clone, Copy)]
+pub struct ResultRow<'a> {
+    columns: &'a [String],
+    row: &'a Row,
+}
+
+impl<'a> ResultRow<'a> {
+    /// The rowid of the table row this result row was produced from.
+    pub fn rowid(&self) -> u64 {
+        self.row.rowid
+    }
+
+    /// The row's values in column order, `None` for NULL.
+    pub fn values(&self) -> &'a [Option<String>] {
+        &self.row.values
+    }
+
+    /// Returns the value of the named column, or `None` if the column doesn't
+    /// exist or the value is NULL.
+    pub fn get(&self, column: &str) -> Option<&'a str> {
+        let index = self.columns.iter().position(|c| names_match(c, column))?;
+        self.row.values.get(index)?.as_deref()
+    }
+
+    /// Returns the value of the named column parsed as an integer.
+    pub fn get_int(&self, column: &str) -> Option<i64> {
+        self.get(column)?.parse().ok()
+    }
+
+    /// Returns the value of the named column parsed as a float.
+    pub fn get_float(&self, column: &str) -> Option<f64> {
+        self.get(column)?.parse().ok()
+    }
+}
+
+/// Serializes as an object mapping each column name to its value.
+#[cfg(feature = "serde")]
+impl Serialize for ResultRow<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let mut map = serializer.serialize_map(Some(self.columns.len()))?;
+        for (column, value) in self.columns.iter().zip(&self.row.values) {
+            map.serialize_entry(column, value)?;
+        }
+        map.end()
+    }
+}
+
+/// Where a result column takes its values from.
+enum Projection {
+    /// The value of the table column at this index
+    Column(usize),
+    /// The row's rowid
+    RowId,
+    /// The value at a path in the JSON of the table column at this index
+    Json(usize, Vec<PathStep>),
+    /// A value computed from columns, such as a `ColumnRef::Cast` or
+    /// `ColumnRef::Coalesce`
+    Derived(ColumnRef),
+    /// The same value for every row
+    Constant(Option<String>),
 }
 
 pub struct Catalog {
     tables: Vec<Table>,
 }
 
+impl Default for Catalog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Catalog {
     pub fn new() -> Self {
         Catalog {
@@ -25,27 +245,35 @@ impl Catalog {
         }
     }
 
-    pub fn create_table(&mut self, name: String, columns: Vec<Column>) -> Result<(), String> {
+    pub fn create_table(&mut self, name: String, columns: Vec<Column>) -> Result<(), IsentaError> {
         // Check if table already exists
-        if self.tables.iter().any(|t| t.name == name) {
-            return Err(format!("Table '{}' already exists", name));
+        if self.tables.iter().any(|t| names_match(&t.name, &name)) {
+            return Err(IsentaError::TableExists(name));
+        }
+        if columns.is_empty() {
+            return Err(IsentaError::Constraint("table must have at least one column".to_string()));
         }
 
+        let now = unix_now();
         let table = Table {
             name,
             columns,
             rows: Vec::new(),
+            created_at: now,
+            updated_at: now,
+            next_rowid: 1,
+            change_count: 0,
         };
         self.tables.push(table);
         Ok(())
     }
 
     pub fn find_table_mut(&mut self, name: &str) -> Option<&mut Table> {
-        self.tables.iter_mut().find(|t| t.name.to_lowercase() == name.to_lowercase())
+        self.tables.iter_mut().find(|t| names_match(&t.name, name))
     }
 
     pub fn find_table(&self, name: &str) -> Option<&Table> {
-        self.tables.iter().find(|t| t.name.to_lowercase() == name.to_lowercase())
+        self.tables.iter().find(|t| names_match(&t.name, name))
     }
 
     pub fn list_tables(&self) -> Vec<&str> {
@@ -56,54 +284,762 @@ impl Catalog {
         &self.tables
     }
 
+    /// The name and row count of every table, without copying any rows.
+    pub fn table_summaries(&self) -> Vec<(String, usize)> {
+        self.tables.iter().map(|t| (t.name.clone(), t.rows.len())).collect()
+    }
+
     pub fn load_tables(&mut self, tables: Vec<Table>) {
         for table in tables {
-            if !self.tables.iter().any(|t| t.name == table.name) {
+            if !self.tables.iter().any(|t| names_match(&t.name, &table.name)) {
                 self.tables.push(table);
             }
         }
     }
     
+    /// Removes a table, returning it; `None` if there is no such table.
+    pub fn drop_table(&mut self, name: &str) -> Option<Table> {
+        let index = self.tables.iter().position(|t| names_match(&t.name, name))?;
+        Some(self.tables.remove(index))
+    }
+
     pub fn add_table(&mut self, table: Table) {
-        if !self.tables.iter().any(|t| t.name.to_lowercase() == table.name.to_lowercase()) {
+        if !self.tables.iter().any(|t| names_match(&t.name, &table.name)) {
             self.tables.push(table);
         }
     }
 }
 
+/// The kind of change made to a table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Insert,
+    Update,
+    Delete,
+    /// A schema change, such as creating a table
+    Ddl,
+}
+
+/// Describes a change that has been written to the database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeEvent {
+    pub table: String,
+    pub kind: ChangeKind,
+}
+
+/// How stored rows whose value count doesn't match their table's column
+/// count are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColumnCountMode {
+    /// Fail any query that reads or rewrites such a row
+    #[default]
+    Strict,
+    /// Pad missing values with NULL, drop extra values and log a warning
+    Lenient,
+}
+
+/// Where `EXPORT` may write its files; see `QueryEngine::set_export_access`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ExportAccess {
+    /// Any path the process can write to
+    #[default]
+    Anywhere,
+    /// Only files inside this directory, with export paths taken relative
+    /// to it
+    Within(PathBuf),
+    /// Every export is refused
+    Disabled,
+}
+
+/// How NULL values are shown in text output unless changed with
+/// `QueryEngine::set_null_string`.
+pub const DEFAULT_NULL_STRING: &str = "NULL";
+
 pub struct QueryEngine {
     catalog: Catalog,
     database: crate::database::Database,
+    on_change: Option<Box<dyn FnMut(ChangeEvent)>>,
+    column_count_mode: ColumnCountMode,
+    null_string: String,
+    max_rows: Option<usize>,
+    timing: bool,
+    relaxed_insert: bool,
+    flush_every: Option<usize>,
+    /// Changes made since the last flush triggered by `flush_every`
+    unflushed_changes: usize,
+    debug: bool,
+    cancel: Option<Arc<AtomicBool>>,
+    logger: Logger,
+    export_access: ExportAccess,
+    /// The database file, which the audit log is kept next to
+    path: String,
+    audit: Option<AuditLog>,
+    /// Auditing was turned off; the log closes once that statement is recorded
+    audit_stopping: bool,
+}
+
+impl Default for QueryEngine {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl QueryEngine {
+    /// Opens `data.db` in the working directory.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file can't be opened; use `try_new` to handle that.
     pub fn new() -> Self {
         Self::with_database("data.db")
     }
 
+    /// Opens `data.db` in the working directory like `new`, returning an
+    /// error instead of panicking when the file can't be opened.
+    pub fn try_new() -> Result<Self, IsentaError> {
+        Self::open("data.db")
+    }
+
+    /// Opens the database at `path`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file can't be opened; use `open` to handle that.
     pub fn with_database(path: &str) -> Self {
-        let mut database = crate::database::Database::new(path)
-            .expect("Failed to initialize database");
+        Self::open(path).expect("Failed to initialize database")
+    }
+
+    /// Opens the database at `path`, returning an error instead of panicking
+    /// when the file can't be opened (e.g. it is a directory or not writable).
+    pub fn open(path: &str) -> Result<Self, IsentaError> {
+        Self::open_with_logger(path, log::stderr())
+    }
+
+    /// Opens the database at `path` like `open`, sending warnings to `logger`
+    /// instead of stderr, including those raised while the file is loaded.
+    pub fn open_with_logger(path: &str, logger: Logger) -> Result<Self, IsentaError> {
+        let mut database = crate::database::Database::with_logger(path, logger.clone())?;
         
         let catalog = database.load_catalog()
             .unwrap_or_else(|e| {
-                eprintln!("Warning: Failed to load catalog: {}. Starting with empty database.", e);
+                logger(&format!("Failed to load catalog: {}. Starting with empty database.", e));
                 Catalog::new()
             });
 
-        QueryEngine {
+        Ok(QueryEngine {
             catalog,
             database,
+            on_change: None,
+            column_count_mode: ColumnCountMode::default(),
+            null_string: DEFAULT_NULL_STRING.to_string(),
+            max_rows: None,
+            timing: false,
+            relaxed_insert: false,
+            flush_every: None,
+            unflushed_changes: 0,
+            debug: false,
+            cancel: None,
+            logger,
+            export_access: ExportAccess::default(),
+            path: path.to_string(),
+            audit: None,
+            audit_stopping: false,
+        })
+    }
+
+    /// Sets when changes reach the database file. With `Durability::Deferred`,
+    /// call `flush()` after a bulk load; unflushed changes are lost on a crash.
+    /// With `Durability::Wal` they are logged first and survive a crash.
+    pub fn set_durability(&mut self, durability: Durability) -> Result<(), IsentaError> {
+        self.database.set_durability(durability)
+    }
+
+    /// Relinks every schema page in the file into a fresh schema chain and
+    /// reloads the catalog from it; see `Database::rebuild_schema_chain`.
+    /// Returns the number of tables linked.
+    pub fn reindex_schema(&mut self) -> Result<usize, IsentaError> {
+        let tables = self.database.rebuild_schema_chain()?;
+        self.catalog = self.database.load_catalog()?;
+        Ok(tables)
+    }
+
+    /// Reads the header fields and size of the database file.
+    pub fn file_info(&mut self) -> Result<crate::database::FileInfo, IsentaError> {
+        self.database.file_info()
+    }
+
+    /// Returns the problems with the file that loading the tables worked
+    /// around, e.g. to show them after opening a damaged database. See
+    /// `Database::take_warnings`.
+    pub fn take_load_warnings(&mut self) -> Vec<crate::database::LoadWarning> {
+        self.database.take_warnings()
+    }
+
+    /// Installs a flag that stops a running SELECT with
+    /// `IsentaError::Cancelled` once it is set, e.g. from a Ctrl-C handler.
+    /// The flag isn't cleared by the engine; reset it before the next query.
+    pub fn set_cancel_flag(&mut self, flag: Arc<AtomicBool>) {
+        self.cancel = Some(flag);
+    }
+
+    /// Sets where `EXPORT` may write. A server taking statements from
+    /// clients should restrict it, since an unrestricted export can create
+    /// or overwrite any file the server can write to.
+    pub fn set_export_access(&mut self, access: ExportAccess) {
+        self.export_access = access;
+    }
+
+    /// Resolves the path an export to `path` writes, as allowed by the
+    /// export access. Inside an export directory, the path must be relative
+    /// and may not climb out of it, including through a symbolic link.
+    fn export_path(&self, path: &str) -> Result<PathBuf, IsentaError> {
+        let dir = match &self.export_access {
+            ExportAccess::Anywhere => return Ok(PathBuf::from(path)),
+            ExportAccess::Within(dir) => dir,
+            ExportAccess::Disabled => return Err(IsentaError::InvalidQuery("EXPORT is disabled".to_string())),
+        };
+        let outside = || IsentaError::InvalidQuery(format!("EXPORT can only write files inside the export directory, not '{}'", path));
+        let relative = Path::new(path);
+        if path.is_empty() || !relative.components().all(|component| matches!(component, Component::Normal(_))) {
+            return Err(outside());
+        }
+        let target = dir.join(relative);
+        let dir = dir
+            .canonicalize()
+            .map_err(|e| IsentaError::Io(format!("cannot open the export directory '{}': {}", dir.display(), e)))?;
+        let parent = target.parent().and_then(|parent| parent.canonicalize().ok()).ok_or_else(outside)?;
+        if !parent.starts_with(&dir) || target.is_symlink() {
+            return Err(outside());
+        }
+        Ok(target)
+    }
+
+    /// Fails with `IsentaError::Cancelled` if the cancel flag has been set.
+    fn check_cancelled(&self) -> Result<(), IsentaError> {
+        match &self.cancel {
+            Some(flag) if flag.load(Ordering::Relaxed) => Err(IsentaError::Cancelled),
+            _ => Ok(()),
+        }
+    }
+
+    /// Writes any changes held back by deferred durability to the file and
+    /// syncs it to disk.
+    pub fn flush(&mut self) -> Result<(), IsentaError> {
+        self.unflushed_changes = 0;
+        self.database.flush()
+    }
+
+    /// Starts a transaction: changes made through the returned guard are
+    /// kept in memory, visible to its queries, and reach the file only when
+    /// it is committed. Dropping the guard without committing rolls them
+    /// back. Only one transaction can be open at a time.
+    ///
+    /// Each table the transaction writes is locked until it ends: other
+    /// engines open on the same file in this process, such as the server's
+    /// other connections, fail to write it with `IsentaError::TableLocked`.
+    /// Reads take no locks and never see uncommitted changes: the isolation
+    /// provided is read committed, with table-level write locks held to the
+    /// end of the transaction. Other processes aren't locked out.
+    pub fn begin(&mut self) -> Result<Transaction<'_>, IsentaError> {
+        self.database.begin_transaction()?;
+        Ok(Transaction { engine: self, finished: false })
+    }
+
+    /// Runs `run` on the engine, then discards every change it made, so a
+    /// statement can be checked by running it without applying it. The
+    /// changes are made in a transaction that is rolled back, and the
+    /// change callback isn't told about them. Fails if a transaction is
+    /// already open.
+    pub fn dry_run<T>(&mut self, run: impl FnOnce(&mut QueryEngine) -> T) -> Result<T, IsentaError> {
+        self.database.begin_transaction()?;
+        let on_change = self.on_change.take();
+        let output = run(self);
+        self.on_change = on_change;
+        self.rollback_transaction()?;
+        Ok(output)
+    }
+
+    /// Discards the open transaction's pages and reloads the tables.
+    fn rollback_transaction(&mut self) -> Result<(), IsentaError> {
+        self.catalog = self.database.rollback_transaction()?;
+        self.unflushed_changes = 0;
+        Ok(())
+    }
+
+    /// Flushes the database and closes it. Unlike dropping the engine, this
+    /// reports a failed final write instead of only printing a warning.
+    pub fn close(mut self) -> Result<(), IsentaError> {
+        self.flush()
+    }
+
+    /// Sets how rows that don't match their table's column count are handled.
+    pub fn set_column_count_mode(&mut self, mode: ColumnCountMode) {
+        self.column_count_mode = mode;
+    }
+
+    /// How rows that don't match their table's column count are handled.
+    pub fn column_count_mode(&self) -> ColumnCountMode {
+        self.column_count_mode
+    }
+
+    /// Sets the text shown in place of NULL values when results are rendered.
+    pub fn set_null_string(&mut self, null_string: &str) {
+        self.null_string = null_string.to_string();
+    }
+
+    /// The text shown in place of NULL values when results are rendered.
+    pub fn null_string(&self) -> &str {
+        &self.null_string
+    }
+
+    /// Sets how many rows of a result are shown when it is rendered as text;
+    /// the rest are counted in a notice. `None`, the default, shows every
+    /// row. Queries still compute their whole result.
+    pub fn set_max_rows(&mut self, max_rows: Option<usize>) {
+        self.max_rows = max_rows;
+    }
+
+    /// How many rows of a result are shown when it is rendered as text.
+    pub fn max_rows(&self) -> Option<usize> {
+        self.max_rows
+    }
+
+    /// Sets whether the time each statement takes is reported with its output.
+    pub fn set_timing(&mut self, timing: bool) {
+        self.timing = timing;
+    }
+
+    /// Whether the time each statement takes is reported with its output.
+    pub fn timing(&self) -> bool {
+        self.timing
+    }
+
+    /// Sets whether an INSERT given fewer values than the table has columns
+    /// pads the remaining columns with NULL instead of failing. Too many
+    /// values are an error either way.
+    pub fn set_relaxed_insert(&mut self, relaxed: bool) {
+        self.relaxed_insert = relaxed;
+    }
+
+    /// Whether short INSERTs are padded with NULL; see `set_relaxed_insert`.
+    pub fn relaxed_insert(&self) -> bool {
+        self.relaxed_insert
+    }
+
+    /// Sets whether statements run through `execute_line` are appended to
+    /// the audit log next to the database file; see `AuditLog`. Turning it
+    /// off takes effect once the statement doing so has been recorded.
+    pub fn set_audit(&mut self, audit: bool) -> Result<(), IsentaError> {
+        if !audit {
+            self.audit_stopping = self.audit.is_some();
+            return Ok(());
+        }
+        if self.path == MEMORY_PATH {
+            return Err(IsentaError::InvalidQuery("An in-memory database has no audit log".to_string()));
+        }
+        if self.audit.is_none() {
+            self.audit = Some(AuditLog::open(&self.path)?);
+        }
+        self.audit_stopping = false;
+        Ok(())
+    }
+
+    /// Whether statements are recorded in the audit log; see `set_audit`.
+    pub fn audit(&self) -> bool {
+        self.audit.is_some() && !self.audit_stopping
+    }
+
+    /// Appends a statement and whether it succeeded to the audit log, if
+    /// auditing is on. A failed write goes to the logger rather than failing
+    /// the statement, which has already run.
+    pub fn record_audit(&mut self, statement: &str, succeeded: bool) {
+        if let Some(audit) = self.audit.as_mut() {
+            if let Err(e) = audit.record(statement, succeeded) {
+                (self.logger)(&e.to_string());
+            }
+        }
+        if self.audit_stopping {
+            self.audit = None;
+            self.audit_stopping = false;
+        }
+    }
+
+    /// Sets how many changing statements may be made before the engine
+    /// flushes to disk on its own, bounding what a crash can lose with
+    /// `Durability::Deferred`. `None` leaves flushing to `flush()`.
+    pub fn set_flush_every(&mut self, changes: Option<usize>) {
+        self.flush_every = changes;
+        self.unflushed_changes = 0;
+    }
+
+    /// How many changes are made between automatic flushes; see
+    /// `set_flush_every`.
+    pub fn flush_every(&self) -> Option<usize> {
+        self.flush_every
+    }
+
+    /// Sets how many contiguous data pages each table created from now on
+    /// gets up front (at least 1); see `Database::set_data_page_preallocation`.
+    pub fn set_data_page_preallocation(&mut self, pages: u64) {
+        self.database.set_data_page_preallocation(pages);
+    }
+
+    /// How many data pages each newly created table gets up front.
+    pub fn data_page_preallocation(&self) -> u64 {
+        self.database.data_page_preallocation()
+    }
+
+    /// Sets how many pages the write-ahead log of `Durability::Wal` may
+    /// hold before a write checkpoints it into the file; `None` leaves it to
+    /// `flush()`.
+    pub fn set_wal_autocheckpoint(&mut self, pages: Option<u64>) {
+        self.database.set_wal_autocheckpoint(pages);
+    }
+
+    /// The write-ahead log size, in pages, that triggers a checkpoint.
+    pub fn wal_autocheckpoint(&self) -> Option<u64> {
+        self.database.wal_autocheckpoint()
+    }
+
+    /// Sets how long after a checkpoint of the write-ahead log the next
+    /// write checkpoints it again; `None` checkpoints by size alone.
+    pub fn set_wal_checkpoint_interval(&mut self, interval: Option<Duration>) {
+        self.database.set_wal_checkpoint_interval(interval);
+    }
+
+    /// The time between write-ahead log checkpoints.
+    pub fn wal_checkpoint_interval(&self) -> Option<Duration> {
+        self.database.wal_checkpoint_interval()
+    }
+
+    /// Sets where warnings are sent, such as rows padded in lenient mode or
+    /// damaged pages skipped while loading. The default prints them to stderr.
+    pub fn set_logger(&mut self, logger: Logger) {
+        self.database.set_logger(logger.clone());
+        self.logger = logger;
+    }
+
+    /// Discards all warnings when `quiet` is set, or prints them to stderr
+    /// again when it isn't. Use `open_with_logger` to also silence the
+    /// warnings raised while opening the database.
+    pub fn set_quiet(&mut self, quiet: bool) {
+        self.set_logger(if quiet { log::quiet() } else { log::stderr() });
+    }
+
+    /// Enables diagnostic commands such as `DUMP PAGE`.
+    pub fn set_debug(&mut self, debug: bool) {
+        self.debug = debug;
+    }
+
+    /// Whether diagnostic commands such as `DUMP PAGE` are available.
+    pub fn debug(&self) -> bool {
+        self.debug
+    }
+
+    /// Describes a table's columns, one row per column with its name, type,
+    /// whether it accepts NULL, its key and its default value.
+    pub fn show_columns(&self, table_name: &str) -> Result<QueryResult, IsentaError> {
+        let table = self
+            .catalog
+            .find_table(table_name)
+            .ok_or_else(|| IsentaError::TableNotFound(table_name.to_string()))?;
+        let columns = ["name", "type", "nullable", "key", "default"].map(String::from).to_vec();
+        let rows = table
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| Row {
+                rowid: i as u64 + 1,
+                values: vec![
+                    Some(column.name.clone()),
+                    Some(column.data_type.clone()),
+                    Some(if column.not_null { "NO" } else { "YES" }.to_string()),
+                    // Tables have no primary or unique keys yet
+                    None,
+                    column.default.clone(),
+                ],
+            })
+            .collect();
+        Ok(QueryResult::Rows { columns, rows })
+    }
+
+    /// Reports how the data pages of a table are used; see
+    /// `Database::table_stats`.
+    pub fn table_stats(&mut self, table_name: &str) -> Result<TableStats, IsentaError> {
+        let table = self
+            .catalog
+            .find_table(table_name)
+            .ok_or_else(|| IsentaError::TableNotFound(table_name.to_string()))?;
+        self.database
+            .table_stats(table)?
+            .ok_or_else(|| IsentaError::TableNotFound(table_name.to_string()))
+    }
+
+    /// Describes one page of the database file; see `Database::dump_page`.
+    pub fn dump_page(&mut self, page_id: u64) -> Result<String, IsentaError> {
+        self.database.dump_page(page_id)
+    }
+
+    /// Reads a setting by name, or changes it when `value` is given, and
+    /// returns its current value. Settings are `strict` (on/off), `null_string`,
+    /// `max_rows` (a count of rows, or off), `timing` (on/off),
+    /// `relaxed_insert` (on/off), `flush_every` (a count of changes, or off),
+    /// `audit` (on/off), `preallocate_pages` (a count of pages),
+    /// `wal_autocheckpoint` (a count of pages, or off) and
+    /// `wal_checkpoint_interval` (a number of seconds, or off).
+    pub fn pragma(&mut self, name: &str, value: Option<&str>) -> Result<String, IsentaError> {
+        match name.to_lowercase().as_str() {
+            "strict" => {
+                if let Some(value) = value {
+                    self.column_count_mode =
+                        if parse_switch(name, value)? { ColumnCountMode::Strict } else { ColumnCountMode::Lenient };
+                }
+                Ok(format_switch(self.column_count_mode == ColumnCountMode::Strict))
+            }
+            "null_string" => {
+                if let Some(value) = value {
+                    self.null_string = value.to_string();
+                }
+                Ok(format!("'{}'", self.null_string))
+            }
+            "max_rows" => {
+                if let Some(value) = value {
+                    self.max_rows = match value.parse::<usize>() {
+                        Ok(0) => None,
+                        Ok(rows) => Some(rows),
+                        Err(_) if value.eq_ignore_ascii_case("off") => None,
+                        Err(_) => {
+                            return Err(IsentaError::InvalidQuery(format!(
+                                "Invalid value '{}' for pragma '{}'; expected a number of rows, or off",
+                                value, name
+                            )))
+                        }
+                    };
+                }
+                Ok(self.max_rows.map_or_else(|| "off".to_string(), |rows| rows.to_string()))
+            }
+            "timing" => {
+                if let Some(value) = value {
+                    self.timing = parse_switch(name, value)?;
+                }
+                Ok(format_switch(self.timing))
+            }
+            "relaxed_insert" => {
+                if let Some(value) = value {
+                    self.relaxed_insert = parse_switch(name, value)?;
+                }
+                Ok(format_switch(self.relaxed_insert))
+            }
+            "audit" => {
+                if let Some(value) = value {
+                    self.set_audit(parse_switch(name, value)?)?;
+                }
+                Ok(format_switch(self.audit()))
+            }
+            "flush_every" => {
+                if let Some(value) = value {
+                    let changes = match value.parse::<usize>() {
+                        Ok(0) => None,
+                        Ok(changes) => Some(changes),
+                        Err(_) if value.eq_ignore_ascii_case("off") => None,
+                        Err(_) => {
+                            return Err(IsentaError::InvalidQuery(format!(
+                                "Invalid value '{}' for pragma '{}'; expected a number of changes, or off",
+                                value, name
+                            )))
+                        }
+                    };
+                    self.set_flush_every(changes);
+                }
+                Ok(self.flush_every.map_or_else(|| "off".to_string(), |changes| changes.to_string()))
+            }
+            "preallocate_pages" => {
+                if let Some(value) = value {
+                    match value.parse::<u64>() {
+                        Ok(pages) if pages > 0 => self.set_data_page_preallocation(pages),
+                        _ => {
+                            return Err(IsentaError::InvalidQuery(format!(
+                                "Invalid value '{}' for pragma '{}'; expected a positive number of pages",
+                                value, name
+                            )))
+                        }
+                    }
+                }
+                Ok(self.data_page_preallocation().to_string())
+            }
+            "wal_autocheckpoint" => {
+                if let Some(value) = value {
+                    let pages = match value.parse::<u64>() {
+                        Ok(0) => None,
+                        Ok(pages) => Some(pages),
+                        Err(_) if value.eq_ignore_ascii_case("off") => None,
+                        Err(_) => {
+                            return Err(IsentaError::InvalidQuery(format!(
+                                "Invalid value '{}' for pragma '{}'; expected a number of pages, or off",
+                                value, name
+                            )))
+                        }
+                    };
+                    self.set_wal_autocheckpoint(pages);
+                }
+                Ok(self.wal_autocheckpoint().map_or_else(|| "off".to_string(), |pages| pages.to_string()))
+            }
+            "wal_checkpoint_interval" => {
+                if let Some(value) = value {
+                    let interval = match value.parse::<u64>() {
+                        Ok(0) => None,
+                        Ok(seconds) => Some(Duration::from_secs(seconds)),
+                        Err(_) if value.eq_ignore_ascii_case("off") => None,
+                        Err(_) => {
+                            return Err(IsentaError::InvalidQuery(format!(
+                                "Invalid value '{}' for pragma '{}'; expected a number of seconds, or off",
+                                value, name
+                            )))
+                        }
+                    };
+                    self.set_wal_checkpoint_interval(interval);
+                }
+                Ok(self.wal_checkpoint_interval().map_or_else(|| "off".to_string(), |interval| interval.as_secs().to_string()))
+            }
+            _ => Err(IsentaError::InvalidQuery(format!(
+                "Unknown pragma '{}'; available pragmas are strict, null_string, max_rows, timing, relaxed_insert, flush_every, audit, preallocate_pages, wal_autocheckpoint and wal_checkpoint_interval",
+                name
+            ))),
+        }
+    }
+
+    /// Registers a callback that is invoked after each mutation has been
+    /// persisted, replacing any previously registered callback.
+    pub fn set_on_change(&mut self, callback: Box<dyn FnMut(ChangeEvent)>) {
+        self.on_change = Some(callback);
+    }
+
+    /// Writes a table changed in the catalog back to the database file and
+    /// returns a copy of it. If another process wrote the table since it was
+    /// loaded, or another connection's transaction holds it, the catalog is
+    /// reloaded from the file instead, dropping the unwritten change so that
+    /// a retry starts from the current rows.
+    fn write_table(&mut self, name: &str) -> Result<Table, IsentaError> {
+        let table = self
+            .catalog
+            .find_table_mut(name)
+            .ok_or_else(|| IsentaError::TableNotFound(name.to_string()))?;
+        match self.database.update_table_data(table) {
+            Ok(()) => {
+                table.change_count += 1;
+                Ok(table.clone())
+            }
+            Err(e @ (IsentaError::ConcurrentChange(_) | IsentaError::TableLocked(_))) => {
+                self.catalog = self.database.load_catalog()?;
+                Err(e)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Counts a persisted change towards `flush_every`, flushing once the
+    /// threshold is reached, and tells the change callback about it.
+    fn record_change(&mut self, table: &str, kind: ChangeKind) -> Result<(), IsentaError> {
+        if let Some(flush_every) = self.flush_every {
+            self.unflushed_changes += 1;
+            if self.unflushed_changes >= flush_every {
+                self.flush()?;
+            }
+        }
+        self.notify_change(table, kind);
+        Ok(())
+    }
+
+    fn notify_change(&mut self, table: &str, kind: ChangeKind) {
+        if let Some(callback) = self.on_change.as_mut() {
+            callback(ChangeEvent { table: table.to_string(), kind });
+        }
+    }
+
+    /// Rejects comparisons against the `NULL` keyword, which can never match.
+    fn validate_where_clause(clause: &WhereClause) -> Result<(), IsentaError> {
+        if clause.value.is_none() && clause.operator != "IS NULL" && clause.operator != "IS NOT NULL" {
+            return Err(IsentaError::InvalidQuery(format!(
+                "Cannot compare '{}' with NULL using '{}'; use '{} IS NULL' or '{} IS NOT NULL' instead",
+                clause.column, clause.operator, clause.column, clause.column
+            )));
+        }
+        Ok(())
+    }
+
+    /// Rejects comparisons whose literal can't be interpreted as the column's
+    /// type, which would otherwise silently match no rows.
+    fn validate_comparison(clause: &WhereClause, column_type: &str) -> Result<(), IsentaError> {
+        // Bytes have no order, only equality
+        if is_blob_type(column_type) && !matches!(clause.operator.as_str(), "=" | "!=" | "IS NULL" | "IS NOT NULL") {
+            return Err(IsentaError::InvalidQuery(format!(
+                "BLOB column '{}' can only be compared with =, != or IS [NOT] NULL",
+                clause.column
+            )));
+        }
+        let value = match &clause.value {
+            Some(value) if clause.operator != "LIKE" && clause.operator != "NOT LIKE" => value,
+            _ => return Ok(()),
+        };
+        let column_type = column_type.to_uppercase();
+        // JSON values compare as numbers or text, whatever the literal
+        if normalized_type(&column_type) == "JSON" {
+            return Ok(());
+        }
+
+        if validate_value(Some(value), &column_type).is_err() {
+            return Err(IsentaError::TypeMismatch(format!(
+                "cannot compare {} column '{}' with text value '{}'",
+                column_type, clause.column, value
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Validates every condition of a WHERE expression against the table
+    /// before any row is filtered.
+    fn validate_where(table: &Table, expr: &WhereExpr) -> Result<(), IsentaError> {
+        match expr {
+            WhereExpr::Condition(clause) => {
+                Self::validate_where_clause(clause)?;
+                let target = resolve_column(table, &clause.column)?;
+                // Column values are compared as they are, without a literal to check
+                if compared_column(clause, &table.columns).is_some() {
+                    return Ok(());
+                }
+                Self::validate_comparison(clause, target.data_type(&table.columns))
+            }
+            WhereExpr::And(left, right) | WhereExpr::Or(left, right) => {
+                Self::validate_where(table, left)?;
+                Self::validate_where(table, right)
+            }
         }
     }
 
+    /// Compares a row value with a clause value. Integer columns (see
+    /// [`is_integer_type`]) compare numerically, and a value that isn't an
+    /// integer matches nothing; every other type compares as
+    /// case-insensitive text, ordered alphabetically by [`compare_text`].
     fn evaluate_condition(
-        row_value: &str,
+        row_value: Option<&str>,
         operator: &str,
-        clause_value: &str,
+        clause_value: Option<&str>,
         column_type: &str,
     ) -> bool {
-        if column_type == "INTEGER" {
+        match operator {
+            "IS NULL" => return row_value.is_none(),
+            "IS NOT NULL" => return row_value.is_some(),
+            _ => {}
+        }
+
+        // A NULL on either side never satisfies a comparison
+        let (row_value, clause_value) = match (row_value, clause_value) {
+            (Some(row_value), Some(clause_value)) => (row_value, clause_value),
+            _ => return false,
+        };
+
+        if is_integer_type(column_type) {
             let row_val: Result<i64, _> = row_value.parse();
             let clause_val: Result<i64, _> = clause_value.parse();
 
@@ -120,6 +1056,15 @@ impl QueryEngine {
             } else {
                 false // Could not parse one of the values as an integer
             }
+        } else if let Some(order) = number_order(row_value, clause_value, operator, column_type) {
+            match operator {
+                "=" => order.is_eq(),
+                "!=" => order.is_ne(),
+                ">" => order.is_gt(),
+                "<" => order.is_lt(),
+                ">=" => order.is_ge(),
+                _ => order.is_le(),
+            }
         } else {
             // Default to TEXT comparison
             match operator {
@@ -141,163 +1086,771 @@ impl QueryEngine {
                     };
                     !re.is_match(row_value)
                 }
-                // GT, LT etc. for text are not part of this implementation
+                ">" | "<" | ">=" | "<=" => {
+                    let order = compare_text(row_value, clause_value);
+                    match operator {
+                        ">" => order.is_gt(),
+                        "<" => order.is_lt(),
+                        ">=" => order.is_ge(),
+                        _ => order.is_le(),
+                    }
+                }
                 _ => false,
             }
         }
     }
 
-    pub fn execute_create_table(&mut self, name: String, columns: Vec<Column>) -> Result<(), String> {
-        self.catalog.create_table(name.clone(), columns.clone())?;
+    pub fn execute_create_table(&mut self, name: String, columns: Vec<Column>) -> Result<(), IsentaError> {
+        // A default must be a value its column can hold
+        for column in &columns {
+            if normalized_type(&column.data_type) == "JSON" && !json::ENABLED {
+                return Err(json_disabled());
+            }
+            if column.data_type.contains('(') && is_text_type(&column.data_type) && column.max_length().is_none() {
+                return Err(IsentaError::InvalidQuery(format!(
+                    "Invalid length in type {} of column '{}'; expected a number such as VARCHAR(50)",
+                    column.data_type, column.name
+                )));
+            }
+            if let Some(default) = &column.default {
+                validate_value(Some(default), &column.data_type)
+                    .and_then(|()| check_length(column, Some(default)))
+                    .map_err(|e| {
+                        IsentaError::TypeMismatch(format!("Invalid default for column '{}': {}", column.name, e))
+                    })?;
+            }
+        }
+        self.database.check_schema_size(&name, &columns)?;
+        self.database.lock_table(&name)?;
+        self.catalog.create_table(name.clone(), columns.clone())?;
         
         // Get the table we just created and save it to disk
         let table = self.catalog.find_table(&name)
-            .ok_or_else(|| format!("Failed to find table '{}' after creation", name))?
+            .ok_or_else(|| IsentaError::TableNotFound(name.clone()))?
             .clone();
         
         self.database.save_table(&table, true)?;
+        self.record_change(&table.name, ChangeKind::Ddl)?;
         Ok(())
     }
 
-    pub fn execute_insert(&mut self, table: String, values: Vec<String>) -> Result<(), String> {
+    /// Creates a table holding the result of a SELECT on `source`, as
+    /// `CREATE TABLE name AS SELECT ...` does, and returns its number of rows.
+    /// The columns are named like the query's result columns and take the
+    /// types of the columns they come from, without their constraints; see
+    /// `projected_types`. The new table is independent of `source`.
+    pub fn execute_create_table_as(
+        &mut self,
+        name: String,
+        source: String,
+        columns: Vec<SelectItem>,
+        where_clause: Option<WhereExpr>,
+        order_by: Vec<OrderBy>,
+    ) -> Result<usize, IsentaError> {
+        if self.catalog.find_table(&name).is_some() {
+            return Err(IsentaError::TableExists(name));
+        }
+        let source_table = self
+            .catalog
+            .find_table(&source)
+            .ok_or_else(|| IsentaError::TableNotFound(source.clone()))?;
+        let types = projected_types(source_table, &columns)?;
+
+        let QueryResult::Rows { columns: names, rows } = self.execute_select_ordered(source, columns, where_clause, order_by)?;
+        let mut new_columns: Vec<Column> = Vec::with_capacity(names.len());
+        for (column, data_type) in names.iter().zip(&types) {
+            if new_columns.iter().any(|other| names_match(&other.name, column)) {
+                return Err(IsentaError::InvalidQuery(format!(
+                    "Column '{}' is selected more than once; name the copies with AS",
+                    column
+                )));
+            }
+            new_columns.push(Column::new(column, data_type));
+        }
+
+        self.database.check_schema_size(&name, &new_columns)?;
+        self.database.lock_table(&name)?;
+        self.catalog.create_table(name.clone(), new_columns)?;
+        let table = self
+            .catalog
+            .find_table_mut(&name)
+            .ok_or_else(|| IsentaError::TableNotFound(name.clone()))?;
+        let count = rows.len();
+        let first_rowid = table.allocate_rowids(count)?;
+        for (offset, row) in rows.into_iter().enumerate() {
+            table.rows.push(Row { rowid: first_rowid + offset as u64, values: row.values });
+        }
+
+        let table = table.clone();
+        self.database.save_table(&table, true)?;
+        self.record_change(&table.name, ChangeKind::Ddl)?;
+        Ok(count)
+    }
+
+    /// Inserts a row given a value for each column, in schema order. With
+    /// `relaxed_insert` set, missing trailing values are NULL.
+    pub fn execute_insert(&mut self, table: String, values: Vec<Option<String>>) -> Result<(), IsentaError> {
+        self.execute_insert_rows(table, None, vec![values]).map(|_| ())
+    }
+
+    /// Inserts a row whose values are given for the named columns, in any
+    /// order. A column that isn't named takes its default; without one it is
+    /// NULL, which a NOT NULL column rejects.
+    pub fn execute_insert_columns(
+        &mut self,
+        table: String,
+        columns: Vec<String>,
+        values: Vec<Option<String>>,
+    ) -> Result<(), IsentaError> {
+        self.execute_insert_rows(table, Some(columns), vec![values]).map(|_| ())
+    }
+
+    /// Inserts the rows of a multi-row `INSERT ... VALUES (...), (...)`,
+    /// each as `execute_insert` does, or `execute_insert_columns` when
+    /// `columns` are named. No row is inserted unless all of them are
+    /// valid, and they are written together. Naming `_rowid` among the
+    /// columns gives a row that rowid instead of the next from the counter.
+    /// Returns the number of rows inserted.
+    pub fn execute_insert_rows(
+        &mut self,
+        table: String,
+        columns: Option<Vec<String>>,
+        rows: Vec<Vec<Option<String>>>,
+    ) -> Result<usize, IsentaError> {
+        let mode = self.column_count_mode;
+        let relaxed_insert = self.relaxed_insert;
         let table_ref = self
             .catalog
             .find_table_mut(&table)
-            .ok_or_else(|| format!("Table '{}' does not exist", table))?;
-
-        // Validate column count
-        if values.len() != table_ref.columns.len() {
-            return Err(format!(
-                "Column count mismatch: expected {}, got {}",
-                table_ref.columns.len(),
-                values.len()
-            ));
+            .ok_or_else(|| IsentaError::TableNotFound(table.clone()))?;
+        conform_rows(&table_ref.name, table_ref.columns.len(), &mut table_ref.rows, mode, &self.logger)?;
+
+        // Naming `_rowid` in the column list gives rows their rowid
+        let named = columns.as_ref().map_or(0, Vec::len);
+        let rowid_index = columns.as_ref().and_then(|columns| columns.iter().position(|c| c.eq_ignore_ascii_case(ROWID_COLUMN)));
+        let columns = columns.map(|mut columns| {
+            if let Some(index) = rowid_index {
+                columns.remove(index);
+            }
+            columns
+        });
+
+        let mut new_rows = Vec::with_capacity(rows.len());
+        let mut given_rowids = Vec::with_capacity(rows.len());
+        for mut values in rows {
+            let given_rowid = match rowid_index {
+                Some(_) if values.len() != named => {
+                    return Err(IsentaError::Constraint(format!(
+                        "{} columns are named but {} values are given",
+                        named,
+                        values.len()
+                    )));
+                }
+                Some(index) => parse_rowid(values.remove(index))?,
+                None => None,
+            };
+            let mut values = match &columns {
+                Some(columns) => row_for_columns(table_ref, columns, values)?,
+                None => values,
+            };
+            if relaxed_insert && values.len() < table_ref.columns.len() {
+                values.resize(table_ref.columns.len(), None);
+            }
+            validate_row(&table_ref.columns, &values)?;
+            new_rows.push(values);
+            given_rowids.push(given_rowid);
+        }
+        if new_rows.is_empty() {
+            return Ok(0);
         }
 
-        table_ref.rows.push(Row { values });
-        
+        // A given rowid must not be in use, by a stored row or another new one
+        if given_rowids.iter().any(Option::is_some) {
+            let mut in_use: HashSet<u64> = table_ref.rows.iter().map(|row| row.rowid).collect();
+            for rowid in given_rowids.iter().flatten() {
+                if !in_use.insert(*rowid) {
+                    return Err(IsentaError::Constraint(format!(
+                        "rowid {} is already in use in table '{}'",
+                        rowid, table_ref.name
+                    )));
+                }
+            }
+        }
+
+        let inserted = new_rows.len();
+        let rowids = table_ref.assign_rowids(&given_rowids)?;
+        for (rowid, values) in rowids.into_iter().zip(new_rows) {
+            // Rows are kept in rowid order, the order they are loaded back in
+            let at = table_ref.rows.partition_point(|row| row.rowid < rowid);
+            table_ref.rows.insert(at, Row { rowid, values });
+        }
+        table_ref.updated_at = unix_now();
+
         // Save updated table to disk
-        let table_clone = table_ref.clone();
-        self.database.update_table_data(&table_clone)?;
-        Ok(())
+        let table_clone = self.write_table(&table)?;
+        self.record_change(&table_clone.name, ChangeKind::Insert)?;
+        Ok(inserted)
+    }
+
+    /// Inserts rows as `execute_insert_rows` does and returns them projected
+    /// onto `returning` like a SELECT, as `INSERT ... RETURNING` does. The
+    /// rows are returned as stored, with their rowids and the defaults they
+    /// were given. Nothing is inserted if the projection is invalid.
+    pub fn execute_insert_returning(
+        &mut self,
+        table: String,
+        columns: Option<Vec<String>>,
+        rows: Vec<Vec<Option<String>>>,
+        returning: Vec<SelectItem>,
+    ) -> Result<QueryResult, IsentaError> {
+        let table_ref = self.catalog.find_table(&table).ok_or_else(|| IsentaError::TableNotFound(table.clone()))?;
+        self.select_rows(table_ref, Vec::new(), returning.clone(), None, None, &[])?;
+        let existing: HashSet<u64> = table_ref.rows.iter().map(|row| row.rowid).collect();
+
+        self.execute_insert_rows(table.clone(), columns, rows)?;
+        let table_ref = self.catalog.find_table(&table).ok_or_else(|| IsentaError::TableNotFound(table.clone()))?;
+        let inserted = table_ref.rows.iter().filter(|row| !existing.contains(&row.rowid)).cloned().collect();
+        self.select_rows(table_ref, inserted, returning, None, None, &[])
+    }
+
+    /// Appends the rows returned by a SELECT on `source` to `table`. The
+    /// query must return one value per column of `table`; no row is inserted
+    /// unless all of them are valid. Returns the number of rows inserted.
+    pub fn execute_insert_select(
+        &mut self,
+        table: String,
+        source: String,
+        columns: Vec<SelectItem>,
+        where_clause: Option<WhereExpr>,
+        order_by: Vec<OrderBy>,
+    ) -> Result<usize, IsentaError> {
+        let result = self.execute_select_ordered(source, columns, where_clause, order_by)?;
+
+        let mode = self.column_count_mode;
+        let table_ref = self
+            .catalog
+            .find_table_mut(&table)
+            .ok_or_else(|| IsentaError::TableNotFound(table.clone()))?;
+        conform_rows(&table_ref.name, table_ref.columns.len(), &mut table_ref.rows, mode, &self.logger)?;
+
+        let QueryResult::Rows { rows, .. } = result;
+        for row in &rows {
+            validate_row(&table_ref.columns, &row.values)?;
+        }
+        if rows.is_empty() {
+            return Ok(0);
+        }
+
+        let inserted = rows.len();
+        let first_rowid = table_ref.allocate_rowids(inserted)?;
+        for (offset, row) in rows.into_iter().enumerate() {
+            table_ref.rows.push(Row { rowid: first_rowid + offset as u64, values: row.values });
+        }
+        table_ref.updated_at = unix_now();
+
+        let table_clone = self.write_table(&table)?;
+        self.record_change(&table_clone.name, ChangeKind::Insert)?;
+        Ok(inserted)
+    }
+
+    pub fn execute_select(&self, table_name: String, columns: Vec<SelectItem>, where_clause: Option<WhereExpr>) -> Result<QueryResult, IsentaError> {
+        self.execute_select_ordered(table_name, columns, where_clause, Vec::new())
     }
 
-    pub fn execute_select(&self, table_name: String, columns: Vec<String>, where_clause: Option<WhereClause>) -> Result<(Vec<String>, Vec<Row>), String> {
+    /// Runs a SELECT whose matching rows are sorted by the `order_by` keys,
+    /// the leftmost first; later keys only order rows the earlier ones tie
+    /// on, and rows tied on every key keep their table order.
+    pub fn execute_select_ordered(
+        &self,
+        table_name: String,
+        columns: Vec<SelectItem>,
+        where_clause: Option<WhereExpr>,
+        order_by: Vec<OrderBy>,
+    ) -> Result<QueryResult, IsentaError> {
+        self.execute_select_grouped(table_name, columns, where_clause, None, order_by)
+    }
+
+    /// Runs a SELECT that returns one row per distinct value of the
+    /// `group_by` column among the matching rows, with the aggregates
+    /// computed over the rows of each group. Groups appear in the order of
+    /// their first row once the rows are sorted by `order_by`.
+    pub fn execute_select_grouped(
+        &self,
+        table_name: String,
+        columns: Vec<SelectItem>,
+        where_clause: Option<WhereExpr>,
+        group_by: Option<String>,
+        order_by: Vec<OrderBy>,
+    ) -> Result<QueryResult, IsentaError> {
         let table = self
             .catalog
             .find_table(&table_name)
-            .ok_or_else(|| format!("Table '{}' does not exist", table_name))?;
+            .ok_or_else(|| IsentaError::TableNotFound(table_name.clone()))?;
 
         let mut rows = table.rows.clone();
+        conform_rows(&table.name, table.columns.len(), &mut rows, self.column_count_mode, &self.logger)?;
+        self.select_rows(table, rows, columns, where_clause, group_by.as_deref(), &order_by)
+    }
 
-        if let Some(clause) = where_clause {
-            let column_index = table.columns.iter().position(|c| c.name.to_lowercase() == clause.column.to_lowercase());
+    /// Returns the rows of a table matching `where_clause`, or all of its
+    /// rows without one, in the order they are stored, which page packing
+    /// may make differ from table order. The scan reads the table's data
+    /// pages from the database one at a time as it reaches them, so only one
+    /// page of rows is held at once rather than a copy of the table, as
+    /// `execute_select` makes.
+    pub fn scan_rows(&mut self, table_name: &str, where_clause: Option<WhereExpr>) -> Result<RowScan<'_>, IsentaError> {
+        let table = self
+            .catalog
+            .find_table(table_name)
+            .ok_or_else(|| IsentaError::TableNotFound(table_name.to_string()))?;
+        if let Some(expr) = &where_clause {
+            Self::validate_where(table, expr)?;
+        }
+        let (table, columns) = (table.name.clone(), table.columns.clone());
+        let next_page = self.database.first_data_page(&table)?;
+        Ok(RowScan {
+            database: &mut self.database,
+            table,
+            columns,
+            mode: self.column_count_mode,
+            logger: self.logger.clone(),
+            cancel: self.cancel.clone(),
+            where_clause,
+            page_rows: Vec::new().into_iter(),
+            next_page,
+            visited: HashSet::new(),
+            position: 0,
+        })
+    }
 
-            if let Some(index) = column_index {
-                let column = &table.columns[index];
-                rows = rows.into_iter().filter(|row| {
-                    if let Some(value) = row.values.get(index) {
-                        return Self::evaluate_condition(value, &clause.operator, &clause.value, &column.data_type);
-                    }
-                    false
-                }).collect();
-            } else {
-                return Err(format!("Column '{}' not found in table '{}'", clause.column, table.name));
+    /// Writes the rows of a table matching `where_clause` to the file at
+    /// `path` as CSV, with a header line of column names. Rows are written as
+    /// they are scanned through a buffer of `EXPORT_BUFFER_SIZE` bytes, so
+    /// the export doesn't build a copy of the table or of the file's contents.
+    /// Returns the number of rows written. The path must be allowed by
+    /// `set_export_access`.
+    pub fn export_csv(&mut self, table_name: &str, where_clause: Option<WhereExpr>, path: &str) -> Result<usize, IsentaError> {
+        let target = self.export_path(path)?;
+        let scan = self.scan_rows(table_name, where_clause)?;
+        let file = std::fs::File::create(&target).map_err(|e| IsentaError::Io(format!("cannot create '{}': {}", path, e)))?;
+        let mut out = std::io::BufWriter::with_capacity(EXPORT_BUFFER_SIZE, file);
+        let write_error = |e: std::io::Error| IsentaError::Io(format!("Failed to write '{}': {}", path, e));
+
+        let header: Vec<String> = scan.columns().iter().map(|column| csv_field(Some(&column.name))).collect();
+        writeln!(out, "{}", header.join(",")).map_err(write_error)?;
+        let mut count = 0;
+        for row in scan {
+            let fields: Vec<String> = row?.values.iter().map(|value| csv_field(value.as_deref())).collect();
+            writeln!(out, "{}", fields.join(",")).map_err(write_error)?;
+            count += 1;
+        }
+        out.flush().map_err(write_error)?;
+        Ok(count)
+    }
+
+    /// Runs a SELECT over every combination of rows of `tables`, the
+    /// implicit join `SELECT ... FROM a, b WHERE a.id = b.a_id`. The columns
+    /// are named `table.column`, and may be referred to by their column name
+    /// alone where that is unambiguous. A warning is printed when the
+    /// combinations exceed `CROSS_JOIN_WARNING_ROWS`.
+    pub fn execute_cross_join(
+        &self,
+        tables: Vec<String>,
+        columns: Vec<SelectItem>,
+        where_clause: Option<WhereExpr>,
+        order_by: Vec<OrderBy>,
+    ) -> Result<QueryResult, IsentaError> {
+        let mut sources = Vec::new();
+        for name in &tables {
+            let table = self
+                .catalog
+                .find_table(name)
+                .ok_or_else(|| IsentaError::TableNotFound(name.clone()))?;
+            if sources.iter().any(|(source, _): &(&Table, Vec<Row>)| names_match(&source.name, &table.name)) {
+                return Err(IsentaError::InvalidQuery(format!("Table '{}' is listed more than once", table.name)));
             }
+            let mut rows = table.rows.clone();
+            conform_rows(&table.name, table.columns.len(), &mut rows, self.column_count_mode, &self.logger)?;
+            sources.push((table, rows));
         }
 
-        let selected_columns;
-        let final_rows;
+        let combinations = sources.iter().fold(1usize, |count, (_, rows)| count.saturating_mul(rows.len()));
+        if combinations > CROSS_JOIN_WARNING_ROWS {
+            (self.logger)(&format!(
+                "joining {} produces {} row combinations; add a join condition to the WHERE clause to narrow it",
+                tables.join(", "),
+                combinations
+            ));
+        }
 
-        if columns.contains(&"*".to_string()) {
-            selected_columns = table.columns.iter().map(|c| c.name.clone()).collect();
-            final_rows = rows;
-        } else {
-            // Find indices for each requested column, returning a specific error for any not found.
-            let mut column_indices = Vec::new();
-            for col_name in &columns {
-                match table.columns.iter().position(|c| c.name.to_lowercase() == col_name.to_lowercase()) {
-                    Some(index) => column_indices.push(index),
-                    None => return Err(format!("Column '{}' not found in table '{}'", col_name, table.name)),
+        let mut joined = Table {
+            name: sources.iter().map(|(table, _)| table.name.as_str()).collect::<Vec<_>>().join(", "),
+            columns: Vec::new(),
+            rows: vec![Row { rowid: 1, values: Vec::new() }],
+            created_at: 0,
+            updated_at: 0,
+            next_rowid: 0,
+            change_count: 0,
+        };
+        for (table, rows) in &sources {
+            for column in &table.columns {
+                let mut qualified = column.clone();
+                qualified.name = format!("{}.{}", table.name, column.name);
+                joined.columns.push(qualified);
+            }
+            let mut product = Vec::with_capacity(joined.rows.len().saturating_mul(rows.len()));
+            for left in &joined.rows {
+                for right in rows {
+                    self.check_cancelled()?;
+                    let mut values = left.values.clone();
+                    values.extend(right.values.iter().cloned());
+                    product.push(Row { rowid: product.len() as u64 + 1, values });
+                }
+            }
+            joined.rows = product;
+        }
+
+        let rows = std::mem::take(&mut joined.rows);
+        self.select_rows(&joined, rows, columns, where_clause, None, &order_by)
+    }
+
+    /// Filters, sorts, groups and projects `rows` of `table` for a SELECT.
+    fn select_rows(
+        &self,
+        table: &Table,
+        mut rows: Vec<Row>,
+        columns: Vec<SelectItem>,
+        where_clause: Option<WhereExpr>,
+        group_by: Option<&str>,
+        order_by: &[OrderBy],
+    ) -> Result<QueryResult, IsentaError> {
+        // Scans check for cancellation on every row, so a query over a large
+        // table can be interrupted
+        if let Some(expr) = where_clause {
+            Self::validate_where(table, &expr)?;
+            let mut matching = Vec::new();
+            for row in rows {
+                self.check_cancelled()?;
+                if row_matches(&row, &expr, &table.columns) {
+                    matching.push(row);
                 }
             }
+            rows = matching;
+        }
 
-            selected_columns = columns.clone();
+        // Sort before projecting, so rows can be ordered by columns that
+        // aren't selected
+        if !order_by.is_empty() {
+            rows = sort_rows(table, rows, order_by)?;
+        }
+
+        // Aggregates collapse the selected rows into one, or into one per group
+        if let Some(group_by) = group_by {
+            return group_rows(table, &columns, rows, group_by);
+        }
+        if columns.iter().any(|item| matches!(split_alias(item).0, SelectItem::Aggregate(_))) {
+            return aggregate_rows(table, &columns, &rows);
+        }
+
+        // Resolve each projection item to column indexes or a constant, returning a
+        // specific error for any column not found. `*` expands to every column in
+        // place, and columns may be repeated.
+        let mut projections = Vec::new();
+        let mut selected_columns = Vec::new();
+        for item in &columns {
+            let (item, alias) = split_alias(item);
+            match item {
+                SelectItem::Wildcard if alias.is_some() => {
+                    return Err(IsentaError::InvalidQuery("'*' can't be given an alias".to_string()));
+                }
+                SelectItem::Wildcard => {
+                    for (index, column) in table.columns.iter().enumerate() {
+                        projections.push(Projection::Column(index));
+                        // Joined columns are only shown qualified where needed
+                        let name = match column.name.split_once('.') {
+                            Some((_, name)) if matches!(find_column(&table.columns, name), Some(ColumnRef::Table(i)) if i == index) => name,
+                            _ => &column.name,
+                        };
+                        selected_columns.push(name.to_string());
+                    }
+                }
+                SelectItem::Column(col_name) => {
+                    match resolve_column(table, col_name)? {
+                        ColumnRef::Table(index) => projections.push(Projection::Column(index)),
+                        ColumnRef::RowId => projections.push(Projection::RowId),
+                        ColumnRef::Json { index, path } => projections.push(Projection::Json(index, path)),
+                        derived => projections.push(Projection::Derived(derived)),
+                    }
+                    selected_columns.push(alias.unwrap_or(col_name).to_string());
+                }
+                SelectItem::Literal(value) => {
+                    projections.push(Projection::Constant(value.clone()));
+                    selected_columns.push(alias.map(str::to_string).unwrap_or_else(|| literal_label(value)));
+                }
+                SelectItem::Aggregate(_) => unreachable!("aggregate queries are handled above"),
+                SelectItem::Aliased { .. } => unreachable!("aliases are removed above"),
+            }
+        }
 
-            final_rows = rows.into_iter().map(|row| {
-                let selected_values = column_indices.iter().map(|&index| {
-                    row.values.get(index).cloned().unwrap_or_default()
-                }).collect();
-                Row { values: selected_values }
+        let mut final_rows = Vec::with_capacity(rows.len());
+        for row in rows {
+            self.check_cancelled()?;
+            let selected_values = projections.iter().map(|projection| match projection {
+                Projection::Column(index) => row.values.get(*index).cloned().flatten(),
+                Projection::RowId => Some(row.rowid.to_string()),
+                Projection::Json(index, path) => {
+                    row.values.get(*index).cloned().flatten().and_then(|document| json::extract(&document, path))
+                }
+                Projection::Derived(derived) => derived.value(&row),
+                Projection::Constant(value) => value.clone(),
             }).collect();
+            final_rows.push(Row { rowid: row.rowid, values: selected_values });
         }
 
-        Ok((selected_columns, final_rows))
+        Ok(QueryResult::Rows {
+            columns: selected_columns,
+            rows: final_rows,
+        })
     }
 
-    pub fn execute_update(&mut self, table_name: String, set_clause: (String, String), where_clause: Option<WhereClause>) -> Result<usize, String> {
+    /// Applies the assignments to rows matching the WHERE clause, or to all
+    /// rows without one, stopping after `limit` rows when given. Returns the
+    /// number of rows updated.
+    pub fn execute_update(
+        &mut self,
+        table_name: String,
+        assignments: Vec<(String, Option<String>)>,
+        where_clause: Option<WhereExpr>,
+        limit: Option<usize>,
+    ) -> Result<usize, IsentaError> {
+        let mode = self.column_count_mode;
         let table = self
             .catalog
             .find_table_mut(&table_name)
-            .ok_or_else(|| format!("Table '{}' does not exist", table_name))?;
+            .ok_or_else(|| IsentaError::TableNotFound(table_name.clone()))?;
+        conform_rows(&table.name, table.columns.len(), &mut table.rows, mode, &self.logger)?;
+
+        // Resolve and validate every assignment before changing any row
+        let mut updates: Vec<(usize, Option<String>)> = Vec::new();
+        for (column_to_set, new_value) in assignments {
+            let set_col_idx = match table.columns.iter().position(|c| names_match(&c.name, &column_to_set)) {
+                Some(index) => index,
+                None => return Err(IsentaError::ColumnNotFound { table: table.name.clone(), column: column_to_set.clone() }),
+            };
 
-        let (column_to_set, new_value) = set_clause;
+            if updates.iter().any(|(index, _)| *index == set_col_idx) {
+                return Err(IsentaError::InvalidQuery(format!("Column '{}' is assigned more than once", column_to_set)));
+            }
+
+            validate_column_value(&table.columns[set_col_idx], new_value.as_deref())?;
 
-        let column_to_set_index = table.columns.iter().position(|c| c.name.to_lowercase() == column_to_set.to_lowercase());
+            updates.push((set_col_idx, new_value));
+        }
 
-        let set_col_idx = match column_to_set_index {
-            Some(index) => index,
-            None => return Err(format!("Column '{}' not found in table '{}'", column_to_set, table.name)),
+        let apply = |row: &mut Row| {
+            for (index, value) in &updates {
+                if let Some(val_to_update) = row.values.get_mut(*index) {
+                    *val_to_update = value.clone();
+                }
+            }
         };
 
         let mut updated_count = 0;
+        let limit = limit.unwrap_or(usize::MAX);
 
         // If there's a WHERE clause, filter by it. Otherwise, update all rows.
-        if let Some(clause) = where_clause {
-            let where_column_index = table.columns.iter().position(|c| c.name.to_lowercase() == clause.column.to_lowercase());
-
-            if let Some(where_idx) = where_column_index {
-                let column = table.columns[where_idx].clone();
-                for row in table.rows.iter_mut() {
-                    if let Some(value) = row.values.get(where_idx) {
-                        if Self::evaluate_condition(value, &clause.operator, &clause.value, &column.data_type) {
-                            if let Some(val_to_update) = row.values.get_mut(set_col_idx) {
-                                *val_to_update = new_value.clone();
-                                updated_count += 1;
-                            }
-                        }
-                    }
+        if let Some(expr) = where_clause {
+            Self::validate_where(table, &expr)?;
+            for row in table.rows.iter_mut() {
+                if updated_count == limit {
+                    break;
+                }
+                if row_matches(row, &expr, &table.columns) {
+                    apply(row);
+                    updated_count += 1;
                 }
-            } else {
-                return Err(format!("Column '{}' not found in table '{}'", clause.column, table.name));
             }
         } else {
             // No WHERE clause, update all rows
-            for row in table.rows.iter_mut() {
-                if let Some(val_to_update) = row.values.get_mut(set_col_idx) {
-                    *val_to_update = new_value.clone();
-                    updated_count += 1;
-                }
+            for row in table.rows.iter_mut().take(limit) {
+                apply(row);
+                updated_count += 1;
             }
         }
         
-        let table_clone = table.clone();
-        self.database.update_table_data(&table_clone)?;
+        if updated_count > 0 {
+            table.updated_at = unix_now();
+        }
+        let table_clone = self.write_table(&table_name)?;
+        if updated_count > 0 {
+            self.record_change(&table_clone.name, ChangeKind::Update)?;
+        }
 
         Ok(updated_count)
     }
 
-    pub fn execute_truncate_table(&mut self, table_name: String) -> Result<(), String> {
+    /// Deletes the rows matching the WHERE clause, or every row without one,
+    /// stopping after `limit` rows when given, and returns how many were
+    /// deleted. Only the data pages that held the
+    /// deleted rows are rewritten, so purging the oldest rows of a large
+    /// table with `WHERE _rowid BETWEEN 1 AND 1000` leaves the rest of its
+    /// pages alone. The remaining rows keep their rowids, and those of the
+    /// deleted rows aren't reused.
+    pub fn execute_delete(
+        &mut self,
+        table_name: String,
+        where_clause: Option<WhereExpr>,
+        limit: Option<usize>,
+    ) -> Result<usize, IsentaError> {
+        let mode = self.column_count_mode;
         let table = self
             .catalog
             .find_table_mut(&table_name)
-            .ok_or_else(|| format!("Table '{}' does not exist", table_name))?;
+            .ok_or_else(|| IsentaError::TableNotFound(table_name.clone()))?;
+        conform_rows(&table.name, table.columns.len(), &mut table.rows, mode, &self.logger)?;
+
+        let limit = limit.unwrap_or(usize::MAX);
+        let deleted: HashSet<u64> = match &where_clause {
+            Some(expr) => {
+                Self::validate_where(table, expr)?;
+                table
+                    .rows
+                    .iter()
+                    .filter(|row| row_matches(row, expr, &table.columns))
+                    .take(limit)
+                    .map(|row| row.rowid)
+                    .collect()
+            }
+            None => table.rows.iter().take(limit).map(|row| row.rowid).collect(),
+        };
+        if deleted.is_empty() {
+            return Ok(0);
+        }
+
+        table.rows.retain(|row| !deleted.contains(&row.rowid));
+        table.updated_at = unix_now();
+        match self.database.delete_table_rows(table, &deleted) {
+            Ok(()) => table.change_count += 1,
+            Err(e @ (IsentaError::ConcurrentChange(_) | IsentaError::TableLocked(_))) => {
+                self.catalog = self.database.load_catalog()?;
+                return Err(e);
+            }
+            Err(e) => return Err(e),
+        }
+        self.record_change(&table_name, ChangeKind::Delete)?;
+        Ok(deleted.len())
+    }
+
+    pub fn execute_truncate_table(&mut self, table_name: String) -> Result<(), IsentaError> {
+        let table = self
+            .catalog
+            .find_table_mut(&table_name)
+            .ok_or_else(|| IsentaError::TableNotFound(table_name.clone()))?;
 
         table.rows.clear();
+        table.updated_at = unix_now();
         
-        let table_clone = table.clone();
-        self.database.update_table_data(&table_clone)?;
+        let table_clone = self.write_table(&table_name)?;
+        self.record_change(&table_clone.name, ChangeKind::Delete)?;
+
+        Ok(())
+    }
+
+    /// Drops a table with its rows. With `if_exists`, a table that doesn't
+    /// exist isn't an error; returns whether a table was dropped.
+    pub fn execute_drop_table(&mut self, table_name: String, if_exists: bool) -> Result<bool, IsentaError> {
+        let table = match self.catalog.find_table(&table_name) {
+            Some(table) => table.clone(),
+            None if if_exists => return Ok(false),
+            None => return Err(IsentaError::TableNotFound(table_name)),
+        };
+
+        match self.database.drop_table(&table) {
+            Ok(()) => {}
+            Err(e @ (IsentaError::ConcurrentChange(_) | IsentaError::TableLocked(_))) => {
+                self.catalog = self.database.load_catalog()?;
+                return Err(e);
+            }
+            Err(e) => return Err(e),
+        }
+        self.catalog.drop_table(&table.name);
+        self.record_change(&table.name, ChangeKind::Ddl)?;
+        Ok(true)
+    }
+
+    /// Renames a table. Its columns, constraints and rows are kept as they
+    /// are; fails with `IsentaError::TableExists` if another table already
+    /// has the new name.
+    ///
+    /// Nothing that refers to the table is rewritten: there are no indexes,
+    /// and the table-level `PRIMARY KEY`, `UNIQUE`, `CHECK` and `FOREIGN KEY`
+    /// clauses CREATE TABLE accepts are not stored. Once any of them are,
+    /// a rename has to rewrite their references to the old name.
+    pub fn execute_rename_table(&mut self, table_name: String, new_name: String) -> Result<(), IsentaError> {
+        let mut table = self
+            .catalog
+            .find_table(&table_name)
+            .ok_or_else(|| IsentaError::TableNotFound(table_name.clone()))?
+            .clone();
+        if self.catalog.find_table(&new_name).is_some_and(|other| !names_match(&other.name, &table.name)) {
+            return Err(IsentaError::TableExists(new_name));
+        }
+
+        let old_name = std::mem::replace(&mut table.name, new_name);
+        self.write_schema(&old_name, table)
+    }
 
+    /// Renames a column of a table. The column keeps its type, constraints
+    /// and values. The new name may not be that of another column, or of
+    /// the virtual `_rowid` column, which would make it ambiguous. As with
+    /// `execute_rename_table`, there are no indexes or stored table
+    /// constraints whose references to the column need rewriting.
+    pub fn execute_rename_column(&mut self, table_name: String, column: String, new_name: String) -> Result<(), IsentaError> {
+        let mut table = self
+            .catalog
+            .find_table(&table_name)
+            .ok_or_else(|| IsentaError::TableNotFound(table_name.clone()))?
+            .clone();
+        let index = table
+            .columns
+            .iter()
+            .position(|c| names_match(&c.name, &column))
+            .ok_or_else(|| IsentaError::ColumnNotFound { table: table.name.clone(), column: column.clone() })?;
+        if new_name.is_empty() {
+            return Err(IsentaError::InvalidQuery("Column name cannot be empty".to_string()));
+        }
+        if new_name.eq_ignore_ascii_case(ROWID_COLUMN) {
+            return Err(IsentaError::InvalidQuery(format!("'{}' is reserved for the rowid column", ROWID_COLUMN)));
+        }
+        if table.columns.iter().enumerate().any(|(other, c)| other != index && names_match(&c.name, &new_name)) {
+            return Err(IsentaError::InvalidQuery(format!(
+                "Column '{}' already exists in table '{}'",
+                new_name, table.name
+            )));
+        }
+
+        table.columns[index].name = new_name;
+        let name = table.name.clone();
+        self.write_schema(&name, table)
+    }
+
+    /// Writes the renamed schema of the table stored as `old_name` and puts
+    /// it in the catalog. If another process wrote the table since it was
+    /// loaded, the catalog is reloaded instead, as `write_table` does.
+    fn write_schema(&mut self, old_name: &str, mut table: Table) -> Result<(), IsentaError> {
+        match self.database.rewrite_table_schema(old_name, &table) {
+            Ok(()) => table.change_count += 1,
+            Err(e @ (IsentaError::ConcurrentChange(_) | IsentaError::TableLocked(_))) => {
+                self.catalog = self.database.load_catalog()?;
+                return Err(e);
+            }
+            Err(e) => return Err(e),
+        }
+        let name = table.name.clone();
+        if let Some(entry) = self.catalog.find_table_mut(old_name) {
+            *entry = table;
+        }
+        self.record_change(&name, ChangeKind::Ddl)?;
         Ok(())
     }
 
@@ -308,4 +1861,1342 @@ impl QueryEngine {
     pub fn get_all_tables(&self) -> &Vec<Table> {
         self.catalog.get_all_tables()
     }
-}
\ No newline at end of file
+
+    /// The tables whose names match a LIKE pattern such as `user%`, as a
+    /// `LIKE` condition matches a text value: ignoring ASCII case, with `%`
+    /// for any run of characters and `_` for any one character.
+    pub fn tables_like(&self, pattern: &str) -> Vec<&Table> {
+        self.catalog
+            .get_all_tables()
+            .iter()
+            .filter(|table| Self::evaluate_condition(Some(&table.name), "LIKE", Some(pattern), "TEXT"))
+            .collect()
+    }
+
+    /// The name and row count of every table, for listings that don't need
+    /// the rows themselves.
+    pub fn table_summaries(&self) -> Vec<(String, usize)> {
+        self.catalog.table_summaries()
+    }
+}
+
+/// A column referred to by a query: a table column, the virtual rowid
+/// column, a value inside the JSON of a table column, written
+/// `json_extract(column, '$.path')`, another column's value converted to
+/// a type, written `CAST(column AS type)`, a string function of another
+/// column, such as `LOWER(column)`, or `COALESCE(...)` / `NULLIF(a, b)`
+/// of other columns and constants.
+enum ColumnRef {
+    Table(usize),
+    RowId,
+    Json { index: usize, path: Vec<PathStep> },
+    Cast { source: Box<ColumnRef>, target: CastType },
+    Function { source: Box<ColumnRef>, function: StringFunction },
+    /// A literal argument of `COALESCE` or `NULLIF`, `None` for NULL
+    Constant(Option<String>),
+    /// The first of the arguments that isn't NULL
+    Coalesce(Vec<ColumnRef>),
+    /// NULL when `value` equals `other`, compared as `=` compares values of
+    /// `value`'s type, and otherwise `value`
+    NullIf { value: Box<ColumnRef>, other: Box<ColumnRef>, data_type: String },
+}
+
+/// The string functions usable wherever a column is. Each returns NULL for
+/// a NULL argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StringFunction {
+    Upper,
+    Lower,
+    /// The number of characters
+    Length,
+    /// Strips whitespace from both ends
+    Trim,
+    /// `SUBSTR(column, start[, length])`: the characters from `start`,
+    /// counted from 1, or from the end when negative
+    Substr { start: i64, length: Option<usize> },
+}
+
+impl StringFunction {
+    /// Parses a call from the function name and its argument list,
+    /// returning the function and the column it applies to.
+    fn parse<'a>(name: &str, arguments: &[&'a str]) -> Result<(StringFunction, &'a str), String> {
+        let name = name.to_ascii_uppercase();
+        let function = match (name.as_str(), arguments) {
+            ("UPPER", [_]) => StringFunction::Upper,
+            ("LOWER", [_]) => StringFunction::Lower,
+            ("LENGTH", [_]) => StringFunction::Length,
+            ("TRIM", [_]) => StringFunction::Trim,
+            ("SUBSTR", [_, start]) | ("SUBSTR", [_, start, _]) => {
+                let start = start
+                    .parse::<i64>()
+                    .map_err(|_| format!("SUBSTR start '{}' is not an integer", start))?;
+                let length = match arguments.get(2) {
+                    Some(length) => Some(
+                        length
+                            .parse::<usize>()
+                            .map_err(|_| format!("SUBSTR length '{}' is not a non-negative integer", length))?,
+                    ),
+                    None => None,
+                };
+                StringFunction::Substr { start, length }
+            }
+            ("SUBSTR", _) => return Err("SUBSTR takes a column, a start and an optional length".to_string()),
+            _ => return Err(format!("{} takes exactly one column", name)),
+        };
+        Ok((function, arguments[0]))
+    }
+
+    fn data_type(self) -> &'static str {
+        match self {
+            StringFunction::Length => "INTEGER",
+            _ => "TEXT",
+        }
+    }
+
+    fn apply(self, value: &str) -> String {
+        match self {
+            StringFunction::Upper => value.to_uppercase(),
+            StringFunction::Lower => value.to_lowercase(),
+            StringFunction::Length => value.chars().count().to_string(),
+            StringFunction::Trim => value.trim().to_string(),
+            StringFunction::Substr { start, length } => {
+                let skip = match start {
+                    0 => 0,
+                    start if start > 0 => (start - 1) as usize,
+                    start => value.chars().count().saturating_sub(start.unsigned_abs() as usize),
+                };
+                let characters = value.chars().skip(skip);
+                match length {
+                    Some(length) => characters.take(length).collect(),
+                    None => characters.collect(),
+                }
+            }
+        }
+    }
+}
+
+/// The types `CAST` converts to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CastType {
+    Integer,
+    Float,
+    Text,
+}
+
+impl CastType {
+    fn parse(name: &str) -> Option<CastType> {
+        match normalized_type(name.trim()).as_str() {
+            "INTEGER" => Some(CastType::Integer),
+            "FLOAT" => Some(CastType::Float),
+            "TEXT" => Some(CastType::Text),
+            _ => None,
+        }
+    }
+
+    fn data_type(self) -> &'static str {
+        match self {
+            CastType::Integer => "INTEGER",
+            CastType::Float => "FLOAT",
+            CastType::Text => "TEXT",
+        }
+    }
+
+    /// Converts a value to this type. Text that isn't a number becomes NULL
+    /// as an INTEGER or FLOAT, and a FLOAT becomes an INTEGER by dropping
+    /// its fraction.
+    fn convert(self, value: &str) -> Option<String> {
+        match self {
+            CastType::Text => Some(value.to_string()),
+            CastType::Float => Number::parse(value).map(|number| Number::Float(number.as_f64()).to_string()),
+            CastType::Integer => match Number::parse(value)? {
+                Number::Int(int) => Some(int.to_string()),
+                Number::Float(float) => {
+                    let truncated = float.trunc();
+                    (truncated >= i64::MIN as f64 && truncated < i64::MAX as f64).then(|| (truncated as i64).to_string())
+                }
+            },
+        }
+    }
+}
+
+impl ColumnRef {
+    /// The value of this column in `row`, `None` for NULL.
+    fn value(&self, row: &Row) -> Option<String> {
+        match self {
+            ColumnRef::Table(index) => row.values.get(*index).cloned().flatten(),
+            ColumnRef::RowId => Some(row.rowid.to_string()),
+            ColumnRef::Json { index, path } => {
+                row.values.get(*index).cloned().flatten().and_then(|document| json::extract(&document, path))
+            }
+            ColumnRef::Cast { source, target } => target.convert(&source.value(row)?),
+            ColumnRef::Function { source, function } => Some(function.apply(&source.value(row)?)),
+            ColumnRef::Constant(value) => value.clone(),
+            ColumnRef::Coalesce(arguments) => arguments.iter().find_map(|argument| argument.value(row)),
+            ColumnRef::NullIf { value, other, data_type } => {
+                let value = value.value(row)?;
+                let equal = QueryEngine::evaluate_condition(Some(&value), "=", other.value(row).as_deref(), data_type);
+                (!equal).then_some(value)
+            }
+        }
+    }
+
+    fn data_type<'t>(&self, columns: &'t [Column]) -> &'t str {
+        match self {
+            ColumnRef::Table(index) => &columns[*index].data_type,
+            ColumnRef::RowId => "INTEGER",
+            ColumnRef::Json { .. } => "JSON",
+            ColumnRef::Cast { target, .. } => target.data_type(),
+            ColumnRef::Function { function, .. } => function.data_type(),
+            ColumnRef::Constant(value) => match value.as_deref().and_then(Number::parse) {
+                Some(Number::Int(_)) => "INTEGER",
+                Some(Number::Float(_)) => "FLOAT",
+                None => "TEXT",
+            },
+            // A column argument decides the type over a fallback constant
+            ColumnRef::Coalesce(arguments) => arguments
+                .iter()
+                .find(|argument| !matches!(argument, ColumnRef::Constant(_)))
+                .unwrap_or(&arguments[0])
+                .data_type(columns),
+            ColumnRef::NullIf { value, .. } => value.data_type(columns),
+        }
+    }
+}
+
+/// Whether two table or column names refer to the same object. Names are
+/// case-insensitive in ASCII only, so that folding never depends on the
+/// language of a name: `É` and `é` are distinct, as are `I` and `ı`. Every
+/// lookup and duplicate check goes through here.
+pub fn names_match(a: &str, b: &str) -> bool {
+    a.eq_ignore_ascii_case(b)
+}
+
+/// Finds a column by name. Table columns take precedence over `_rowid`.
+///
+/// The columns of a join are named `table.column`; those can also be found
+/// by their column name alone, as long as only one table has such a column.
+fn find_column(columns: &[Column], name: &str) -> Option<ColumnRef> {
+    if let Some((column, target)) = split_cast(name) {
+        let source = Box::new(find_column(columns, column)?);
+        return Some(ColumnRef::Cast { source, target: CastType::parse(target)? });
+    }
+    if let Some((function, arguments)) = split_call(name, &STRING_FUNCTIONS) {
+        let (function, column) = StringFunction::parse(function, &arguments).ok()?;
+        return Some(ColumnRef::Function { source: Box::new(find_column(columns, column)?), function });
+    }
+    if let Some((function, arguments)) = split_call(name, &NULL_FUNCTIONS) {
+        check_null_function(function, &arguments).ok()?;
+        let mut arguments = arguments
+            .into_iter()
+            .map(|argument| match literal_argument(argument) {
+                Some(value) => Some(ColumnRef::Constant(value)),
+                None => find_column(columns, argument),
+            })
+            .collect::<Option<Vec<_>>>()?;
+        if !function.eq_ignore_ascii_case("NULLIF") {
+            return Some(ColumnRef::Coalesce(arguments));
+        }
+        let other = Box::new(arguments.pop()?);
+        let value = Box::new(arguments.pop()?);
+        let data_type = value.data_type(columns).to_string();
+        return Some(ColumnRef::NullIf { value, other, data_type });
+    }
+    if let Some((column, path)) = split_json_extract(name) {
+        return match find_column(columns, column)? {
+            ColumnRef::Table(index) => Some(ColumnRef::Json { index, path: json::parse_path(path).ok()? }),
+            _ => None,
+        };
+    }
+    if let Some(index) = columns.iter().position(|c| names_match(&c.name, name)) {
+        return Some(ColumnRef::Table(index));
+    }
+    let mut qualified = columns.iter().enumerate().filter(|(_, c)| unqualified_name_matches(&c.name, name));
+    if let Some((index, _)) = qualified.next() {
+        return match qualified.next() {
+            Some(_) => None,
+            None => Some(ColumnRef::Table(index)),
+        };
+    }
+    if name.eq_ignore_ascii_case(ROWID_COLUMN) {
+        return Some(ColumnRef::RowId);
+    }
+    None
+}
+
+/// Splits `json_extract(column, '$.path')` into the column and the path.
+fn split_json_extract(name: &str) -> Option<(&str, &str)> {
+    let (function, arguments) = name.split_once('(')?;
+    if !function.trim().eq_ignore_ascii_case("json_extract") {
+        return None;
+    }
+    let (column, path) = arguments.strip_suffix(')')?.split_once(',')?;
+    let path = path.trim();
+    let path = path.strip_prefix('\'').and_then(|path| path.strip_suffix('\''))?;
+    Some((column.trim(), path))
+}
+
+/// Splits `CAST(column AS type)` into the column and the type.
+fn split_cast(name: &str) -> Option<(&str, &str)> {
+    let (function, arguments) = name.split_once('(')?;
+    if !function.trim().eq_ignore_ascii_case("CAST") {
+        return None;
+    }
+    let arguments = arguments.strip_suffix(')')?;
+    let position = arguments.to_ascii_uppercase().rfind(" AS ")?;
+    Some((arguments[..position].trim(), arguments[position + " AS ".len()..].trim()))
+}
+
+/// The names of the `StringFunction`s.
+const STRING_FUNCTIONS: [&str; 5] = ["UPPER", "LOWER", "LENGTH", "TRIM", "SUBSTR"];
+
+/// The functions that turn NULL into a value or a value into NULL.
+const NULL_FUNCTIONS: [&str; 2] = ["COALESCE", "NULLIF"];
+
+/// Splits a call of one of `functions`, such as `SUBSTR(column, 2, 3)`,
+/// into the function name and its arguments.
+fn split_call<'a>(name: &'a str, functions: &[&str]) -> Option<(&'a str, Vec<&'a str>)> {
+    let (function, arguments) = name.split_once('(')?;
+    let function = function.trim();
+    if !functions.iter().any(|known| known.eq_ignore_ascii_case(function)) {
+        return None;
+    }
+    let arguments = arguments.strip_suffix(')')?;
+    Some((function, split_top_level(arguments, ',').into_iter().map(str::trim).collect()))
+}
+
+/// Checks the argument count of a `COALESCE` or `NULLIF` call.
+fn check_null_function(function: &str, arguments: &[&str]) -> Result<(), String> {
+    let function = function.to_ascii_uppercase();
+    if arguments.iter().any(|argument| argument.is_empty()) {
+        return Err(format!("{} has an empty argument", function));
+    }
+    match (function.as_str(), arguments.len()) {
+        ("NULLIF", 2) | ("COALESCE", 1..) => Ok(()),
+        ("NULLIF", _) => Err("NULLIF takes exactly two arguments".to_string()),
+        _ => Err(format!("{} takes at least one argument", function)),
+    }
+}
+
+/// The value of a function argument written as a constant: a quoted or
+/// numeric literal, or NULL. `None` for anything else, which names a
+/// column.
+fn literal_argument(argument: &str) -> Option<Option<String>> {
+    let is_literal = is_quoted(argument) || argument.eq_ignore_ascii_case("NULL") || Number::parse(argument).is_some();
+    is_literal.then(|| Parser::new().parse_literal(argument))
+}
+
+/// Whether `name` is the column part of the qualified column name
+/// `table.column`.
+fn unqualified_name_matches(qualified: &str, name: &str) -> bool {
+    !name.contains('.') && qualified.split_once('.').is_some_and(|(_, column)| names_match(column, name))
+}
+
+/// Like `find_column`, with an error naming the table when it doesn't exist
+/// or, in a join, when more than one table has the column.
+fn resolve_column(table: &Table, name: &str) -> Result<ColumnRef, IsentaError> {
+    if let Some((column, target)) = split_cast(name) {
+        if CastType::parse(target).is_none() {
+            return Err(IsentaError::InvalidQuery(format!(
+                "Cannot CAST to '{}'; expected INTEGER, FLOAT or TEXT",
+                target
+            )));
+        }
+        resolve_column(table, column)?;
+    }
+    if let Some((function, arguments)) = split_call(name, &STRING_FUNCTIONS) {
+        let (_, column) = StringFunction::parse(function, &arguments).map_err(IsentaError::InvalidQuery)?;
+        resolve_column(table, column)?;
+    }
+    if let Some((function, arguments)) = split_call(name, &NULL_FUNCTIONS) {
+        check_null_function(function, &arguments).map_err(IsentaError::InvalidQuery)?;
+        for argument in arguments.into_iter().filter(|argument| literal_argument(argument).is_none()) {
+            resolve_column(table, argument)?;
+        }
+    }
+    if let Some((column, path)) = split_json_extract(name) {
+        if !json::ENABLED {
+            return Err(json_disabled());
+        }
+        json::parse_path(path).map_err(|e| IsentaError::InvalidQuery(format!("Invalid JSON path '{}': {}", path, e)))?;
+        if let ColumnRef::RowId = resolve_column(table, column)? {
+            return Err(IsentaError::InvalidQuery(format!("'{}' holds no JSON to extract from", column)));
+        }
+    }
+    find_column(&table.columns, name).ok_or_else(|| {
+        if table.columns.iter().filter(|c| unqualified_name_matches(&c.name, name)).count() > 1 {
+            IsentaError::InvalidQuery(format!("Column '{}' is ambiguous; qualify it with its table name", name))
+        } else {
+            IsentaError::ColumnNotFound { table: table.name.clone(), column: name.to_string() }
+        }
+    })
+}
+
+/// The column a condition compares with instead of a literal: its value when
+/// that is an unquoted column name, as in the join condition `a.id = b.a_id`.
+fn compared_column(clause: &WhereClause, columns: &[Column]) -> Option<ColumnRef> {
+    match &clause.value {
+        Some(value) if !clause.quoted => find_column(columns, value),
+        _ => None,
+    }
+}
+
+/// Whether `row` satisfies a WHERE expression. Every command that filters
+/// rows goes through here, so a condition matches the same rows in each.
+fn row_matches(row: &Row, expr: &WhereExpr, columns: &[Column]) -> bool {
+    match expr {
+        WhereExpr::Condition(clause) => match find_column(columns, &clause.column) {
+            Some(target) => {
+                let other = compared_column(clause, columns).map(|other| other.value(row));
+                QueryEngine::evaluate_condition(
+                    target.value(row).as_deref(),
+                    &clause.operator,
+                    match &other {
+                        Some(value) => value.as_deref(),
+                        None => clause.value.as_deref(),
+                    },
+                    target.data_type(columns),
+                )
+            }
+            None => false,
+        },
+        WhereExpr::And(left, right) => row_matches(row, left, columns) && row_matches(row, right, columns),
+        WhereExpr::Or(left, right) => row_matches(row, left, columns) || row_matches(row, right, columns),
+    }
+}
+
+/// A number in arithmetic and aggregates. Integers stay exact until an
+/// operation overflows or involves a float.
+#[derive(Debug, Clone, Copy)]
+enum Number {
+    Int(i64),
+    Float(f64),
+}
+
+impl Number {
+    fn parse(value: &str) -> Option<Number> {
+        let value = value.trim();
+        match value.parse::<i64>() {
+            Ok(int) => Some(Number::Int(int)),
+            Err(_) => value.parse::<f64>().ok().filter(|float| float.is_finite()).map(Number::Float),
+        }
+    }
+
+    fn as_f64(self) -> f64 {
+        match self {
+            Number::Int(int) => int as f64,
+            Number::Float(float) => float,
+        }
+    }
+
+    /// Applies one of `+ - * /`; `None` for a division by zero.
+    fn apply(self, operator: char, other: Number) -> Option<Number> {
+        if let (Number::Int(left), Number::Int(right)) = (self, other) {
+            let exact = match operator {
+                '+' => left.checked_add(right),
+                '-' => left.checked_sub(right),
+                '*' => left.checked_mul(right),
+                _ if right == 0 => return None,
+                _ => left.checked_div(right),
+            };
+            if let Some(result) = exact {
+                return Some(Number::Int(result));
+            }
+        }
+
+        let (left, right) = (self.as_f64(), other.as_f64());
+        match operator {
+            '+' => Some(Number::Float(left + right)),
+            '-' => Some(Number::Float(left - right)),
+            '*' => Some(Number::Float(left * right)),
+            _ if right == 0.0 => None,
+            _ => Some(Number::Float(left / right)),
+        }
+    }
+}
+
+impl std::fmt::Display for Number {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Number::Int(int) => write!(f, "{}", int),
+            Number::Float(float) => write!(f, "{}", float),
+        }
+    }
+}
+
+/// Checks that every column an expression refers to exists.
+fn validate_expr(table: &Table, expr: &Expr) -> Result<(), IsentaError> {
+    match expr {
+        Expr::Column(name) => resolve_column(table, name).map(|_| ()),
+        Expr::Literal(_) => Ok(()),
+        Expr::Binary { left, right, .. } => {
+            validate_expr(table, left)?;
+            validate_expr(table, right)
+        }
+    }
+}
+
+/// Evaluates an expression for one row. NULL in arithmetic gives NULL, as
+/// does a division by zero.
+fn evaluate_expr(expr: &Expr, row: &Row, columns: &[Column]) -> Result<Option<String>, IsentaError> {
+    match expr {
+        Expr::Column(name) => Ok(find_column(columns, name).and_then(|column| column.value(row))),
+        Expr::Literal(value) => Ok(value.clone()),
+        Expr::Binary { left, operator, right } => {
+            let (Some(left), Some(right)) = (evaluate_expr(left, row, columns)?, evaluate_expr(right, row, columns)?) else {
+                return Ok(None);
+            };
+            let operand = |value: &str| {
+                Number::parse(value).ok_or_else(|| {
+                    IsentaError::TypeMismatch(format!("cannot apply '{}' to non-numeric value '{}'", operator, value))
+                })
+            };
+            Ok(operand(&left)?.apply(*operator, operand(&right)?).map(|result| result.to_string()))
+        }
+    }
+}
+
+/// The column types of a SELECT's result, for `CREATE TABLE ... AS`: each
+/// selected column keeps its type, COUNT is INTEGER and AVG is FLOAT. SUM,
+/// MIN and MAX of a column have the column's type, and of any other numeric
+/// expression FLOAT. Constants are INTEGER, FLOAT or TEXT by their value.
+fn projected_types(table: &Table, items: &[SelectItem]) -> Result<Vec<String>, IsentaError> {
+    let mut types = Vec::new();
+    for item in items {
+        let (item, _) = split_alias(item);
+        match item {
+            SelectItem::Wildcard => types.extend(table.columns.iter().map(|column| column.data_type.clone())),
+            SelectItem::Column(name) => match resolve_column(table, name)? {
+                // Extracted strings are no longer JSON
+                ColumnRef::Json { .. } => types.push("TEXT".to_string()),
+                column => types.push(column.data_type(&table.columns).to_string()),
+            },
+            SelectItem::Literal(value) => {
+                let data_type = match value.as_deref().map(Number::parse) {
+                    Some(Some(Number::Int(_))) => "INTEGER",
+                    Some(Some(Number::Float(_))) => "FLOAT",
+                    _ => "TEXT",
+                };
+                types.push(data_type.to_string());
+            }
+            SelectItem::Aggregate(aggregate) => {
+                let data_type = match (&aggregate.function, &aggregate.argument) {
+                    (AggregateFunction::Count, _) | (_, None) => "INTEGER".to_string(),
+                    (AggregateFunction::Avg, _) => "FLOAT".to_string(),
+                    (_, Some(Expr::Column(name))) => resolve_column(table, name)?.data_type(&table.columns).to_string(),
+                    (_, Some(argument)) if is_numeric_expr(argument, &table.columns) => "FLOAT".to_string(),
+                    _ => "TEXT".to_string(),
+                };
+                types.push(data_type);
+            }
+            SelectItem::Aliased { .. } => unreachable!("aliases are removed above"),
+        }
+    }
+    Ok(types)
+}
+
+/// Whether an expression yields numbers, so that MIN and MAX compare its
+/// values numerically rather than as text.
+fn is_numeric_expr(expr: &Expr, columns: &[Column]) -> bool {
+    match expr {
+        Expr::Column(name) => find_column(columns, name).is_some_and(|column| !is_text_type(column.data_type(columns))),
+        Expr::Literal(value) => value.as_deref().and_then(Number::parse).is_some(),
+        Expr::Binary { .. } => true,
+    }
+}
+
+/// Sorts rows by the `order_by` keys with a stable sort, comparing each key
+/// with `compare_values` after placing NULLs as its `nulls_first` says.
+fn sort_rows(table: &Table, rows: Vec<Row>, order_by: &[OrderBy]) -> Result<Vec<Row>, IsentaError> {
+    let mut keys = Vec::new();
+    for key in order_by {
+        let column = resolve_column(table, &key.column)?;
+        let data_type = column.data_type(&table.columns);
+        keys.push((column, data_type, key));
+    }
+
+    // Each row's sort values are read once rather than on every comparison
+    let mut keyed: Vec<(Vec<Option<String>>, Row)> = rows
+        .into_iter()
+        .map(|row| (keys.iter().map(|(column, _, _)| column.value(&row)).collect(), row))
+        .collect();
+    keyed.sort_by(|(a, _), (b, _)| {
+        keys.iter().zip(a.iter().zip(b)).fold(std::cmp::Ordering::Equal, |order, ((_, data_type, key), (a, b))| {
+            order.then_with(|| match (key.nulls_first.unwrap_or(true), a, b) {
+                // NULLs are placed first, or last with NULLS LAST, in either
+                // direction
+                (true, None, Some(_)) | (false, Some(_), None) => std::cmp::Ordering::Less,
+                (true, Some(_), None) | (false, None, Some(_)) => std::cmp::Ordering::Greater,
+                _ => {
+                    let order = compare_values(a.as_deref(), b.as_deref(), data_type);
+                    if key.ascending { order } else { order.reverse() }
+                }
+            })
+        })
+    });
+    Ok(keyed.into_iter().map(|(_, row)| row).collect())
+}
+
+/// Orders two values of a column of the given type. NULL sorts before every
+/// value. INTEGER and FLOAT values, and JSON numbers, compare numerically;
+/// everything else, and numbers that don't parse, compare as text, ignoring
+/// case as `=` does, with ties broken by case so that the order is total.
+fn compare_values(a: Option<&str>, b: Option<&str>, data_type: &str) -> std::cmp::Ordering {
+    let (a, b) = match (a, b) {
+        (None, None) => return std::cmp::Ordering::Equal,
+        (None, Some(_)) => return std::cmp::Ordering::Less,
+        (Some(_), None) => return std::cmp::Ordering::Greater,
+        (Some(a), Some(b)) => (a, b),
+    };
+    match normalized_type(data_type).as_str() {
+        "INTEGER" => {
+            if let (Ok(a), Ok(b)) = (a.parse::<i64>(), b.parse::<i64>()) {
+                return a.cmp(&b);
+            }
+        }
+        "FLOAT" | "JSON" => {
+            if let (Ok(a), Ok(b)) = (a.parse::<f64>(), b.parse::<f64>()) {
+                return a.total_cmp(&b);
+            }
+        }
+        _ => {}
+    }
+    compare_text(a, b).then_with(|| a.cmp(b))
+}
+
+/// How two FLOAT or JSON values compare when both are numbers and the
+/// operator orders or equates them; `None` when they compare as text instead.
+fn number_order(a: &str, b: &str, operator: &str, data_type: &str) -> Option<std::cmp::Ordering> {
+    if !matches!(normalized_type(data_type).as_str(), "FLOAT" | "JSON")
+        || !matches!(operator, "=" | "!=" | ">" | "<" | ">=" | "<=")
+    {
+        return None;
+    }
+    Some(a.parse::<f64>().ok()?.total_cmp(&b.parse::<f64>().ok()?))
+}
+
+/// The collation of text values: alphabetical by character, ignoring case,
+/// so that `'apple' < 'Banana'`.
+fn compare_text(a: &str, b: &str) -> std::cmp::Ordering {
+    a.to_lowercase().cmp(&b.to_lowercase())
+}
+
+/// Computes a query whose projection contains aggregates, producing a single
+/// row. Apart from aggregates only constants may be selected.
+fn aggregate_rows(table: &Table, items: &[SelectItem], rows: &[Row]) -> Result<QueryResult, IsentaError> {
+    let mut labels = Vec::new();
+    let mut values = Vec::new();
+    for item in items {
+        let (item, alias) = split_alias(item);
+        match item {
+            SelectItem::Aggregate(aggregate) => {
+                values.push(compute_aggregate(table, aggregate, rows)?);
+                labels.push(alias.map(str::to_string).unwrap_or_else(|| aggregate.default_name()));
+            }
+            SelectItem::Literal(value) => {
+                values.push(value.clone());
+                labels.push(alias.map(str::to_string).unwrap_or_else(|| literal_label(value)));
+            }
+            SelectItem::Wildcard => {
+                return Err(IsentaError::InvalidQuery("'*' can't be selected together with aggregate functions".to_string()));
+            }
+            SelectItem::Column(name) => {
+                resolve_column(table, name)?;
+                return Err(IsentaError::InvalidQuery(format!(
+                    "Column '{}' must be used inside an aggregate function when other columns are aggregated",
+                    name
+                )));
+            }
+            SelectItem::Aliased { .. } => unreachable!("aliases are removed above"),
+        }
+    }
+    Ok(QueryResult::Rows { columns: labels, rows: vec![Row { rowid: 0, values }] })
+}
+
+/// Computes a query with a GROUP BY column, producing one row for each
+/// distinct value of the column, NULL included. Values are grouped as `=`
+/// compares them for the column's type, so INTEGER `7` and `07` fall in one
+/// group, and each group shows the value of its first row. Apart from
+/// aggregates only the grouped column and constants may be selected.
+fn group_rows(table: &Table, items: &[SelectItem], rows: Vec<Row>, group_by: &str) -> Result<QueryResult, IsentaError> {
+    let group_column = resolve_column(table, group_by)?;
+    let data_type = group_column.data_type(&table.columns).to_string();
+
+    let mut labels = Vec::new();
+    for item in items {
+        let (item, alias) = split_alias(item);
+        let label = match item {
+            SelectItem::Aggregate(aggregate) => aggregate.default_name(),
+            SelectItem::Literal(value) => literal_label(value),
+            SelectItem::Column(name) if is_group_column(table, name, group_by)? => name.clone(),
+            SelectItem::Column(name) => {
+                return Err(IsentaError::InvalidQuery(format!(
+                    "Column '{}' must be the GROUP BY column or be used inside an aggregate function",
+                    name
+                )));
+            }
+            SelectItem::Wildcard => {
+                return Err(IsentaError::InvalidQuery("'*' can't be selected together with GROUP BY".to_string()));
+            }
+            SelectItem::Aliased { .. } => unreachable!("aliases are removed above"),
+        };
+        labels.push(alias.map(str::to_string).unwrap_or(label));
+    }
+
+    let mut groups: Vec<Vec<Row>> = Vec::new();
+    let mut group_index: HashMap<Option<String>, usize> = HashMap::new();
+    for row in rows {
+        let key = group_column.value(&row).map(|value| group_key(&value, &data_type));
+        match group_index.get(&key) {
+            Some(&index) => groups[index].push(row),
+            None => {
+                group_index.insert(key, groups.len());
+                groups.push(vec![row]);
+            }
+        }
+    }
+
+    let mut result = Vec::with_capacity(groups.len());
+    for group in &groups {
+        let mut values = Vec::new();
+        for item in items {
+            match split_alias(item).0 {
+                SelectItem::Aggregate(aggregate) => values.push(compute_aggregate(table, aggregate, group)?),
+                SelectItem::Literal(value) => values.push(value.clone()),
+                _ => values.push(group_column.value(&group[0])),
+            }
+        }
+        result.push(Row { rowid: 0, values });
+    }
+    Ok(QueryResult::Rows { columns: labels, rows: result })
+}
+
+/// Whether a selected column is the GROUP BY column, by name or by
+/// resolving to the same table column.
+fn is_group_column(table: &Table, name: &str, group_by: &str) -> Result<bool, IsentaError> {
+    if names_match(name, group_by) {
+        return Ok(true);
+    }
+    Ok(match (resolve_column(table, name)?, resolve_column(table, group_by)?) {
+        (ColumnRef::Table(a), ColumnRef::Table(b)) => a == b,
+        (ColumnRef::RowId, ColumnRef::RowId) => true,
+        _ => false,
+    })
+}
+
+/// The value a GROUP BY groups by: numbers of a numeric column by their
+/// value and text ignoring ASCII case, as `=` compares them.
+fn group_key(value: &str, data_type: &str) -> String {
+    match normalized_type(data_type).as_str() {
+        "INTEGER" => value.parse::<i64>().map_or_else(|_| value.to_string(), |n| n.to_string()),
+        "FLOAT" | "JSON" => value.parse::<f64>().map_or_else(|_| value.to_string(), |n| n.to_string()),
+        _ => value.to_ascii_lowercase(),
+    }
+}
+
+/// Splits `item AS alias` into the item and its alias.
+fn split_alias(item: &SelectItem) -> (&SelectItem, Option<&str>) {
+    match item {
+        SelectItem::Aliased { item, alias } => (item, Some(alias)),
+        item => (item, None),
+    }
+}
+
+/// The result column name of a constant without an alias.
+fn literal_label(value: &Option<String>) -> String {
+    value.clone().unwrap_or_else(|| "NULL".to_string())
+}
+
+/// Computes one aggregate over `rows`. NULL values are ignored. As in SQL,
+/// COUNT of no values is 0 while SUM, AVG, MIN and MAX of no values are
+/// NULL, so an empty SUM can be told apart from one that adds up to 0.
+/// MIN and MAX order text as ORDER BY does.
+fn compute_aggregate(table: &Table, aggregate: &Aggregate, rows: &[Row]) -> Result<Option<String>, IsentaError> {
+    let argument = match &aggregate.argument {
+        Some(argument) => argument,
+        None => return Ok(Some(rows.len().to_string())),
+    };
+    validate_expr(table, argument)?;
+
+    let mut values = Vec::new();
+    for row in rows {
+        if let Some(value) = evaluate_expr(argument, row, &table.columns)? {
+            values.push(value);
+        }
+    }
+    // DISTINCT values are told apart as GROUP BY tells groups apart
+    if aggregate.distinct {
+        let data_type = match argument {
+            Expr::Column(name) => resolve_column(table, name)?.data_type(&table.columns).to_string(),
+            argument if is_numeric_expr(argument, &table.columns) => "FLOAT".to_string(),
+            _ => "TEXT".to_string(),
+        };
+        let mut seen = HashSet::new();
+        values.retain(|value| seen.insert(group_key(value, &data_type)));
+    }
+
+    match aggregate.function {
+        AggregateFunction::Count => Ok(Some(values.len().to_string())),
+        AggregateFunction::Sum | AggregateFunction::Avg => {
+            if values.is_empty() {
+                return Ok(None);
+            }
+            let mut sum = Number::Int(0);
+            for value in &values {
+                let number = Number::parse(value).ok_or_else(|| {
+                    IsentaError::TypeMismatch(format!(
+                        "{} needs numeric values, got '{}'",
+                        aggregate.function.name(),
+                        value
+                    ))
+                })?;
+                sum = sum.apply('+', number).unwrap_or(sum);
+            }
+            if aggregate.function == AggregateFunction::Sum {
+                Ok(Some(sum.to_string()))
+            } else {
+                Ok(Some(Number::Float(sum.as_f64() / values.len() as f64).to_string()))
+            }
+        }
+        AggregateFunction::Min | AggregateFunction::Max => {
+            let numeric =
+                is_numeric_expr(argument, &table.columns) && values.iter().all(|value| Number::parse(value).is_some());
+            let compare = |a: &String, b: &String| {
+                if numeric {
+                    let (a, b) = (Number::parse(a).map_or(0.0, Number::as_f64), Number::parse(b).map_or(0.0, Number::as_f64));
+                    a.total_cmp(&b)
+                } else {
+                    compare_text(a, b).then_with(|| a.cmp(b))
+                }
+            };
+            Ok(if aggregate.function == AggregateFunction::Min {
+                values.into_iter().min_by(compare)
+            } else {
+                values.into_iter().max_by(compare)
+            })
+        }
+    }
+}
+
+/// Parses an on/off pragma value.
+fn parse_switch(pragma: &str, value: &str) -> Result<bool, IsentaError> {
+    match value.to_lowercase().as_str() {
+        "on" | "true" | "yes" | "1" => Ok(true),
+        "off" | "false" | "no" | "0" => Ok(false),
+        _ => Err(IsentaError::InvalidQuery(format!(
+            "Invalid value '{}' for pragma '{}'; expected on or off",
+            value, pragma
+        ))),
+    }
+}
+
+fn format_switch(on: bool) -> String {
+    if on { "on" } else { "off" }.to_string()
+}
+
+/// The current time in Unix epoch seconds.
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+/// Checks that every row has one value per column. In lenient mode, rows
+/// that don't are padded with NULL or truncated, with a warning.
+fn conform_rows(
+    table: &str,
+    column_count: usize,
+    rows: &mut [Row],
+    mode: ColumnCountMode,
+    logger: &Logger,
+) -> Result<(), IsentaError> {
+    for (index, row) in rows.iter_mut().enumerate() {
+        conform_row(table, column_count, index, row, mode, logger)?;
+    }
+    Ok(())
+}
+
+/// Checks that the row at `index` of a table has one value per column,
+/// padding or truncating it in lenient mode.
+fn conform_row(
+    table: &str,
+    column_count: usize,
+    index: usize,
+    row: &mut Row,
+    mode: ColumnCountMode,
+    logger: &Logger,
+) -> Result<(), IsentaError> {
+    if row.values.len() != column_count {
+        match mode {
+            ColumnCountMode::Strict => {
+                return Err(IsentaError::Corruption(format!(
+                    "Row {} of table '{}' has {} values but the table has {} columns; use lenient mode to pad or truncate it",
+                    index + 1,
+                    table,
+                    row.values.len(),
+                    column_count
+                )));
+            }
+            ColumnCountMode::Lenient => {
+                logger(&format!(
+                    "Row {} of table '{}' has {} values; {} to {} columns",
+                    index + 1,
+                    table,
+                    row.values.len(),
+                    if row.values.len() < column_count { "padded" } else { "truncated" },
+                    column_count
+                ));
+                row.values.resize(column_count, None);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// An open transaction, returned by `QueryEngine::begin`. Statements run
+/// against it as against the engine it derefs to. It ends with `commit` or
+/// `rollback`; dropped without either, it rolls back, so a transaction can't
+/// be left open by an early return or a panic.
+///
+/// Change callbacks are invoked as statements run, including for changes
+/// that are rolled back later.
+pub struct Transaction<'a> {
+    engine: &'a mut QueryEngine,
+    finished: bool,
+}
+
+impl Transaction<'_> {
+    /// Keeps the transaction's changes, writing them to the file unless
+    /// durability is deferred.
+    pub fn commit(mut self) -> Result<(), IsentaError> {
+        self.finished = true;
+        self.engine.database.commit_transaction()
+    }
+
+    /// Discards the transaction's changes, leaving the tables as they were
+    /// when it began.
+    pub fn rollback(mut self) -> Result<(), IsentaError> {
+        self.finished = true;
+        self.engine.rollback_transaction()
+    }
+}
+
+impl std::ops::Deref for Transaction<'_> {
+    type Target = QueryEngine;
+
+    fn deref(&self) -> &QueryEngine {
+        self.engine
+    }
+}
+
+impl std::ops::DerefMut for Transaction<'_> {
+    fn deref_mut(&mut self) -> &mut QueryEngine {
+        self.engine
+    }
+}
+
+impl Drop for Transaction<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            if let Err(e) = self.engine.rollback_transaction() {
+                (self.engine.logger)(&format!("Failed to roll back transaction: {}", e));
+            }
+        }
+    }
+}
+
+/// The rows of a table matching a WHERE clause, produced one at a time by
+/// `QueryEngine::scan_rows`. The table's data pages are read through the
+/// database as the scan reaches them, including pages not yet written to
+/// the file, so only the rows of the current page are held.
+pub struct RowScan<'a> {
+    database: &'a mut crate::database::Database,
+    table: String,
+    columns: Vec<Column>,
+    mode: ColumnCountMode,
+    logger: Logger,
+    cancel: Option<Arc<AtomicBool>>,
+    where_clause: Option<WhereExpr>,
+    /// The rows of the page read last that haven't been checked yet
+    page_rows: std::vec::IntoIter<Row>,
+    next_page: Option<u64>,
+    visited: HashSet<u64>,
+    /// How many rows have been read, which numbers the rows of files
+    /// without rowids
+    position: usize,
+}
+
+impl RowScan<'_> {
+    /// The columns of the scanned table.
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+}
+
+impl Iterator for RowScan<'_> {
+    type Item = Result<Row, IsentaError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if matches!(&self.cancel, Some(flag) if flag.load(Ordering::Relaxed)) {
+                return Some(Err(IsentaError::Cancelled));
+            }
+            let mut row = match self.page_rows.next() {
+                Some(row) => row,
+                None => {
+                    // A chain that loops back ends where it would repeat
+                    let page_id = self.next_page.take().filter(|&page_id| self.visited.insert(page_id))?;
+                    match self.database.read_data_page(page_id, &self.columns) {
+                        Ok((rows, next_page)) => {
+                            self.page_rows = rows.into_iter();
+                            self.next_page = next_page;
+                        }
+                        Err(e) => return Some(Err(e)),
+                    }
+                    continue;
+                }
+            };
+
+            let index = self.position;
+            self.position += 1;
+            if row.rowid == 0 {
+                row.rowid = index as u64 + 1;
+            }
+            if let Err(e) = conform_row(&self.table, self.columns.len(), index, &mut row, self.mode, &self.logger) {
+                return Some(Err(e));
+            }
+            if self.where_clause.as_ref().is_none_or(|expr| row_matches(&row, expr, &self.columns)) {
+                return Some(Ok(row));
+            }
+        }
+    }
+}
+
+/// Formats a value as a CSV field, quoting it when it contains a comma, a
+/// quote or a line break. NULL is written as an empty field.
+fn csv_field(value: Option<&str>) -> String {
+    match value {
+        None => String::new(),
+        Some(value) if value.contains([',', '"', '\n', '\r']) => format!("\"{}\"", value.replace('"', "\"\"")),
+        Some(value) => value.to_string(),
+    }
+}
+
+/// Places values given for the named columns of an INSERT at their
+/// columns' positions in the schema. A column that isn't named takes its
+/// default; without one it is NULL, which a NOT NULL column rejects.
+/// Reads a rowid given in an INSERT, `None` for NULL, which leaves the row
+/// to take the next rowid from the counter.
+fn parse_rowid(value: Option<String>) -> Result<Option<u64>, IsentaError> {
+    match value {
+        None => Ok(None),
+        Some(value) => match value.parse::<u64>() {
+            Ok(rowid) if rowid > 0 => Ok(Some(rowid)),
+            _ => Err(IsentaError::TypeMismatch(format!(
+                "Invalid value for column '{}': '{}' is not a valid rowid",
+                ROWID_COLUMN, value
+            ))),
+        },
+    }
+}
+
+fn row_for_columns(table: &Table, columns: &[String], values: Vec<Option<String>>) -> Result<Vec<Option<String>>, IsentaError> {
+    if columns.len() != values.len() {
+        return Err(IsentaError::Constraint(format!(
+            "{} columns are named but {} values are given",
+            columns.len(),
+            values.len()
+        )));
+    }
+
+    let mut given: Vec<Option<Option<String>>> = vec![None; table.columns.len()];
+    for (column, value) in columns.iter().zip(values) {
+        let index = table
+            .columns
+            .iter()
+            .position(|c| names_match(&c.name, column))
+            .ok_or_else(|| IsentaError::ColumnNotFound { table: table.name.clone(), column: column.clone() })?;
+        if given[index].is_some() {
+            return Err(IsentaError::InvalidQuery(format!("Column '{}' is named more than once", column)));
+        }
+        given[index] = Some(value);
+    }
+
+    // An explicit value wins over the default; an omitted NOT NULL column
+    // without a default is an error, any other omitted column is NULL
+    let mut row = Vec::with_capacity(given.len());
+    for (value, column) in given.into_iter().zip(&table.columns) {
+        let value = match value {
+            Some(value) => value,
+            None if column.default.is_some() => column.default.clone(),
+            None if column.not_null => {
+                return Err(IsentaError::Constraint(format!(
+                    "Column '{}' is NOT NULL and has no default, so it must be given a value",
+                    column.name
+                )))
+            }
+            None => None,
+        };
+        row.push(value);
+    }
+    Ok(row)
+}
+
+/// Checks that a row to be inserted has one valid value per column.
+fn validate_row(columns: &[Column], values: &[Option<String>]) -> Result<(), IsentaError> {
+    if values.len() != columns.len() {
+        return Err(IsentaError::Constraint(format!(
+            "Column count mismatch: expected {}, got {}",
+            columns.len(),
+            values.len()
+        )));
+    }
+
+    for (value, column) in values.iter().zip(columns) {
+        validate_column_value(column, value.as_deref())?;
+    }
+    Ok(())
+}
+
+/// Checks that a value can be stored in a column: that it fits the column's
+/// type and maximum length and isn't NULL in a NOT NULL column.
+fn validate_column_value(column: &Column, value: Option<&str>) -> Result<(), IsentaError> {
+    if value.is_none() && column.not_null {
+        return Err(IsentaError::Constraint(format!("Column '{}' is NOT NULL and can't be set to NULL", column.name)));
+    }
+    check_length(column, value)?;
+    validate_value(value, &column.data_type)
+        .map_err(|e| IsentaError::TypeMismatch(format!("Invalid value for column '{}': {}", column.name, e)))
+}
+
+/// Rejects a value with more characters than the column's declared length.
+fn check_length(column: &Column, value: Option<&str>) -> Result<(), IsentaError> {
+    match (column.max_length(), value) {
+        (Some(max_length), Some(value)) if value.chars().count() > max_length => Err(IsentaError::Constraint(format!(
+            "value exceeds max length {} for column '{}'",
+            max_length, column.name
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Whether columns of this type hold free-form text.
+fn is_text_type(data_type: &str) -> bool {
+    let data_type = data_type.to_uppercase();
+    data_type == "TEXT"
+        || data_type.starts_with("TEXT(")
+        || data_type == "STRING"
+        || data_type.starts_with("STRING(")
+        || data_type.starts_with("VARCHAR")
+        || data_type.starts_with("CHAR")
+}
+
+/// The canonical name of a column type, so that synonyms such as INT and
+/// INTEGER are compared the same way.
+///
+/// INT becomes INTEGER, REAL and DOUBLE become FLOAT and BOOL becomes BOOLEAN;
+/// any other type is returned uppercased.
+pub fn normalized_type(data_type: &str) -> String {
+    let data_type = data_type.to_uppercase();
+    match data_type.as_str() {
+        "INT" | "INTEGER" => "INTEGER".to_string(),
+        "FLOAT" | "REAL" | "DOUBLE" => "FLOAT".to_string(),
+        "BOOL" | "BOOLEAN" => "BOOLEAN".to_string(),
+        _ => data_type,
+    }
+}
+
+/// The error for JSON columns and `json_extract` in a build without the
+/// `json` feature.
+fn json_disabled() -> IsentaError {
+    IsentaError::InvalidQuery("JSON support is not enabled; rebuild with the 'json' feature".to_string())
+}
+
+/// Whether values of this type are stored and compared as integers.
+///
+/// Storage encoding and WHERE comparisons both go through this check, so a
+/// column declared INT behaves exactly like one declared INTEGER.
+pub fn is_integer_type(data_type: &str) -> bool {
+    normalized_type(data_type) == "INTEGER"
+}
+
+/// Whether columns of this type hold binary values, written as hex literals
+/// such as `X'48656C6C6F'`.
+pub fn is_blob_type(data_type: &str) -> bool {
+    normalized_type(data_type) == "BLOB"
+}
+
+/// The bytes of a hex literal such as `X'48656C6C6F'`, or why it isn't one.
+pub fn blob_bytes(value: &str) -> Result<Vec<u8>, String> {
+    let hex = value
+        .strip_prefix(['x', 'X'])
+        .and_then(|hex| hex.strip_prefix('\''))
+        .and_then(|hex| hex.strip_suffix('\''))
+        .ok_or_else(|| format!("'{}' is not a valid BLOB; write it as a hex literal such as X'48656C6C6F'", value))?;
+    if hex.len() % 2 != 0 {
+        return Err(format!("{} is not a valid BLOB: a hex literal needs two digits per byte", value));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                .ok_or_else(|| format!("{} is not a valid BLOB: only the hex digits 0-9 and A-F are allowed", value))
+        })
+        .collect()
+}
+
+/// The hex literal of a BLOB value, as it is displayed and compared.
+pub fn blob_literal(bytes: &[u8]) -> String {
+    let hex: String = bytes.iter().map(|byte| format!("{:02X}", byte)).collect();
+    format!("X'{}'", hex)
+}
+
+/// Checks that a value can be stored in a column of the given type.
+///
+/// INTEGER, FLOAT, BOOLEAN and JSON values must parse as their type and BLOB
+/// values must be hex literals; NULL and values of any other type are always
+/// accepted.
+pub fn validate_value(value: Option<&str>, data_type: &str) -> Result<(), IsentaError> {
+    let value = match value {
+        Some(value) => value,
+        None => return Ok(()),
+    };
+
+    let valid = match normalized_type(data_type).as_str() {
+        "INTEGER" => value.parse::<i64>().is_ok(),
+        "FLOAT" => value.parse::<f64>().is_ok(),
+        "BOOLEAN" => ["true", "false", "1", "0"].iter().any(|b| value.eq_ignore_ascii_case(b)),
+        "JSON" if !json::ENABLED => return Err(json_disabled()),
+        "JSON" => json::is_valid(value),
+        "BLOB" => return blob_bytes(value).map(|_| ()).map_err(IsentaError::TypeMismatch),
+        _ => true,
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(IsentaError::TypeMismatch(format!("'{}' is not a valid {}", value, data_type)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MEMORY_PATH;
+
+    fn column(name: &str, data_type: &str) -> Column {
+        Column::new(name, data_type)
+    }
+
+    fn condition(column: &str, operator: &str, value: &str) -> WhereExpr {
+        WhereExpr::Condition(WhereClause {
+            column: column.to_string(),
+            operator: operator.to_string(),
+            value: Some(value.to_string()),
+            quoted: false,
+        })
+    }
+
+    fn engine_with_users() -> QueryEngine {
+        let mut engine = QueryEngine::with_database(MEMORY_PATH);
+        engine
+            .execute_create_table("users".to_string(), vec![column("id", "INTEGER"), column("name", "TEXT")])
+            .unwrap();
+        for (id, name) in [(1, "Alice"), (2, "Bob"), (10, "Carol")] {
+            engine.execute_insert("users".to_string(), vec![Some(id.to_string()), Some(name.to_string())]).unwrap();
+        }
+        engine
+    }
+
+    #[test]
+    fn integer_columns_compare_numerically() {
+        assert!(QueryEngine::evaluate_condition(Some("10"), ">", Some("9"), "INTEGER"));
+        assert!(!QueryEngine::evaluate_condition(Some("10"), "<", Some("9"), "INTEGER"));
+        assert!(QueryEngine::evaluate_condition(Some("abc"), "LIKE", Some("A%"), "TEXT"));
+        assert!(!QueryEngine::evaluate_condition(None, "=", Some("1"), "INTEGER"));
+        assert!(QueryEngine::evaluate_condition(None, "IS NULL", None, "INTEGER"));
+    }
+
+    #[test]
+    fn type_synonyms_share_a_canonical_name() {
+        assert_eq!(normalized_type("int"), "INTEGER");
+        assert_eq!(normalized_type("DOUBLE"), "FLOAT");
+        assert_eq!(normalized_type("Bool"), "BOOLEAN");
+        assert_eq!(normalized_type("varchar(20)"), "VARCHAR(20)");
+        assert!(QueryEngine::evaluate_condition(Some("10"), ">", Some("9"), "INT"));
+    }
+
+    #[test]
+    fn int_columns_compare_numerically_in_where() {
+        let mut engine = QueryEngine::with_database(MEMORY_PATH);
+        engine.execute_create_table("scores".to_string(), vec![column("x", "INT")]).unwrap();
+        for x in [3, 10, 40] {
+            engine.execute_insert("scores".to_string(), vec![Some(x.to_string())]).unwrap();
+        }
+        let result = engine
+            .execute_select("scores".to_string(), vec![SelectItem::Column("x".to_string())], Some(condition("x", ">", "5")))
+            .unwrap();
+        let values: Vec<&str> = result.rows().map(|row| row.get("x").unwrap()).collect();
+        assert_eq!(values, vec!["10", "40"]);
+    }
+
+    #[test]
+    fn row_matches_combines_conditions() {
+        let columns = vec![column("id", "INTEGER"), column("name", "TEXT")];
+        let row = Row { rowid: 1, values: vec![Some("2".to_string()), Some("Bob".to_string())] };
+        let is_bob = condition("name", "=", "bob");
+        let is_one = condition("id", "=", "1");
+
+        assert!(row_matches(&row, &WhereExpr::Or(Box::new(is_one.clone()), Box::new(is_bob.clone())), &columns));
+        assert!(!row_matches(&row, &WhereExpr::And(Box::new(is_one), Box::new(is_bob)), &columns));
+        assert!(row_matches(&row, &condition("_rowid", "=", "1"), &columns));
+    }
+
+    #[test]
+    fn table_columns_shadow_the_rowid_column() {
+        let columns = vec![column("_rowid", "TEXT")];
+        assert!(matches!(find_column(&columns, "_ROWID"), Some(ColumnRef::Table(0))));
+        assert!(matches!(find_column(&[], "_rowid"), Some(ColumnRef::RowId)));
+        assert!(find_column(&columns, "missing").is_none());
+    }
+
+    #[test]
+    fn lenient_mode_pads_and_truncates_rows() {
+        let mut rows = vec![
+            Row { rowid: 1, values: vec![Some("1".to_string())] },
+            Row { rowid: 2, values: vec![None, None, Some("x".to_string())] },
+        ];
+        assert!(conform_rows("t", 2, &mut rows, ColumnCountMode::Strict, &log::quiet()).is_err());
+        conform_rows("t", 2, &mut rows, ColumnCountMode::Lenient, &log::quiet()).unwrap();
+        assert!(rows.iter().all(|row| row.values.len() == 2));
+    }
+
+    #[test]
+    fn select_returns_matching_rows() {
+        let engine = engine_with_users();
+        let result = engine
+            .execute_select("users".to_string(), vec![SelectItem::Column("name".to_string())], Some(condition("id", ">=", "2")))
+            .unwrap();
+        let names: Vec<&str> = result.rows().map(|row| row.get("name").unwrap()).collect();
+        assert_eq!(names, vec!["Bob", "Carol"]);
+    }
+
+    #[test]
+    fn update_reports_the_rows_changed() {
+        let mut engine = engine_with_users();
+        let assignments = vec![("name".to_string(), Some("Zed".to_string()))];
+        assert_eq!(engine.execute_update("users".to_string(), assignments.clone(), None, Some(2)).unwrap(), 2);
+        assert_eq!(
+            engine.execute_update("users".to_string(), assignments, Some(condition("id", "=", "99")), None).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn pragma_switches_accept_common_spellings() {
+        assert!(parse_switch("timing", "ON").unwrap());
+        assert!(!parse_switch("timing", "0").unwrap());
+        assert!(parse_switch("timing", "sometimes").is_err());
+    }
+}
@@ -0,0 +1,57 @@
+// src/error.rs
+
+use thiserror::Error;
+
+/// Errors returned by the storage layer, the database and the query engine.
+///
+/// Each variant displays as a human-readable message; match on the variant
+/// to react to a kind of error programmatically.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum IsentaError {
+    #[error("Table '{0}' does not exist")]
+    TableNotFound(String),
+
+    #[error("Table '{0}' already exists")]
+    TableExists(String),
+
+    #[error("Column '{column}' not found in table '{table}'")]
+    ColumnNotFound { table: String, column: String },
+
+    /// A value that can't be stored in, or compared with, a column's type
+    #[error("{0}")]
+    TypeMismatch(String),
+
+    /// A statement that conflicts with the table's shape or limits
+    #[error("{0}")]
+    Constraint(String),
+
+    /// A statement that is well-formed but can't be executed as written
+    #[error("{0}")]
+    InvalidQuery(String),
+
+    /// A statement that couldn't be parsed
+    #[error("{0}")]
+    Parse(String),
+
+    /// The table was written by someone else since it was loaded; reload and
+    /// try again
+    #[error("table '{0}' changed concurrently, retry")]
+    ConcurrentChange(String),
+
+    /// The table was written by another connection's transaction, which
+    /// hasn't been committed or rolled back yet
+    #[error("table '{0}' is locked by another transaction")]
+    TableLocked(String),
+
+    /// The query was interrupted through the engine's cancel flag
+    #[error("Query cancelled")]
+    Cancelled,
+
+    /// The database file couldn't be opened, read or written
+    #[error("{0}")]
+    Io(String),
+
+    /// The database file contains data that can't be decoded
+    #[error("{0}")]
+    Corruption(String),
+}
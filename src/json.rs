@@ -0,0 +1,184 @@
+// src/json.rs
+
+//! Minimal JSON output, so the default build doesn't depend on serde, and
+//! the JSON column type and `json_extract`, which parse values with
+//! serde_json when the `json` feature is enabled.
+
+use crate::engine::Table;
+
+/// Formats a table as pretty-printed JSON with two-space indentation.
+pub fn table_to_json(table: &Table) -> String {
+    let columns: Vec<String> = table
+        .columns
+        .iter()
+        .map(|column| {
+            // Constraints are only listed when set, as serde does
+            let mut fields = vec![("name", string(&column.name)), ("data_type", string(&column.data_type))];
+            if column.not_null {
+                fields.push(("not_null", "true".to_string()));
+            }
+            if let Some(default) = &column.default {
+                fields.push(("default", string(default)));
+            }
+            object(&fields, 2)
+        })
+        .collect();
+
+    let rows: Vec<String> = table
+        .rows
+        .iter()
+        .map(|row| {
+            let values: Vec<String> = row
+                .values
+                .iter()
+                .map(|value| value.as_deref().map_or_else(|| "null".to_string(), string))
+                .collect();
+            object(&[("rowid", row.rowid.to_string()), ("values", array(&values, 3))], 2)
+        })
+        .collect();
+
+    object(
+        &[
+            ("name", string(&table.name)),
+            ("columns", array(&columns, 1)),
+            ("rows", array(&rows, 1)),
+            ("created_at", table.created_at.to_string()),
+            ("updated_at", table.updated_at.to_string()),
+            ("next_rowid", table.next_rowid.to_string()),
+        ],
+        0,
+    )
+}
+
+/// Quotes and escapes a string as a JSON string literal.
+pub fn string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0c}' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Formats already-encoded fields as an object nested `depth` levels deep.
+fn object(fields: &[(&str, String)], depth: usize) -> String {
+    let items: Vec<String> = fields
+        .iter()
+        .map(|(key, value)| format!("{}: {}", string(key), value))
+        .collect();
+    block('{', '}', &items, depth)
+}
+
+/// Formats already-encoded items as an array nested `depth` levels deep.
+fn array(items: &[String], depth: usize) -> String {
+    block('[', ']', items, depth)
+}
+
+fn block(open: char, close: char, items: &[String], depth: usize) -> String {
+    if items.is_empty() {
+        return format!("{}{}", open, close);
+    }
+
+    let indent = "  ".repeat(depth + 1);
+    let mut out = String::new();
+    out.push(open);
+    out.push('\n');
+    for (i, item) in items.iter().enumerate() {
+        out.push_str(&indent);
+        out.push_str(item);
+        if i + 1 < items.len() {
+            out.push(',');
+        }
+        out.push('\n');
+    }
+    out.push_str(&"  ".repeat(depth));
+    out.push(close);
+    out
+}
+
+/// Whether this build can validate and query JSON values.
+pub const ENABLED: bool = cfg!(feature = "json");
+
+/// One step of a `json_extract` path.
+#[derive(Debug, PartialEq, Clone)]
+pub enum PathStep {
+    /// `.key`, a member of an object
+    Key(String),
+    /// `[index]`, an element of an array
+    Index(usize),
+}
+
+/// Parses a path such as `$.items[0].name`: `$` for the whole value,
+/// followed by any number of `.key` and `[index]` steps.
+pub fn parse_path(path: &str) -> Result<Vec<PathStep>, String> {
+    let mut rest = path.trim().strip_prefix('$').ok_or_else(|| "a path starts with '$'".to_string())?;
+    let mut steps = Vec::new();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            if end == 0 {
+                return Err("expected a key after '.'".to_string());
+            }
+            steps.push(PathStep::Key(after[..end].to_string()));
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let (index, after) = after.split_once(']').ok_or_else(|| "missing ']'".to_string())?;
+            let index = index.trim().parse().map_err(|_| format!("'{}' is not an array index", index))?;
+            steps.push(PathStep::Index(index));
+            rest = after;
+        } else {
+            return Err(format!("unexpected '{}'", rest));
+        }
+    }
+    Ok(steps)
+}
+
+/// Whether `text` is a single valid JSON value.
+#[cfg(feature = "json")]
+pub fn is_valid(text: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(text).is_ok()
+}
+
+#[cfg(not(feature = "json"))]
+pub fn is_valid(_text: &str) -> bool {
+    false
+}
+
+/// The value at `path` in the JSON `document`: strings without their
+/// quotes, numbers and booleans as written, and objects and arrays as
+/// compact JSON. `None` when the document isn't valid JSON, the path doesn't
+/// exist or the value is JSON null.
+#[cfg(feature = "json")]
+pub fn extract(document: &str, path: &[PathStep]) -> Option<String> {
+    use serde_json::Value;
+
+    let root: Value = serde_json::from_str(document).ok()?;
+    let mut value = &root;
+    for step in path {
+        value = match step {
+            PathStep::Key(key) => value.get(key.as_str())?,
+            PathStep::Index(index) => value.get(*index)?,
+        };
+    }
+    match value {
+        Value::Null => None,
+        Value::String(text) => Some(text.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+#[cfg(not(feature = "json"))]
+pub fn extract(_document: &str, _path: &[PathStep]) -> Option<String> {
+    None
+}
@@ -6,9 +6,17 @@ pub mod parser;
 pub mod engine;
 pub mod database;
 pub mod wal;
+pub mod error;
+pub mod log;
+pub mod audit;
+mod json;
+mod lock;
 
-use parser::{Command, Parser};
-use engine::QueryEngine;
+use parser::{Command, Limit, Parser};
+use engine::{QueryEngine, QueryResult};
+use error::IsentaError;
+use std::borrow::Cow;
+use std::time::Instant;
 
 /// Executes a single line of input against the query engine.
 ///
@@ -33,17 +41,49 @@ pub fn execute_line(input: &str, query_engine: &mut QueryEngine, parser: &Parser
 
     // Handle special commands that don't require SQL parsing.
     // This logic is kept separate from the SQL command parsing.
-    match input.to_lowercase().as_str() {
-        "help" => {
-            return print_help();
-        }
-        // Note: "exit" and "quit" are not handled here because they are process-specific.
-        // The caller (CLI or server) is responsible for managing its own lifecycle.
-        _ => {}
+    // Note: "exit" and "quit" are not handled here because they are process-specific.
+    // The caller (CLI or server) is responsible for managing its own lifecycle.
+    if input.eq_ignore_ascii_case("help") {
+        return print_help(query_engine.debug());
     }
 
     // Parse and execute the SQL command using the provided parser.
     let command = parser.parse(input);
+    let started = Instant::now();
+    let output = execute_command(command, query_engine);
+    query_engine.record_audit(input, !is_error(&output));
+    if query_engine.timing() {
+        let elapsed = started.elapsed().as_secs_f64() * 1000.0;
+        format!("{}\nTime: {:.3} ms", output, elapsed)
+    } else {
+        output
+    }
+}
+
+/// Whether formatted command output reports a failure.
+fn is_error(output: &str) -> bool {
+    output.starts_with("Error:") || output.starts_with("Unknown command:")
+}
+
+/// Drops the rows of a query result before its `OFFSET`, then those past
+/// its `LIMIT` or `TOP`, so a percentage is of the rows left.
+fn apply_limit(
+    result: Result<QueryResult, IsentaError>,
+    limit: Option<Limit>,
+    offset: Option<usize>,
+) -> Result<QueryResult, IsentaError> {
+    let mut result = result?;
+    if let Some(offset) = offset {
+        result.offset(offset);
+    }
+    if let Some(limit) = limit {
+        result.limit(limit);
+    }
+    Ok(result)
+}
+
+/// Executes a parsed command and formats its result.
+fn execute_command(command: Command, query_engine: &mut QueryEngine) -> String {
     match command {
         Command::CreateTable { name, columns } => {
             match query_engine.execute_create_table(name.clone(), columns) {
@@ -51,41 +91,67 @@ pub fn execute_line(input: &str, query_engine: &mut QueryEngine, parser: &Parser
                 Err(e) => format!("Error: {}", e),
             }
         }
-        Command::Insert { table, values } => {
-            match query_engine.execute_insert(table.clone(), values) {
-                Ok(_) => format!("Inserted 1 row into '{}'", table),
+        Command::CreateTableAs { name, source, columns, where_clause, order_by } => {
+            match query_engine.execute_create_table_as(name.clone(), source, columns, where_clause, order_by) {
+                Ok(count) => format!("Table '{}' created with {} rows", name, count),
                 Err(e) => format!("Error: {}", e),
             }
         }
-        Command::Select { table, columns, where_clause } => {
-            match query_engine.execute_select(table.clone(), columns, where_clause) {
-                Ok((cols, rows)) => {
-                    if rows.is_empty() {
-                        format!("No rows found in '{}'", table)
-                    } else {
-                        // Format the output as a text-based table.
-                        let mut output = String::new();
-                        let header = cols.join(" | ");
-                        output.push_str(&header);
-                        output.push('\n');
-                        output.push_str(&"-".repeat(header.len()));
-                        output.push('\n');
-
-                        for row in &rows {
-                            output.push_str(&row.values.join(" | "));
-                            output.push('\n');
-                        }
-                        // Trim the final newline for a clean output.
-                        output.trim_end().to_string()
-                    }
-                }
+        Command::Insert { table, columns, values, returning: Some(returning) } => {
+            let result = query_engine.execute_insert_returning(table.clone(), columns, values, returning);
+            format_rows(result, &table, query_engine.null_string(), query_engine.max_rows())
+        }
+        Command::Insert { table, columns, values, returning: None } => {
+            match query_engine.execute_insert_rows(table.clone(), columns, values) {
+                Ok(1) => format!("Inserted 1 row into '{}'", table),
+                Ok(count) => format!("Inserted {} rows into '{}'", count, table),
+                Err(e) => format!("Error: {}", e),
+            }
+        }
+        Command::InsertSelect { table, source, columns, where_clause, order_by } => {
+            match query_engine.execute_insert_select(table.clone(), source, columns, where_clause, order_by) {
+                Ok(count) => format!("Inserted {} rows into '{}'", count, table),
+                Err(e) => format!("Error: {}", e),
+            }
+        }
+        Command::Select { table, columns, where_clause, group_by, order_by, limit, offset } => {
+            let result = query_engine.execute_select_grouped(table.clone(), columns, where_clause, group_by, order_by);
+            format_limited_rows(result, limit, offset, &table, query_engine)
+        }
+        Command::CrossJoin { tables, columns, where_clause, order_by, limit, offset } => {
+            let from = tables.join(", ");
+            let result = query_engine.execute_cross_join(tables, columns, where_clause, order_by);
+            format_limited_rows(result, limit, offset, &from, query_engine)
+        }
+        Command::RenameTable { table, new_name } => {
+            match query_engine.execute_rename_table(table.clone(), new_name.clone()) {
+                Ok(()) => format!("Table '{}' renamed to '{}'", table, new_name),
                 Err(e) => format!("Error: {}", e),
             }
         }
-        Command::ShowTables => {
-            let tables = query_engine.get_all_tables();
+        Command::RenameColumn { table, column, new_name } => {
+            match query_engine.execute_rename_column(table.clone(), column.clone(), new_name.clone()) {
+                Ok(()) => format!("Column '{}' of '{}' renamed to '{}'", column, table, new_name),
+                Err(e) => format!("Error: {}", e),
+            }
+        }
+        Command::Export { table, path, where_clause } => {
+            match query_engine.export_csv(&table, where_clause, &path) {
+                Ok(count) => format!("Exported {} rows from '{}' to '{}'", count, table, path),
+                Err(IsentaError::Cancelled) => IsentaError::Cancelled.to_string(),
+                Err(e) => format!("Error: {}", e),
+            }
+        }
+        Command::ShowTables { pattern } => {
+            let tables = match &pattern {
+                Some(pattern) => query_engine.tables_like(pattern),
+                None => query_engine.get_all_tables().iter().collect(),
+            };
             if tables.is_empty() {
-                "No tables in database".to_string()
+                match pattern {
+                    Some(pattern) => format!("No tables matching '{}'", pattern),
+                    None => "No tables in database".to_string(),
+                }
             } else {
                 let mut output = "Tables:\n".to_string();
                 for table in tables {
@@ -94,6 +160,10 @@ pub fn execute_line(input: &str, query_engine: &mut QueryEngine, parser: &Parser
                 output.trim_end().to_string()
             }
         }
+        Command::ShowColumns { table } => {
+            let result = query_engine.show_columns(&table);
+            format_rows(result, &table, query_engine.null_string(), None)
+        }
         Command::InspectTable { name } => {
             if let Some(table) = query_engine.get_table_schema(&name) {
                 let mut output = format!("Table: {}\n", name);
@@ -102,32 +172,67 @@ pub fn execute_line(input: &str, query_engine: &mut QueryEngine, parser: &Parser
                 output.push_str(&format!("{:-<20}-+-{:-<15}\n", "", ""));
                 
                 for column in &table.columns {
-                    output.push_str(&format!("{:<20} | {}\n", column.name, column.data_type));
+                    output.push_str(&format!("{:<20} | {}\n", column.name, column.type_definition()));
                 }
+
+                output.push('\n');
+                output.push_str(&format!("Created: {}\n", format_timestamp(table.created_at)));
+                output.push_str(&format!("Updated: {}\n", format_timestamp(table.updated_at)));
+                output.push_str(&format!("Schema hash: {:016x}\n", table.schema_hash()));
                 output.trim_end().to_string()
             } else {
                 format!("Table '{}' not found", name)
             }
         }
-        Command::Update { table, set_column, set_value, where_clause } => {
-            match query_engine.execute_update(table.clone(), (set_column, set_value), where_clause) {
+        Command::TableStats { table } => {
+            match query_engine.table_stats(&table) {
+                Ok(stats) => {
+                    let wasted_percent = (stats.wasted_bytes() * 100).checked_div(stats.capacity_bytes).unwrap_or(0);
+                    format!(
+                        "Table: {}\nSchema page: {}\nData pages: {}\nRows: {}\nAverage row size: {} bytes\nUsed space: {} of {} bytes\nWasted space: {} bytes ({}%)",
+                        table,
+                        stats.schema_page,
+                        stats.data_pages,
+                        stats.rows,
+                        stats.average_row_size(),
+                        stats.used_bytes,
+                        stats.capacity_bytes,
+                        stats.wasted_bytes(),
+                        wasted_percent
+                    )
+                }
+                Err(e) => format!("Error: {}", e),
+            }
+        }
+        Command::Update { table, assignments, where_clause, limit } => {
+            match query_engine.execute_update(table.clone(), assignments, where_clause, limit) {
                 Ok(count) => format!("Updated {} rows in '{}'", count, table),
                 Err(e) => format!("Error: {}", e),
             }
         }
+        Command::Delete { table, where_clause, limit } => {
+            match query_engine.execute_delete(table.clone(), where_clause, limit) {
+                Ok(count) => format!("Deleted {} rows from '{}'", count, table),
+                Err(e) => format!("Error: {}", e),
+            }
+        }
         Command::Truncate { table } => {
             match query_engine.execute_truncate_table(table.clone()) {
                 Ok(_) => format!("Table '{}' truncated successfully", table),
                 Err(e) => format!("Error: {}", e),
             }
         }
+        Command::DropTable { name, if_exists } => {
+            match query_engine.execute_drop_table(name.clone(), if_exists) {
+                Ok(true) => format!("Table '{}' dropped successfully", name),
+                Ok(false) => format!("Table '{}' does not exist, skipping", name),
+                Err(e) => format!("Error: {}", e),
+            }
+        }
         Command::Get { table, format } => {
             if format.to_uppercase() == "JSON" {
                 if let Some(table_data) = query_engine.get_table_schema(&table) {
-                    match serde_json::to_string_pretty(&table_data) {
-                        Ok(json) => json,
-                        Err(e) => format!("Error serializing to JSON: {}", e),
-                    }
+                    json::table_to_json(table_data)
                 } else {
                     format!("Table '{}' not found", table)
                 }
@@ -135,26 +240,203 @@ pub fn execute_line(input: &str, query_engine: &mut QueryEngine, parser: &Parser
                 format!("Unsupported format: {}", format)
             }
         }
+        // Diagnostic commands stay unknown unless debugging is enabled
+        Command::DumpPage { page_id } if query_engine.debug() => {
+            match query_engine.dump_page(page_id) {
+                Ok(dump) => dump,
+                Err(e) => format!("Error: {}", e),
+            }
+        }
+        Command::DumpPage { page_id } => {
+            format!("Unknown command: DUMP PAGE {}\nType 'help' for available commands", page_id)
+        }
+        Command::ReindexSchema => {
+            match query_engine.reindex_schema() {
+                Ok(count) => format!("Schema chain rebuilt: {} tables relinked", count),
+                Err(e) => format!("Error: {}", e),
+            }
+        }
+        Command::Pragma { name, value } => {
+            match query_engine.pragma(&name, value.as_deref()) {
+                Ok(current) => format!("{} = {}", name, current),
+                Err(e) => format!("Error: {}", e),
+            }
+        }
+        Command::Validate(command) => validate_command(*command, query_engine),
         Command::Unknown(cmd) => {
             format!("Unknown command: {}\nType 'help' for available commands", cmd)
         }
     }
 }
 
-/// Returns a help string with available commands.
+/// Runs a statement for `VALIDATE` and rolls it back, reporting the first
+/// check it fails. Statements with effects outside the tables, such as
+/// writing a file or changing a setting, can't be rolled back and are
+/// refused.
+fn validate_command(command: Command, query_engine: &mut QueryEngine) -> String {
+    match command {
+        Command::CreateTable { .. }
+        | Command::CreateTableAs { .. }
+        | Command::Insert { .. }
+        | Command::InsertSelect { .. }
+        | Command::Select { .. }
+        | Command::CrossJoin { .. }
+        | Command::Update { .. }
+        | Command::Delete { .. }
+        | Command::Truncate { .. }
+        | Command::DropTable { .. }
+        | Command::RenameTable { .. }
+        | Command::RenameColumn { .. } => {}
+        Command::Unknown(cmd) => return execute_command(Command::Unknown(cmd), query_engine),
+        _ => return "Error: VALIDATE only checks statements that create, read or change tables".to_string(),
+    }
+    match query_engine.dry_run(|engine| execute_command(command, engine)) {
+        Ok(output) if is_error(&output) => output,
+        Ok(_) => "Statement is valid".to_string(),
+        Err(e) => format!("Error: {}", e),
+    }
+}
+
+/// Formats the result of a SELECT after its `OFFSET` and `LIMIT`. When they
+/// leave none of the matching rows, only the header is shown, since the
+/// query did match rows; a query that matches nothing reports that instead.
+fn format_limited_rows(
+    result: Result<QueryResult, IsentaError>,
+    limit: Option<Limit>,
+    offset: Option<usize>,
+    from: &str,
+    query_engine: &QueryEngine,
+) -> String {
+    let matched = result.as_ref().is_ok_and(|result| !result.is_empty());
+    match apply_limit(result, limit, offset) {
+        Ok(result) if matched && result.is_empty() => format_header(result.columns()),
+        result => format_rows(result, from, query_engine.null_string(), query_engine.max_rows()),
+    }
+}
+
+/// The header of a text table: the column names and a line under them.
+fn format_header(columns: &[String]) -> String {
+    let header = columns.join(" | ");
+    format!("{}\n{}", header, "-".repeat(header.len()))
+}
+
+/// Formats the result of a query on `from` as a text table, showing at most
+/// `max_rows` rows followed by a notice of how many were left out.
+fn format_rows(result: Result<QueryResult, IsentaError>, from: &str, null_string: &str, max_rows: Option<usize>) -> String {
+    match result {
+        Ok(QueryResult::Rows { columns: cols, rows }) => {
+            if rows.is_empty() {
+                format!("No rows found in '{}'", from)
+            } else {
+                // Format the output as a text-based table.
+                let mut output = format_header(&cols);
+                output.push('\n');
+
+                let shown = max_rows.map_or(rows.len(), |max_rows| max_rows.min(rows.len()));
+                for row in &rows[..shown] {
+                    let values: Vec<Cow<str>> = row
+                        .values
+                        .iter()
+                        .map(|v| v.as_deref().map_or(Cow::Borrowed(null_string), escape_value))
+                        .collect();
+                    output.push_str(&values.join(" | "));
+                    output.push('\n');
+                }
+                if shown < rows.len() {
+                    output.push_str(&format!("... ({} more rows not shown)", rows.len() - shown));
+                }
+                // Trim the final newline for a clean output.
+                output.trim_end().to_string()
+            }
+        }
+        // Interrupting a query is not a failure
+        Err(IsentaError::Cancelled) => IsentaError::Cancelled.to_string(),
+        Err(e) => format!("Error: {}", e),
+    }
+}
+
+/// Writes a value for display with control characters escaped the way
+/// they are typed in a string literal, so each row stays on one line.
+pub(crate) fn escape_value(value: &str) -> Cow<'_, str> {
+    if !value.contains(['\\', '\n', '\t', '\r']) {
+        return Cow::Borrowed(value);
+    }
+    let mut out = String::with_capacity(value.len() + 2);
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c => out.push(c),
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Formats a Unix epoch timestamp for display; 0 means the time isn't known.
+fn format_timestamp(seconds: u64) -> String {
+    if seconds == 0 {
+        "unknown".to_string()
+    } else {
+        format!("{} (Unix time)", seconds)
+    }
+}
+
+/// Returns a help string with available commands, including the diagnostic
+/// ones when `debug` is set.
 ///
 /// This is a helper function to avoid cluttering the main execution logic.
-fn print_help() -> String {
+fn print_help(debug: bool) -> String {
+    let debug_commands = if debug {
+        "  DUMP PAGE <id> - Show the type, first fields and bytes of a page of the database file\n"
+    } else {
+        ""
+    };
     "Available commands:\n".to_owned() +
-    "  CREATE TABLE <table_name> (col1 TYPE, col2 TYPE, ...) - Create a new table\n" +
-    "  INSERT INTO <table_name> VALUES (val1, val2, ...) - Insert data into a table\n" +
+    "  CREATE TABLE <table_name> (col1 TYPE [NOT NULL] [DEFAULT <value>], col2 TYPE, ...) - Create a new table\n" +
+    "  CREATE TABLE <table_name> AS SELECT ... - Create a table holding a query's result, e.g. a copy of another table\n" +
+    "  INSERT INTO <table_name> VALUES (val1, val2, ...)[, (val1, val2, ...) ...] - Insert one or more rows into a table (use NULL for missing values); none is inserted unless all are valid\n" +
+    "  INSERT INTO <table_name> (col1, col2, ...) VALUES (val1, val2, ...) - Insert values for the named columns; the others are NULL\n" +
+    "  INSERT INTO <table_name> SELECT ... - Insert the rows a query returns; it must return one value per column\n" +
+    "  INSERT INTO ... VALUES (...) RETURNING * | <column>, ... - Insert rows and show them as stored, e.g. their _rowid and defaults\n" +
+    "  ... X'48656C6C6F' - A hex literal, the value of a BLOB column; BLOBs compare only with =, != and IS [NOT] NULL\n" +
     "  SELECT * FROM <table_name> - Query data from a table\n" +
     "  SELECT * FROM <table_name> WHERE <column> [=, !=, <, >, <=, >=, LIKE, NOT LIKE] <value> - Query data with a where clause\n" +
-    "  UPDATE <table_name> SET <column> = <value> WHERE <column> [=, !=, <, >, <=, >=, LIKE, NOT LIKE] <value> - Update data in a table\n" +
+    "  SELECT * FROM <table_name> WHERE <column> IS [NOT] NULL - Query rows with (or without) missing values\n" +
+    "  SELECT COUNT(*), COUNT([DISTINCT] <expr>), SUM|AVG|MIN|MAX([DISTINCT] <expr>) FROM <table_name> - Aggregate the selected rows; <expr> may use + - * /\n" +
+    "  SELECT <column>, COUNT(*), ... FROM <table_name> GROUP BY <column> - Aggregate the rows of each distinct value of a column\n" +
+    "  SELECT <item> AS <name>, ... FROM <table_name> - Name a result column; aggregates are otherwise named like count or sum_price\n" +
+    "  SELECT ... ORDER BY <column> [ASC|DESC] [NULLS FIRST|LAST][, ...] - Sort the result; later columns break ties, NULLs sort first unless NULLS LAST\n" +
+    "  SELECT ... LIMIT <n>|<p>% or SELECT TOP <n> [PERCENT] ... - Return at most n rows, or p percent of the matching rows (rounded)\n" +
+    "  SELECT ... [LIMIT <n>] OFFSET <m> - Skip the first m rows of the result; a LIMIT applies to the rows after them\n" +
+    "  ... WHERE <condition> AND|OR <condition> - Combine conditions, using parentheses to group them\n" +
+    "  SELECT _rowid, ... FROM <table_name> WHERE _rowid = <id> - Every row has a stable, virtual _rowid column\n" +
+    "  SELECT json_extract(<column>, '$.key[0]') FROM <table_name> - Read a value from JSON; usable wherever a column is (JSON columns need the json feature)\n" +
+    "  SELECT CAST(<column> AS INTEGER|FLOAT|TEXT) FROM <table_name> - Convert a column's values, e.g. to compare text as numbers; usable wherever a column is\n" +
+    "  SELECT UPPER|LOWER|LENGTH|TRIM(<column>), SUBSTR(<column>, <start>[, <length>]) FROM <table_name> - String functions, NULL for NULL; usable wherever a column is\n" +
+    "  SELECT COALESCE(<column>|<value>, ...), NULLIF(<column>, <column>|<value>) FROM <table_name> - The first argument that isn't NULL; NULL when the two are equal; usable wherever a column is\n" +
+    "  SELECT * FROM <table1>, <table2> WHERE <table1>.<column> = <table2>.<column> - Join tables; columns may be written <table>.<column>\n" +
+    "  UPDATE <table_name> SET <column> = <value>[, <column> = <value> ...] WHERE <column> [=, !=, <, >, <=, >=, LIKE, NOT LIKE] <value> [LIMIT <n>] - Update data in a table\n" +
+    "  DELETE FROM <table_name> [WHERE <condition>] [LIMIT <n>] - Delete the matching rows, or every row\n" +
+    "  ... WHERE <column> BETWEEN <low> AND <high> - Match values in a range, including both ends\n" +
     "  TRUNCATE TABLE <table_name> - Remove all rows from a table\n" +
+    "  DROP TABLE [IF EXISTS] <table_name> - Remove a table and its rows; with IF EXISTS a missing table is skipped\n" +
+    "  ALTER TABLE <table_name> RENAME TO <new_name> - Rename a table\n" +
+    "  ALTER TABLE <table_name> RENAME COLUMN <column> TO <new_name> - Rename a column, keeping its type and constraints\n" +
+    "  VALIDATE <statement> - Run every check of a CREATE, INSERT, UPDATE, DELETE, ALTER, DROP or SELECT statement without applying it\n" +
     "  GET <table_name> AS JSON - Get a table's data in JSON format\n" +
+    "  EXPORT <table_name> TO '<file>' [WHERE <condition>] - Write a table's rows to a CSV file\n" +
     "  INSPECT <table_name> - Show table schema and column types\n" +
-    "  SHOW TABLES - List all tables in the database\n" +
+    "  SHOW TABLES [LIKE '<pattern>'] - List all tables in the database, or those whose names match the pattern\n" +
+    "  SHOW COLUMNS FROM <table_name> - List a table's columns with their type, nullability, key and default\n" +
+    "  TABLE STATS <table_name> - Show the table's data pages, rows and how much of the pages' space is unused\n" +
+    "  REINDEX SCHEMA - Relink every table's schema page, repairing a damaged list of tables\n" +
+    "  PRAGMA <name> [= <value>] - Show or change a setting: strict (on/off), null_string, max_rows (a count, or off), timing (on/off), relaxed_insert (on/off), flush_every (a count, or off), audit (on/off), preallocate_pages, wal_autocheckpoint (a count of pages, or off), wal_checkpoint_interval (seconds, or off)\n" +
+    debug_commands +
+    "  Statements end with ';' and may span several lines in the shell\n" +
+    "  Ctrl-C - Cancel the running query in the shell\n" +
+    "  \\g - Run the statement typed so far, or the previous one again; an empty line at the prompt does the same\n" +
     "  help - Show this help message\n" +
     "  exit | quit - Exit the program"
 }
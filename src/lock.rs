@@ -0,0 +1,71 @@
+// src/lock.rs
+
+use crate::engine::names_match;
+use crate::error::IsentaError;
+use crate::storage::MEMORY_PATH;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// The tables written by open transactions: the database file, the table
+/// name and the owner holding it.
+static LOCKS: Mutex<Vec<(PathBuf, String, u64)>> = Mutex::new(Vec::new());
+
+/// Hands out a distinct owner to every `TableLocks`.
+static NEXT_OWNER: AtomicU64 = AtomicU64::new(1);
+
+/// The table locks of one open database, shared with every other database
+/// opened on the same file in this process, such as the connections of the
+/// server. A transaction locks each table it writes until it ends, and
+/// writes to a table locked by someone else are refused with
+/// `IsentaError::TableLocked`. Other processes don't see these locks.
+pub(crate) struct TableLocks {
+    /// The canonical path of the database file, `None` for an in-memory
+    /// database, which nobody else can open
+    file: Option<PathBuf>,
+    owner: u64,
+}
+
+impl TableLocks {
+    /// Locks for the database at `path`, which must already exist.
+    pub(crate) fn new(path: &str) -> Self {
+        let file = if path == MEMORY_PATH { None } else { std::fs::canonicalize(path).ok() };
+        TableLocks { file, owner: NEXT_OWNER.fetch_add(1, Ordering::Relaxed) }
+    }
+
+    /// Fails with `IsentaError::TableLocked` if another owner holds a lock
+    /// on `table`; otherwise takes the lock when `hold` is set.
+    pub(crate) fn check(&self, table: &str, hold: bool) -> Result<(), IsentaError> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        let mut locks = LOCKS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut held = false;
+        for (locked_file, locked_table, owner) in locks.iter() {
+            if locked_file == file && names_match(locked_table, table) {
+                if *owner != self.owner {
+                    return Err(IsentaError::TableLocked(table.to_string()));
+                }
+                held = true;
+            }
+        }
+        if hold && !held {
+            locks.push((file.clone(), table.to_string(), self.owner));
+        }
+        Ok(())
+    }
+
+    /// Releases every lock held, at the end of a transaction.
+    pub(crate) fn release(&self) {
+        if self.file.is_some() {
+            let mut locks = LOCKS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            locks.retain(|(_, _, owner)| *owner != self.owner);
+        }
+    }
+}
+
+impl Drop for TableLocks {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
@@ -0,0 +1,23 @@
+// src/log.rs
+
+use std::sync::Arc;
+
+/// Receives the library's diagnostic warnings, such as a damaged page being
+/// skipped or a row being padded in lenient mode. Messages don't carry a
+/// `Warning:` prefix; each logger decides how to present them.
+///
+/// The engine shares one logger with its database and storage, so it is
+/// reference counted.
+pub type Logger = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// The default logger, which prints each message to stderr as
+/// `Warning: <message>`.
+pub fn stderr() -> Logger {
+    Arc::new(|message| eprintln!("Warning: {}", message))
+}
+
+/// A logger that discards every message, for embedders that must not write
+/// to the process's output streams.
+pub fn quiet() -> Logger {
+    Arc::new(|_| {})
+}
@@ -1,48 +1,326 @@
 // src/main.rs
 
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
+#[cfg(feature = "line-editor")]
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 // The CLI now uses the library crate for all core logic.
 use rust_dbms::{
-    parser::Parser,
-    engine::QueryEngine,
+    database::DB_VERSION,
+    parser::{is_complete_statement, split_statements, Parser},
+    engine::{ColumnCountMode, QueryEngine},
     execute_line,
 };
 
+/// How many rows of a result the shell shows at a terminal unless changed
+/// with `PRAGMA max_rows`.
+const DEFAULT_MAX_ROWS: usize = 1000;
+
 fn main() {
-    println!("IsentaDB v0.1.0");
-    println!("Type 'help' for commands, 'exit' to quit\n");
+    // Usage: isenta [--db <path>] [--strict | --lenient] [--null-string <text>] [--debug] [--audit] [statement ...]
+    //        isenta --version
+    //        isenta info <path>
+    if let Some(command) = std::env::args().nth(1) {
+        match command.as_str() {
+            "--version" => {
+                println!("IsentaDB v{}", env!("CARGO_PKG_VERSION"));
+                println!("Database file format version {}", DB_VERSION);
+                return;
+            }
+            "info" => match std::env::args().nth(2) {
+                Some(path) => {
+                    print_file_info(&path);
+                    return;
+                }
+                None => {
+                    eprintln!("Error: info requires a database path");
+                    process::exit(2);
+                }
+            },
+            _ => {}
+        }
+    }
+
+    let mut db_path = "data.db".to_string();
+    let mut column_count_mode = ColumnCountMode::Strict;
+    let mut null_string = None;
+    let mut debug = false;
+    let mut audit = false;
+    let mut statements = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--db" => match args.next() {
+                Some(path) => db_path = path,
+                None => {
+                    eprintln!("Error: --db requires a path");
+                    process::exit(2);
+                }
+            },
+            "--null-string" => match args.next() {
+                Some(text) => null_string = Some(text),
+                None => {
+                    eprintln!("Error: --null-string requires a value");
+                    process::exit(2);
+                }
+            },
+            "--debug" => debug = true,
+            "--audit" => audit = true,
+            "--strict" => column_count_mode = ColumnCountMode::Strict,
+            "--lenient" => column_count_mode = ColumnCountMode::Lenient,
+            _ => statements.push(arg),
+        }
+    }
 
     // Initialize the query engine and parser from the library.
-    let mut query_engine = QueryEngine::new();
+    // A database that can't be opened is reported without a panic backtrace.
+    let mut query_engine = match QueryEngine::open(&db_path) {
+        Ok(engine) => engine,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+    };
+    query_engine.set_column_count_mode(column_count_mode);
+    query_engine.set_debug(debug);
+    if audit {
+        if let Err(e) = query_engine.set_audit(true) {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+    }
+    if let Some(null_string) = &null_string {
+        query_engine.set_null_string(null_string);
+    }
+    // Output to a terminal is capped; piped output keeps every row
+    if io::stdout().is_terminal() {
+        query_engine.set_max_rows(Some(DEFAULT_MAX_ROWS));
+    }
     let parser = Parser::new();
 
-    // The REPL loop is now much simpler.
+    // Statements given on the command line are run once, without the REPL.
+    if !statements.is_empty() {
+        for statement in &statements {
+            let result = execute_line(statement.trim(), &mut query_engine, &parser);
+            if !result.is_empty() {
+                println!("{}", result);
+            }
+        }
+        close(query_engine);
+        return;
+    }
+
+    // Ctrl-C cancels the running query instead of ending the shell
+    let cancel = Arc::new(AtomicBool::new(false));
+    let handler_flag = Arc::clone(&cancel);
+    match ctrlc::set_handler(move || handler_flag.store(true, Ordering::Relaxed)) {
+        Ok(()) => query_engine.set_cancel_flag(Arc::clone(&cancel)),
+        Err(e) => eprintln!("Warning: Ctrl-C won't cancel queries: {}", e),
+    }
+
+    println!("IsentaDB v{}", env!("CARGO_PKG_VERSION"));
+    println!("Type 'help' for commands, 'exit' to quit\n");
+
+    // Statements may span several lines; input is buffered until it ends
+    // with a `;` outside quotes and parentheses.
+    let mut reader = LineReader::new();
+    let interactive = io::stdin().is_terminal();
+    let mut buffer = String::new();
+    // The statements last run, which `\g` runs again
+    let mut last_statement: Option<String> = None;
     loop {
-        print!("isenta> ");
-        io::stdout().flush().unwrap();
+        let prompt = if buffer.is_empty() { "isenta> " } else { "    -> " };
+        let input = match reader.read_line(prompt) {
+            Input::Line(line) => line,
+            Input::Interrupted => {
+                buffer.clear();
+                continue;
+            }
+            Input::End => {
+                println!();
+                if !buffer.trim().is_empty() {
+                    eprintln!("Error: incomplete statement at end of input (missing ';'?)");
+                }
+                break;
+            }
+        };
 
-        let mut input = String::new();
-        if io::stdin().read_line(&mut input).is_err() {
-            println!("Error reading input");
-            continue;
+        let input = input.trim();
+
+        // `\g` runs the statement typed so far, or the previous one again
+        // when nothing is typed; so does an empty line at a terminal
+        if input == "\\g" || (interactive && input.is_empty() && buffer.is_empty()) {
+            if buffer.is_empty() {
+                match &last_statement {
+                    Some(statement) => buffer = statement.clone(),
+                    None => {
+                        if !input.is_empty() {
+                            println!("No previous statement to run");
+                        }
+                        continue;
+                    }
+                }
+            }
+        } else {
+            // Process-specific commands like 'exit' or 'quit', and 'help', don't
+            // need a terminator when typed on their own line.
+            if buffer.is_empty() {
+                match input.trim_end_matches(';').trim().to_lowercase().as_str() {
+                    "" => continue,
+                    "exit" | "quit" => {
+                        println!("Goodbye!");
+                        break;
+                    }
+                    "help" => {
+                        println!("{}", execute_line("help", &mut query_engine, &parser));
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+
+            if !buffer.is_empty() {
+                buffer.push(' ');
+            }
+            buffer.push_str(input);
+            if !is_complete_statement(&buffer) {
+                continue;
+            }
         }
 
-        let input = input.trim();
+        reader.add_history(&buffer);
+        for statement in split_statements(&buffer) {
+            // A Ctrl-C pressed while no query was running is ignored
+            cancel.store(false, Ordering::Relaxed);
+            let result = execute_line(statement, &mut query_engine, &parser);
+            if !result.is_empty() {
+                println!("{}", result);
+            }
+        }
+        last_statement = Some(std::mem::take(&mut buffer));
+    }
+    reader.save_history();
+    close(query_engine);
+}
 
-        // Handle process-specific commands like 'exit' or 'quit'.
-        // The `execute_line` function does not handle these, as it's stateless.
-        match input.to_lowercase().as_str() {
-            "exit" | "quit" => {
-                println!("Goodbye!");
-                break;
+/// What reading a line at the prompt produced.
+enum Input {
+    Line(String),
+    /// Ctrl-C at the prompt, which discards the statement being typed
+    #[cfg_attr(not(feature = "line-editor"), allow(dead_code))]
+    Interrupted,
+    /// End of input (e.g. Ctrl-D or a closed pipe)
+    End,
+}
+
+/// Reads the lines typed at the prompt. Built with the `line-editor` feature
+/// and reading from a terminal, lines can be edited and earlier statements
+/// recalled with the arrow keys, and the history is kept in
+/// `~/.isenta_history`; otherwise stdin is read a line at a time.
+struct LineReader {
+    #[cfg(feature = "line-editor")]
+    editor: Option<rustyline::DefaultEditor>,
+}
+
+impl LineReader {
+    fn new() -> Self {
+        #[cfg(feature = "line-editor")]
+        {
+            let mut editor = if io::stdin().is_terminal() { rustyline::DefaultEditor::new().ok() } else { None };
+            if let (Some(editor), Some(path)) = (editor.as_mut(), history_path()) {
+                // There is no history file before the first session ends
+                let _ = editor.load_history(&path);
             }
-            _ => {
-                // All other commands are passed to the centralized execution function.
-                let result = execute_line(input, &mut query_engine, &parser);
-                if !result.is_empty() {
-                    println!("{}", result);
+            LineReader { editor }
+        }
+        #[cfg(not(feature = "line-editor"))]
+        LineReader {}
+    }
+
+    fn read_line(&mut self, prompt: &str) -> Input {
+        #[cfg(feature = "line-editor")]
+        if let Some(editor) = self.editor.as_mut() {
+            use rustyline::error::ReadlineError;
+            return match editor.readline(prompt) {
+                Ok(line) => Input::Line(line),
+                Err(ReadlineError::Interrupted) => Input::Interrupted,
+                Err(ReadlineError::Eof) => Input::End,
+                Err(e) => {
+                    eprintln!("Error reading input: {}", e);
+                    Input::End
                 }
+            };
+        }
+
+        loop {
+            print!("{}", prompt);
+            io::stdout().flush().unwrap();
+            let mut line = String::new();
+            match io::stdin().read_line(&mut line) {
+                Ok(0) => return Input::End,
+                Ok(_) => return Input::Line(line),
+                Err(_) => println!("Error reading input"),
+            }
+        }
+    }
+
+    /// Adds a statement that was run to the history.
+    fn add_history(&mut self, statement: &str) {
+        #[cfg(feature = "line-editor")]
+        if let Some(editor) = self.editor.as_mut() {
+            let _ = editor.add_history_entry(statement);
+        }
+        #[cfg(not(feature = "line-editor"))]
+        let _ = statement;
+    }
+
+    /// Writes the history to its file for the next session.
+    fn save_history(&mut self) {
+        #[cfg(feature = "line-editor")]
+        if let (Some(editor), Some(path)) = (self.editor.as_mut(), history_path()) {
+            if let Err(e) = editor.save_history(&path) {
+                eprintln!("Warning: history not saved to '{}': {}", path.display(), e);
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Where the shell history is kept: `.isenta_history` in the home directory.
+#[cfg(feature = "line-editor")]
+fn history_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")).map(|home| PathBuf::from(home).join(".isenta_history"))
+}
+
+/// Prints what the header of the database file at `path` says about it,
+/// without creating the file if it doesn't exist.
+fn print_file_info(path: &str) {
+    if !std::path::Path::new(path).is_file() {
+        eprintln!("Error: '{}' is not a database file", path);
+        process::exit(1);
+    }
+    let info = QueryEngine::open(path).and_then(|mut engine| engine.file_info());
+    match info {
+        Ok(info) => {
+            println!("File: {}", path);
+            println!("Magic number: 0x{:016X}", info.magic);
+            println!("Format version: {}", info.version);
+            println!("Tables: {}", info.table_count);
+            println!("Pages: {}", info.page_count);
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Flushes and closes the database, exiting with an error if the final
+/// write fails so the loss isn't silent.
+fn close(query_engine: QueryEngine) {
+    if let Err(e) = query_engine.close() {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
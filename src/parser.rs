@@ -1,63 +1,743 @@
-use serde::Serialize;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
 
-#[derive(Debug, PartialEq, Clone, Serialize)]
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct WhereClause {
     pub column: String,
     pub operator: String,
-    pub value: String,
+    /// The literal to compare against, `None` for the `NULL` keyword.
+    pub value: Option<String>,
+    /// Whether the literal was written as a quoted string.
+    pub quoted: bool,
 }
 
-#[derive(Debug, PartialEq)]
+/// A WHERE condition: a single comparison, or conditions combined with
+/// AND / OR. AND binds tighter than OR; parentheses group conditions.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum WhereExpr {
+    Condition(WhereClause),
+    And(Box<WhereExpr>, Box<WhereExpr>),
+    Or(Box<WhereExpr>, Box<WhereExpr>),
+}
+
+impl From<WhereClause> for WhereExpr {
+    fn from(clause: WhereClause) -> Self {
+        WhereExpr::Condition(clause)
+    }
+}
+
+/// An item of a SELECT projection list.
+#[derive(Debug, PartialEq, Clone)]
+pub enum SelectItem {
+    /// `*`, all columns of the table
+    Wildcard,
+    /// A column of the table
+    Column(String),
+    /// A constant repeated on every row, `None` for NULL
+    Literal(Option<String>),
+    /// An aggregate over all selected rows, e.g. `COUNT(DISTINCT city)`
+    Aggregate(Aggregate),
+    /// `item AS alias`, naming the result column
+    Aliased { item: Box<SelectItem>, alias: String },
+}
+
+/// One key of an `ORDER BY` list.
+#[derive(Debug, PartialEq, Clone)]
+pub struct OrderBy {
+    pub column: String,
+    /// `ASC`, the default, or `DESC`
+    pub ascending: bool,
+    /// `NULLS FIRST` or `NULLS LAST`; `None` sorts NULL before every value
+    /// in either direction, as `NULLS FIRST` does
+    pub nulls_first: Option<bool>,
+}
+
+/// How many rows a SELECT returns, from `LIMIT n` / `TOP n`, or
+/// `LIMIT p%` / `TOP p PERCENT` for a share of the rows it matches.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Limit {
+    Rows(usize),
+    /// A percentage between 0 and 100
+    Percent(u32),
+}
+
+impl Limit {
+    /// Parses the count of a `LIMIT` clause: `n`, or `p%` for a percentage.
+    fn parse(count: &str) -> Result<Limit, String> {
+        match count.strip_suffix('%') {
+            Some(percent) => Limit::percent(percent.trim_end())
+                .ok_or_else(|| format!("Invalid LIMIT: '{}' is not a percentage between 0 and 100", count)),
+            None => count
+                .parse::<usize>()
+                .map(Limit::Rows)
+                .map_err(|_| format!("Invalid LIMIT: '{}' is not a non-negative integer", count)),
+        }
+    }
+
+    fn percent(percent: &str) -> Option<Limit> {
+        percent.parse::<u32>().ok().filter(|&percent| percent <= 100).map(Limit::Percent)
+    }
+
+    /// How many of `matched` rows to keep; a percentage is rounded to the
+    /// nearest row.
+    pub fn rows(self, matched: usize) -> usize {
+        match self {
+            Limit::Rows(count) => count.min(matched),
+            Limit::Percent(percent) => (matched * percent as usize + 50) / 100,
+        }
+    }
+}
+
+/// A scalar expression over the columns of a row.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Expr {
+    Column(String),
+    /// A constant, `None` for NULL
+    Literal(Option<String>),
+    /// Arithmetic on two expressions; the operator is one of `+ - * /`
+    Binary { left: Box<Expr>, operator: char, right: Box<Expr> },
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Column(name) => write!(f, "{}", name),
+            Expr::Literal(None) => write!(f, "NULL"),
+            Expr::Literal(Some(value)) if value.parse::<f64>().is_ok() => write!(f, "{}", value),
+            Expr::Literal(Some(value)) => write!(f, "'{}'", value),
+            Expr::Binary { left, operator, right } => write!(f, "{} {} {}", left, operator, right),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl AggregateFunction {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_uppercase().as_str() {
+            "COUNT" => Some(AggregateFunction::Count),
+            "SUM" => Some(AggregateFunction::Sum),
+            "AVG" => Some(AggregateFunction::Avg),
+            "MIN" => Some(AggregateFunction::Min),
+            "MAX" => Some(AggregateFunction::Max),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AggregateFunction::Count => "COUNT",
+            AggregateFunction::Sum => "SUM",
+            AggregateFunction::Avg => "AVG",
+            AggregateFunction::Min => "MIN",
+            AggregateFunction::Max => "MAX",
+        }
+    }
+}
+
+/// An aggregate function call such as `SUM(price * qty)`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Aggregate {
+    pub function: AggregateFunction,
+    /// Whether duplicate values are dropped before aggregating
+    pub distinct: bool,
+    /// The aggregated expression, `None` for `COUNT(*)`
+    pub argument: Option<Expr>,
+}
+
+impl Aggregate {
+    /// The result column name used without an alias: the function in
+    /// lowercase followed by the argument, e.g. `count`, `sum_price` or
+    /// `count_distinct_city`.
+    pub fn default_name(&self) -> String {
+        let mut name = self.function.name().to_lowercase();
+        if self.distinct {
+            name.push_str("_distinct");
+        }
+        if let Some(argument) = &self.argument {
+            // Anything but letters and digits becomes a single underscore
+            for word in argument.to_string().to_lowercase().split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()) {
+                name.push('_');
+                name.push_str(word);
+            }
+        }
+        name
+    }
+}
+
+impl fmt::Display for Aggregate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}(", self.function.name())?;
+        if self.distinct {
+            write!(f, "DISTINCT ")?;
+        }
+        match &self.argument {
+            Some(argument) => write!(f, "{})", argument),
+            None => write!(f, "*)"),
+        }
+    }
+}
+
+/// A parsed statement, the syntax tree `Parser::parse` produces.
+#[derive(Debug, PartialEq, Clone)]
 pub enum Command {
     CreateTable {
         name: String,
         columns: Vec<Column>,
     },
+    /// `CREATE TABLE name AS SELECT ...`, creating a table with the query's
+    /// columns and rows
+    CreateTableAs {
+        name: String,
+        source: String,
+        columns: Vec<SelectItem>,
+        where_clause: Option<WhereExpr>,
+        order_by: Vec<OrderBy>,
+    },
+    /// `INSERT INTO table [(col, ...)] VALUES (...)[, (...) ...]
+    /// [RETURNING ...]`; without a column list the values are given for
+    /// every column in schema order
     Insert {
         table: String,
-        values: Vec<String>,
+        columns: Option<Vec<String>>,
+        /// The values of each row to insert
+        values: Vec<Vec<Option<String>>>,
+        /// The projection of the inserted rows to return, as in a SELECT
+        returning: Option<Vec<SelectItem>>,
     },
+    /// `INSERT INTO table SELECT ...`, appending the rows the query returns
+    InsertSelect {
+        table: String,
+        source: String,
+        columns: Vec<SelectItem>,
+        where_clause: Option<WhereExpr>,
+        order_by: Vec<OrderBy>,
+    },
+    /// `SELECT [TOP n] ... FROM table [WHERE ...] [GROUP BY column]
+    /// [ORDER BY ...] [LIMIT n] [OFFSET m]`; an empty `order_by` leaves rows
+    /// in table order
     Select {
         table: String,
-        columns: Vec<String>,
-        where_clause: Option<WhereClause>,
+        columns: Vec<SelectItem>,
+        where_clause: Option<WhereExpr>,
+        /// The column whose values split the rows into groups, each giving
+        /// one result row
+        group_by: Option<String>,
+        order_by: Vec<OrderBy>,
+        /// The most rows to return, applied after sorting and the offset
+        limit: Option<Limit>,
+        /// How many rows to skip, applied after sorting
+        offset: Option<usize>,
+    },
+    /// `SELECT ... FROM a, b [WHERE ...] [ORDER BY ...] [LIMIT n] [OFFSET m]`
+    /// over every combination of rows of the listed tables
+    CrossJoin {
+        tables: Vec<String>,
+        columns: Vec<SelectItem>,
+        where_clause: Option<WhereExpr>,
+        order_by: Vec<OrderBy>,
+        limit: Option<Limit>,
+        offset: Option<usize>,
     },
     Update {
         table: String,
-        set_column: String,
-        set_value: String,
-        where_clause: Option<WhereClause>,
+        /// `(column, value)` pairs of the SET list, value `None` for NULL
+        assignments: Vec<(String, Option<String>)>,
+        where_clause: Option<WhereExpr>,
+        /// The most rows to update
+        limit: Option<usize>,
+    },
+    /// `DELETE FROM table [WHERE ...]`; without a WHERE clause every row
+    /// is deleted
+    Delete {
+        table: String,
+        where_clause: Option<WhereExpr>,
+        /// The most rows to delete
+        limit: Option<usize>,
     },
     Truncate {
         table: String,
     },
+    /// `DROP TABLE [IF EXISTS] table`
+    DropTable {
+        name: String,
+        if_exists: bool,
+    },
     Get {
         table: String,
         format: String,
     },
-    ShowTables,
+    /// `ALTER TABLE table RENAME TO new_name`
+    RenameTable {
+        table: String,
+        new_name: String,
+    },
+    /// `ALTER TABLE table RENAME COLUMN column TO new_name`
+    RenameColumn {
+        table: String,
+        column: String,
+        new_name: String,
+    },
+    /// `EXPORT table TO 'file' [WHERE ...]` writes the matching rows to a
+    /// CSV file
+    Export {
+        table: String,
+        path: String,
+        where_clause: Option<WhereExpr>,
+    },
+    /// `SHOW TABLES [LIKE 'pattern']` lists the tables, or those whose
+    /// names match the pattern
+    ShowTables {
+        pattern: Option<String>,
+    },
+    /// `SHOW COLUMNS FROM table` lists the table's columns as rows
+    ShowColumns {
+        table: String,
+    },
     InspectTable {
         name: String,
     },
+    /// `TABLE STATS name` reports how full the table's data pages are
+    TableStats {
+        table: String,
+    },
+    /// Diagnostic dump of one page of the database file
+    DumpPage {
+        page_id: u64,
+    },
+    /// `REINDEX SCHEMA` rebuilds the chain of schema pages from the pages
+    /// tagged as schema pages
+    ReindexSchema,
+    /// `PRAGMA name = value` changes a setting, `PRAGMA name` reads it
+    Pragma {
+        name: String,
+        value: Option<String>,
+    },
+    /// `VALIDATE statement` runs every check of the statement without
+    /// applying it
+    Validate(Box<Command>),
     Unknown(String),
 }
 
-#[derive(Debug, PartialEq, Clone, Serialize)]
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Column {
     pub name: String,
     pub data_type: String,
+    /// Whether the column rejects NULL values
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "std::ops::Not::not"))]
+    pub not_null: bool,
+    /// The value an INSERT that omits the column stores, `None` for NULL
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub default: Option<String>,
+}
+
+impl Column {
+    /// A nullable column without a default.
+    pub fn new(name: &str, data_type: &str) -> Self {
+        Column {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            not_null: false,
+            default: None,
+        }
+    }
+
+    /// The column's type followed by its constraints, as written in
+    /// CREATE TABLE, e.g. `INTEGER NOT NULL DEFAULT 0`.
+    pub fn type_definition(&self) -> String {
+        let mut definition = self.data_type.clone();
+        if self.not_null {
+            definition.push_str(" NOT NULL");
+        }
+        if let Some(default) = &self.default {
+            if default.parse::<f64>().is_ok() {
+                definition.push_str(&format!(" DEFAULT {}", default));
+            } else {
+                definition.push_str(&format!(" DEFAULT '{}'", default));
+            }
+        }
+        definition
+    }
+
+    /// The most characters a value may have, from a length given with a
+    /// text type such as `VARCHAR(50)` or `TEXT(20)`. `None` means the column
+    /// is unbounded, as plain `TEXT` is.
+    pub fn max_length(&self) -> Option<usize> {
+        let (base, rest) = self.data_type.split_once('(')?;
+        if !matches!(base.trim().to_uppercase().as_str(), "TEXT" | "STRING" | "VARCHAR" | "CHAR") {
+            return None;
+        }
+        rest.strip_suffix(')')?.trim().parse().ok()
+    }
 }
 
 pub struct Parser {}
 
+/// Keywords that start a table constraint rather than a column definition
+const TABLE_CONSTRAINTS: [&str; 5] = ["CONSTRAINT", "PRIMARY", "UNIQUE", "CHECK", "FOREIGN"];
+
+/// Follows the quoted values of a statement as it is scanned a character
+/// at a time. In a single-quoted value a backslash escapes the character
+/// after it, so `'it\'s'` is one value.
+#[derive(Default)]
+struct Quotes {
+    open: Option<char>,
+    escaped: bool,
+}
+
+impl Quotes {
+    /// Takes the next character and returns whether it is part of a quoted
+    /// value, its quotes included.
+    fn scan(&mut self, c: char) -> bool {
+        match self.open {
+            Some(_) if self.escaped => self.escaped = false,
+            Some('\'') if c == '\\' => self.escaped = true,
+            Some(q) if c == q => self.open = None,
+            Some(_) => {}
+            None if c == '\'' || c == '"' => self.open = Some(c),
+            None => return false,
+        }
+        true
+    }
+
+    /// Whether the scan ended inside an unterminated quoted value.
+    fn is_open(&self) -> bool {
+        self.open.is_some()
+    }
+}
+
+/// Splits `input` on a keyword such as `AND`, ignoring case and any occurrence
+/// inside quotes or parentheses. The keyword must be surrounded by spaces.
+fn split_keyword<'a>(input: &'a str, keyword: &str) -> Vec<&'a str> {
+    let separator = format!(" {} ", keyword);
+    let bytes = input.as_bytes();
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut quotes = Quotes::default();
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+        if quotes.scan(c as char) {
+            i += 1;
+            continue;
+        }
+        match c {
+            b'(' => depth += 1,
+            b')' => depth -= 1,
+            _ if depth == 0
+                && bytes[i..].len() >= separator.len()
+                && bytes[i..i + separator.len()].eq_ignore_ascii_case(separator.as_bytes()) =>
+            {
+                parts.push(&input[start..i]);
+                i += separator.len();
+                start = i;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    parts.push(&input[start..]);
+    parts
+}
+
+/// Finds the last of `operators` outside quotes and parentheses that is used
+/// as a binary operator, so evaluating left to right keeps `a - b - c`
+/// left-associative. A sign directly after another operator doesn't count.
+fn find_last_operator(input: &str, operators: [char; 2]) -> Option<usize> {
+    let mut depth = 0i32;
+    let mut quotes = Quotes::default();
+    let mut found = None;
+    let mut previous = None;
+
+    for (i, c) in input.char_indices() {
+        match c {
+            _ if quotes.scan(c) => {}
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ if depth == 0 && operators.contains(&c) && previous.is_some_and(|p: char| !"+-*/(".contains(p)) => {
+                found = Some(i);
+            }
+            _ => {}
+        }
+        if !c.is_whitespace() {
+            previous = Some(c);
+        }
+    }
+    found
+}
+
+/// Strips one pair of parentheses enclosing all of `input`, if present.
+fn strip_enclosing_parens(input: &str) -> Option<&str> {
+    let inner = input.strip_prefix('(')?.strip_suffix(')')?;
+    // `(a) OR (b)` starts and ends with parentheses that don't match each other
+    let mut depth = 0i32;
+    let mut quotes = Quotes::default();
+    for c in inner.chars() {
+        match c {
+            _ if quotes.scan(c) => {}
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth < 0 {
+                    return None;
+                }
+            }
+            _ => {}
+        }
+    }
+    Some(inner)
+}
+
+/// Parses a statement into its syntax tree without executing it, the same as
+/// `Parser::new().parse(input)`. Parsing never touches an engine or database,
+/// so tools and tests can inspect statements in isolation.
+pub fn parse_to_ast(input: &str) -> Command {
+    Parser::new().parse(input)
+}
+
+/// Whether `input` holds one or more whole statements: quotes and parentheses
+/// are balanced and the input ends with a `;` terminator.
+pub fn is_complete_statement(input: &str) -> bool {
+    let mut depth = 0i32;
+    let mut quotes = Quotes::default();
+    let mut last = None;
+
+    for c in input.chars() {
+        match c {
+            _ if quotes.scan(c) => {}
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        if !c.is_whitespace() {
+            last = Some(c);
+        }
+    }
+
+    !quotes.is_open() && depth <= 0 && last == Some(';')
+}
+
+/// Splits input holding several `;`-terminated statements into the
+/// individual statements, without their terminators.
+pub fn split_statements(input: &str) -> Vec<&str> {
+    split_top_level(input, ';')
+        .into_iter()
+        .map(str::trim)
+        .filter(|statement| !statement.is_empty())
+        .collect()
+}
+
+/// Strips a leading keyword, ignoring its case, keeping the rest of the input
+/// as written.
+fn strip_keyword_prefix<'a>(input: &'a str, keyword: &str) -> Option<&'a str> {
+    match input.get(..keyword.len()) {
+        Some(prefix) if prefix.eq_ignore_ascii_case(keyword) => Some(&input[keyword.len()..]),
+        _ => None,
+    }
+}
+
+/// Comparison operators of a WHERE condition, longer ones before their
+/// prefixes
+const OPERATORS: [&str; 6] = ["<=", ">=", "!=", "<", ">", "="];
+
+/// Finds the operator of a WHERE condition: the first comparison operator,
+/// or `LIKE` / `NOT LIKE` as a whole word, outside quotes. Operators inside a
+/// quoted value, as in `note = 'a>b'`, are part of the value. Returns its
+/// byte position and the operator.
+fn find_operator(condition: &str) -> Option<(usize, &'static str)> {
+    let bytes = condition.as_bytes();
+    let mut quotes = Quotes::default();
+    for (i, &c) in bytes.iter().enumerate() {
+        if quotes.scan(c as char) {
+            continue;
+        }
+        // Operators are ASCII, so they never start inside a multi-byte character
+        let Some(rest) = condition.get(i..) else {
+            continue;
+        };
+        if let Some(operator) = OPERATORS.iter().find(|operator| rest.starts_with(**operator)) {
+            return Some((i, operator));
+        }
+        let word_start = i > 0 && bytes[i - 1].is_ascii_whitespace();
+        for operator in ["NOT LIKE", "LIKE"] {
+            let word_end = match rest.get(operator.len()..) {
+                Some(after) if rest[..operator.len()].eq_ignore_ascii_case(operator) => after,
+                _ => continue,
+            };
+            if word_start && word_end.starts_with(|c: char| c.is_whitespace() || c == '\'' || c == '"') {
+                return Some((i, operator));
+            }
+        }
+    }
+    None
+}
+
+/// Decodes the escapes of a single-quoted value. A backslash before any
+/// other character is kept, so a path such as `'C:\data'` reads as written.
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some(c @ ('\\' | '\'')) => out.push(c),
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Whether `raw` is a string literal enclosed in single or double quotes.
+pub(crate) fn is_quoted(raw: &str) -> bool {
+    ['\'', '"'].iter().any(|&quote| raw.len() >= 2 && raw.starts_with(quote) && raw.ends_with(quote))
+}
+
+/// Whether `raw` is exactly one quoted literal, with nothing after its
+/// closing quote.
+fn is_single_literal(raw: &str) -> bool {
+    let mut quotes = Quotes::default();
+    for (i, c) in raw.char_indices() {
+        quotes.scan(c);
+        if i > 0 && !quotes.is_open() {
+            return i + c.len_utf8() == raw.len();
+        }
+    }
+    false
+}
+
+/// Splits a trailing `LIMIT n` clause off a statement.
+///
+/// A `LIMIT` followed by anything but a single unquoted token is left alone,
+/// so the word can still appear inside a quoted value.
+fn split_limit(input: &str) -> Result<(&str, Option<usize>), String> {
+    let Some((statement, count)) = trailing_clause(input, "LIMIT") else {
+        return Ok((input, None));
+    };
+
+    match count.parse::<usize>() {
+        Ok(limit) => Ok((statement, Some(limit))),
+        Err(_) => Err(format!("Invalid LIMIT: '{}' is not a non-negative integer", count)),
+    }
+}
+
+/// Finds a trailing clause of `keyword` and a count, such as `LIMIT 10`,
+/// returning the statement before it and the count token.
+fn trailing_clause<'a>(input: &'a str, keyword: &str) -> Option<(&'a str, &'a str)> {
+    let pos = input.to_ascii_uppercase().rfind(&format!(" {} ", keyword))?;
+
+    let count = input[pos + keyword.len() + 2..].trim();
+    if count.is_empty() || count.contains(char::is_whitespace) || count.contains(['\'', '"']) {
+        return None;
+    }
+    Some((input[..pos].trim_end(), count))
+}
+
+/// Splits a leading `TOP n [PERCENT]` off a projection list. `TOP` not
+/// followed by a number is left alone, so it can still name a column.
+fn split_top(columns: &str) -> Result<(&str, Option<Limit>), String> {
+    let Some(rest) = strip_keyword_prefix(columns, "TOP ") else {
+        return Ok((columns, None));
+    };
+
+    let rest = rest.trim_start();
+    let (count, rest) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    if !count.starts_with(|c: char| c.is_ascii_digit()) {
+        return Ok((columns, None));
+    }
+    let rest = rest.trim_start();
+    if let Some(after_percent) = strip_keyword_prefix(rest, "PERCENT") {
+        if after_percent.is_empty() || after_percent.starts_with(char::is_whitespace) {
+            let limit = Limit::percent(count)
+                .ok_or_else(|| format!("Invalid TOP: '{}' is not a percentage between 0 and 100", count))?;
+            return Ok((after_percent.trim_start(), Some(limit)));
+        }
+    }
+    match count.parse::<usize>() {
+        Ok(count) => Ok((rest, Some(Limit::Rows(count)))),
+        Err(_) => Err(format!("Invalid TOP: '{}' is not a non-negative integer", count)),
+    }
+}
+
+/// Removes one trailing `;` (and the whitespace before it), unless it is
+/// inside an unterminated quoted value.
+fn strip_trailing_semicolon(input: &str) -> &str {
+    let Some(stripped) = input.strip_suffix(';') else {
+        return input;
+    };
+
+    let mut quotes = Quotes::default();
+    for c in stripped.chars() {
+        quotes.scan(c);
+    }
+
+    if quotes.is_open() {
+        input
+    } else {
+        stripped.trim_end()
+    }
+}
+
+/// Splits `input` on `separator`, ignoring separators inside quotes or parentheses.
+pub(crate) fn split_top_level(input: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut quotes = Quotes::default();
+    let mut start = 0;
+
+    for (i, c) in input.char_indices() {
+        match c {
+            _ if quotes.scan(c) => {}
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ if c == separator && depth == 0 => {
+                parts.push(&input[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&input[start..]);
+    parts
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Parser {
     pub fn new() -> Self {
         Parser {}
     }
 
     pub fn parse(&self, input: &str) -> Command {
-        let input = input.trim();
-        let input_upper = input.to_uppercase();
+        let input = strip_trailing_semicolon(input.trim());
+        let input_upper = input.to_ascii_uppercase();
 
         if input_upper.starts_with("CREATE TABLE") {
             self.parse_create_table(input)
@@ -67,99 +747,203 @@ impl Parser {
             self.parse_select(input)
         } else if input_upper.starts_with("UPDATE") {
             self.parse_update(input)
+        } else if input_upper.starts_with("DELETE FROM") {
+            self.parse_delete(input)
         } else if input_upper.starts_with("TRUNCATE TABLE") {
             self.parse_truncate(input)
+        } else if input_upper.starts_with("DROP TABLE") {
+            self.parse_drop_table(input)
         } else if input_upper.starts_with("GET") {
             self.parse_get(input)
+        } else if input_upper.starts_with("ALTER TABLE") {
+            self.parse_alter_table(input)
+        } else if input_upper.starts_with("EXPORT ") {
+            self.parse_export(input)
         } else if input_upper.starts_with("SHOW TABLES") {
-            Command::ShowTables
+            self.parse_show_tables(input)
+        } else if input_upper.starts_with("SHOW COLUMNS ") {
+            self.parse_show_columns(input)
+        } else if input_upper.starts_with("TABLE STATS ") {
+            self.parse_table_stats(input)
         } else if input_upper.starts_with("INSPECT") {
             self.parse_inspect(input)
+        } else if input_upper.starts_with("DUMP PAGE") {
+            self.parse_dump_page(input)
+        } else if input_upper.split_whitespace().eq(["REINDEX", "SCHEMA"]) {
+            Command::ReindexSchema
+        } else if input_upper.starts_with("PRAGMA ") {
+            self.parse_pragma(input)
+        } else if input_upper.starts_with("VALIDATE ") {
+            Command::Validate(Box::new(self.parse(&input["VALIDATE ".len()..])))
         } else {
             Command::Unknown(input.to_string())
         }
     }
 
-    /// Parses a simple WHERE clause with operators =, !=, <, >, <=, >=, LIKE, and NOT LIKE.
+    /// Parses a single literal value, returning `None` for the `NULL` keyword.
+    ///
+    /// Quoted values keep their contents, so `'NULL'` is a string; in
+    /// single quotes the escapes `\n`, `\t`, `\r`, `\\` and `\'` are
+    /// decoded. An unquoted empty value is treated as NULL.
+    pub(crate) fn parse_literal(&self, raw: &str) -> Option<String> {
+        let raw = raw.trim();
+        if raw.is_empty() || raw.eq_ignore_ascii_case("NULL") {
+            return None;
+        }
+
+        if is_quoted(raw) {
+            let contents = &raw[1..raw.len() - 1];
+            return Some(if raw.starts_with('\'') { unescape(contents) } else { contents.to_string() });
+        }
+
+        // A hex literal is kept in the form BLOB values are read back in, X'FF'
+        if let Some(hex) = raw.strip_prefix(['x', 'X']).filter(|hex| hex.starts_with('\'') && is_quoted(hex)) {
+            return Some(format!("X{}", hex.to_ascii_uppercase()));
+        }
+
+        Some(raw.to_string())
+    }
+
+    /// Parses a WHERE expression of conditions joined with AND / OR, optionally
+    /// grouped with parentheses. `column BETWEEN low AND high` is inclusive.
+    fn parse_where(&self, where_str: &str) -> Option<WhereExpr> {
+        let where_str = where_str.trim();
+
+        let alternatives = split_keyword(where_str, "OR");
+        if alternatives.len() > 1 {
+            return alternatives
+                .into_iter()
+                .map(|part| self.parse_where(part))
+                .reduce(|left, right| Some(WhereExpr::Or(Box::new(left?), Box::new(right?))))?;
+        }
+
+        let conjuncts = split_keyword(where_str, "AND");
+        if conjuncts.len() > 1 {
+            // `column BETWEEN low AND high` spans two conjuncts and becomes
+            // `column >= low AND column <= high`
+            let mut conditions = Vec::new();
+            let mut parts = conjuncts.into_iter();
+            while let Some(part) = parts.next() {
+                match split_keyword(part, "BETWEEN")[..] {
+                    [column, low] => {
+                        let high = parts.next()?;
+                        conditions.push(self.parse_where(&format!("{} >= {}", column.trim(), low.trim()))?);
+                        conditions.push(self.parse_where(&format!("{} <= {}", column.trim(), high.trim()))?);
+                    }
+                    _ => conditions.push(self.parse_where(part)?),
+                }
+            }
+            return conditions.into_iter().reduce(|left, right| WhereExpr::And(Box::new(left), Box::new(right)));
+        }
+
+        match strip_enclosing_parens(where_str) {
+            Some(inner) => self.parse_where(inner),
+            None => self.parse_where_clause(where_str).map(WhereExpr::Condition),
+        }
+    }
+
+    /// Parses a simple WHERE clause with operators =, !=, <, >, <=, >=, LIKE, NOT LIKE,
+    /// IS NULL, and IS NOT NULL.
     fn parse_where_clause(&self, where_str: &str) -> Option<WhereClause> {
-        let where_upper = where_str.to_uppercase();
-        let operator_str;
-        let operator_len;
-
-        if where_upper.contains("NOT LIKE") {
-            operator_str = "NOT LIKE";
-            operator_len = 8;
-        } else if where_upper.contains("LIKE") {
-            operator_str = "LIKE";
-            operator_len = 4;
-        } else if where_upper.contains("<=") {
-            operator_str = "<=";
-            operator_len = 2;
-        } else if where_upper.contains(">=") {
-            operator_str = ">=";
-            operator_len = 2;
-        } else if where_upper.contains("!=") {
-            operator_str = "!=";
-            operator_len = 2;
-        } else if where_upper.contains('<') {
-            operator_str = "<";
-            operator_len = 1;
-        } else if where_upper.contains('>') {
-            operator_str = ">";
-            operator_len = 1;
-        } else if where_upper.contains('=') {
-            operator_str = "=";
-            operator_len = 1;
-        } else {
-            return None; // No supported operator found
-        };
+        let where_upper = where_str.to_ascii_uppercase();
 
-        if let Some(op_pos) = where_upper.find(operator_str) {
-            let column = where_str[..op_pos].trim().to_string();
-            let value = where_str[op_pos + operator_len..].trim().trim_matches('"').trim_matches('\'').to_string();
-            Some(WhereClause {
-                column,
-                operator: operator_str.to_string(),
-                value,
-            })
-        } else {
-            None // Should not happen if we found the operator string
+        // Null checks have no value to compare against
+        for operator in ["IS NOT NULL", "IS NULL"] {
+            if let Some(column) = where_upper.strip_suffix(operator) {
+                if column.ends_with(' ') {
+                    return Some(WhereClause {
+                        column: where_str[..column.len()].trim().to_string(),
+                        operator: operator.to_string(),
+                        value: None,
+                        quoted: false,
+                    });
+                }
+            }
+        }
+
+        let (op_pos, operator_str) = find_operator(where_str)?;
+        let column = where_str[..op_pos].trim().to_string();
+        let raw_value = &where_str[op_pos + operator_str.len()..];
+        // A value is a single literal; anything after it, such as a stray
+        // clause, is an error rather than part of the value
+        let value = raw_value.trim();
+        let quoted = value.starts_with(['\'', '"']);
+        if (quoted && !is_single_literal(value)) || (!quoted && value.contains(char::is_whitespace)) {
+            return None;
         }
+        Some(WhereClause {
+            column,
+            operator: operator_str.to_string(),
+            value: self.parse_literal(raw_value),
+            quoted: is_quoted(raw_value.trim()),
+        })
     }
 
     fn parse_create_table(&self, input: &str) -> Command {
         // Format: CREATE TABLE name (col1 TYPE, col2 TYPE)
-        let input_upper = input.to_uppercase();
-        let rest = match input_upper.strip_prefix("CREATE TABLE") {
+        let rest = match strip_keyword_prefix(input, "CREATE TABLE") {
             Some(r) => r.trim(),
             None => return Command::Unknown(input.to_string()),
         };
 
+        // CREATE TABLE name AS SELECT ... takes its columns and rows from a query
+        if let [name, _, ..] = split_keyword(rest, "AS")[..] {
+            // The query may have AS clauses of its own
+            let query = rest[name.len() + " AS ".len()..].trim();
+            if strip_keyword_prefix(query, "SELECT ").is_some() {
+                return match self.parse_select(query) {
+                    Command::Select { limit: Some(_), .. } | Command::Select { offset: Some(_), .. } => {
+                        Command::Unknown("LIMIT, TOP and OFFSET are not supported in CREATE TABLE ... AS SELECT".to_string())
+                    }
+                    Command::Select { group_by: Some(_), .. } => {
+                        Command::Unknown("GROUP BY is not supported in CREATE TABLE ... AS SELECT".to_string())
+                    }
+                    Command::Select { table: source, columns, where_clause, group_by: None, order_by, limit: None, offset: None } => {
+                        Command::CreateTableAs { name: name.trim().to_string(), source, columns, where_clause, order_by }
+                    }
+                    _ => Command::Unknown(input.to_string()),
+                };
+            }
+        }
+
         // Find the opening parenthesis
         let parts: Vec<&str> = rest.splitn(2, '(').collect();
         if parts.len() != 2 {
+            // A bare name has no columns, which the engine rejects
+            if !rest.is_empty() && !rest.contains(char::is_whitespace) {
+                return Command::CreateTable { name: rest.to_string(), columns: Vec::new() };
+            }
             return Command::Unknown(input.to_string());
         }
 
         let table_name = parts[0].trim().to_string();
-        let columns_str = parts[1].trim_end_matches(')').trim();
+        // Only the closing parenthesis of the column list; nested ones belong to the columns
+        let columns_str = parts[1].trim_end();
+        let columns_str = columns_str.strip_suffix(')').unwrap_or(columns_str).trim();
 
-        // Parse columns: "col1 TYPE, col2 TYPE"
-        let columns: Vec<Column> = columns_str
-            .split(',')
+        // Parse columns: "col1 TYPE, col2 TYPE". Only top-level commas separate
+        // columns, so commas inside quotes or parentheses stay with their column.
+        let columns: Vec<Column> = split_top_level(columns_str, ',')
+            .into_iter()
             .filter_map(|col| {
-                let parts: Vec<&str> = col.trim().split_whitespace().collect();
+                // Quoted defaults may contain spaces
+                let parts: Vec<&str> = split_top_level(col.trim(), ' ').into_iter().filter(|part| !part.is_empty()).collect();
+                // Table constraints aren't columns
+                if parts.first().is_some_and(|first| TABLE_CONSTRAINTS.contains(&first.to_uppercase().as_str())) {
+                    return None;
+                }
                 if parts.len() >= 2 {
-                    Some(Column {
-                        name: parts[0].to_string(),
-                        data_type: parts[1].to_uppercase(),
-                    })
-                } else if parts.len() == 1 && !parts[0].is_empty() {
+                    // A length may be written apart from its type, as in `VARCHAR (50)`
+                    let (data_type, constraints) = match parts.get(2) {
+                        Some(length) if length.starts_with('(') => (format!("{}{}", parts[1], length), &parts[3..]),
+                        _ => (parts[1].to_string(), &parts[2..]),
+                    };
+                    let mut column = Column::new(parts[0], &data_type.to_uppercase());
+                    self.parse_column_constraints(&mut column, constraints);
+                    Some(column)
+                } else if parts.len() == 1 {
                     // Default to TEXT if no type specified
-                    Some(Column {
-                        name: parts[0].to_string(),
-                        data_type: "TEXT".to_string(),
-                    })
+                    Some(Column::new(parts[0], "TEXT"))
                 } else {
                     None
                 }
@@ -172,36 +956,129 @@ impl Parser {
         }
     }
 
+    /// Applies the `NOT NULL`, `NULL` and `DEFAULT <value>` constraints that
+    /// follow a column's type. Other constraints, such as `PRIMARY KEY`, are
+    /// accepted and ignored.
+    fn parse_column_constraints(&self, column: &mut Column, words: &[&str]) {
+        let mut i = 0;
+        while i < words.len() {
+            let word = words[i].to_uppercase();
+            if word == "NOT" && words.get(i + 1).is_some_and(|next| next.eq_ignore_ascii_case("NULL")) {
+                column.not_null = true;
+                i += 2;
+            } else if word == "DEFAULT" && i + 1 < words.len() {
+                column.default = self.parse_literal(words[i + 1]);
+                i += 2;
+            } else {
+                i += 1;
+            }
+        }
+    }
+
     fn parse_insert(&self, input: &str) -> Command {
-        // Format: INSERT INTO table VALUES (val1, val2)
-        let input_upper = input.to_uppercase();
+        // Format: INSERT INTO table VALUES (val1, val2)[, (val1, val2) ...]
+        let input_upper = input.to_ascii_uppercase();
         if !input_upper.starts_with("INSERT INTO") {
             return Command::Unknown(input.to_string());
         }
 
         let after_insert = &input[11..].trim_start(); // Skip "INSERT INTO" (11 chars)
-        let values_pos_original = match after_insert.to_uppercase().find("VALUES") {
+
+        // INSERT INTO table SELECT ... takes its rows from a query
+        if let Some((table, rest)) = after_insert.split_once(char::is_whitespace) {
+            let rest = rest.trim_start();
+            if strip_keyword_prefix(rest, "SELECT ").is_some() {
+                return match self.parse_select(rest) {
+                    Command::Select { limit: Some(_), .. } | Command::Select { offset: Some(_), .. } => {
+                        Command::Unknown("LIMIT, TOP and OFFSET are not supported in INSERT INTO ... SELECT".to_string())
+                    }
+                    Command::Select { group_by: Some(_), .. } => {
+                        Command::Unknown("GROUP BY is not supported in INSERT INTO ... SELECT".to_string())
+                    }
+                    Command::Select { table: source, columns, where_clause, group_by: None, order_by, limit: None, offset: None } => {
+                        Command::InsertSelect { table: table.to_string(), source, columns, where_clause, order_by }
+                    }
+                    _ => Command::Unknown(input.to_string()),
+                };
+            }
+        }
+
+        // A trailing RETURNING list selects from the inserted rows
+        let (after_insert, returning) = match split_keyword(after_insert, "RETURNING")[..] {
+            [rest] if !rest.to_ascii_uppercase().ends_with(" RETURNING") => (rest, None),
+            [rest, items] if !items.trim().is_empty() => {
+                let items = split_top_level(items, ',').into_iter().map(|item| self.parse_select_item(item)).collect();
+                (rest.trim_end(), Some(items))
+            }
+            _ => return Command::Unknown(input.to_string()),
+        };
+
+        let values_pos_original = match after_insert.to_ascii_uppercase().find("VALUES") {
             Some(pos) => pos,
             None => return Command::Unknown(input.to_string()),
         };
         
-        let table_name = after_insert[..values_pos_original].trim().to_string();
-        let values_str = after_insert[values_pos_original + 6..].trim().trim_start_matches('(').trim_end_matches(')');
+        let target = after_insert[..values_pos_original].trim();
+        // An optional list of the columns the values are for follows the table name
+        let (table_name, columns) = match target.split_once('(') {
+            Some((name, list)) => {
+                let list = match list.trim_end().strip_suffix(')') {
+                    Some(list) => list,
+                    None => return Command::Unknown(input.to_string()),
+                };
+                let columns: Vec<String> = list.split(',').map(|c| c.trim().to_string()).collect();
+                if columns.iter().any(|c| c.is_empty()) {
+                    return Command::Unknown(input.to_string());
+                }
+                (name.trim().to_string(), Some(columns))
+            }
+            None => (target.to_string(), None),
+        };
+        let values_str = after_insert[values_pos_original + 6..].trim();
 
-        let values: Vec<String> = values_str
-            .split(',')
-            .map(|v| v.trim().trim_matches('"').trim_matches('"').to_string())
-            .collect();
+        // Several rows are each given in parentheses: VALUES (1, 'a'), (2, 'b')
+        let groups = split_top_level(values_str, ',');
+        let values = if groups.len() > 1 && values_str.starts_with('(') {
+            let mut rows = Vec::with_capacity(groups.len());
+            for group in groups {
+                match group.trim().strip_prefix('(').and_then(|group| group.strip_suffix(')')) {
+                    Some(group) => rows.push(self.parse_values(group)),
+                    None => return Command::Unknown(input.to_string()),
+                }
+            }
+            rows
+        } else {
+            vec![self.parse_values(values_str.trim_start_matches('(').trim_end_matches(')'))]
+        };
 
         Command::Insert {
             table: table_name,
+            columns,
             values,
+            returning,
         }
     }
 
+    /// Parses the comma-separated literals of one row of VALUES.
+    fn parse_values(&self, values: &str) -> Vec<Option<String>> {
+        split_top_level(values, ',').into_iter().map(|v| self.parse_literal(v)).collect()
+    }
+
     fn parse_select(&self, input: &str) -> Command {
-        // Format: SELECT col1, col2 FROM table WHERE col = val
-        let input_upper = input.to_uppercase();
+        // Format: SELECT [TOP n] col1, col2 FROM table WHERE col = val GROUP BY col ORDER BY col1 [ASC|DESC], ... [LIMIT n] [OFFSET m]
+        let (input, offset) = match trailing_clause(input, "OFFSET") {
+            Some((statement, count)) => match count.parse::<usize>() {
+                Ok(offset) => (statement, Some(offset)),
+                Err(_) => return Command::Unknown(format!("Invalid OFFSET: '{}' is not a non-negative integer", count)),
+            },
+            None => (input, None),
+        };
+        let (input, limit) = match trailing_clause(input, "LIMIT").map(|(statement, count)| (statement, Limit::parse(count))) {
+            Some((statement, Ok(limit))) => (statement, Some(limit)),
+            Some((_, Err(message))) => return Command::Unknown(message),
+            None => (input, None),
+        };
+        let input_upper = input.to_ascii_uppercase();
         let after_select = &input[6..].trim_start(); // Skip "SELECT "
         let after_select_upper = &input_upper[6..].trim_start();
 
@@ -210,39 +1087,215 @@ impl Parser {
             None => return Command::Unknown(input.to_string()),
         };
 
-        let columns_str = after_select[..from_pos].trim();
-        let after_from = &after_select[from_pos + 5..].trim_start(); // Skip "FROM "
-        let after_from_upper = &after_select_upper[from_pos + 5..].trim_start();
+        let (columns_str, limit) = match split_top(after_select[..from_pos].trim()) {
+            Ok((columns, None)) => (columns, limit),
+            Ok((_, Some(_))) if limit.is_some() => {
+                return Command::Unknown("Use either TOP or LIMIT, not both".to_string());
+            }
+            Ok((columns, top)) => (columns, top),
+            Err(message) => return Command::Unknown(message),
+        };
+        let after_from = after_select[from_pos + 5..].trim_start(); // Skip "FROM "
+
+        // A trailing ORDER BY list sorts the result
+        let (after_from, order_by) = match split_keyword(after_from, "ORDER BY")[..] {
+            [rest] => (rest, Vec::new()),
+            [rest, keys] => match self.parse_order_by(keys) {
+                Some(order_by) => (rest.trim_end(), order_by),
+                None => return Command::Unknown(input.to_string()),
+            },
+            _ => return Command::Unknown(input.to_string()),
+        };
+        // GROUP BY takes a single column
+        let (after_from, group_by) = match split_keyword(after_from, "GROUP BY")[..] {
+            [rest] => (rest, None),
+            [rest, column] if !column.trim().is_empty() && split_top_level(column, ',').len() == 1 => {
+                (rest.trim_end(), Some(column.trim().to_string()))
+            }
+            _ => return Command::Unknown(input.to_string()),
+        };
+        let after_from_upper = after_from.to_ascii_uppercase();
 
         let where_pos = after_from_upper.find("WHERE ");
 
         let (table_name, where_clause) = if let Some(pos) = where_pos {
             let table_part = &after_from[..pos].trim();
             let where_part = &after_from[pos + 6..].trim(); // Skip "WHERE "
-            (table_part.to_string(), self.parse_where_clause(where_part))
+            match self.parse_where(where_part) {
+                Some(expr) => (table_part.to_string(), Some(expr)),
+                None => return Command::Unknown(format!("Invalid WHERE clause: {}", where_part)),
+            }
         } else {
             (after_from.to_string(), None)
         };
 
-        let columns: Vec<String> = if columns_str == "*" {
-            vec!["*".to_string()]
-        } else {
-            columns_str
-                .split(',')
-                .map(|c| c.trim().to_string())
-                .collect()
-        };
+        let columns: Vec<SelectItem> = split_top_level(columns_str, ',')
+            .into_iter()
+            .map(|c| self.parse_select_item(c))
+            .collect();
+
+        // FROM a, b joins every row of a with every row of b
+        if table_name.contains(',') {
+            if group_by.is_some() {
+                return Command::Unknown("GROUP BY is not supported when selecting from several tables".to_string());
+            }
+            let tables: Vec<String> = table_name.split(',').map(|t| t.trim().to_string()).collect();
+            if tables.iter().any(|t| t.is_empty()) {
+                return Command::Unknown(input.to_string());
+            }
+            return Command::CrossJoin {
+                tables,
+                columns,
+                where_clause,
+                order_by,
+                limit,
+                offset,
+            };
+        }
 
         Command::Select {
             table: table_name,
             columns,
             where_clause,
+            group_by,
+            order_by,
+            limit,
+            offset,
+        }
+    }
+
+    /// Parses the keys of `ORDER BY col1 [ASC|DESC], ...`.
+    fn parse_order_by(&self, input: &str) -> Option<Vec<OrderBy>> {
+        split_top_level(input, ',')
+            .into_iter()
+            .map(|key| {
+                // A key may be a call with spaces between its arguments
+                let mut words = split_top_level(key, ' ').into_iter().filter(|word| !word.is_empty()).collect::<Vec<_>>();
+                let nulls_first = match words[..] {
+                    [.., nulls, placement] if nulls.eq_ignore_ascii_case("NULLS") => {
+                        words.truncate(words.len() - 2);
+                        match placement.to_uppercase().as_str() {
+                            "FIRST" => Some(true),
+                            "LAST" => Some(false),
+                            _ => return None,
+                        }
+                    }
+                    _ => None,
+                };
+                let ascending = match words[..] {
+                    [_] => true,
+                    [_, direction] if direction.eq_ignore_ascii_case("ASC") => true,
+                    [_, direction] if direction.eq_ignore_ascii_case("DESC") => false,
+                    _ => return None,
+                };
+                Some(OrderBy { column: words[0].to_string(), ascending, nulls_first })
+            })
+            .collect()
+    }
+
+    /// Parses one item of a projection list: `*`, a quoted, numeric or NULL
+    /// constant, an aggregate call, or otherwise a column name.
+    fn parse_select_item(&self, item: &str) -> SelectItem {
+        let item = item.trim();
+
+        if let [expression, alias] = split_keyword(item, "AS")[..] {
+            let alias = alias.trim();
+            if !alias.is_empty() && alias.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                return SelectItem::Aliased { item: Box::new(self.parse_select_item(expression)), alias: alias.to_string() };
+            }
+        }
+
+        if item == "*" {
+            SelectItem::Wildcard
+        } else if is_quoted(item) || item.eq_ignore_ascii_case("NULL") || item.parse::<f64>().is_ok() {
+            SelectItem::Literal(self.parse_literal(item))
+        } else if let Some(aggregate) = self.parse_aggregate(item) {
+            SelectItem::Aggregate(aggregate)
+        } else {
+            SelectItem::Column(item.to_string())
+        }
+    }
+
+    /// Parses `FUNCTION([DISTINCT] expression)`, or `COUNT(*)`.
+    fn parse_aggregate(&self, item: &str) -> Option<Aggregate> {
+        let (name, rest) = item.split_once('(')?;
+        let function = AggregateFunction::from_name(name.trim())?;
+        let argument = rest.strip_suffix(')')?.trim();
+
+        if argument == "*" {
+            return (function == AggregateFunction::Count).then_some(Aggregate { function, distinct: false, argument: None });
+        }
+
+        let (distinct, argument) = match argument.get(..9) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("DISTINCT ") => (true, &argument[9..]),
+            _ => (false, argument),
+        };
+        Some(Aggregate { function, distinct, argument: Some(self.parse_expr(argument)?) })
+    }
+
+    /// Parses an arithmetic expression of columns and constants with `+ - * /`
+    /// and parentheses; `*` and `/` bind tighter than `+` and `-`.
+    fn parse_expr(&self, input: &str) -> Option<Expr> {
+        let input = input.trim();
+        if input.is_empty() {
+            return None;
+        }
+
+        for operators in [['+', '-'], ['*', '/']] {
+            if let Some(pos) = find_last_operator(input, operators) {
+                return Some(Expr::Binary {
+                    left: Box::new(self.parse_expr(&input[..pos])?),
+                    operator: input[pos..].chars().next()?,
+                    right: Box::new(self.parse_expr(&input[pos + 1..])?),
+                });
+            }
+        }
+
+        if let Some(inner) = strip_enclosing_parens(input) {
+            self.parse_expr(inner)
+        } else if is_quoted(input) || input.eq_ignore_ascii_case("NULL") || input.parse::<f64>().is_ok() {
+            Some(Expr::Literal(self.parse_literal(input)))
+        } else if input.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            Some(Expr::Column(input.to_string()))
+        } else {
+            None
+        }
+    }
+
+    fn parse_dump_page(&self, input: &str) -> Command {
+        // Format: DUMP PAGE <id>
+        match input["DUMP PAGE".len()..].trim().parse::<u64>() {
+            Ok(page_id) => Command::DumpPage { page_id },
+            Err(_) => Command::Unknown(input.to_string()),
         }
     }
 
+    fn parse_pragma(&self, input: &str) -> Command {
+        // Format: PRAGMA name [= value]
+        let rest = input["PRAGMA ".len()..].trim();
+        let (name, value) = match rest.split_once('=') {
+            Some((name, value)) => {
+                let value = value.trim();
+                let value = if is_quoted(value) { &value[1..value.len() - 1] } else { value };
+                (name.trim(), Some(value.to_string()))
+            }
+            None => (rest, None),
+        };
+
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Command::Unknown(input.to_string());
+        }
+
+        Command::Pragma { name: name.to_lowercase(), value }
+    }
+
     fn parse_update(&self, input: &str) -> Command {
-        // Format: UPDATE table SET col = val WHERE other_col = other_val
-        let input_upper = input.to_uppercase();
+        // Format: UPDATE table SET col = val, col2 = val2 WHERE other_col = other_val [LIMIT n]
+        let (input, limit) = match split_limit(input) {
+            Ok(split) => split,
+            Err(message) => return Command::Unknown(message),
+        };
+        let input_upper = input.to_ascii_uppercase();
     
         let set_pos = match input_upper.find(" SET ") {
             Some(pos) => pos,
@@ -260,34 +1313,66 @@ impl Parser {
         let (set_part, where_clause) = if let Some(pos) = where_pos {
             // " WHERE ".len() is 7
             let where_part_str = &after_set[pos + 7..].trim();
-            (
-                after_set[..pos].trim(),
-                self.parse_where_clause(where_part_str),
-            )
+            match self.parse_where(where_part_str) {
+                Some(expr) => (after_set[..pos].trim(), Some(expr)),
+                None => return Command::Unknown(format!("Invalid WHERE clause: {}", where_part_str)),
+            }
         } else {
             (after_set.trim(), None)
         };
     
-        // Parse SET part: "col = val"
-        let set_parts: Vec<&str> = set_part.split('=').map(|s| s.trim()).collect();
-        if set_parts.len() != 2 {
-            return Command::Unknown(format!("Invalid SET clause: {}", set_part));
+        // Parse SET part: "col = val, col2 = val2", where commas and '=' in quoted
+        // values belong to the value
+        let mut assignments = Vec::new();
+        for assignment in split_top_level(set_part, ',') {
+            let (column, value) = match assignment.split_once('=') {
+                Some((column, value)) if !column.trim().is_empty() => (column.trim(), value),
+                _ => return Command::Unknown(format!("Invalid SET clause: {}", set_part)),
+            };
+            assignments.push((column.to_string(), self.parse_literal(value)));
         }
-        let set_column = set_parts[0].to_string();
-        let set_value = set_parts[1].trim_matches('"').trim_matches('"').to_string();
     
         Command::Update {
             table: table_name,
-            set_column,
-            set_value,
+            assignments,
+            where_clause,
+            limit,
+        }
+    }
+
+    fn parse_delete(&self, input: &str) -> Command {
+        // Format: DELETE FROM table_name [WHERE condition] [LIMIT n]
+        let (input, limit) = match split_limit(input) {
+            Ok(split) => split,
+            Err(message) => return Command::Unknown(message),
+        };
+        let rest = match strip_keyword_prefix(input, "DELETE FROM") {
+            Some(r) => r.trim(),
+            None => return Command::Unknown(input.to_string()),
+        };
+
+        let (table, where_clause) = match split_keyword(rest, "WHERE").as_slice() {
+            [table] => (table.trim(), None),
+            [table, condition] => match self.parse_where(condition) {
+                Some(expr) => (table.trim(), Some(expr)),
+                None => return Command::Unknown(format!("Invalid WHERE clause: {}", condition.trim())),
+            },
+            _ => return Command::Unknown(input.to_string()),
+        };
+        if table.is_empty() || table.contains(char::is_whitespace) {
+            return Command::Unknown(input.to_string());
+        }
+
+        Command::Delete {
+            table: table.to_string(),
             where_clause,
+            limit,
         }
     }
 
     fn parse_truncate(&self, input: &str) -> Command {
         // Format: TRUNCATE TABLE table_name
-        let input_upper = input.to_uppercase();
-        let rest = match input_upper.strip_prefix("TRUNCATE TABLE") {
+        let rest = match strip_keyword_prefix(input, "TRUNCATE TABLE") {
             Some(r) => r.trim(),
             None => return Command::Unknown(input.to_string()),
         };
@@ -301,15 +1386,35 @@ impl Parser {
         }
     }
 
+    fn parse_drop_table(&self, input: &str) -> Command {
+        // Format: DROP TABLE [IF EXISTS] table_name
+        let rest = match strip_keyword_prefix(input, "DROP TABLE ") {
+            Some(r) => r.trim(),
+            None => return Command::Unknown(input.to_string()),
+        };
+        let (name, if_exists) = match strip_keyword_prefix(rest, "IF EXISTS ") {
+            Some(name) => (name.trim(), true),
+            None => (rest, false),
+        };
+
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Command::Unknown(input.to_string());
+        }
+
+        Command::DropTable {
+            name: name.to_string(),
+            if_exists,
+        }
+    }
+
     fn parse_get(&self, input: &str) -> Command {
         // Format: GET <tablename> AS JSON
-        let input_upper = input.to_uppercase();
-        let rest = match input_upper.strip_prefix("GET") {
+        let rest = match strip_keyword_prefix(input, "GET") {
             Some(r) => r.trim(),
             None => return Command::Unknown(input.to_string()),
         };
 
-        let parts: Vec<&str> = rest.split(" AS ").collect();
+        let parts = split_keyword(rest, "AS");
         if parts.len() != 2 {
             return Command::Unknown(input.to_string());
         }
@@ -327,9 +1432,88 @@ impl Parser {
         }
     }
 
+    fn parse_alter_table(&self, input: &str) -> Command {
+        // Format: ALTER TABLE <tablename> RENAME [COLUMN <column>] TO <new_name>
+        let words: Vec<&str> = match strip_keyword_prefix(input, "ALTER TABLE") {
+            Some(rest) => rest.split_whitespace().collect(),
+            None => return Command::Unknown(input.to_string()),
+        };
+        let keyword = |index: usize, keyword: &str| words.get(index).is_some_and(|word| word.eq_ignore_ascii_case(keyword));
+
+        match words.len() {
+            4 if keyword(1, "RENAME") && keyword(2, "TO") => Command::RenameTable {
+                table: words[0].to_string(),
+                new_name: words[3].to_string(),
+            },
+            6 if keyword(1, "RENAME") && keyword(2, "COLUMN") && keyword(4, "TO") => Command::RenameColumn {
+                table: words[0].to_string(),
+                column: words[3].to_string(),
+                new_name: words[5].to_string(),
+            },
+            _ => Command::Unknown(input.to_string()),
+        }
+    }
+
+    fn parse_export(&self, input: &str) -> Command {
+        // Format: EXPORT <tablename> TO '<file>' [WHERE <condition>]
+        let rest = match strip_keyword_prefix(input, "EXPORT") {
+            Some(r) => r.trim(),
+            None => return Command::Unknown(input.to_string()),
+        };
+
+        let (rest, where_clause) = match split_keyword(rest, "WHERE").as_slice() {
+            [rest] => (*rest, None),
+            [rest, condition] => match self.parse_where(condition) {
+                Some(expr) => (*rest, Some(expr)),
+                None => return Command::Unknown(format!("Invalid WHERE clause: {}", condition.trim())),
+            },
+            _ => return Command::Unknown(input.to_string()),
+        };
+
+        let (table, path) = match split_keyword(rest, "TO").as_slice() {
+            [table, path] => (table.trim(), path.trim()),
+            _ => return Command::Unknown(input.to_string()),
+        };
+        match self.parse_literal(path) {
+            Some(path) if !table.is_empty() => Command::Export {
+                table: table.to_string(),
+                path,
+                where_clause,
+            },
+            _ => Command::Unknown(input.to_string()),
+        }
+    }
+
+    /// Parses `TABLE STATS <name>`.
+    fn parse_table_stats(&self, input: &str) -> Command {
+        match input["TABLE STATS ".len()..].split_whitespace().collect::<Vec<_>>()[..] {
+            [table] => Command::TableStats { table: table.to_string() },
+            _ => Command::Unknown(input.to_string()),
+        }
+    }
+
+    fn parse_show_tables(&self, input: &str) -> Command {
+        // Format: SHOW TABLES [LIKE 'pattern']
+        let rest = input["SHOW TABLES".len()..].trim();
+        if rest.is_empty() {
+            return Command::ShowTables { pattern: None };
+        }
+        match strip_keyword_prefix(rest, "LIKE ").map(str::trim) {
+            Some(pattern) if is_quoted(pattern) => Command::ShowTables { pattern: self.parse_literal(pattern) },
+            _ => Command::Unknown(input.to_string()),
+        }
+    }
+
+    fn parse_show_columns(&self, input: &str) -> Command {
+        let words: Vec<&str> = input.split_whitespace().collect();
+        match words[..] {
+            [_, _, from, table] if from.eq_ignore_ascii_case("FROM") => Command::ShowColumns { table: table.to_string() },
+            _ => Command::Unknown(input.to_string()),
+        }
+    }
+
     fn parse_inspect(&self, input: &str) -> Command {
-        let input_upper = input.to_uppercase();
-        let rest = match input_upper.strip_prefix("INSPECT") {
+        let rest = match strip_keyword_prefix(input, "INSPECT") {
             Some(r) => r.trim(),
             None => return Command::Unknown(input.to_string()),
         };
@@ -342,4 +1526,51 @@ impl Parser {
             name: rest.to_string(),
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_top_level_ignores_quoted_and_nested_separators() {
+        assert_eq!(split_top_level("a, 'b, c', f(d, e)", ','), vec!["a", " 'b, c'", " f(d, e)"]);
+    }
+
+    #[test]
+    fn find_operator_skips_quoted_values() {
+        assert_eq!(find_operator("note = 'a>b'"), Some((5, "=")));
+        assert_eq!(find_operator("note != 'x=y'"), Some((5, "!=")));
+        assert_eq!(find_operator("age >= 18"), Some((4, ">=")));
+        assert_eq!(find_operator("note = 'I like it'"), Some((5, "=")));
+        assert_eq!(find_operator("name not like 'A%'"), Some((5, "NOT LIKE")));
+        assert_eq!(find_operator("likes LIKE '%a'"), Some((6, "LIKE")));
+        assert_eq!(find_operator("'a=b'"), None);
+    }
+
+    #[test]
+    fn split_keyword_matches_whole_words_outside_quotes() {
+        assert_eq!(split_keyword("a = 1 or b = 'x OR y' OR c = 2", "OR"), vec!["a = 1", "b = 'x OR y'", "c = 2"]);
+        assert_eq!(split_keyword("color = 'red'", "OR"), vec!["color = 'red'"]);
+        assert_eq!(split_keyword("(a = 1 AND b = 2)", "AND"), vec!["(a = 1 AND b = 2)"]);
+    }
+
+    #[test]
+    fn strip_enclosing_parens_requires_one_matching_pair() {
+        assert_eq!(strip_enclosing_parens("(a = 1)"), Some("a = 1"));
+        assert_eq!(strip_enclosing_parens("(a = 1) OR (b = 2)"), None);
+        assert_eq!(strip_enclosing_parens("a = 1"), None);
+    }
+
+    #[test]
+    fn split_limit_takes_a_trailing_count() {
+        assert_eq!(split_limit("UPDATE t SET a = 1 LIMIT 5"), Ok(("UPDATE t SET a = 1", Some(5))));
+        assert_eq!(split_limit("UPDATE t SET a = 'x LIMIT y'"), Ok(("UPDATE t SET a = 'x LIMIT y'", None)));
+        assert!(split_limit("UPDATE t SET a = 1 LIMIT x").is_err());
+    }
+
+    #[test]
+    fn strip_trailing_semicolon_keeps_quoted_semicolons() {
+        assert_eq!(strip_trailing_semicolon("SHOW TABLES ;"), "SHOW TABLES");
+        assert_eq!(strip_trailing_semicolon("SELECT 'a;"), "SELECT 'a;");
+    }
+}
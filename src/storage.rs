@@ -1,8 +1,21 @@
+use std::collections::BTreeMap;
 use std::fs::{File, OpenOptions};
-use std::io::{Read, Seek, SeekFrom, Write};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::error::IsentaError;
+use crate::log::{self, Logger};
+use crate::wal::{WalRecord, WriteAheadLog};
 
 pub const PAGE_SIZE: usize = 4096;
 
+/// The bytes a page takes in the write-ahead log: its record header, then the page
+const WAL_RECORD_SIZE: u64 = 24 + PAGE_SIZE as u64;
+
+/// How many pages the write-ahead log holds before it is checkpointed, by default
+pub const DEFAULT_WAL_AUTOCHECKPOINT: u64 = 1000;
+
 pub struct Page {
     pub id: u64,
     pub data: [u8; PAGE_SIZE],
@@ -17,88 +30,445 @@ impl Page {
     }
 }
 
+/// Path that selects the in-memory backend instead of a file
+pub const MEMORY_PATH: &str = ":memory:";
+
+enum Backend {
+    File(File),
+    /// Pages kept in memory; discarded when the engine is dropped
+    Memory(Vec<[u8; PAGE_SIZE]>),
+}
+
+/// When written pages reach the database file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Durability {
+    /// Every page is written and flushed as soon as it changes. Slower, but
+    /// a crash loses at most the statement in progress.
+    #[default]
+    Sync,
+    /// Pages are kept in memory until `flush()` is called or the engine is
+    /// dropped. Much faster for bulk loads, but a crash loses every change
+    /// since the last flush and may leave a table only partially written.
+    Deferred,
+    /// Pages are appended to a write-ahead log next to the database file,
+    /// and reach the file itself when the log is checkpointed: on `flush()`,
+    /// once the log grows past `set_wal_autocheckpoint` pages or when
+    /// `set_wal_checkpoint_interval` has passed. Opening the file again after
+    /// a crash replays the log, so a crash loses at most the statement in
+    /// progress, as with `Sync`.
+    Wal,
+}
+
 pub struct StorageEngine {
-    file: File,
+    backend: Backend,
+    durability: Durability,
+    /// Pages written in deferred mode that haven't reached the file yet
+    pending: BTreeMap<u64, [u8; PAGE_SIZE]>,
+    /// Whether a transaction is open, holding every written page in
+    /// `pending` until it is committed
+    in_transaction: bool,
+    /// The log pages are appended to while durability is `Wal`
+    wal: Option<WriteAheadLog>,
+    /// Where the log is kept: the database path with `-wal` appended
+    wal_path: Option<String>,
+    /// The log size, in pages, that triggers a checkpoint
+    wal_autocheckpoint: Option<u64>,
+    /// The time since the last checkpoint that triggers one on the next write
+    wal_checkpoint_interval: Option<Duration>,
+    last_checkpoint: Instant,
+    logger: Logger,
 }
 
 impl StorageEngine {
-    /// Opens or creates the databse-file
-    pub fn new(path: &str) -> Self {
+    /// Opens or creates the databse-file, or an empty in-memory
+    /// database if `path` is `:memory:`. A write-ahead log left behind by a
+    /// crash is replayed into the file first.
+    pub fn new(path: &str) -> Result<Self, IsentaError> {
+        if path == MEMORY_PATH {
+            return Ok(Self::in_memory());
+        }
+
+        if Path::new(path).is_dir() {
+            return Err(IsentaError::Io(format!("cannot open '{}': is a directory", path)));
+        }
+
         let file = OpenOptions::new()
             .read(true)
             .write(true)
             .create(true)
+            .truncate(false)
             .open(path)
-            .expect("Could not open database file");
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::PermissionDenied => IsentaError::Io(format!("cannot open '{}': permission denied", path)),
+                _ => IsentaError::Io(format!("cannot open '{}': {}", path, e)),
+            })?;
+
+        let wal_path = format!("{}-wal", path);
+        let mut storage = Self {
+            backend: Backend::File(file),
+            durability: Durability::default(),
+            pending: BTreeMap::new(),
+            in_transaction: false,
+            wal: None,
+            wal_path: None,
+            wal_autocheckpoint: Some(DEFAULT_WAL_AUTOCHECKPOINT),
+            wal_checkpoint_interval: None,
+            last_checkpoint: Instant::now(),
+            logger: log::stderr(),
+        };
+        if Path::new(&wal_path).exists() {
+            storage.replay_wal(&wal_path)?;
+        }
+        storage.wal_path = Some(wal_path);
+        Ok(storage)
+    }
 
-        Self { file }
+    /// Writes the pages in the log at `wal_path` to the file and syncs it,
+    /// then empties the log. Replaying a log that was already checkpointed
+    /// writes the same pages again, so this is safe even if the process
+    /// that wrote it is still running.
+    fn replay_wal(&mut self, wal_path: &str) -> Result<(), IsentaError> {
+        let mut wal = WriteAheadLog::open(wal_path)?;
+        let records = wal.read_all();
+        wal.checkpoint(|| {
+            for record in &records {
+                let mut data = self.read_page(record.page_id)?.data;
+                let start = record.offset as usize;
+                match data.get_mut(start..start + record.data.len()) {
+                    Some(range) => range.copy_from_slice(&record.data),
+                    None => return Err(IsentaError::Corruption(format!("WAL record for page {} runs past the page", record.page_id))),
+                }
+                self.write_page_to_backend(record.page_id, &data)?;
+            }
+            self.sync_backend()
+        })
+    }
+
+    /// Creates an empty database that lives only in memory
+    pub fn in_memory() -> Self {
+        Self {
+            backend: Backend::Memory(Vec::new()),
+            durability: Durability::default(),
+            pending: BTreeMap::new(),
+            in_transaction: false,
+            wal: None,
+            wal_path: None,
+            wal_autocheckpoint: Some(DEFAULT_WAL_AUTOCHECKPOINT),
+            wal_checkpoint_interval: None,
+            last_checkpoint: Instant::now(),
+            logger: log::stderr(),
+        }
+    }
+
+    /// Sets where warnings are sent, such as a failed write of pending pages
+    /// when the engine is dropped.
+    pub fn set_logger(&mut self, logger: Logger) {
+        self.logger = logger;
+    }
+
+    /// Returns true if pages are kept in memory instead of a file
+    pub fn is_in_memory(&self) -> bool {
+        matches!(self.backend, Backend::Memory(_))
+    }
+
+    /// Returns the current durability setting
+    pub fn durability(&self) -> Durability {
+        self.durability
+    }
+
+    /// Changes when written pages reach the file. Switching to `Sync`, or
+    /// from one mode to another, first writes any pages still pending.
+    /// Switching to `Wal` opens the log, which is removed when switching away
+    /// from it; an in-memory database keeps no log.
+    pub fn set_durability(&mut self, durability: Durability) -> Result<(), IsentaError> {
+        if durability == Durability::Sync || durability != self.durability {
+            self.flush()?;
+        }
+        if durability == Durability::Wal {
+            if let (None, Some(wal_path)) = (&self.wal, &self.wal_path) {
+                self.wal = Some(WriteAheadLog::open(wal_path)?);
+                self.last_checkpoint = Instant::now();
+            }
+        } else {
+            self.close_wal();
+        }
+        self.durability = durability;
+        Ok(())
+    }
+
+    /// Sets how many pages the write-ahead log may hold before a write
+    /// checkpoints it; `None` leaves it to grow until `flush()`.
+    pub fn set_wal_autocheckpoint(&mut self, pages: Option<u64>) {
+        self.wal_autocheckpoint = pages;
+    }
+
+    /// The log size, in pages, that triggers a checkpoint; see
+    /// `set_wal_autocheckpoint`.
+    pub fn wal_autocheckpoint(&self) -> Option<u64> {
+        self.wal_autocheckpoint
+    }
+
+    /// Sets how long after a checkpoint the next write checkpoints the
+    /// write-ahead log again, however small it is; `None` turns this off.
+    pub fn set_wal_checkpoint_interval(&mut self, interval: Option<Duration>) {
+        self.wal_checkpoint_interval = interval;
+    }
+
+    /// The time between checkpoints; see `set_wal_checkpoint_interval`.
+    pub fn wal_checkpoint_interval(&self) -> Option<Duration> {
+        self.wal_checkpoint_interval
     }
 
     /// Reads one page with given ID
-    /// Returns a zero-filled page if the page doesn't exist yet
-    pub fn read_page(&mut self, page_id: u64) -> Page {
+    /// Returns a zero-filled page if the page doesn't exist yet. A page the
+    /// file ends partway through is zero-filled past the end. Failing to
+    /// read the file is an error, never a zero-filled page, since an empty
+    /// page read in place of a good one could be "repaired" over it.
+    pub fn read_page(&mut self, page_id: u64) -> Result<Page, IsentaError> {
         let mut page = Page::new(page_id);
 
+        if let Some(data) = self.pending.get(&page_id) {
+            page.data = *data;
+            return Ok(page);
+        }
+
+        let file = match &mut self.backend {
+            Backend::File(file) => file,
+            Backend::Memory(pages) => {
+                if let Some(data) = pages.get(page_id as usize) {
+                    page.data = *data;
+                }
+                return Ok(page);
+            }
+        };
+
         let offset = page_id * PAGE_SIZE as u64;
-        let file_len = self.file.metadata().unwrap().len();
-        
+        let file_len = file
+            .metadata()
+            .map(|metadata| metadata.len())
+            .map_err(|e| IsentaError::Io(format!("Failed to get file metadata: {}", e)))?;
+
         // If the page is beyond the file, return zero-filled page
         if offset >= file_len {
-            return page;
-        }
-
-        self.file
-            .seek(SeekFrom::Start(offset))
-            .expect("Seek failed");
-
-        // Read as much as we can, rest will be zeros
-        match self.file.read(&mut page.data) {
-            Ok(bytes_read) => {
-                // If we didn't read a full page, the rest is already zero-filled
-                if bytes_read < PAGE_SIZE {
-                    // Clear any remaining bytes (though they should already be zero)
-                    for i in bytes_read..PAGE_SIZE {
-                        page.data[i] = 0;
-                    }
-                }
-            }
-            Err(_) => {
-                // On error, return zero-filled page
-                // This handles cases where the file is truncated or corrupted
+            return Ok(page);
+        }
+
+        file.seek(SeekFrom::Start(offset))
+            .map_err(|e| IsentaError::Io(format!("Failed to seek to page {}: {}", page_id, e)))?;
+
+        // Read up to the end of the file; the rest of the page stays zero
+        let mut filled = 0;
+        while filled < PAGE_SIZE {
+            match file.read(&mut page.data[filled..]) {
+                Ok(0) => break,
+                Ok(bytes_read) => filled += bytes_read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(IsentaError::Io(format!("Failed to read page {}: {}", page_id, e))),
             }
         }
 
-        page
+        Ok(page)
     }
 
     /// Writes a Page
-    pub fn write_page(&mut self, page: &Page) {
-        let offset = page.id * PAGE_SIZE as u64;
+    pub fn write_page(&mut self, page: &Page) -> Result<(), IsentaError> {
+        if self.in_transaction || (self.durability != Durability::Sync && !self.is_in_memory()) {
+            self.pending.insert(page.id, page.data);
+            if !self.in_transaction {
+                self.log_pages(&[page.id])?;
+            }
+            return Ok(());
+        }
+        self.write_page_to_backend(page.id, &page.data)?;
+        self.flush_backend()
+    }
+
+    /// Appends the pending pages `page_ids` to the write-ahead log, if one
+    /// is open, and checkpoints it once it is due.
+    fn log_pages(&mut self, page_ids: &[u64]) -> Result<(), IsentaError> {
+        let wal = match &mut self.wal {
+            Some(wal) => wal,
+            None => return Ok(()),
+        };
+        for page_id in page_ids {
+            if let Some(data) = self.pending.get(page_id) {
+                wal.append(&WalRecord { page_id: *page_id, offset: 0, length: PAGE_SIZE as u64, data: data.to_vec() })?;
+            }
+        }
+
+        let too_large = match self.wal_autocheckpoint {
+            Some(pages) => wal.size()? >= pages.saturating_mul(WAL_RECORD_SIZE),
+            None => false,
+        };
+        let too_old = self.wal_checkpoint_interval.is_some_and(|interval| self.last_checkpoint.elapsed() >= interval);
+        if too_large || too_old {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Writes every pending page to the file, flushes it and waits until the
+    /// operating system has put the data on disk. While a transaction is
+    /// open nothing is written; its pages wait for the commit. This is also
+    /// the write-ahead log's checkpoint: the log is emptied only once the
+    /// file has been synced, so a crash before then replays it.
+    pub fn flush(&mut self) -> Result<(), IsentaError> {
+        if self.in_transaction {
+            return Ok(());
+        }
+        match self.wal.take() {
+            Some(mut wal) => {
+                let result = wal.checkpoint(|| self.write_pending());
+                self.wal = Some(wal);
+                self.last_checkpoint = Instant::now();
+                result
+            }
+            None => self.write_pending(),
+        }
+    }
+
+    fn write_pending(&mut self) -> Result<(), IsentaError> {
+        let pending = std::mem::take(&mut self.pending);
+        for (page_id, data) in &pending {
+            self.write_page_to_backend(*page_id, data)?;
+        }
+        self.flush_backend()?;
+        self.sync_backend()
+    }
+
+    fn sync_backend(&mut self) -> Result<(), IsentaError> {
+        match &mut self.backend {
+            Backend::File(file) => file.sync_data().map_err(|e| IsentaError::Io(format!("Failed to sync database file: {}", e))),
+            Backend::Memory(_) => Ok(()),
+        }
+    }
+
+    /// Stops logging pages and removes the log, which must have been
+    /// checkpointed.
+    fn close_wal(&mut self) {
+        if self.wal.take().is_some() {
+            if let Some(wal_path) = &self.wal_path {
+                if let Err(e) = std::fs::remove_file(wal_path) {
+                    (self.logger)(&format!("Failed to remove the write-ahead log: {}", e));
+                }
+            }
+        }
+    }
 
-        self.file
-            .seek(SeekFrom::Start(offset))
-            .expect("Seek failed");
+    /// Starts holding every written page in memory until
+    /// `commit_transaction` or `rollback_transaction`. Pages still pending
+    /// from deferred durability are written first, so a rollback discards
+    /// only the transaction's own pages.
+    pub fn begin_transaction(&mut self) -> Result<(), IsentaError> {
+        if self.in_transaction {
+            return Err(IsentaError::InvalidQuery("A transaction is already open".to_string()));
+        }
+        self.flush()?;
+        self.in_transaction = true;
+        Ok(())
+    }
 
-        self.file
-            .write_all(&page.data)
-            .expect("Failed to write page");
+    /// Ends the transaction, keeping its pages. They are written now unless
+    /// durability is deferred, in which case they wait for `flush()` as any
+    /// other deferred page does, or `Wal`, in which case they are logged.
+    pub fn commit_transaction(&mut self) -> Result<(), IsentaError> {
+        self.in_transaction = false;
+        if self.durability == Durability::Sync || self.is_in_memory() {
+            self.flush()?;
+        } else {
+            // Only the transaction's pages are pending; see begin_transaction
+            let page_ids: Vec<u64> = self.pending.keys().copied().collect();
+            self.log_pages(&page_ids)?;
+        }
+        Ok(())
+    }
 
-        self.file.flush().unwrap();
+    /// Ends the transaction, discarding every page it wrote.
+    pub fn rollback_transaction(&mut self) {
+        self.pending.clear();
+        self.in_transaction = false;
+    }
+
+    /// Whether a transaction is open; see `begin_transaction`.
+    pub fn in_transaction(&self) -> bool {
+        self.in_transaction
+    }
+
+    fn write_page_to_backend(&mut self, page_id: u64, data: &[u8; PAGE_SIZE]) -> Result<(), IsentaError> {
+        let file = match &mut self.backend {
+            Backend::File(file) => file,
+            Backend::Memory(pages) => {
+                let index = page_id as usize;
+                if index >= pages.len() {
+                    pages.resize(index + 1, [0; PAGE_SIZE]);
+                }
+                pages[index] = *data;
+                return Ok(());
+            }
+        };
+
+        let offset = page_id * PAGE_SIZE as u64;
+
+        file.seek(SeekFrom::Start(offset))
+            .map_err(|e| IsentaError::Io(format!("Failed to seek to page {}: {}", page_id, e)))?;
+
+        file.write_all(data)
+            .map_err(|e| IsentaError::Io(format!("Failed to write page {}: {}", page_id, e)))
+    }
+
+    fn flush_backend(&mut self) -> Result<(), IsentaError> {
+        match &mut self.backend {
+            Backend::File(file) => file.flush().map_err(|e| IsentaError::Io(format!("Failed to flush database file: {}", e))),
+            Backend::Memory(_) => Ok(()),
+        }
     }
 
     /// Creates a new Page a the end of file
-    pub fn allocate_page(&mut self) -> Page {
-        let file_len = self.file.metadata().unwrap().len();
-        let next_page_id = file_len / PAGE_SIZE as u64;
+    pub fn allocate_page(&mut self) -> Result<Page, IsentaError> {
+        let next_page_id = self.file_len()? / PAGE_SIZE as u64;
 
         let page = Page::new(next_page_id);
-        self.write_page(&page);
+        self.write_page(&page)?;
+
+        Ok(page)
+    }
 
-        page
+    /// Returns the size of the database in bytes, including pending pages
+    pub fn file_len(&self) -> Result<u64, IsentaError> {
+        let stored_len = match &self.backend {
+            Backend::File(file) => file
+                .metadata()
+                .map(|metadata| metadata.len())
+                .map_err(|e| IsentaError::Io(format!("Failed to get file metadata: {}", e)))?,
+            Backend::Memory(pages) => (pages.len() * PAGE_SIZE) as u64,
+        };
+        let pending_len = self
+            .pending
+            .keys()
+            .next_back()
+            .map_or(0, |last| (last + 1) * PAGE_SIZE as u64);
+        Ok(stored_len.max(pending_len))
     }
 
-    /// Get file metadata
-    pub fn file(&mut self) -> &mut File {
-        &mut self.file
+    /// Get the underlying file, if the database is file-backed
+    pub fn file(&mut self) -> Option<&mut File> {
+        match &mut self.backend {
+            Backend::File(file) => Some(file),
+            Backend::Memory(_) => None,
+        }
+    }
+}
+
+impl Drop for StorageEngine {
+    fn drop(&mut self) {
+        // A transaction that was never committed leaves the file as it was
+        if self.in_transaction {
+            self.rollback_transaction();
+        }
+        match self.flush() {
+            Ok(()) => self.close_wal(),
+            // The log is kept, to be replayed when the file is opened again
+            Err(e) => (self.logger)(&format!("Failed to write pending pages: {}", e)),
+        }
     }
 }
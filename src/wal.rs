@@ -1,6 +1,8 @@
 use std::fs::{OpenOptions, File};
 use std::io::{Write, Read};
 
+use crate::error::IsentaError;
+
 pub struct WalRecord {
     pub page_id: u64,
     pub offset: u64,
@@ -14,25 +16,56 @@ pub struct WriteAheadLog {
 
 impl WriteAheadLog {
     pub fn new(path: &str) -> Self {
+        Self::open(path).expect("Could not open WAL file")
+    }
+
+    /// Opens the log at `path`, creating it if it doesn't exist.
+    pub fn open(path: &str) -> Result<Self, IsentaError> {
         let file = OpenOptions::new()
             .append(true)
             .create(true)
             .read(true)
             .open(path)
-            .expect("Could not open WAL file");
+            .map_err(|e| IsentaError::Io(format!("cannot open WAL '{}': {}", path, e)))?;
 
-        Self { file }
+        Ok(Self { file })
     }
 
     /// Append a WAL record to the log
-    pub fn append(&mut self, record: &WalRecord) {
-        // Write page_id, offset, length and data
-        self.file.write_all(&record.page_id.to_le_bytes()).unwrap();
-        self.file.write_all(&record.offset.to_le_bytes()).unwrap();
-        self.file.write_all(&record.length.to_le_bytes()).unwrap();
-        self.file.write_all(&record.data).unwrap();
-
-        self.file.flush().unwrap();
+    pub fn append(&mut self, record: &WalRecord) -> Result<(), IsentaError> {
+        // Write page_id, offset, length and data in one go, so a failed
+        // write leaves at most a truncated record, which read_all skips
+        let mut bytes = Vec::with_capacity(24 + record.data.len());
+        bytes.extend_from_slice(&record.page_id.to_le_bytes());
+        bytes.extend_from_slice(&record.offset.to_le_bytes());
+        bytes.extend_from_slice(&record.length.to_le_bytes());
+        bytes.extend_from_slice(&record.data);
+
+        self.file
+            .write_all(&bytes)
+            .and_then(|()| self.file.flush())
+            .map_err(|e| IsentaError::Io(format!("Failed to append to WAL: {}", e)))
+    }
+
+    /// The size of the log in bytes.
+    pub fn size(&self) -> Result<u64, IsentaError> {
+        self.file
+            .metadata()
+            .map(|metadata| metadata.len())
+            .map_err(|e| IsentaError::Io(format!("Failed to get WAL size: {}", e)))
+    }
+
+    /// Empties the log once the pages its records describe are durable.
+    ///
+    /// `sync_database` must write those pages to the database file and sync
+    /// it. The log is only truncated after it succeeds: if it fails, or the
+    /// process dies first, the records are still there to be replayed.
+    pub fn checkpoint(&mut self, sync_database: impl FnOnce() -> Result<(), IsentaError>) -> Result<(), IsentaError> {
+        sync_database()?;
+        self.file
+            .set_len(0)
+            .and_then(|()| self.file.sync_data())
+            .map_err(|e| IsentaError::Io(format!("Failed to truncate WAL: {}", e)))
     }
 
     /// Read all WAL records from the log
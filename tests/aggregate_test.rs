@@ -0,0 +1,173 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::error::IsentaError;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{parse_to_ast, Aggregate, AggregateFunction, Command, Expr, Parser, SelectItem};
+use rust_dbms::storage::MEMORY_PATH;
+
+fn engine_with_sales() -> (QueryEngine, Parser) {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE sales (category TEXT, price INTEGER, qty INTEGER)", &mut engine, &parser);
+    for (category, price, qty) in [("books", "10", "2"), ("games", "30", "1"), ("books", "5", "4"), ("music", "NULL", "3"), ("games", "30", "2")] {
+        execute_line(&format!("INSERT INTO sales VALUES ('{}', {}, {})", category, price, qty), &mut engine, &parser);
+    }
+    (engine, parser)
+}
+
+/// Runs a SELECT and returns its single result row.
+fn select_one(engine: &QueryEngine, sql: &str) -> Result<Vec<Option<String>>, IsentaError> {
+    let Command::Select { table, columns, where_clause, .. } = parse_to_ast(sql) else {
+        panic!("not a SELECT: {}", sql);
+    };
+    let result = engine.execute_select(table, columns, where_clause)?;
+    assert_eq!(result.len(), 1);
+    let values = result.rows().next().unwrap().values().to_vec();
+    Ok(values)
+}
+
+fn values(values: &[&str]) -> Vec<Option<String>> {
+    values.iter().map(|value| (*value != "NULL").then(|| value.to_string())).collect()
+}
+
+#[test]
+fn test_parse_aggregates() {
+    let Command::Select { columns, .. } = parse_to_ast("SELECT COUNT(*), count(DISTINCT category), SUM(price * qty) FROM sales") else {
+        panic!("not a SELECT");
+    };
+    assert_eq!(
+        columns,
+        vec![
+            SelectItem::Aggregate(Aggregate { function: AggregateFunction::Count, distinct: false, argument: None }),
+            SelectItem::Aggregate(Aggregate {
+                function: AggregateFunction::Count,
+                distinct: true,
+                argument: Some(Expr::Column("category".to_string())),
+            }),
+            SelectItem::Aggregate(Aggregate {
+                function: AggregateFunction::Sum,
+                distinct: false,
+                argument: Some(Expr::Binary {
+                    left: Box::new(Expr::Column("price".to_string())),
+                    operator: '*',
+                    right: Box::new(Expr::Column("qty".to_string())),
+                }),
+            }),
+        ]
+    );
+}
+
+#[test]
+fn test_count_distinct_ignores_repeats() {
+    let (engine, _) = engine_with_sales();
+    assert_eq!(
+        select_one(&engine, "SELECT COUNT(*), COUNT(category), COUNT(DISTINCT category), COUNT(DISTINCT price) FROM sales").unwrap(),
+        values(&["5", "5", "3", "3"])
+    );
+}
+
+#[test]
+fn test_aggregates_over_expressions() {
+    let (engine, _) = engine_with_sales();
+    // The NULL price makes its row's product NULL, which is skipped
+    assert_eq!(
+        select_one(&engine, "SELECT SUM(price * qty), MAX(price * qty), SUM(DISTINCT price), AVG(qty) FROM sales").unwrap(),
+        values(&["130", "60", "45", "2.4"])
+    );
+    assert_eq!(
+        select_one(&engine, "SELECT SUM((price + 1) * qty - 1) FROM sales WHERE category = 'books'").unwrap(),
+        values(&["44"])
+    );
+}
+
+#[test]
+fn test_min_and_max_follow_the_column_type() {
+    let (engine, _) = engine_with_sales();
+    assert_eq!(
+        select_one(&engine, "SELECT MIN(price), MAX(price), MIN(category), MAX(category) FROM sales").unwrap(),
+        values(&["5", "30", "books", "music"])
+    );
+}
+
+#[test]
+fn test_aggregates_of_no_rows() {
+    let (engine, _) = engine_with_sales();
+    assert_eq!(
+        select_one(&engine, "SELECT COUNT(*), SUM(price), MIN(price) FROM sales WHERE category = 'films'").unwrap(),
+        values(&["0", "NULL", "NULL"])
+    );
+}
+
+#[test]
+fn test_aggregates_of_an_empty_table() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE empty (name TEXT, price INTEGER)", &mut engine, &parser);
+
+    for (sql, expected) in [
+        ("SELECT COUNT(*) FROM empty", "0"),
+        ("SELECT COUNT(price) FROM empty", "0"),
+        ("SELECT COUNT(DISTINCT name) FROM empty", "0"),
+        ("SELECT SUM(price) FROM empty", "NULL"),
+        ("SELECT AVG(price) FROM empty", "NULL"),
+        ("SELECT MIN(price) FROM empty", "NULL"),
+        ("SELECT MAX(name) FROM empty", "NULL"),
+    ] {
+        assert_eq!(select_one(&engine, sql).unwrap(), values(&[expected]), "{}", sql);
+    }
+
+    // The single result row is shown even though the table has no rows
+    let output = execute_line("SELECT COUNT(*), SUM(price) FROM empty", &mut engine, &parser);
+    assert_eq!(output.lines().collect::<Vec<_>>(), vec!["count | sum_price", "-----------------", "0 | NULL"]);
+}
+
+#[test]
+fn test_aggregate_errors() {
+    let (engine, _) = engine_with_sales();
+    assert!(matches!(select_one(&engine, "SELECT SUM(category) FROM sales"), Err(IsentaError::TypeMismatch(_))));
+    assert!(matches!(select_one(&engine, "SELECT COUNT(missing) FROM sales"), Err(IsentaError::ColumnNotFound { .. })));
+    assert!(matches!(select_one(&engine, "SELECT category, COUNT(*) FROM sales"), Err(IsentaError::InvalidQuery(_))));
+}
+
+#[test]
+fn test_aggregate_result_is_labelled() {
+    let (mut engine, parser) = engine_with_sales();
+    let output = execute_line("SELECT COUNT(DISTINCT category) FROM sales", &mut engine, &parser);
+    assert_eq!(output.lines().collect::<Vec<_>>(), vec!["count_distinct_category", "-----------------------", "3"]);
+
+    let Command::Select { table, columns, .. } = parse_to_ast("SELECT COUNT(*), SUM(price), AVG(price * qty), 1 FROM sales") else {
+        panic!("not a SELECT");
+    };
+    let result = engine.execute_select(table, columns, None).unwrap();
+    assert_eq!(result.columns(), ["count", "sum_price", "avg_price_qty", "1"]);
+}
+
+#[test]
+fn test_aliases_name_result_columns() {
+    let (mut engine, parser) = engine_with_sales();
+    let output = execute_line("SELECT COUNT(*) AS total, SUM(DISTINCT qty) as qty_sum FROM sales", &mut engine, &parser);
+    assert_eq!(output.lines().next(), Some("total | qty_sum"));
+
+    let output = execute_line("SELECT category AS kind, 'x' AS mark FROM sales WHERE qty = 1", &mut engine, &parser);
+    assert_eq!(output.lines().next(), Some("kind | mark"));
+
+    let output = execute_line("SELECT * AS everything FROM sales", &mut engine, &parser);
+    assert_eq!(output, "Error: '*' can't be given an alias");
+}
+
+#[test]
+fn test_distinct_and_text_min_max_compare_like_group_by_and_order_by() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE tags (cat TEXT, n INTEGER)", &mut engine, &parser);
+    execute_line("INSERT INTO tags VALUES ('a', 1), ('b', 01), ('a', 2), ('B', 1), ('c', 3), ('Zed', NULL)", &mut engine, &parser);
+
+    // 'b' and 'B' are one value, as they are one group, and 01 equals 1
+    assert_eq!(select_one(&engine, "SELECT COUNT(DISTINCT cat), COUNT(DISTINCT n) FROM tags").unwrap(), values(&["4", "3"]));
+    let output = execute_line("SELECT cat FROM tags GROUP BY cat", &mut engine, &parser);
+    assert_eq!(output.lines().count() - 2, 4);
+
+    // Text ignores case, so 'Zed' is the largest, as ORDER BY has it
+    assert_eq!(select_one(&engine, "SELECT MIN(cat), MAX(cat) FROM tags").unwrap(), values(&["a", "Zed"]));
+    let output = execute_line("SELECT cat FROM tags ORDER BY cat DESC LIMIT 1", &mut engine, &parser);
+    assert_eq!(output, "cat\n---\nZed");
+}
@@ -0,0 +1,79 @@
+use rust_dbms::audit::audit_path;
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+use rust_dbms::storage::MEMORY_PATH;
+
+mod common;
+
+fn temp_db(name: &str) -> String {
+    let path = common::temp_db(name);
+    let _ = std::fs::remove_file(audit_path(&path));
+    path
+}
+
+/// The fields of each line of the audit log, without the timestamp.
+fn entries(path: &str) -> Vec<Vec<String>> {
+    let log = std::fs::read_to_string(audit_path(path)).unwrap_or_default();
+    log.lines()
+        .map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            assert_eq!(fields.len(), 5, "{}", line);
+            assert!(fields[1].parse::<f64>().is_ok(), "{}", line);
+            [fields[0], fields[2], fields[3], fields[4]].map(String::from).to_vec()
+        })
+        .collect()
+}
+
+#[test]
+fn test_statements_are_recorded_once_audit_is_on() {
+    let path = temp_db("audit_pragma");
+    let parser = Parser::new();
+    let mut engine = QueryEngine::with_database(&path);
+    execute_line("CREATE TABLE t (id INTEGER)", &mut engine, &parser);
+    assert_eq!(execute_line("PRAGMA audit = on", &mut engine, &parser), "audit = on");
+    execute_line("INSERT INTO t VALUES (1)", &mut engine, &parser);
+    // Results are unchanged by auditing
+    assert_eq!(execute_line("SELECT id FROM t", &mut engine, &parser), "id\n--\n1");
+    execute_line("SELECT nothing FROM t\nWHERE id = 1", &mut engine, &parser);
+    assert_eq!(execute_line("PRAGMA audit = off", &mut engine, &parser), "audit = off");
+    execute_line("INSERT INTO t VALUES (2)", &mut engine, &parser);
+
+    let expected = [
+        ["1", &path, "ok", "PRAGMA audit = on"],
+        ["2", &path, "ok", "INSERT INTO t VALUES (1)"],
+        ["3", &path, "ok", "SELECT id FROM t"],
+        ["4", &path, "error", "SELECT nothing FROM t\\nWHERE id = 1"],
+        ["5", &path, "ok", "PRAGMA audit = off"],
+    ];
+    assert_eq!(entries(&path), expected.map(|entry| entry.map(String::from).to_vec()));
+
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(audit_path(&path));
+}
+
+#[test]
+fn test_sequence_numbers_continue_after_reopening() {
+    let path = temp_db("audit_sequence");
+    let parser = Parser::new();
+    for _ in 0..2 {
+        let mut engine = QueryEngine::with_database(&path);
+        engine.set_audit(true).unwrap();
+        assert!(engine.audit());
+        execute_line("SHOW TABLES", &mut engine, &parser);
+        execute_line("SHOW TABLES", &mut engine, &parser);
+    }
+    let sequence: Vec<String> = entries(&path).into_iter().map(|entry| entry[0].clone()).collect();
+    assert_eq!(sequence, ["1", "2", "3", "4"]);
+
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(audit_path(&path));
+}
+
+#[test]
+fn test_in_memory_databases_have_no_audit_log() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let output = execute_line("PRAGMA audit = on", &mut engine, &Parser::new());
+    assert_eq!(output, "Error: An in-memory database has no audit log");
+    assert!(!engine.audit());
+}
@@ -0,0 +1,72 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+
+mod common;
+
+use common::temp_db;
+
+#[test]
+fn test_blob_round_trips_through_the_file() {
+    let path = temp_db("blob_round_trip");
+    let parser = Parser::new();
+    {
+        let mut engine = QueryEngine::with_database(&path);
+        execute_line("CREATE TABLE files (id INTEGER, data BLOB)", &mut engine, &parser);
+        assert_eq!(
+            execute_line("INSERT INTO files VALUES (1, X'48656C6C6F')", &mut engine, &parser),
+            "Inserted 1 row into 'files'"
+        );
+        // Lowercase digits and prefix are accepted, and shown uppercase
+        execute_line("INSERT INTO files VALUES (2, x'00ff')", &mut engine, &parser);
+        execute_line("INSERT INTO files VALUES (3, X'')", &mut engine, &parser);
+        execute_line("INSERT INTO files VALUES (4, NULL)", &mut engine, &parser);
+    }
+
+    let mut engine = QueryEngine::with_database(&path);
+    let output = execute_line("SELECT id, data FROM files", &mut engine, &parser);
+    assert_eq!(output, "id | data\n---------\n1 | X'48656C6C6F'\n2 | X'00FF'\n3 | X''\n4 | NULL");
+}
+
+#[test]
+fn test_invalid_hex_literals_are_rejected() {
+    let path = temp_db("blob_invalid");
+    let parser = Parser::new();
+    let mut engine = QueryEngine::with_database(&path);
+    execute_line("CREATE TABLE files (id INTEGER, data BLOB)", &mut engine, &parser);
+
+    let output = execute_line("INSERT INTO files VALUES (1, X'ABC')", &mut engine, &parser);
+    assert_eq!(output, "Error: Invalid value for column 'data': X'ABC' is not a valid BLOB: a hex literal needs two digits per byte");
+    let output = execute_line("INSERT INTO files VALUES (1, X'ZZ')", &mut engine, &parser);
+    assert_eq!(output, "Error: Invalid value for column 'data': X'ZZ' is not a valid BLOB: only the hex digits 0-9 and A-F are allowed");
+    let output = execute_line("INSERT INTO files VALUES (1, 'hello')", &mut engine, &parser);
+    assert_eq!(output, "Error: Invalid value for column 'data': 'hello' is not a valid BLOB; write it as a hex literal such as X'48656C6C6F'");
+    assert_eq!(execute_line("SELECT * FROM files", &mut engine, &parser), "No rows found in 'files'");
+}
+
+#[test]
+fn test_blobs_compare_only_for_equality() {
+    let path = temp_db("blob_compare");
+    let parser = Parser::new();
+    let mut engine = QueryEngine::with_database(&path);
+    execute_line("CREATE TABLE files (id INTEGER, data BLOB)", &mut engine, &parser);
+    execute_line("INSERT INTO files VALUES (1, X'0102')", &mut engine, &parser);
+    execute_line("INSERT INTO files VALUES (2, X'0A0B')", &mut engine, &parser);
+    execute_line("INSERT INTO files VALUES (3, NULL)", &mut engine, &parser);
+
+    assert_eq!(execute_line("SELECT id FROM files WHERE data = x'0a0b'", &mut engine, &parser), "id\n--\n2");
+    assert_eq!(execute_line("SELECT id FROM files WHERE data != X'0A0B'", &mut engine, &parser), "id\n--\n1");
+    assert_eq!(execute_line("SELECT id FROM files WHERE data IS NULL", &mut engine, &parser), "id\n--\n3");
+    assert_eq!(
+        execute_line("SELECT id FROM files WHERE data > X'00'", &mut engine, &parser),
+        "Error: BLOB column 'data' can only be compared with =, != or IS [NOT] NULL"
+    );
+    assert_eq!(
+        execute_line("SELECT id FROM files WHERE data LIKE 'X%'", &mut engine, &parser),
+        "Error: BLOB column 'data' can only be compared with =, != or IS [NOT] NULL"
+    );
+    assert!(execute_line("SELECT id FROM files WHERE data = 'abc'", &mut engine, &parser).starts_with("Error:"));
+
+    assert_eq!(execute_line("UPDATE files SET data = X'FF' WHERE id = 1", &mut engine, &parser), "Updated 1 rows in 'files'");
+    assert_eq!(execute_line("SELECT id FROM files WHERE data = X'FF'", &mut engine, &parser), "id\n--\n1");
+}
@@ -0,0 +1,29 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::error::IsentaError;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{Parser, SelectItem};
+use rust_dbms::storage::MEMORY_PATH;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+#[test]
+fn test_cancel_flag_stops_select() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    let cancel = Arc::new(AtomicBool::new(false));
+    engine.set_cancel_flag(Arc::clone(&cancel));
+
+    execute_line("CREATE TABLE t (id INTEGER)", &mut engine, &parser);
+    for id in 1..=3 {
+        execute_line(&format!("INSERT INTO t VALUES ({})", id), &mut engine, &parser);
+    }
+
+    cancel.store(true, Ordering::Relaxed);
+    let err = engine.execute_select("t".to_string(), vec![SelectItem::Wildcard], None).unwrap_err();
+    assert_eq!(err, IsentaError::Cancelled);
+    assert_eq!(execute_line("SELECT id FROM t WHERE id > 1", &mut engine, &parser), "Query cancelled");
+
+    // Once the flag is reset, queries run again
+    cancel.store(false, Ordering::Relaxed);
+    assert_eq!(execute_line("SELECT id FROM t WHERE id > 2", &mut engine, &parser), "id\n--\n3");
+}
@@ -0,0 +1,70 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+use rust_dbms::storage::MEMORY_PATH;
+
+fn engine_with_codes() -> (QueryEngine, Parser) {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE codes (id INTEGER, code TEXT, price FLOAT)", &mut engine, &parser);
+    execute_line("INSERT INTO codes VALUES (1, '10', 2.5)", &mut engine, &parser);
+    execute_line("INSERT INTO codes VALUES (2, '9', 10.75)", &mut engine, &parser);
+    execute_line("INSERT INTO codes VALUES (3, 'x1', 9)", &mut engine, &parser);
+    execute_line("INSERT INTO codes VALUES (4, NULL, 100)", &mut engine, &parser);
+    (engine, parser)
+}
+
+#[test]
+fn test_cast_compares_text_as_numbers_in_where() {
+    let (mut engine, parser) = engine_with_codes();
+    // As text, '10' sorts before '9'
+    let output = execute_line("SELECT id FROM codes WHERE code > '9'", &mut engine, &parser);
+    assert_eq!(output, "id\n--\n3");
+    let output = execute_line("SELECT id FROM codes WHERE CAST(code AS INTEGER) > 9", &mut engine, &parser);
+    assert_eq!(output, "id\n--\n1");
+    let output = execute_line("SELECT id FROM codes WHERE cast(code as float) <= 9.5", &mut engine, &parser);
+    assert_eq!(output, "id\n--\n2");
+    // And a number can be compared as text
+    let output = execute_line("SELECT id FROM codes WHERE CAST(id AS TEXT) LIKE '4%'", &mut engine, &parser);
+    assert_eq!(output, "id\n--\n4");
+}
+
+#[test]
+fn test_cast_in_the_projection_and_order_by() {
+    let (mut engine, parser) = engine_with_codes();
+    let output = execute_line(
+        "SELECT id, CAST(code AS INTEGER) AS n, CAST(price AS INTEGER) AS whole, CAST(code AS FLOAT) AS f FROM codes",
+        &mut engine,
+        &parser,
+    );
+    // Text that isn't a number, and NULL, cast to NULL; fractions are dropped
+    assert_eq!(
+        output,
+        "id | n | whole | f\n------------------\n1 | 10 | 2 | 10\n2 | 9 | 10 | 9\n3 | NULL | 9 | NULL\n4 | NULL | 100 | NULL"
+    );
+
+    let output = execute_line("SELECT code FROM codes WHERE code IS NOT NULL ORDER BY CAST(code AS INTEGER)", &mut engine, &parser);
+    assert_eq!(output, "code\n----\nx1\n9\n10");
+
+    // The cast column keeps the type it was cast to
+    execute_line("CREATE TABLE numbers AS SELECT CAST(code AS INTEGER) AS n FROM codes", &mut engine, &parser);
+    assert_eq!(engine.get_table_schema("numbers").unwrap().columns[0].data_type, "INTEGER");
+}
+
+#[test]
+fn test_float_columns_compare_as_numbers() {
+    let (mut engine, parser) = engine_with_codes();
+    let output = execute_line("SELECT id FROM codes WHERE price > 9", &mut engine, &parser);
+    assert_eq!(output, "id\n--\n2\n4");
+}
+
+#[test]
+fn test_invalid_casts_are_errors() {
+    let (mut engine, parser) = engine_with_codes();
+    let output = execute_line("SELECT id FROM codes WHERE CAST(code AS BLOB) = 1", &mut engine, &parser);
+    assert_eq!(output, "Error: Cannot CAST to 'BLOB'; expected INTEGER, FLOAT or TEXT");
+    let output = execute_line("SELECT CAST(missing AS INTEGER) FROM codes", &mut engine, &parser);
+    assert_eq!(output, "Error: Column 'missing' not found in table 'codes'");
+    let output = execute_line("SELECT id FROM codes WHERE CAST(code AS INTEGER) > 'abc'", &mut engine, &parser);
+    assert!(output.starts_with("Error:"), "{}", output);
+}
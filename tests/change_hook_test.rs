@@ -0,0 +1,42 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rust_dbms::engine::{ChangeEvent, ChangeKind, QueryEngine};
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+use rust_dbms::storage::MEMORY_PATH;
+
+fn event(table: &str, kind: ChangeKind) -> ChangeEvent {
+    ChangeEvent { table: table.to_string(), kind }
+}
+
+#[test]
+fn test_on_change_reports_each_persisted_mutation() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let recorded = Rc::clone(&events);
+    engine.set_on_change(Box::new(move |event| recorded.borrow_mut().push(event)));
+
+    execute_line("CREATE TABLE items (id INTEGER, name TEXT)", &mut engine, &parser);
+    execute_line("INSERT INTO items VALUES (1, 'a')", &mut engine, &parser);
+    execute_line("UPDATE items SET name = 'b' WHERE id = 1", &mut engine, &parser);
+    execute_line("TRUNCATE TABLE items", &mut engine, &parser);
+
+    // Reads, failed statements and updates that change nothing are not reported
+    execute_line("SELECT * FROM items", &mut engine, &parser);
+    execute_line("INSERT INTO items VALUES ('x', 'a')", &mut engine, &parser);
+    execute_line("UPDATE items SET name = 'c' WHERE id = 99", &mut engine, &parser);
+
+    let table = engine.get_all_tables()[0].name.clone();
+    assert_eq!(
+        *events.borrow(),
+        vec![
+            event(&table, ChangeKind::Ddl),
+            event(&table, ChangeKind::Insert),
+            event(&table, ChangeKind::Update),
+            event(&table, ChangeKind::Delete),
+        ]
+    );
+}
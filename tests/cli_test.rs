@@ -1,52 +1,62 @@
-use assert_cmd::Command;
+use assert_cmd::cargo::cargo_bin_cmd;
 use predicates::prelude::*;
 
+mod common;
+
+use common::temp_db;
+
 #[test]
 fn test_update_statement() {
-    let mut cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("CREATE TABLE users (id INT, name TEXT)");
+    let db = temp_db("update_statement");
+    let mut cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("CREATE TABLE users (id INT, name TEXT)");
     cmd.assert().success();
 
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("INSERT INTO users VALUES (1, 'Alice')");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("INSERT INTO users VALUES (1, 'Alice')");
     cmd.assert().success();
 
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("UPDATE users SET name = 'Alicia' WHERE id = 1");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("UPDATE users SET name = 'Alicia' WHERE id = 1");
     cmd.assert()
         .success()
         .stdout(predicate::str::contains("Updated 1 rows in 'users'"));
 
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("SELECT name FROM users WHERE id = 1");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("SELECT name FROM users WHERE id = 1");
     cmd.assert()
         .success()
         .stdout(predicate::str::contains("Alicia"));
+
+    let _ = std::fs::remove_file(&db);
 }
 
 #[test]
 fn test_select_with_where_clause() {
-    let mut cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("CREATE TABLE users (id INT, name TEXT)");
+    let db = temp_db("select_with_where_clause");
+    let mut cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("CREATE TABLE users (id INT, name TEXT)");
     cmd.assert().success();
 
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("INSERT INTO users VALUES (1, 'Alice')");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("INSERT INTO users VALUES (1, 'Alice')");
     cmd.assert().success();
 
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("INSERT INTO users VALUES (2, 'Bob')");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("INSERT INTO users VALUES (2, 'Bob')");
     cmd.assert().success();
 
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("SELECT * FROM users WHERE name = 'Alice'");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("SELECT * FROM users WHERE name = 'Alice'");
     cmd.assert()
         .success()
         .stdout(predicate::str::contains("1 | Alice"));
 
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("SELECT name FROM users WHERE id = 2");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("SELECT name FROM users WHERE id = 2");
     cmd.assert()
         .success()
         .stdout(predicate::str::contains("Bob"));
+
+    let _ = std::fs::remove_file(&db);
 }
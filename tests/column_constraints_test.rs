@@ -0,0 +1,94 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{Column, Command, Parser};
+use rust_dbms::storage::MEMORY_PATH;
+
+mod common;
+
+use common::temp_db;
+
+fn engine_with_table(path: &str, parser: &Parser) -> QueryEngine {
+    let mut engine = QueryEngine::with_database(path);
+    let output = execute_line(
+        "CREATE TABLE items (id INTEGER NOT NULL, status TEXT NOT NULL DEFAULT 'new', qty INTEGER DEFAULT 1, note TEXT)",
+        &mut engine,
+        parser,
+    );
+    assert_eq!(output, "Table 'items' created successfully");
+    engine
+}
+
+#[test]
+fn test_parse_not_null_and_default() {
+    let parser = Parser::new();
+    let mut status = Column::new("status", "TEXT");
+    status.not_null = true;
+    status.default = Some("on hold".to_string());
+    let mut qty = Column::new("qty", "INTEGER");
+    qty.default = Some("0".to_string());
+
+    assert_eq!(
+        parser.parse("CREATE TABLE t (status TEXT NOT NULL DEFAULT 'on hold', qty INTEGER DEFAULT 0 NULL, note TEXT DEFAULT NULL)"),
+        Command::CreateTable { name: "t".to_string(), columns: vec![status, qty, Column::new("note", "TEXT")] }
+    );
+}
+
+#[test]
+fn test_omitted_columns_take_default_then_null() {
+    let parser = Parser::new();
+    let mut engine = engine_with_table(MEMORY_PATH, &parser);
+
+    // NOT NULL with a default and nullable columns may be omitted
+    assert_eq!(execute_line("INSERT INTO items (id) VALUES (1)", &mut engine, &parser), "Inserted 1 row into 'items'");
+    // An explicit value overrides the default
+    execute_line("INSERT INTO items (qty, id, status) VALUES (5, 2, 'sent')", &mut engine, &parser);
+
+    let output = execute_line("SELECT * FROM items", &mut engine, &parser);
+    assert_eq!(output, "id | status | qty | note\n------------------------\n1 | new | 1 | NULL\n2 | sent | 5 | NULL");
+}
+
+#[test]
+fn test_not_null_without_default_is_required() {
+    let parser = Parser::new();
+    let mut engine = engine_with_table(MEMORY_PATH, &parser);
+
+    let output = execute_line("INSERT INTO items (status) VALUES ('new')", &mut engine, &parser);
+    assert_eq!(output, "Error: Column 'id' is NOT NULL and has no default, so it must be given a value");
+
+    let output = execute_line("INSERT INTO items (id, status) VALUES (1, NULL)", &mut engine, &parser);
+    assert_eq!(output, "Error: Column 'status' is NOT NULL and can't be set to NULL");
+
+    let output = execute_line("INSERT INTO items VALUES (NULL, 'new', 1, NULL)", &mut engine, &parser);
+    assert_eq!(output, "Error: Column 'id' is NOT NULL and can't be set to NULL");
+
+    execute_line("INSERT INTO items (id) VALUES (1)", &mut engine, &parser);
+    let output = execute_line("UPDATE items SET status = NULL", &mut engine, &parser);
+    assert_eq!(output, "Error: Column 'status' is NOT NULL and can't be set to NULL");
+
+    let output = execute_line("CREATE TABLE bad (n INTEGER DEFAULT 'x')", &mut engine, &parser);
+    assert!(output.starts_with("Error: Invalid default for column 'n'"), "{}", output);
+}
+
+#[test]
+fn test_constraints_are_stored_with_the_schema() {
+    let path = temp_db("column_constraints");
+    let parser = Parser::new();
+    drop(engine_with_table(&path, &parser));
+
+    let mut engine = QueryEngine::with_database(&path);
+    let output = execute_line("INSPECT items", &mut engine, &parser);
+    assert!(output.contains("id                   | INTEGER NOT NULL\n"), "{}", output);
+    assert!(output.contains("status               | TEXT NOT NULL DEFAULT 'new'\n"), "{}", output);
+    assert!(output.contains("qty                  | INTEGER DEFAULT 1\n"), "{}", output);
+    assert!(output.contains("note                 | TEXT\n"), "{}", output);
+
+    execute_line("INSERT INTO items (id) VALUES (7)", &mut engine, &parser);
+    execute_line("INSERT INTO items (id) VALUES (8)", &mut engine, &parser);
+    drop(engine);
+
+    let mut engine = QueryEngine::with_database(&path);
+    let output = execute_line("SELECT id, status, qty FROM items", &mut engine, &parser);
+    assert_eq!(output, "id | status | qty\n-----------------\n7 | new | 1\n8 | new | 1");
+
+    let _ = std::fs::remove_file(&path);
+}
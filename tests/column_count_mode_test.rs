@@ -0,0 +1,112 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use rust_dbms::engine::{ColumnCountMode, QueryEngine};
+use rust_dbms::execute_line;
+use rust_dbms::log::Logger;
+use rust_dbms::parser::Parser;
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+use common::temp_db;
+
+/// Creates a two-column table whose second row only stores its first value.
+fn db_with_short_row(name: &str) -> String {
+    let path = temp_db(name);
+    {
+        let mut engine = QueryEngine::with_database(&path);
+        let parser = Parser::new();
+        execute_line("CREATE TABLE drift (id INTEGER, note TEXT)", &mut engine, &parser);
+        execute_line("INSERT INTO drift VALUES (1, 'kept')", &mut engine, &parser);
+        execute_line("INSERT INTO drift VALUES (987654321, NULL)", &mut engine, &parser);
+    }
+
+    // Shrink the stored length of the second row so its trailing NULL is cut off:
+    // row length 18 = rowid + INT tag + 8 bytes + NULL tag
+    let mut bytes = std::fs::read(&path).unwrap();
+    let mut pattern = vec![18u8, 0];
+    pattern.extend_from_slice(&2u64.to_le_bytes());
+    pattern.push(1);
+    pattern.extend_from_slice(&987654321i64.to_le_bytes());
+    pattern.push(0);
+    let pos = bytes.windows(pattern.len()).position(|w| w == pattern.as_slice()).unwrap();
+    bytes[pos] = 17;
+    std::fs::write(&path, bytes).unwrap();
+
+    path
+}
+
+#[test]
+fn test_strict_mode_rejects_short_rows() {
+    let path = db_with_short_row("column_count_strict");
+    let mut engine = QueryEngine::with_database(&path);
+    let parser = Parser::new();
+
+    let output = execute_line("SELECT * FROM drift", &mut engine, &parser);
+    assert!(output.starts_with("Error: Row 2 of table"), "{}", output);
+    assert!(output.contains("has 1 values but the table has 2 columns"), "{}", output);
+
+    let output = execute_line("UPDATE drift SET note = 'x'", &mut engine, &parser);
+    assert!(output.starts_with("Error: Row 2 of table"), "{}", output);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_lenient_mode_pads_short_rows() {
+    let path = db_with_short_row("column_count_lenient");
+    let mut engine = QueryEngine::with_database(&path);
+    engine.set_column_count_mode(ColumnCountMode::Lenient);
+    let parser = Parser::new();
+
+    let output = execute_line("SELECT id FROM drift WHERE note IS NULL", &mut engine, &parser);
+    assert_eq!(output, "id\n--\n987654321");
+
+    // Rewriting the table stores the padded row, so strict mode accepts it afterwards
+    execute_line("INSERT INTO drift VALUES (3, 'new')", &mut engine, &parser);
+    drop(engine);
+    let mut engine = QueryEngine::with_database(&path);
+    let output = execute_line("SELECT id FROM drift", &mut engine, &parser);
+    assert_eq!(output, "id\n--\n1\n987654321\n3");
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_lenient_warnings_go_to_the_logger_unless_quiet() {
+    let path = db_with_short_row("column_count_logger");
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&messages);
+    let logger: Logger = Arc::new(move |message: &str| sink.lock().unwrap().push(message.to_string()));
+
+    let mut engine = QueryEngine::with_database(&path);
+    engine.set_column_count_mode(ColumnCountMode::Lenient);
+    engine.set_logger(logger);
+    let parser = Parser::new();
+    execute_line("SELECT id FROM drift", &mut engine, &parser);
+    assert_eq!(*messages.lock().unwrap(), vec!["Row 2 of table 'drift' has 1 values; padded to 2 columns".to_string()]);
+
+    engine.set_quiet(true);
+    execute_line("SELECT id FROM drift", &mut engine, &parser);
+    assert_eq!(messages.lock().unwrap().len(), 1);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_cli_lenient_flag() {
+    let path = db_with_short_row("column_count_cli");
+
+    let mut cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&path).arg("SELECT id FROM drift");
+    cmd.assert().success().stdout(predicate::str::contains("Error: Row 2 of table"));
+
+    let mut cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&path).arg("--lenient").arg("SELECT id FROM drift");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("987654321"))
+        .stderr(predicate::str::contains("padded to 2 columns"));
+
+    let _ = std::fs::remove_file(&path);
+}
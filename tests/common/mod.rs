@@ -0,0 +1,10 @@
+//! Helpers shared by the integration tests.
+
+/// A path in the temporary directory for a test database called `name`,
+/// unique to this test run, with any file left there by an earlier run
+/// removed.
+pub fn temp_db(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("isenta_{}_{}.db", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path.to_string_lossy().into_owned()
+}
@@ -0,0 +1,59 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+
+mod common;
+
+use common::temp_db;
+
+#[test]
+fn test_stale_write_is_refused_instead_of_losing_rows() {
+    let path = temp_db("concurrent_insert");
+    let parser = Parser::new();
+
+    let mut first = QueryEngine::with_database(&path);
+    execute_line("CREATE TABLE t (id INTEGER, name TEXT)", &mut first, &parser);
+
+    // Both sessions load the table before either of them writes
+    let mut second = QueryEngine::with_database(&path);
+    assert_eq!(execute_line("INSERT INTO t VALUES (1, 'first')", &mut first, &parser), "Inserted 1 row into 't'");
+
+    let output = execute_line("INSERT INTO t VALUES (2, 'second')", &mut second, &parser);
+    assert_eq!(output, "Error: table 't' changed concurrently, retry");
+
+    // The failed session has reloaded the table, so a retry keeps both rows
+    assert_eq!(execute_line("INSERT INTO t VALUES (2, 'second')", &mut second, &parser), "Inserted 1 row into 't'");
+
+    let mut reader = QueryEngine::with_database(&path);
+    let output = execute_line("SELECT id, name FROM t", &mut reader, &parser);
+    assert_eq!(output, "id | name\n---------\n1 | first\n2 | second");
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_stale_update_and_truncate_are_refused() {
+    let path = temp_db("concurrent_update");
+    let parser = Parser::new();
+
+    let mut first = QueryEngine::with_database(&path);
+    execute_line("CREATE TABLE t (id INTEGER, name TEXT)", &mut first, &parser);
+    execute_line("INSERT INTO t VALUES (1, 'a')", &mut first, &parser);
+
+    let mut second = QueryEngine::with_database(&path);
+    execute_line("INSERT INTO t VALUES (2, 'b')", &mut first, &parser);
+
+    let output = execute_line("UPDATE t SET name = 'z'", &mut second, &parser);
+    assert_eq!(output, "Error: table 't' changed concurrently, retry");
+
+    execute_line("INSERT INTO t VALUES (3, 'c')", &mut first, &parser);
+    let output = execute_line("TRUNCATE TABLE t", &mut second, &parser);
+    assert_eq!(output, "Error: table 't' changed concurrently, retry");
+
+    // A session's own writes never conflict with each other
+    execute_line("UPDATE t SET name = 'y' WHERE id = 1", &mut first, &parser);
+    let output = execute_line("SELECT name FROM t", &mut first, &parser);
+    assert_eq!(output, "name\n----\ny\nb\nc");
+
+    let _ = std::fs::remove_file(&path);
+}
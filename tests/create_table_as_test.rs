@@ -0,0 +1,79 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+
+mod common;
+
+use common::temp_db;
+
+fn engine_with_users(path: &str) -> (QueryEngine, Parser) {
+    let mut engine = QueryEngine::with_database(path);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE users (id INTEGER NOT NULL, name VARCHAR(20), score FLOAT)", &mut engine, &parser);
+    execute_line("INSERT INTO users VALUES (1, 'ann', 2.5)", &mut engine, &parser);
+    execute_line("INSERT INTO users VALUES (2, 'bob', 4)", &mut engine, &parser);
+    execute_line("INSERT INTO users VALUES (3, 'cat', NULL)", &mut engine, &parser);
+    (engine, parser)
+}
+
+#[test]
+fn test_cloned_table_is_independent_of_its_source() {
+    let path = temp_db("create_table_as_clone");
+    {
+        let (mut engine, parser) = engine_with_users(&path);
+        let output = execute_line("CREATE TABLE backup AS SELECT * FROM users", &mut engine, &parser);
+        assert_eq!(output, "Table 'backup' created with 3 rows");
+
+        execute_line("UPDATE users SET name = 'zed' WHERE id = 1", &mut engine, &parser);
+        execute_line("INSERT INTO backup VALUES (4, 'dan', 1)", &mut engine, &parser);
+        engine.close().unwrap();
+    }
+
+    let mut engine = QueryEngine::with_database(&path);
+    let parser = Parser::new();
+    let output = execute_line("SELECT * FROM backup", &mut engine, &parser);
+    assert_eq!(output, "id | name | score\n-----------------\n1 | ann | 2.5\n2 | bob | 4\n3 | cat | NULL\n4 | dan | 1");
+    let output = execute_line("SELECT name FROM users", &mut engine, &parser);
+    assert_eq!(output, "name\n----\nzed\nbob\ncat");
+
+    // The column types are copied, but not the constraints
+    let backup = engine.get_table_schema("backup").unwrap();
+    let types: Vec<(&str, &str)> = backup.columns.iter().map(|c| (c.name.as_str(), c.data_type.as_str())).collect();
+    assert_eq!(types, vec![("id", "INTEGER"), ("name", "VARCHAR(20)"), ("score", "FLOAT")]);
+    assert!(!backup.columns[0].not_null);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_columns_come_from_the_projection() {
+    let path = temp_db("create_table_as_projection");
+    let (mut engine, parser) = engine_with_users(&path);
+    let output = execute_line(
+        "CREATE TABLE top AS SELECT name AS who, 'x' AS tag FROM users WHERE score > 3 ORDER BY name",
+        &mut engine,
+        &parser,
+    );
+    assert_eq!(output, "Table 'top' created with 1 rows");
+    assert_eq!(execute_line("SELECT * FROM top", &mut engine, &parser), "who | tag\n---------\nbob | x");
+
+    execute_line("CREATE TABLE stats AS SELECT COUNT(*) AS n, AVG(score), MAX(id) FROM users", &mut engine, &parser);
+    let stats = engine.get_table_schema("stats").unwrap();
+    let types: Vec<(&str, &str)> = stats.columns.iter().map(|c| (c.name.as_str(), c.data_type.as_str())).collect();
+    assert_eq!(types, vec![("n", "INTEGER"), ("avg_score", "FLOAT"), ("max_id", "INTEGER")]);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_create_table_as_rejects_existing_names() {
+    let path = temp_db("create_table_as_errors");
+    let (mut engine, parser) = engine_with_users(&path);
+    let output = execute_line("CREATE TABLE USERS AS SELECT * FROM users", &mut engine, &parser);
+    assert!(output.starts_with("Error:") && output.contains("already exists"), "{}", output);
+
+    let output = execute_line("CREATE TABLE twice AS SELECT id, id FROM users", &mut engine, &parser);
+    assert!(output.starts_with("Error: Column 'id' is selected more than once"), "{}", output);
+    let output = execute_line("CREATE TABLE copy AS SELECT * FROM nowhere", &mut engine, &parser);
+    assert!(output.starts_with("Error:"), "{}", output);
+    assert_eq!(execute_line("SHOW TABLES", &mut engine, &parser), "Tables:\n- users");
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,48 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{Column, Command, Parser};
+use rust_dbms::storage::MEMORY_PATH;
+
+fn column(name: &str, data_type: &str) -> Column {
+    Column::new(name, data_type)
+}
+
+#[test]
+fn test_commas_inside_quotes_and_parentheses_do_not_split_columns() {
+    let parser = Parser::new();
+    let command = parser.parse("CREATE TABLE t (id INTEGER, note TEXT DEFAULT 'a, b', CHECK (id, note), score FLOAT)");
+
+    let mut note = column("note", "TEXT");
+    note.default = Some("a, b".to_string());
+    assert_eq!(
+        command,
+        Command::CreateTable {
+            name: "t".to_string(),
+            columns: vec![column("id", "INTEGER"), note, column("score", "FLOAT")],
+        }
+    );
+}
+
+#[test]
+fn test_plain_columns_still_split_on_commas() {
+    let parser = Parser::new();
+    let command = parser.parse("CREATE TABLE t (id INTEGER,name TEXT , flag)");
+
+    assert_eq!(
+        command,
+        Command::CreateTable {
+            name: "t".to_string(),
+            columns: vec![column("id", "INTEGER"), column("name", "TEXT"), column("flag", "TEXT")],
+        }
+    );
+}
+
+#[test]
+fn test_tables_need_at_least_one_column() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    for sql in ["CREATE TABLE t ()", "CREATE TABLE t", "CREATE TABLE t (PRIMARY KEY (id))"] {
+        assert_eq!(execute_line(sql, &mut engine, &parser), "Error: table must have at least one column", "{}", sql);
+    }
+    assert!(engine.get_all_tables().is_empty());
+}
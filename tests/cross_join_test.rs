@@ -0,0 +1,102 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{parse_to_ast, Command, Parser};
+use rust_dbms::storage::MEMORY_PATH;
+
+fn engine_with_tables() -> (QueryEngine, Parser) {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    for sql in [
+        "CREATE TABLE authors (id INTEGER, name TEXT)",
+        "INSERT INTO authors VALUES (1, 'Ann')",
+        "INSERT INTO authors VALUES (2, 'Bo')",
+        "CREATE TABLE books (id INTEGER, author_id INTEGER, title TEXT)",
+        "INSERT INTO books VALUES (10, 1, 'Alpha')",
+        "INSERT INTO books VALUES (11, 2, 'Beta')",
+        "INSERT INTO books VALUES (12, 1, 'Gamma')",
+    ] {
+        execute_line(sql, &mut engine, &parser);
+    }
+    (engine, parser)
+}
+
+#[test]
+fn test_parse_from_list() {
+    match parse_to_ast("SELECT * FROM a, b WHERE a.id = b.a_id") {
+        Command::CrossJoin { tables, where_clause, .. } => {
+            assert_eq!(tables, vec!["a".to_string(), "b".to_string()]);
+            assert!(where_clause.is_some());
+        }
+        other => panic!("unexpected command: {:?}", other),
+    }
+    assert!(matches!(parse_to_ast("SELECT * FROM a, "), Command::Unknown(_)));
+}
+
+#[test]
+fn test_join_condition_filters_the_product() {
+    let (mut engine, parser) = engine_with_tables();
+
+    let output = execute_line("SELECT name, title FROM authors, books WHERE authors.id = books.author_id", &mut engine, &parser);
+    assert_eq!(output, "name | title\n------------\nAnn | Alpha\nAnn | Gamma\nBo | Beta");
+
+    // Join conditions combine with ordinary ones
+    let output = execute_line(
+        "SELECT title FROM authors, books WHERE author_id = authors.id AND name = 'Ann' AND books.id > 10",
+        &mut engine,
+        &parser,
+    );
+    assert_eq!(output, "title\n-----\nGamma");
+}
+
+#[test]
+fn test_joined_rows_can_be_ordered() {
+    let (mut engine, parser) = engine_with_tables();
+    let output = execute_line(
+        "SELECT name, title FROM authors, books WHERE authors.id = author_id ORDER BY name DESC, books.id DESC",
+        &mut engine,
+        &parser,
+    );
+    assert_eq!(output, "name | title\n------------\nBo | Beta\nAnn | Gamma\nAnn | Alpha");
+}
+
+#[test]
+fn test_wildcard_qualifies_only_ambiguous_columns() {
+    let (mut engine, parser) = engine_with_tables();
+
+    let output = execute_line("SELECT * FROM authors, books WHERE authors.id = 2 AND author_id = 2", &mut engine, &parser);
+    let header = "authors.id | name | books.id | author_id | title";
+    assert_eq!(output, format!("{}\n{}\n2 | Bo | 11 | 2 | Beta", header, "-".repeat(header.len())));
+
+    // Without a condition every combination is returned
+    let output = execute_line("SELECT COUNT(*) FROM authors, books", &mut engine, &parser);
+    assert_eq!(output, "count\n-----\n6");
+}
+
+#[test]
+fn test_join_errors() {
+    let (mut engine, parser) = engine_with_tables();
+
+    let output = execute_line("SELECT id FROM authors, books", &mut engine, &parser);
+    assert_eq!(output, "Error: Column 'id' is ambiguous; qualify it with its table name");
+
+    let output = execute_line("SELECT * FROM authors, missing", &mut engine, &parser);
+    assert_eq!(output, "Error: Table 'missing' does not exist");
+
+    let output = execute_line("SELECT * FROM authors, AUTHORS", &mut engine, &parser);
+    assert_eq!(output, "Error: Table 'authors' is listed more than once");
+
+    let output = execute_line("SELECT authors.age FROM authors, books", &mut engine, &parser);
+    assert_eq!(output, "Error: Column 'authors.age' not found in table 'authors, books'");
+}
+
+#[test]
+fn test_single_table_compares_columns() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE pairs (a INTEGER, b INTEGER)", &mut engine, &parser);
+    execute_line("INSERT INTO pairs VALUES (1, 1)", &mut engine, &parser);
+    execute_line("INSERT INTO pairs VALUES (1, 2)", &mut engine, &parser);
+
+    let output = execute_line("SELECT * FROM pairs WHERE a = b", &mut engine, &parser);
+    assert_eq!(output, "a | b\n-----\n1 | 1");
+}
@@ -0,0 +1,145 @@
+use rust_dbms::engine::{ExportAccess, QueryEngine};
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+mod common;
+
+use common::temp_db;
+
+fn temp_file(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("isenta_{}_{}.csv", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn test_export_writes_a_large_table_as_csv() {
+    let path = temp_file("export_large");
+    let mut engine = QueryEngine::with_database(":memory:");
+    let parser = Parser::new();
+    execute_line("CREATE TABLE events (id INTEGER, name TEXT)", &mut engine, &parser);
+    for id in 0..8 {
+        engine.execute_insert("events".to_string(), vec![Some(id.to_string()), Some(format!("event {:0>40}", id))]).unwrap();
+    }
+    // Double the table up to 8192 rows
+    for _ in 0..10 {
+        execute_line("INSERT INTO events SELECT * FROM events", &mut engine, &parser);
+    }
+
+    let output = execute_line(&format!("EXPORT events TO '{}'", path), &mut engine, &parser);
+    assert_eq!(output, format!("Exported 8192 rows from 'events' to '{}'", path));
+
+    // Larger than the export buffer, so it was written in several flushes
+    let csv = std::fs::read_to_string(&path).unwrap();
+    assert!(csv.len() > rust_dbms::engine::EXPORT_BUFFER_SIZE);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 8193);
+    assert_eq!(lines[0], "id,name");
+    for (index, line) in lines[1..].iter().enumerate() {
+        let id = index % 8;
+        assert_eq!(*line, format!("{},event {:0>40}", id, id));
+    }
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_export_quotes_fields_and_filters_rows() {
+    let path = temp_file("export_quoting");
+    let mut engine = QueryEngine::with_database(":memory:");
+    let parser = Parser::new();
+    execute_line("CREATE TABLE notes (id INTEGER, body TEXT)", &mut engine, &parser);
+    execute_line("INSERT INTO notes VALUES (1, 'plain')", &mut engine, &parser);
+    engine.execute_insert("notes".to_string(), vec![Some("2".to_string()), Some("a, b".to_string())]).unwrap();
+    execute_line("INSERT INTO notes VALUES (3, 'say \"hi\"')", &mut engine, &parser);
+    execute_line("INSERT INTO notes VALUES (4, NULL)", &mut engine, &parser);
+
+    let output = execute_line(&format!("EXPORT notes TO '{}' WHERE id > 1", path), &mut engine, &parser);
+    assert_eq!(output, format!("Exported 3 rows from 'notes' to '{}'", path));
+    let csv = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(csv, "id,body\n2,\"a, b\"\n3,\"say \"\"hi\"\"\"\n4,\n");
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_scan_rows_yields_matching_rows_lazily() {
+    let mut engine = QueryEngine::with_database(":memory:");
+    let parser = Parser::new();
+    execute_line("CREATE TABLE numbers (n INTEGER)", &mut engine, &parser);
+    for n in 0..100 {
+        engine.execute_insert("numbers".to_string(), vec![Some(n.to_string())]).unwrap();
+    }
+    let flag = Arc::new(AtomicBool::new(false));
+    engine.set_cancel_flag(flag.clone());
+
+    let where_clause = match parser.parse("SELECT * FROM numbers WHERE n >= 50") {
+        rust_dbms::parser::Command::Select { where_clause, .. } => where_clause,
+        other => panic!("unexpected command {:?}", other),
+    };
+    let mut scan = engine.scan_rows("numbers", where_clause).unwrap();
+    let first = scan.next().unwrap().unwrap();
+    assert_eq!(first.values, vec![Some("50".to_string())]);
+
+    // Cancelling part-way stops the rest of the scan
+    flag.store(true, std::sync::atomic::Ordering::Relaxed);
+    assert!(matches!(scan.next(), Some(Err(rust_dbms::error::IsentaError::Cancelled))));
+}
+
+#[test]
+fn test_export_of_a_missing_table_fails() {
+    let path = temp_file("export_missing");
+    let mut engine = QueryEngine::with_database(":memory:");
+    let parser = Parser::new();
+    let output = execute_line(&format!("EXPORT nowhere TO '{}'", path), &mut engine, &parser);
+    assert!(output.starts_with("Error:"), "{}", output);
+    assert!(!std::path::Path::new(&path).exists());
+}
+
+#[test]
+fn test_scan_reads_the_table_pages() {
+    let path = temp_db("export_pages");
+    let parser = Parser::new();
+    let mut engine = QueryEngine::with_database(&path);
+    execute_line("CREATE TABLE numbers (n INTEGER)", &mut engine, &parser);
+    execute_line("INSERT INTO numbers VALUES (1), (2)", &mut engine, &parser);
+
+    // A row another connection stores is read from the file, though the
+    // engine's catalog has not loaded it
+    let mut other = QueryEngine::with_database(&path);
+    execute_line("INSERT INTO numbers VALUES (3)", &mut other, &parser);
+    let scanned: Vec<Vec<Option<String>>> = engine.scan_rows("numbers", None).unwrap().map(|row| row.unwrap().values).collect();
+    assert_eq!(scanned, vec![vec![Some("1".to_string())], vec![Some("2".to_string())], vec![Some("3".to_string())]]);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_export_access_restricts_paths() {
+    let dir = std::env::temp_dir().join(format!("isenta_export_dir_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("sub")).unwrap();
+    let mut engine = QueryEngine::with_database(":memory:");
+    let parser = Parser::new();
+    execute_line("CREATE TABLE notes (id INTEGER)", &mut engine, &parser);
+    execute_line("INSERT INTO notes VALUES (1)", &mut engine, &parser);
+
+    engine.set_export_access(ExportAccess::Disabled);
+    assert_eq!(execute_line("EXPORT notes TO 'notes.csv'", &mut engine, &parser), "Error: EXPORT is disabled");
+
+    engine.set_export_access(ExportAccess::Within(dir.clone()));
+    assert_eq!(
+        execute_line("EXPORT notes TO 'sub/notes.csv'", &mut engine, &parser),
+        "Exported 1 rows from 'notes' to 'sub/notes.csv'"
+    );
+    assert_eq!(std::fs::read_to_string(dir.join("sub/notes.csv")).unwrap(), "id\n1\n");
+
+    let outside = temp_file("export_outside");
+    for path in [outside.as_str(), "../notes.csv", "sub/../../notes.csv", ""] {
+        let output = execute_line(&format!("EXPORT notes TO '{}'", path), &mut engine, &parser);
+        assert_eq!(output, format!("Error: EXPORT can only write files inside the export directory, not '{}'", path));
+    }
+    assert!(!std::path::Path::new(&outside).exists());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
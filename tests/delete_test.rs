@@ -0,0 +1,155 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{Command, Parser};
+use rust_dbms::storage::{MEMORY_PATH, PAGE_SIZE};
+
+mod common;
+
+use common::temp_db;
+
+/// A log table of 50 rows of about 200 bytes each, roughly 19 to a data
+/// page, with rowids 1 to 50. The schema page is page 1 and the data pages
+/// follow it.
+fn log_table(path: &str) -> (QueryEngine, Parser) {
+    let mut engine = QueryEngine::with_database(path);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE logs (id INTEGER, message TEXT)", &mut engine, &parser);
+    for id in 1..=50 {
+        let values = vec![Some(id.to_string()), Some(format!("{:0>200}", id))];
+        engine.execute_insert("logs".to_string(), values).unwrap();
+    }
+    (engine, parser)
+}
+
+/// The pages that differ between two copies of the file.
+fn changed_pages(before: &[u8], after: &[u8]) -> Vec<usize> {
+    (0..before.len() / PAGE_SIZE)
+        .filter(|page| before[page * PAGE_SIZE..(page + 1) * PAGE_SIZE] != after[page * PAGE_SIZE..(page + 1) * PAGE_SIZE])
+        .collect()
+}
+
+#[test]
+fn test_delete_rowid_range_keeps_remaining_rows_and_rowids() {
+    let path = temp_db("delete_range");
+    {
+        let (mut engine, parser) = log_table(&path);
+        let output = execute_line("DELETE FROM logs WHERE _rowid BETWEEN 1 AND 10", &mut engine, &parser);
+        assert_eq!(output, "Deleted 10 rows from 'logs'");
+        engine.close().unwrap();
+    }
+
+    let mut engine = QueryEngine::with_database(&path);
+    let parser = Parser::new();
+    let output = execute_line("SELECT _rowid, id FROM logs", &mut engine, &parser);
+    let rows: Vec<&str> = output.lines().skip(2).collect();
+    let expected: Vec<String> = (11..=50).map(|id| format!("{} | {}", id, id)).collect();
+    assert_eq!(rows, expected);
+
+    // Rowids of deleted rows aren't handed out again
+    execute_line("INSERT INTO logs VALUES (51, 'new')", &mut engine, &parser);
+    let output = execute_line("SELECT _rowid FROM logs WHERE id = 51", &mut engine, &parser);
+    assert_eq!(output.lines().last(), Some("51"));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_delete_rewrites_only_the_affected_pages() {
+    let path = temp_db("delete_pages");
+    let (mut engine, parser) = log_table(&path);
+    engine.flush().unwrap();
+    let before = std::fs::read(&path).unwrap();
+
+    execute_line("DELETE FROM logs WHERE _rowid BETWEEN 2 AND 4", &mut engine, &parser);
+    engine.flush().unwrap();
+    let after = std::fs::read(&path).unwrap();
+    // The schema page and the first data page
+    assert_eq!(changed_pages(&before, &after), vec![1, 2]);
+
+    let output = execute_line("SELECT COUNT(*) FROM logs", &mut engine, &parser);
+    assert_eq!(output.lines().last(), Some("47"));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_emptied_pages_are_unlinked_and_freed() {
+    let path = temp_db("delete_emptied");
+    {
+        let (mut engine, parser) = log_table(&path);
+        // Every row of the first data page, and a few of the second
+        let output = execute_line("DELETE FROM logs WHERE _rowid <= 25", &mut engine, &parser);
+        assert_eq!(output, "Deleted 25 rows from 'logs'");
+        engine.close().unwrap();
+    }
+    let data = std::fs::read(&path).unwrap();
+    // The first data page is tagged free
+    assert_eq!(data[3 * PAGE_SIZE - 1], 4);
+
+    let mut engine = QueryEngine::with_database(&path);
+    let parser = Parser::new();
+    let output = execute_line("SELECT MIN(_rowid), COUNT(*) FROM logs", &mut engine, &parser);
+    assert_eq!(output.lines().last(), Some("26 | 25"));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_delete_with_condition_and_without() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE people (name TEXT, age INTEGER)", &mut engine, &parser);
+    for (name, age) in [("ann", 31), ("bob", 17), ("cat", 45), ("dan", 12)] {
+        execute_line(&format!("INSERT INTO people VALUES ('{}', {})", name, age), &mut engine, &parser);
+    }
+
+    let output = execute_line("DELETE FROM people WHERE name BETWEEN 'b' AND 'c' OR age < 15", &mut engine, &parser);
+    assert_eq!(output, "Deleted 2 rows from 'people'");
+    let output = execute_line("SELECT name FROM people", &mut engine, &parser);
+    assert_eq!(output, "name\n----\nann\ncat");
+
+    let output = execute_line("DELETE FROM people WHERE age > 100", &mut engine, &parser);
+    assert_eq!(output, "Deleted 0 rows from 'people'");
+    let output = execute_line("DELETE FROM people", &mut engine, &parser);
+    assert_eq!(output, "Deleted 2 rows from 'people'");
+    let output = execute_line("SELECT * FROM people", &mut engine, &parser);
+    assert_eq!(output, "No rows found in 'people'");
+
+    let output = execute_line("DELETE FROM nowhere", &mut engine, &parser);
+    assert!(output.starts_with("Error:"), "{}", output);
+}
+
+#[test]
+fn test_delete_limit_caps_affected_rows() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+
+    execute_line("CREATE TABLE jobs (id INTEGER, status TEXT)", &mut engine, &parser);
+    for id in 1..=5 {
+        let status = if id == 3 { "new" } else { "old" };
+        execute_line(&format!("INSERT INTO jobs VALUES ({}, '{}')", id, status), &mut engine, &parser);
+    }
+
+    // The rows are deleted in table order
+    let output = execute_line("DELETE FROM jobs WHERE status != 'new' LIMIT 1", &mut engine, &parser);
+    assert_eq!(output, "Deleted 1 rows from 'jobs'");
+    let output = execute_line("DELETE FROM jobs WHERE id > 1 LIMIT 2", &mut engine, &parser);
+    assert_eq!(output, "Deleted 2 rows from 'jobs'");
+    let output = execute_line("SELECT * FROM jobs", &mut engine, &parser);
+    assert_eq!(output, "id | status
+-----------
+4 | old
+5 | old");
+
+    // Fewer matches than the limit reports the actual count
+    let output = execute_line("DELETE FROM jobs WHERE status = 'old' LIMIT 10", &mut engine, &parser);
+    assert_eq!(output, "Deleted 2 rows from 'jobs'");
+    let output = execute_line("DELETE FROM jobs LIMIT 0", &mut engine, &parser);
+    assert_eq!(output, "Deleted 0 rows from 'jobs'");
+
+    match parser.parse("DELETE FROM jobs WHERE status = 'a LIMIT 5'") {
+        Command::Delete { where_clause: Some(_), limit: None, .. } => {}
+        other => panic!("unexpected command: {:?}", other),
+    }
+    match parser.parse("DELETE FROM jobs LIMIT x") {
+        Command::Unknown(message) => assert!(message.starts_with("Invalid LIMIT"), "{}", message),
+        other => panic!("unexpected command: {:?}", other),
+    }
+}
@@ -0,0 +1,146 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{Column, Parser, SelectItem};
+use rust_dbms::storage::PAGE_SIZE;
+
+mod common;
+
+use common::temp_db;
+
+fn columns() -> Vec<Column> {
+    vec![
+        Column::new("id", "INTEGER"),
+        Column::new("status", "TEXT"),
+    ]
+}
+
+#[test]
+fn test_low_cardinality_values_round_trip() {
+    let path = temp_db("dictionary_low");
+    let statuses = ["pending_review_by_administrator", "approved", "rejected_permanently"];
+
+    {
+        let mut engine = QueryEngine::with_database(&path);
+        engine.execute_create_table("orders".to_string(), columns()).unwrap();
+        for i in 0..300 {
+            let status = statuses[i % statuses.len()].to_string();
+            engine.execute_insert("orders".to_string(), vec![Some(i.to_string()), Some(status)]).unwrap();
+        }
+    }
+
+    let engine = QueryEngine::with_database(&path);
+    let result = engine.execute_select("orders".to_string(), vec![SelectItem::Wildcard], None).unwrap();
+    assert_eq!(result.len(), 300);
+    for (i, row) in result.rows().enumerate() {
+        assert_eq!(row.get_int("id"), Some(i as i64));
+        assert_eq!(row.get("status"), Some(statuses[i % statuses.len()]));
+    }
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_high_cardinality_values_round_trip() {
+    let path = temp_db("dictionary_high");
+
+    {
+        let mut engine = QueryEngine::with_database(&path);
+        engine.execute_create_table("events".to_string(), columns()).unwrap();
+        for i in 0..200 {
+            engine
+                .execute_insert("events".to_string(), vec![Some(i.to_string()), Some(format!("event number {}", i))])
+                .unwrap();
+        }
+    }
+
+    let engine = QueryEngine::with_database(&path);
+    let result = engine.execute_select("events".to_string(), vec![SelectItem::Wildcard], None).unwrap();
+    assert_eq!(result.len(), 200);
+    for (i, row) in result.rows().enumerate() {
+        assert_eq!(row.get("status"), Some(format!("event number {}", i).as_str()));
+    }
+
+    let _ = std::fs::remove_file(&path);
+}
+
+/// Stores 300 rows whose status is given by `status`, returning how many
+/// pages the file takes.
+fn pages_for_statuses(name: &str, status: impl Fn(usize) -> String) -> u64 {
+    let path = temp_db(name);
+    {
+        let mut engine = QueryEngine::with_database(&path);
+        engine.execute_create_table("orders".to_string(), columns()).unwrap();
+        for i in 0..300 {
+            engine.execute_insert("orders".to_string(), vec![Some(i.to_string()), Some(status(i))]).unwrap();
+        }
+    }
+    let pages = std::fs::metadata(&path).unwrap().len() / PAGE_SIZE as u64;
+    let _ = std::fs::remove_file(&path);
+    pages
+}
+
+#[test]
+fn test_repeated_values_take_fewer_pages() {
+    let statuses = ["pending_review_by_administrator", "approved_after_a_careful_review", "rejected_permanently_by_owners"];
+    let repeated = pages_for_statuses("dictionary_pages_repeated", |i| statuses[i % statuses.len()].to_string());
+    // Values of the same length that are all distinct are stored inline
+    let distinct = pages_for_statuses("dictionary_pages_distinct", |i| format!("pending_review_by_admin_{:07}", i));
+    assert!(repeated < distinct, "{} pages with a dictionary, {} without", repeated, distinct);
+}
+
+/// Writes a version 1 file, which predates dictionaries, holding a table
+/// `orders (id INTEGER, status TEXT)` with a row for each of `statuses`.
+fn write_version_1_file(path: &str, statuses: &[&str]) {
+    let page = |data: Vec<u8>| {
+        let mut page = vec![0u8; PAGE_SIZE];
+        page[..data.len()].copy_from_slice(&data);
+        page
+    };
+    let text = |value: &str| [&(value.len() as u32).to_le_bytes()[..], value.as_bytes()].concat();
+
+    // Magic number, version, schema root and table count
+    let header = [&0x4953454E54414442u64.to_le_bytes()[..], &1u32.to_le_bytes(), &1u64.to_le_bytes(), &1u32.to_le_bytes()].concat();
+    // Name, column count, each column's name and type, then the first data page and the next schema page
+    let schema = [
+        text("orders"),
+        2u32.to_le_bytes().to_vec(),
+        text("id"),
+        text("INTEGER"),
+        text("status"),
+        text("TEXT"),
+        2u64.to_le_bytes().to_vec(),
+        0u64.to_le_bytes().to_vec(),
+    ]
+    .concat();
+    // Row count, then each row's INTEGER and inline TEXT values, then no next page
+    let mut data = (statuses.len() as u32).to_le_bytes().to_vec();
+    for (id, status) in statuses.iter().enumerate() {
+        data.push(1);
+        data.extend_from_slice(&(id as i64).to_le_bytes());
+        data.push(2);
+        data.extend_from_slice(&text(status));
+    }
+    data.extend_from_slice(&0u64.to_le_bytes());
+
+    std::fs::write(path, [page(header), page(schema), page(data)].concat()).unwrap();
+}
+
+#[test]
+fn test_files_without_dictionaries_still_load() {
+    let path = temp_db("dictionary_version_1");
+    write_version_1_file(&path, &["approved", "approved", "rejected"]);
+    let parser = Parser::new();
+    {
+        let mut engine = QueryEngine::with_database(&path);
+        let output = execute_line("SELECT * FROM orders", &mut engine, &parser);
+        assert_eq!(output, "id | status\n-----------\n0 | approved\n1 | approved\n2 | rejected");
+        execute_line("INSERT INTO orders VALUES (3, 'approved')", &mut engine, &parser);
+    }
+
+    // The insert rewrote the file in a newer format, which the rows survive
+    let mut engine = QueryEngine::with_database(&path);
+    let output = execute_line("SELECT * FROM orders", &mut engine, &parser);
+    assert_eq!(output, "id | status\n-----------\n0 | approved\n1 | approved\n2 | rejected\n3 | approved");
+
+    let _ = std::fs::remove_file(&path);
+}
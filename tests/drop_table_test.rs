@@ -0,0 +1,67 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{parse_to_ast, Command, Parser};
+use rust_dbms::storage::MEMORY_PATH;
+
+mod common;
+
+use common::temp_db;
+
+#[test]
+fn test_parse_drop_table() {
+    assert_eq!(
+        parse_to_ast("DROP TABLE users"),
+        Command::DropTable { name: "users".to_string(), if_exists: false }
+    );
+    assert_eq!(
+        parse_to_ast("drop table if exists Users;"),
+        Command::DropTable { name: "Users".to_string(), if_exists: true }
+    );
+    assert!(matches!(parse_to_ast("DROP TABLE"), Command::Unknown(_)));
+    assert!(matches!(parse_to_ast("DROP TABLE IF EXISTS"), Command::Unknown(_)));
+    assert!(matches!(parse_to_ast("DROP TABLE a b"), Command::Unknown(_)));
+}
+
+#[test]
+fn test_drop_if_exists_skips_a_missing_table() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    assert_eq!(
+        execute_line("DROP TABLE IF EXISTS ghosts", &mut engine, &parser),
+        "Table 'ghosts' does not exist, skipping"
+    );
+    assert_eq!(execute_line("DROP TABLE ghosts", &mut engine, &parser), "Error: Table 'ghosts' does not exist");
+}
+
+#[test]
+fn test_drop_then_create_is_idempotent() {
+    let path = temp_db("drop_table");
+    let parser = Parser::new();
+    {
+        let mut engine = QueryEngine::with_database(&path);
+        for _ in 0..3 {
+            execute_line("DROP TABLE IF EXISTS items", &mut engine, &parser);
+            execute_line("CREATE TABLE items (id INTEGER, name TEXT)", &mut engine, &parser);
+            execute_line("INSERT INTO items VALUES (1, 'tea')", &mut engine, &parser);
+        }
+        execute_line("CREATE TABLE other (id INTEGER)", &mut engine, &parser);
+        execute_line("INSERT INTO other VALUES (7)", &mut engine, &parser);
+    }
+    let file_len = std::fs::metadata(&path).unwrap().len();
+
+    let mut engine = QueryEngine::with_database(&path);
+    assert_eq!(execute_line("SELECT * FROM items", &mut engine, &parser), "id | name\n---------\n1 | tea");
+    assert_eq!(execute_line("DROP TABLE items", &mut engine, &parser), "Table 'items' dropped successfully");
+    assert!(engine.get_table_schema("items").is_none());
+    drop(engine);
+
+    // The drop persists, the other table is still linked and the dropped
+    // table's pages are reused
+    let mut engine = QueryEngine::with_database(&path);
+    assert!(engine.get_table_schema("items").is_none());
+    assert_eq!(execute_line("SELECT * FROM other", &mut engine, &parser), "id\n--\n7");
+    execute_line("CREATE TABLE items (id INTEGER, name TEXT)", &mut engine, &parser);
+    execute_line("INSERT INTO items VALUES (2, 'cake')", &mut engine, &parser);
+    assert_eq!(execute_line("SELECT * FROM items", &mut engine, &parser), "id | name\n---------\n2 | cake");
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), file_len);
+}
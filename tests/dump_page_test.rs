@@ -0,0 +1,57 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{parse_to_ast, Command, Parser};
+use rust_dbms::storage::MEMORY_PATH;
+
+fn engine_with_table(debug: bool) -> (QueryEngine, Parser) {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    engine.set_debug(debug);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE notes (id INTEGER, body TEXT)", &mut engine, &parser);
+    execute_line("INSERT INTO notes VALUES (1, 'hello')", &mut engine, &parser);
+    (engine, parser)
+}
+
+#[test]
+fn test_parse_dump_page() {
+    assert_eq!(parse_to_ast("DUMP PAGE 3"), Command::DumpPage { page_id: 3 });
+    assert!(matches!(parse_to_ast("DUMP PAGE x"), Command::Unknown(_)));
+}
+
+#[test]
+fn test_dump_page_is_hidden_without_debug() {
+    let (mut engine, parser) = engine_with_table(false);
+    assert!(execute_line("DUMP PAGE 0", &mut engine, &parser).starts_with("Unknown command"));
+    assert!(!execute_line("help", &mut engine, &parser).contains("DUMP PAGE"));
+
+    engine.set_debug(true);
+    assert!(execute_line("help", &mut engine, &parser).contains("DUMP PAGE"));
+}
+
+#[test]
+fn test_dump_page_describes_each_page_type() {
+    let (mut engine, parser) = engine_with_table(true);
+
+    let header = execute_line("DUMP PAGE 0", &mut engine, &parser);
+    assert!(header.starts_with("Page 0 of 3 (header)"), "{}", header);
+    assert!(header.contains("Tables: 1"), "{}", header);
+
+    let schema = execute_line("DUMP PAGE 1", &mut engine, &parser);
+    assert!(schema.starts_with("Page 1 of 3 (schema)"), "{}", schema);
+    assert!(schema.contains("Columns: 2"), "{}", schema);
+
+    let data = execute_line("DUMP PAGE 2", &mut engine, &parser);
+    assert!(data.starts_with("Page 2 of 3 (data)"), "{}", data);
+    assert!(data.contains("Rows: 1"), "{}", data);
+    assert!(data.contains("|hello"), "{}", data);
+    // Runs of zero lines are collapsed, the last line is always shown
+    assert!(data.lines().any(|line| line == "*"), "{}", data);
+    assert!(data.lines().last().unwrap().starts_with("0ff0: "), "{}", data);
+}
+
+#[test]
+fn test_dump_page_past_the_end_is_an_error() {
+    let (mut engine, parser) = engine_with_table(true);
+    let output = execute_line("DUMP PAGE 7", &mut engine, &parser);
+    assert_eq!(output, "Error: Page 7 is past the end of the file, which has 3 pages");
+}
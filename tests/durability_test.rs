@@ -0,0 +1,112 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+use rust_dbms::storage::{Durability, Page, StorageEngine, PAGE_SIZE};
+
+mod common;
+
+use common::temp_db;
+
+#[test]
+fn test_deferred_pages_reach_the_file_on_flush() {
+    let path = temp_db("durability_storage");
+    let mut storage = StorageEngine::new(&path).unwrap();
+    storage.set_durability(Durability::Deferred).unwrap();
+
+    let mut page = Page::new(2);
+    page.data[0] = 7;
+    storage.write_page(&page).unwrap();
+
+    // Visible through the engine, but not yet in the file
+    assert_eq!(storage.read_page(2).unwrap().data[0], 7);
+    assert_eq!(storage.file_len().unwrap(), 3 * PAGE_SIZE as u64);
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), 0);
+
+    storage.flush().unwrap();
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), 3 * PAGE_SIZE as u64);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_deferred_bulk_load_is_persisted_after_flush_and_drop() {
+    let path = temp_db("durability_engine");
+    let parser = Parser::new();
+    {
+        let mut engine = QueryEngine::with_database(&path);
+        engine.set_durability(Durability::Deferred).unwrap();
+        execute_line("CREATE TABLE log (id INTEGER, message TEXT)", &mut engine, &parser);
+        for i in 0..200 {
+            execute_line(&format!("INSERT INTO log VALUES ({}, 'entry {}')", i, i), &mut engine, &parser);
+        }
+
+        // Nothing but the header has been written so far
+        let mut reader = QueryEngine::with_database(&path);
+        assert_eq!(execute_line("SHOW TABLES", &mut reader, &parser), "No tables in database");
+
+        engine.flush().unwrap();
+        let mut reader = QueryEngine::with_database(&path);
+        let output = execute_line("SELECT message FROM log WHERE id = 199", &mut reader, &parser);
+        assert_eq!(output, "message\n-------\nentry 199");
+
+        // Dropping the engine writes whatever is still pending
+        execute_line("INSERT INTO log VALUES (200, 'last')", &mut engine, &parser);
+    }
+
+    let mut engine = QueryEngine::with_database(&path);
+    let output = execute_line("SELECT message FROM log WHERE id = 200", &mut engine, &parser);
+    assert_eq!(output, "message\n-------\nlast");
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_close_writes_pending_changes() {
+    let path = temp_db("durability_close");
+    let parser = Parser::new();
+
+    let mut engine = QueryEngine::with_database(&path);
+    engine.set_durability(Durability::Deferred).unwrap();
+    execute_line("CREATE TABLE log (id INTEGER, message TEXT)", &mut engine, &parser);
+    execute_line("INSERT INTO log VALUES (1, 'closed')", &mut engine, &parser);
+    engine.close().unwrap();
+
+    let mut engine = QueryEngine::with_database(&path);
+    let output = execute_line("SELECT message FROM log", &mut engine, &parser);
+    assert_eq!(output, "message\n-------\nclosed");
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_flush_every_bounds_what_a_crash_loses() {
+    let path = temp_db("durability_flush_every");
+    let parser = Parser::new();
+
+    let mut engine = QueryEngine::with_database(&path);
+    engine.set_durability(Durability::Deferred).unwrap();
+    assert_eq!(execute_line("PRAGMA flush_every = 10", &mut engine, &parser), "flush_every = 10");
+    // The CREATE TABLE and nine inserts make ten changes, flushed together
+    execute_line("CREATE TABLE log (id INTEGER)", &mut engine, &parser);
+    for i in 1..=12 {
+        execute_line(&format!("INSERT INTO log VALUES ({})", i), &mut engine, &parser);
+    }
+    // Simulate a crash: the engine never gets to write its pending pages
+    std::mem::forget(engine);
+
+    let mut engine = QueryEngine::with_database(&path);
+    let output = execute_line("SELECT COUNT(*) FROM log", &mut engine, &parser);
+    assert_eq!(output, "count\n-----\n9");
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_flush_every_pragma_values() {
+    let mut engine = QueryEngine::with_database(":memory:");
+    assert_eq!(engine.pragma("flush_every", None).unwrap(), "off");
+    assert_eq!(engine.pragma("flush_every", Some("1000")).unwrap(), "1000");
+    assert_eq!(engine.flush_every(), Some(1000));
+    assert_eq!(engine.pragma("flush_every", Some("0")).unwrap(), "off");
+    assert!(engine.pragma("flush_every", Some("often")).is_err());
+}
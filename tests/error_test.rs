@@ -0,0 +1,57 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::error::IsentaError;
+use rust_dbms::parser::{Column, SelectItem, WhereClause};
+use rust_dbms::storage::MEMORY_PATH;
+
+fn engine_with_table() -> QueryEngine {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    engine
+        .execute_create_table(
+            "users".to_string(),
+            vec![
+                Column::new("id", "INTEGER"),
+                Column::new("name", "TEXT"),
+            ],
+        )
+        .unwrap();
+    engine
+}
+
+#[test]
+fn test_engine_errors_have_distinct_kinds() {
+    let mut engine = engine_with_table();
+
+    let err = engine.execute_select("missing".to_string(), vec![SelectItem::Wildcard], None).unwrap_err();
+    assert_eq!(err, IsentaError::TableNotFound("missing".to_string()));
+
+    let err = engine.execute_select("users".to_string(), vec![SelectItem::Column("age".to_string())], None).unwrap_err();
+    assert_eq!(err, IsentaError::ColumnNotFound { table: "users".to_string(), column: "age".to_string() });
+
+    let err = engine.execute_create_table("users".to_string(), Vec::new()).unwrap_err();
+    assert_eq!(err, IsentaError::TableExists("users".to_string()));
+
+    let err = engine.execute_insert("users".to_string(), vec![Some("x".to_string()), None]).unwrap_err();
+    assert!(matches!(err, IsentaError::TypeMismatch(_)), "{:?}", err);
+
+    let err = engine.execute_insert("users".to_string(), vec![Some("1".to_string())]).unwrap_err();
+    assert!(matches!(err, IsentaError::Constraint(_)), "{:?}", err);
+
+    let where_null = WhereClause { column: "name".to_string(), operator: "=".to_string(), value: None, quoted: false };
+    let err = engine.execute_select("users".to_string(), vec![SelectItem::Wildcard], Some(where_null.into())).unwrap_err();
+    assert!(matches!(err, IsentaError::InvalidQuery(_)), "{:?}", err);
+}
+
+#[test]
+fn test_errors_display_human_readable_messages() {
+    let mut engine = engine_with_table();
+
+    let err = engine.execute_select("missing".to_string(), vec![SelectItem::Wildcard], None).unwrap_err();
+    assert_eq!(err.to_string(), "Table 'missing' does not exist");
+
+    let err = engine.execute_insert("users".to_string(), vec![Some("x".to_string()), None]).unwrap_err();
+    assert_eq!(err.to_string(), "Invalid value for column 'id': 'x' is not a valid INTEGER");
+
+    // Usable as a boxed standard error
+    let boxed: Box<dyn std::error::Error> = Box::new(err);
+    assert!(boxed.to_string().starts_with("Invalid value"));
+}
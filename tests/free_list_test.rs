@@ -0,0 +1,70 @@
+use rust_dbms::database::Database;
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+
+mod common;
+
+use common::temp_db;
+
+/// Inserts rows of about 200 bytes each, roughly 19 to a data page.
+fn insert_rows(engine: &mut QueryEngine, table: &str, count: u32) {
+    for id in 0..count {
+        let values = vec![Some(id.to_string()), Some(format!("{:0>200}", id))];
+        engine.execute_insert(table.to_string(), values).unwrap();
+    }
+}
+
+/// A database whose `logs` table spanned three data pages before it was
+/// truncated, freeing two of them.
+fn database_with_freed_pages(name: &str) -> String {
+    let path = temp_db(name);
+    let parser = Parser::new();
+    let mut engine = QueryEngine::with_database(&path);
+    execute_line("CREATE TABLE logs (id INTEGER, message TEXT)", &mut engine, &parser);
+    execute_line("CREATE TABLE users (id INTEGER, name TEXT)", &mut engine, &parser);
+    insert_rows(&mut engine, "logs", 50);
+    execute_line("TRUNCATE TABLE logs", &mut engine, &parser);
+    engine.close().unwrap();
+    path
+}
+
+#[test]
+fn test_free_list_survives_reopening() {
+    let path = database_with_freed_pages("free_list_reopen");
+    let mut db = Database::new(&path).unwrap();
+    let freed = db.free_pages();
+    assert_eq!(freed.len(), 2);
+    let page_count = db.file_info().unwrap().page_count;
+
+    // The next allocation reuses a freed page instead of extending the file
+    let page = db.allocate_page().unwrap();
+    assert_eq!(page.id, freed[0]);
+    assert_eq!(db.file_info().unwrap().page_count, page_count);
+    drop(db);
+
+    // Taking a page off the list is persisted as well
+    let db = Database::new(&path).unwrap();
+    assert_eq!(db.free_pages(), freed[1..]);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_new_tables_reuse_freed_pages() {
+    let path = database_with_freed_pages("free_list_tables");
+    let parser = Parser::new();
+    let mut engine = QueryEngine::with_database(&path);
+    let page_count = engine.file_info().unwrap().page_count;
+
+    // A schema page and a data page, both from the free list
+    execute_line("CREATE TABLE tags (name TEXT)", &mut engine, &parser);
+    execute_line("INSERT INTO tags VALUES ('new')", &mut engine, &parser);
+    assert_eq!(engine.file_info().unwrap().page_count, page_count);
+    engine.close().unwrap();
+
+    let mut engine = QueryEngine::with_database(&path);
+    assert_eq!(execute_line("SELECT name FROM tags", &mut engine, &parser), "name\n----\nnew");
+    let output = execute_line("SELECT COUNT(*) FROM users", &mut engine, &parser);
+    assert_eq!(output.lines().last(), Some("0"));
+    let _ = std::fs::remove_file(&path);
+}
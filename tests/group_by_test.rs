@@ -0,0 +1,90 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{parse_to_ast, Command, Parser};
+use rust_dbms::storage::MEMORY_PATH;
+
+fn engine_with_sales() -> (QueryEngine, Parser) {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE sales (category TEXT, shelf INTEGER, price INTEGER)", &mut engine, &parser);
+    execute_line(
+        "INSERT INTO sales VALUES ('books', 2, 10), ('games', 10, 30), ('Books', 02, 5), (NULL, NULL, 3), ('games', 10, NULL)",
+        &mut engine,
+        &parser,
+    );
+    (engine, parser)
+}
+
+#[test]
+fn test_parse_group_by() {
+    match parse_to_ast("SELECT category, COUNT(*) FROM sales WHERE price > 1 GROUP BY category ORDER BY category LIMIT 2") {
+        Command::Select { table, where_clause, group_by, order_by, .. } => {
+            assert_eq!(table, "sales");
+            assert!(where_clause.is_some());
+            assert_eq!(group_by.as_deref(), Some("category"));
+            assert_eq!(order_by.len(), 1);
+        }
+        other => panic!("expected a SELECT, got {:?}", other),
+    }
+    assert!(matches!(parse_to_ast("SELECT COUNT(*) FROM sales GROUP BY a, b"), Command::Unknown(_)));
+    assert!(matches!(parse_to_ast("SELECT COUNT(*) FROM a, b GROUP BY x"), Command::Unknown(_)));
+    assert!(matches!(parse_to_ast("CREATE TABLE c AS SELECT category FROM sales GROUP BY category"), Command::Unknown(_)));
+}
+
+#[test]
+fn test_group_by_counts_each_group() {
+    let (mut engine, parser) = engine_with_sales();
+    // Text groups ignore case, as = does, and show the first row's value
+    let output = execute_line("SELECT category, COUNT(*), SUM(price) AS total FROM sales GROUP BY category", &mut engine, &parser);
+    assert_eq!(output, "category | count | total\n------------------------\nbooks | 2 | 15\ngames | 2 | 30\nNULL | 1 | 3");
+
+    let output = execute_line(
+        "SELECT category, MAX(price) FROM sales WHERE price > 5 GROUP BY category ORDER BY category DESC",
+        &mut engine,
+        &parser,
+    );
+    assert_eq!(output, "category | max_price\n--------------------\ngames | 30\nbooks | 10");
+}
+
+#[test]
+fn test_integer_groups_compare_by_value() {
+    let (mut engine, parser) = engine_with_sales();
+    let output = execute_line("SELECT shelf, COUNT(price) FROM sales GROUP BY shelf ORDER BY shelf", &mut engine, &parser);
+    assert_eq!(output, "shelf | count_price\n-------------------\nNULL | 1\n2 | 2\n10 | 1");
+}
+
+#[test]
+fn test_group_by_of_no_rows_returns_no_groups() {
+    let (mut engine, parser) = engine_with_sales();
+    let output = execute_line("SELECT category, COUNT(*) FROM sales WHERE price > 100 GROUP BY category", &mut engine, &parser);
+    assert_eq!(output, "No rows found in 'sales'");
+}
+
+#[test]
+fn test_ungrouped_columns_are_rejected() {
+    let (mut engine, parser) = engine_with_sales();
+    assert_eq!(
+        execute_line("SELECT category, price, COUNT(*) FROM sales GROUP BY category", &mut engine, &parser),
+        "Error: Column 'price' must be the GROUP BY column or be used inside an aggregate function"
+    );
+    assert_eq!(
+        execute_line("SELECT * FROM sales GROUP BY category", &mut engine, &parser),
+        "Error: '*' can't be selected together with GROUP BY"
+    );
+    assert_eq!(
+        execute_line("SELECT COUNT(*) FROM sales GROUP BY colour", &mut engine, &parser),
+        "Error: Column 'colour' not found in table 'sales'"
+    );
+}
+
+#[test]
+fn test_text_groups_fold_only_ascii_case() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE words (word TEXT)", &mut engine, &parser);
+    execute_line("INSERT INTO words VALUES ('Ä'), ('ä'), ('Ab'), ('aB')", &mut engine, &parser);
+    // = treats 'Ä' and 'ä' as different values, so GROUP BY does too
+    assert_eq!(execute_line("SELECT COUNT(*) FROM words WHERE word = 'ä'", &mut engine, &parser), "count\n-----\n1");
+    let output = execute_line("SELECT word, COUNT(*) FROM words GROUP BY word", &mut engine, &parser);
+    assert_eq!(output, "word | count\n------------\nÄ | 1\nä | 1\nAb | 2");
+}
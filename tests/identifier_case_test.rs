@@ -0,0 +1,37 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+use rust_dbms::storage::MEMORY_PATH;
+
+#[test]
+fn test_non_ascii_column_in_where() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE städte (name TEXT, größe INTEGER)", &mut engine, &parser);
+    execute_line("INSERT INTO städte VALUES ('Köln', 405)", &mut engine, &parser);
+    execute_line("INSERT INTO STäDTE VALUES ('Bonn', 141)", &mut engine, &parser);
+
+    let output = execute_line("SELECT name FROM städte WHERE größe > 200", &mut engine, &parser);
+    assert_eq!(output, "name\n----\nKöln");
+    // ASCII letters still fold, whatever else is in the name
+    let output = execute_line("SELECT name FROM STäDTE WHERE GRößE < 200", &mut engine, &parser);
+    assert_eq!(output, "name\n----\nBonn");
+    // Other letters don't
+    let output = execute_line("SELECT name FROM städte WHERE GRÖßE < 200", &mut engine, &parser);
+    assert_eq!(output, "Error: Column 'GRÖßE' not found in table 'städte'");
+    let output = execute_line("SELECT name FROM STÄDTE", &mut engine, &parser);
+    assert_eq!(output, "Error: Table 'STÄDTE' does not exist");
+}
+
+#[test]
+fn test_names_only_folding_together_outside_ascii_are_distinct() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    // The Kelvin sign lowercases to an ASCII k, and the dotted capital I to i
+    let output = execute_line("CREATE TABLE t (k INTEGER, \u{212A} INTEGER, \u{130}d TEXT, id TEXT)", &mut engine, &parser);
+    assert_eq!(output, "Table 't' created successfully");
+    execute_line("INSERT INTO t VALUES (1, 2, 'dotted', 'plain')", &mut engine, &parser);
+
+    assert_eq!(execute_line("SELECT \u{212A} FROM t WHERE k = 1", &mut engine, &parser), "\u{212A}\n---\n2");
+    assert_eq!(execute_line("SELECT id FROM t WHERE \u{130}d = 'dotted'", &mut engine, &parser), "id\n--\nplain");
+}
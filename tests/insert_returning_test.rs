@@ -0,0 +1,62 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{parse_to_ast, Command, Parser, SelectItem};
+use rust_dbms::storage::MEMORY_PATH;
+
+fn engine_with_orders() -> (QueryEngine, Parser) {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line(
+        "CREATE TABLE orders (id INTEGER, item TEXT, status TEXT DEFAULT 'new')",
+        &mut engine,
+        &parser,
+    );
+    (engine, parser)
+}
+
+#[test]
+fn test_parse_returning() {
+    match parse_to_ast("INSERT INTO orders (id) VALUES (1) RETURNING id, status AS s") {
+        Command::Insert { table, values, returning: Some(returning), .. } => {
+            assert_eq!(table, "orders");
+            assert_eq!(values, vec![vec![Some("1".to_string())]]);
+            assert_eq!(returning.len(), 2);
+            assert_eq!(returning[0], SelectItem::Column("id".to_string()));
+        }
+        other => panic!("expected an INSERT, got {:?}", other),
+    }
+    // The keyword inside a value is not a RETURNING clause
+    assert!(matches!(
+        parse_to_ast("INSERT INTO orders VALUES (1, 'x RETURNING y', NULL)"),
+        Command::Insert { returning: None, .. }
+    ));
+    assert!(matches!(parse_to_ast("INSERT INTO orders VALUES (1) RETURNING "), Command::Unknown(_)));
+}
+
+#[test]
+fn test_returning_id() {
+    let (mut engine, parser) = engine_with_orders();
+    let output = execute_line("INSERT INTO orders VALUES (7, 'tea', 'paid') RETURNING id", &mut engine, &parser);
+    assert_eq!(output, "id\n--\n7");
+    let output = execute_line("INSERT INTO orders (id, item) VALUES (8, 'cake') RETURNING _rowid, id", &mut engine, &parser);
+    assert_eq!(output, "_rowid | id\n-----------\n2 | 8");
+}
+
+#[test]
+fn test_returning_star_includes_defaults() {
+    let (mut engine, parser) = engine_with_orders();
+    let output = execute_line("INSERT INTO orders (item, id) VALUES ('tea', 1) RETURNING *", &mut engine, &parser);
+    assert_eq!(output, "id | item | status\n------------------\n1 | tea | new");
+    let output = execute_line("SELECT * FROM orders", &mut engine, &parser);
+    assert_eq!(output, "id | item | status\n------------------\n1 | tea | new");
+}
+
+#[test]
+fn test_invalid_returning_inserts_nothing() {
+    let (mut engine, parser) = engine_with_orders();
+    let output = execute_line("INSERT INTO orders VALUES (1, 'tea', NULL) RETURNING price", &mut engine, &parser);
+    assert_eq!(output, "Error: Column 'price' not found in table 'orders'");
+    let output = execute_line("INSERT INTO orders VALUES ('x', 'tea', NULL) RETURNING *", &mut engine, &parser);
+    assert!(output.starts_with("Error:"), "{}", output);
+    assert_eq!(execute_line("SELECT * FROM orders", &mut engine, &parser), "No rows found in 'orders'");
+}
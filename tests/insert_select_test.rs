@@ -0,0 +1,73 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{parse_to_ast, Command, Parser, SelectItem, WhereClause, WhereExpr};
+use rust_dbms::storage::MEMORY_PATH;
+
+fn engine_with_orders() -> (QueryEngine, Parser) {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE orders (id INTEGER, created_at TEXT)", &mut engine, &parser);
+    execute_line("CREATE TABLE archive (id INTEGER, created_at TEXT)", &mut engine, &parser);
+    for (id, created_at) in [(1, "2022-05-01"), (2, "2023-03-10"), (3, "2022-11-30")] {
+        execute_line(&format!("INSERT INTO orders VALUES ({}, '{}')", id, created_at), &mut engine, &parser);
+    }
+    (engine, parser)
+}
+
+#[test]
+fn test_parse_insert_select() {
+    assert_eq!(
+        parse_to_ast("INSERT INTO archive SELECT * FROM orders WHERE id = 1"),
+        Command::InsertSelect {
+            table: "archive".to_string(),
+            source: "orders".to_string(),
+            columns: vec![SelectItem::Wildcard],
+            where_clause: Some(WhereExpr::Condition(WhereClause {
+                column: "id".to_string(),
+                operator: "=".to_string(),
+                value: Some("1".to_string()),
+                quoted: false,
+            })),
+            order_by: Vec::new(),
+        }
+    );
+    // A SELECT inside a quoted value is just text
+    assert!(matches!(parse_to_ast("INSERT INTO t VALUES ('x SELECT y')"), Command::Insert { .. }));
+}
+
+#[test]
+fn test_insert_select_copies_matching_rows() {
+    let (mut engine, parser) = engine_with_orders();
+    assert_eq!(
+        execute_line("INSERT INTO archive SELECT * FROM orders WHERE created_at LIKE '2022%'", &mut engine, &parser),
+        "Inserted 2 rows into 'archive'"
+    );
+    assert_eq!(
+        execute_line("SELECT _rowid, id FROM archive", &mut engine, &parser),
+        "_rowid | id\n-----------\n1 | 1\n2 | 3"
+    );
+}
+
+#[test]
+fn test_insert_select_into_the_source_table() {
+    let (mut engine, parser) = engine_with_orders();
+    assert_eq!(
+        execute_line("INSERT INTO orders SELECT id, 'copy' FROM orders", &mut engine, &parser),
+        "Inserted 3 rows into 'orders'"
+    );
+    assert_eq!(execute_line("SELECT COUNT(*) FROM orders", &mut engine, &parser), "count\n-----\n6");
+}
+
+#[test]
+fn test_insert_select_requires_matching_columns() {
+    let (mut engine, parser) = engine_with_orders();
+    assert_eq!(
+        execute_line("INSERT INTO archive SELECT id FROM orders", &mut engine, &parser),
+        "Error: Column count mismatch: expected 2, got 1"
+    );
+    let output = execute_line("INSERT INTO archive SELECT created_at, id FROM orders", &mut engine, &parser);
+    assert!(output.starts_with("Error: Invalid value for column 'id'"), "{}", output);
+
+    // Nothing is inserted when any row is rejected
+    assert_eq!(execute_line("SELECT * FROM archive", &mut engine, &parser), "No rows found in 'archive'");
+}
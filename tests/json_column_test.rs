@@ -0,0 +1,96 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+use rust_dbms::storage::MEMORY_PATH;
+
+#[cfg(feature = "json")]
+fn engine_with_events() -> (QueryEngine, Parser) {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE events (id INTEGER, data JSON)", &mut engine, &parser);
+    let documents = [
+        r#"{"user": {"name": "ann", "age": 31}, "tags": ["a", "b"]}"#,
+        r#"{"user": {"name": "bob", "age": 9}, "tags": []}"#,
+        r#"{"user": {"name": "cat", "age": 100}, "tags": ["c"], "note": null}"#,
+    ];
+    for (id, document) in documents.iter().enumerate() {
+        let values = vec![Some((id + 1).to_string()), Some(document.to_string())];
+        engine.execute_insert("events".to_string(), values).unwrap();
+    }
+    (engine, parser)
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_json_extract_in_the_projection() {
+    let (mut engine, parser) = engine_with_events();
+    let output = execute_line(
+        "SELECT id, json_extract(data, '$.user.name') AS name, json_extract(data, '$.tags[0]') FROM events",
+        &mut engine,
+        &parser,
+    );
+    assert_eq!(
+        output,
+        "id | name | json_extract(data, '$.tags[0]')\n-------------------------------------------\n1 | ann | a\n2 | bob | NULL\n3 | cat | c"
+    );
+
+    // Objects and arrays come back as JSON; JSON null and missing paths as NULL
+    let output = execute_line("SELECT json_extract(data, '$.tags') AS tags FROM events WHERE id = 1", &mut engine, &parser);
+    assert_eq!(output, "tags\n----\n[\"a\",\"b\"]");
+    let output = execute_line("SELECT json_extract(data, '$.note') AS note FROM events WHERE id = 3", &mut engine, &parser);
+    assert_eq!(output, "note\n----\nNULL");
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_json_extract_in_where_and_order_by() {
+    let (mut engine, parser) = engine_with_events();
+    // Numbers compare by value, not as text
+    let output = execute_line("SELECT id FROM events WHERE json_extract(data, '$.user.age') > 30", &mut engine, &parser);
+    assert_eq!(output, "id\n--\n1\n3");
+    let output = execute_line("SELECT id FROM events WHERE json_extract(data, '$.user.name') = 'BOB'", &mut engine, &parser);
+    assert_eq!(output, "id\n--\n2");
+    let output = execute_line("SELECT id FROM events WHERE json_extract(data, '$.tags[0]') IS NULL", &mut engine, &parser);
+    assert_eq!(output, "id\n--\n2");
+
+    let output = execute_line("SELECT id FROM events ORDER BY json_extract(data, '$.user.age') DESC", &mut engine, &parser);
+    assert_eq!(output, "id\n--\n3\n1\n2");
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_json_columns_reject_invalid_json() {
+    let (mut engine, parser) = engine_with_events();
+    let result = engine.execute_insert("events".to_string(), vec![Some("4".to_string()), Some("{not json".to_string())]);
+    assert!(result.unwrap_err().to_string().contains("is not a valid JSON"));
+
+    let output = execute_line("UPDATE events SET data = 'plain' WHERE id = 1", &mut engine, &parser);
+    assert!(output.starts_with("Error:"), "{}", output);
+    let output = execute_line("SELECT COUNT(*) FROM events", &mut engine, &parser);
+    assert_eq!(output.lines().last(), Some("3"));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_invalid_json_paths_are_errors() {
+    let (mut engine, parser) = engine_with_events();
+    let output = execute_line("SELECT json_extract(data, 'user') FROM events", &mut engine, &parser);
+    assert_eq!(output, "Error: Invalid JSON path 'user': a path starts with '$'");
+    let output = execute_line("SELECT id FROM events WHERE json_extract(data, '$.tags[x]') = 'a'", &mut engine, &parser);
+    assert!(output.starts_with("Error:") && output.contains("not an array index"), "{}", output);
+    let output = execute_line("SELECT json_extract(missing, '$.a') FROM events", &mut engine, &parser);
+    assert!(output.starts_with("Error:") && output.contains("missing"), "{}", output);
+}
+
+#[cfg(not(feature = "json"))]
+#[test]
+fn test_json_needs_the_json_feature() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    let output = execute_line("CREATE TABLE events (id INTEGER, data JSON)", &mut engine, &parser);
+    assert!(output.contains("rebuild with the 'json' feature"), "{}", output);
+
+    execute_line("CREATE TABLE notes (body TEXT)", &mut engine, &parser);
+    let output = execute_line("SELECT json_extract(body, '$.a') FROM notes", &mut engine, &parser);
+    assert!(output.contains("rebuild with the 'json' feature"), "{}", output);
+}
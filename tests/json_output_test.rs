@@ -0,0 +1,62 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+use rust_dbms::storage::MEMORY_PATH;
+
+#[test]
+fn test_get_as_json_formats_schema_and_rows() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+
+    execute_line("CREATE TABLE notes (id INTEGER, body TEXT)", &mut engine, &parser);
+    execute_line("INSERT INTO notes VALUES (1, NULL)", &mut engine, &parser);
+    execute_line("INSERT INTO notes VALUES (2, 'say \"hi\"\\now')", &mut engine, &parser);
+
+    let table = engine.get_all_tables()[0].clone();
+    let expected = format!(
+        r#"{{
+  "name": "notes",
+  "columns": [
+    {{
+      "name": "id",
+      "data_type": "INTEGER"
+    }},
+    {{
+      "name": "body",
+      "data_type": "TEXT"
+    }}
+  ],
+  "rows": [
+    {{
+      "rowid": 1,
+      "values": [
+        "1",
+        null
+      ]
+    }},
+    {{
+      "rowid": 2,
+      "values": [
+        "2",
+        "say \"hi\"\now"
+      ]
+    }}
+  ],
+  "created_at": {},
+  "updated_at": {},
+  "next_rowid": 3
+}}"#,
+        table.created_at, table.updated_at
+    );
+    assert_eq!(execute_line("GET notes AS JSON", &mut engine, &parser), expected);
+}
+
+#[test]
+fn test_get_as_json_with_no_rows() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+
+    execute_line("CREATE TABLE empty (id INTEGER)", &mut engine, &parser);
+    let output = execute_line("GET empty AS JSON", &mut engine, &parser);
+    assert!(output.contains("\"rows\": [],"), "{}", output);
+}
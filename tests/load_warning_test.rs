@@ -0,0 +1,126 @@
+use rust_dbms::database::{Database, LoadWarning};
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::log;
+use rust_dbms::parser::Parser;
+use rust_dbms::storage::PAGE_SIZE;
+
+mod common;
+
+use common::temp_db;
+
+fn patch(path: &str, offset: usize, bytes: &[u8]) {
+    let mut data = std::fs::read(path).unwrap();
+    data[offset..offset + bytes.len()].copy_from_slice(bytes);
+    std::fs::write(path, data).unwrap();
+}
+
+/// A database with the table `items (id INTEGER)` in page 1 and its data in
+/// page 2.
+fn database_with_table(name: &str) -> String {
+    let path = temp_db(name);
+    let mut engine = QueryEngine::with_database(&path);
+    execute_line("CREATE TABLE items (id INTEGER)", &mut engine, &Parser::new());
+    execute_line("INSERT INTO items VALUES (1)", &mut engine, &Parser::new());
+    engine.close().unwrap();
+    path
+}
+
+fn load_warnings(path: &str) -> Vec<LoadWarning> {
+    let mut db = Database::with_logger(path, log::quiet()).unwrap();
+    db.load_catalog().unwrap();
+    db.take_warnings()
+}
+
+#[test]
+fn test_a_clean_load_has_no_warnings() {
+    let path = database_with_table("load_warning_clean");
+    assert!(load_warnings(&path).is_empty());
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_wrong_table_count_is_reported_once() {
+    let path = database_with_table("load_warning_count");
+    // The table count is at offset 20 of the header page
+    patch(&path, 20, &2u32.to_le_bytes());
+
+    let mut engine = QueryEngine::open_with_logger(&path, log::quiet()).unwrap();
+    let warnings = engine.take_load_warnings();
+    assert_eq!(warnings, vec![LoadWarning::TableCountRepaired { expected: 2, loaded: 1 }]);
+    assert_eq!(warnings[0].to_string(), "Expected 2 tables but only loaded 1. Repairing database...");
+    assert!(engine.take_load_warnings().is_empty());
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_circular_schema_chain_is_reported() {
+    let path = database_with_table("load_warning_circular");
+    patch(&path, 20, &2u32.to_le_bytes());
+    // The next schema page ID follows the name (4 + 5 bytes), the column
+    // count (4), the column "id INTEGER" (4 + 2 + 4 + 7 + 1) and the data page
+    // ID (8); point it back at the page itself
+    patch(&path, PAGE_SIZE + 39, &1u64.to_le_bytes());
+
+    assert_eq!(
+        load_warnings(&path),
+        vec![
+            LoadWarning::CircularSchemaChain { page_id: 1 },
+            LoadWarning::TableCountRepaired { expected: 2, loaded: 1 },
+        ]
+    );
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_schema_root_on_a_data_page_is_reported() {
+    let path = database_with_table("load_warning_page_type");
+    // Point the schema root (header offset 12) at the data page
+    patch(&path, 12, &2u64.to_le_bytes());
+
+    assert_eq!(
+        load_warnings(&path),
+        vec![
+            LoadWarning::WrongPageType("page 2 is a data page, not a schema page".to_string()),
+            LoadWarning::InvalidTablePage { page_id: 2 },
+            LoadWarning::TableCountRepaired { expected: 1, loaded: 0 },
+        ]
+    );
+    let _ = std::fs::remove_file(&path);
+}
+
+/// The position in the file of the first occurrence of `text`.
+fn find(path: &str, text: &str) -> usize {
+    let data = std::fs::read(path).unwrap();
+    data.windows(text.len()).position(|window| window == text.as_bytes()).unwrap()
+}
+
+#[test]
+fn test_unreadable_text_is_replaced_and_the_rows_kept() {
+    let path = temp_db("load_warning_text");
+    let parser = Parser::new();
+    let mut engine = QueryEngine::with_database(&path);
+    execute_line("CREATE TABLE notes (id INTEGER, body TEXT, tag TEXT)", &mut engine, &parser);
+    for (id, body) in [(1, "first"), (2, "second"), (3, "third"), (4, "fourth")] {
+        execute_line(&format!("INSERT INTO notes VALUES ({}, '{}', 'tag{}')", id, body, id), &mut engine, &parser);
+    }
+    engine.close().unwrap();
+
+    // A length prefix running far past the row, and bytes that aren't UTF-8
+    let second = find(&path, "second");
+    patch(&path, second - 4, &60_000u32.to_le_bytes());
+    let third = find(&path, "third");
+    patch(&path, third, &[0xff]);
+
+    assert_eq!(load_warnings(&path), vec![LoadWarning::UnreadableText { table: "notes".to_string(), count: 2 }]);
+
+    let mut engine = QueryEngine::open_with_logger(&path, log::quiet()).unwrap();
+    let output = execute_line("SELECT * FROM notes", &mut engine, &parser);
+    assert_eq!(
+        output,
+        "id | body | tag\n---------------\n1 | first | tag1\n2 | \u{FFFD} | NULL\n3 | \u{FFFD} | tag3\n4 | fourth | tag4"
+    );
+    let warning = &engine.take_load_warnings()[0];
+    assert_eq!(warning.to_string(), "Replaced 2 unreadable text values in table 'notes' with '\u{FFFD}'");
+    let _ = std::fs::remove_file(&path);
+}
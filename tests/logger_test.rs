@@ -0,0 +1,46 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::log::Logger;
+use rust_dbms::parser::Parser;
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+use common::temp_db;
+
+fn patch(path: &str, offset: usize, bytes: &[u8]) {
+    let mut data = std::fs::read(path).unwrap();
+    data[offset..offset + bytes.len()].copy_from_slice(bytes);
+    std::fs::write(path, data).unwrap();
+}
+
+/// A logger that keeps every message it receives.
+fn capturing_logger() -> (Logger, Arc<Mutex<Vec<String>>>) {
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&messages);
+    let logger: Logger = Arc::new(move |message: &str| sink.lock().unwrap().push(message.to_string()));
+    (logger, messages)
+}
+
+/// A database with one table whose header claims it holds two.
+fn database_with_wrong_table_count(name: &str) -> String {
+    let path = temp_db(name);
+    {
+        let mut engine = QueryEngine::with_database(&path);
+        execute_line("CREATE TABLE items (id INTEGER)", &mut engine, &Parser::new());
+    }
+    // The table count is at offset 20 of the header page
+    patch(&path, 20, &2u32.to_le_bytes());
+    path
+}
+
+#[test]
+fn test_warnings_while_opening_go_to_the_logger() {
+    let path = database_with_wrong_table_count("logger_open");
+    let (logger, messages) = capturing_logger();
+
+    let engine = QueryEngine::open_with_logger(&path, logger).unwrap();
+    assert_eq!(engine.get_all_tables().len(), 1);
+    assert_eq!(*messages.lock().unwrap(), vec!["Expected 2 tables but only loaded 1. Repairing database...".to_string()]);
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,54 @@
+use rust_dbms::database::Database;
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+use rust_dbms::storage::{StorageEngine, MEMORY_PATH, PAGE_SIZE};
+
+#[test]
+fn test_memory_database_round_trip() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+
+    execute_line("CREATE TABLE notes (id INTEGER, body TEXT)", &mut engine, &parser);
+    for i in 0..500 {
+        execute_line(&format!("INSERT INTO notes VALUES ({}, 'note {}')", i, i), &mut engine, &parser);
+    }
+    execute_line("UPDATE notes SET body = 'changed' WHERE id = 7", &mut engine, &parser);
+
+    let output = execute_line("SELECT body FROM notes WHERE id = 7", &mut engine, &parser);
+    assert_eq!(output, "body\n----\nchanged");
+
+    // Nothing is written to a file named after the special path
+    assert!(!std::path::Path::new(MEMORY_PATH).exists());
+}
+
+#[test]
+fn test_memory_databases_are_independent() {
+    let parser = Parser::new();
+    let mut first = QueryEngine::with_database(MEMORY_PATH);
+    execute_line("CREATE TABLE t (id INTEGER)", &mut first, &parser);
+
+    let mut second = QueryEngine::with_database(MEMORY_PATH);
+    let output = execute_line("SELECT * FROM t", &mut second, &parser);
+    assert!(output.starts_with("Error:"), "{}", output);
+}
+
+#[test]
+fn test_memory_pages_are_allocated_sequentially() {
+    let mut storage = StorageEngine::in_memory();
+    assert!(storage.is_in_memory());
+    assert!(storage.file().is_none());
+
+    let first = storage.allocate_page().unwrap();
+    let mut second = storage.allocate_page().unwrap();
+    assert_eq!((first.id, second.id), (0, 1));
+
+    second.data[0] = 42;
+    storage.write_page(&second).unwrap();
+    assert_eq!(storage.read_page(1).unwrap().data[0], 42);
+    assert_eq!(storage.read_page(5).unwrap().data[0], 0);
+    assert_eq!(storage.file_len().unwrap(), 2 * PAGE_SIZE as u64);
+
+    let mut db = Database::new(MEMORY_PATH).unwrap();
+    assert!(db.load_catalog().unwrap().list_tables().is_empty());
+}
@@ -0,0 +1,71 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use rust_dbms::parser::{is_complete_statement, split_statements};
+
+mod common;
+
+use common::temp_db;
+
+#[test]
+fn test_statement_needs_a_terminator() {
+    assert!(!is_complete_statement("SELECT * FROM users"));
+    assert!(is_complete_statement("SELECT * FROM users;"));
+    assert!(is_complete_statement("SELECT * FROM users;  "));
+}
+
+#[test]
+fn test_unbalanced_quotes_and_parentheses_are_incomplete() {
+    assert!(!is_complete_statement("INSERT INTO t VALUES (1, 'a;"));
+    assert!(!is_complete_statement("CREATE TABLE t (id INT;"));
+    assert!(is_complete_statement("INSERT INTO t VALUES (1, 'a;b');"));
+}
+
+#[test]
+fn test_split_statements() {
+    assert_eq!(
+        split_statements("INSERT INTO t VALUES (1, 'a;b'); SELECT * FROM t;"),
+        vec!["INSERT INTO t VALUES (1, 'a;b')", "SELECT * FROM t"]
+    );
+}
+
+#[test]
+fn test_repl_accumulates_lines_until_terminator() {
+    let db = temp_db("multi_line_repl");
+    let mut cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db")
+        .arg(&db)
+        .write_stdin("CREATE TABLE users (\n  id INT,\n  name TEXT\n);\nINSERT INTO users VALUES (1, 'Alice'); SELECT name\nFROM users\nWHERE id = 1;\nexit\n");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("    -> "))
+        .stdout(predicate::str::contains("Alice"));
+
+    let _ = std::fs::remove_file(&db);
+}
+
+#[test]
+fn test_unterminated_statement_at_end_of_input_is_reported() {
+    let db = temp_db("multi_line_unterminated");
+    let mut cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).write_stdin("SHOW TABLES\n");
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("incomplete statement"));
+
+    let _ = std::fs::remove_file(&db);
+}
+
+#[test]
+fn test_backslash_g_reruns_the_previous_statement() {
+    let db = temp_db("rerun_statement");
+    let mut cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db")
+        .arg(&db)
+        .write_stdin("CREATE TABLE hits (id INT);\nINSERT INTO hits VALUES (1);\n\\g\n\nSELECT COUNT(*) FROM hits;\nSELECT id\nFROM hits\n\\g\n")
+        .assert()
+        .success()
+        // A blank line in piped input doesn't run anything again
+        .stdout(predicate::str::contains("count\n-----\n2"))
+        .stdout(predicate::str::contains("id\n--\n1\n1"));
+    let _ = std::fs::remove_file(&db);
+}
@@ -0,0 +1,70 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{parse_to_ast, Command, Parser};
+
+mod common;
+
+use common::temp_db;
+
+#[test]
+fn test_parse_several_value_groups() {
+    match parse_to_ast("INSERT INTO items VALUES (1, 'a, b'), (2, NULL);") {
+        Command::Insert { values, .. } => assert_eq!(
+            values,
+            vec![vec![Some("1".to_string()), Some("a, b".to_string())], vec![Some("2".to_string()), None]]
+        ),
+        other => panic!("expected an INSERT, got {:?}", other),
+    }
+    assert!(matches!(parse_to_ast("INSERT INTO items VALUES (1), 2"), Command::Unknown(_)));
+    assert!(matches!(parse_to_ast("INSERT INTO items VALUES (1), "), Command::Unknown(_)));
+}
+
+#[test]
+fn test_insert_several_rows() {
+    let path = temp_db("multi_row_insert");
+    let parser = Parser::new();
+    {
+        let mut engine = QueryEngine::with_database(&path);
+        execute_line("CREATE TABLE items (id INTEGER, name TEXT)", &mut engine, &parser);
+        assert_eq!(
+            execute_line("INSERT INTO items VALUES (1, 'tea'), (2, 'cake, iced')", &mut engine, &parser),
+            "Inserted 2 rows into 'items'"
+        );
+        assert_eq!(
+            execute_line("INSERT INTO items (name, id) VALUES ('jam', 3)", &mut engine, &parser),
+            "Inserted 1 row into 'items'"
+        );
+    }
+    let mut engine = QueryEngine::with_database(&path);
+    assert_eq!(
+        execute_line("SELECT * FROM items", &mut engine, &parser),
+        "id | name\n---------\n1 | tea\n2 | cake, iced\n3 | jam"
+    );
+}
+
+#[test]
+fn test_one_bad_row_inserts_nothing() {
+    let path = temp_db("multi_row_insert_bad");
+    let parser = Parser::new();
+    {
+        let mut engine = QueryEngine::with_database(&path);
+        execute_line("CREATE TABLE items (id INTEGER NOT NULL, name TEXT)", &mut engine, &parser);
+        let output = execute_line("INSERT INTO items VALUES (1, 'tea'), ('x', 'cake')", &mut engine, &parser);
+        assert_eq!(output, "Error: Invalid value for column 'id': 'x' is not a valid INTEGER");
+        let output = execute_line("INSERT INTO items (id, name) VALUES (1, 'tea'), (2)", &mut engine, &parser);
+        assert!(output.starts_with("Error:"), "{}", output);
+        assert_eq!(execute_line("SELECT * FROM items", &mut engine, &parser), "No rows found in 'items'");
+    }
+    let mut engine = QueryEngine::with_database(&path);
+    assert_eq!(execute_line("SELECT * FROM items", &mut engine, &parser), "No rows found in 'items'");
+}
+
+#[test]
+fn test_returning_every_inserted_row() {
+    let path = temp_db("multi_row_insert_returning");
+    let mut engine = QueryEngine::with_database(&path);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE items (id INTEGER, name TEXT)", &mut engine, &parser);
+    let output = execute_line("INSERT INTO items VALUES (1, 'tea'), (2, 'cake') RETURNING _rowid, name", &mut engine, &parser);
+    assert_eq!(output, "_rowid | name\n-------------\n1 | tea\n2 | cake");
+}
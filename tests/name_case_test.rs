@@ -0,0 +1,67 @@
+use rust_dbms::database::Database;
+use rust_dbms::engine::{names_match, QueryEngine, Table};
+use rust_dbms::error::IsentaError;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{Column, Parser};
+
+mod common;
+
+use common::temp_db;
+
+#[test]
+fn test_names_match_ignores_case() {
+    assert!(names_match("Users", "USERS"));
+    assert!(names_match("straße", "STRAßE"));
+    assert!(!names_match("users", "user"));
+}
+
+#[test]
+fn test_mixed_case_table_created_then_updated() {
+    let path = temp_db("name_case_update");
+    let parser = Parser::new();
+    {
+        let mut engine = QueryEngine::with_database(&path);
+        assert_eq!(
+            execute_line("CREATE TABLE Users (Id INTEGER, Name TEXT)", &mut engine, &parser),
+            "Table 'Users' created successfully"
+        );
+        assert_eq!(
+            execute_line("CREATE TABLE USERS (id INTEGER)", &mut engine, &parser),
+            "Error: Table 'USERS' already exists"
+        );
+        execute_line("INSERT INTO users VALUES (1, 'Alice')", &mut engine, &parser);
+        assert_eq!(
+            execute_line("UPDATE USERS SET name = 'Alicia' WHERE ID = 1", &mut engine, &parser),
+            "Updated 1 rows in 'USERS'"
+        );
+    }
+
+    // The update was written to the table's own schema chain, not a new copy
+    let mut engine = QueryEngine::with_database(&path);
+    assert_eq!(execute_line("SHOW TABLES", &mut engine, &parser), "Tables:\n- Users");
+    assert_eq!(execute_line("SELECT Name FROM uSeRs", &mut engine, &parser), "Name\n----\nAlicia");
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_saving_a_new_table_under_an_existing_name_fails() {
+    let path = temp_db("name_case_save");
+    let mut db = Database::new(&path).unwrap();
+    let mut table = Table {
+        name: "Orders".to_string(),
+        columns: vec![Column::new("id", "INTEGER")],
+        rows: Vec::new(),
+        created_at: 0,
+        updated_at: 0,
+        next_rowid: 1,
+        change_count: 0,
+    };
+    db.save_table(&table, true).unwrap();
+
+    table.name = "ORDERS".to_string();
+    let err = db.save_table(&table, true).unwrap_err();
+    assert_eq!(err, IsentaError::TableExists("ORDERS".to_string()));
+
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,54 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{parse_to_ast, Command, Parser};
+use rust_dbms::storage::MEMORY_PATH;
+
+#[test]
+fn test_parse_insert_with_column_list() {
+    assert_eq!(
+        parse_to_ast("INSERT INTO t (b, a) VALUES (2, 'x')"),
+        Command::Insert {
+            table: "t".to_string(),
+            columns: Some(vec!["b".to_string(), "a".to_string()]),
+            values: vec![vec![Some("2".to_string()), Some("x".to_string())]],
+            returning: None,
+        }
+    );
+    assert!(matches!(parse_to_ast("INSERT INTO t (b, ) VALUES (2)"), Command::Unknown(_)));
+}
+
+#[test]
+fn test_named_columns_are_placed_in_schema_order() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+
+    execute_line("CREATE TABLE t (a INTEGER, b INTEGER, c TEXT)", &mut engine, &parser);
+    let output = execute_line("INSERT INTO t (c, a) VALUES ('three', 1)", &mut engine, &parser);
+    assert_eq!(output, "Inserted 1 row into 't'");
+
+    // The omitted column is NULL
+    let output = execute_line("SELECT a, b, c FROM t", &mut engine, &parser);
+    assert_eq!(output, "a | b | c\n---------\n1 | NULL | three");
+}
+
+#[test]
+fn test_invalid_column_lists_insert_nothing() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+
+    execute_line("CREATE TABLE t (a INTEGER, b INTEGER)", &mut engine, &parser);
+
+    let output = execute_line("INSERT INTO t (a, z) VALUES (1, 2)", &mut engine, &parser);
+    assert_eq!(output, "Error: Column 'z' not found in table 't'");
+
+    let output = execute_line("INSERT INTO t (a, A) VALUES (1, 2)", &mut engine, &parser);
+    assert_eq!(output, "Error: Column 'A' is named more than once");
+
+    let output = execute_line("INSERT INTO t (a, b) VALUES (1)", &mut engine, &parser);
+    assert_eq!(output, "Error: 2 columns are named but 1 values are given");
+
+    let output = execute_line("INSERT INTO t (b) VALUES ('x')", &mut engine, &parser);
+    assert!(output.starts_with("Error: Invalid value for column 'b'"), "{}", output);
+
+    assert_eq!(execute_line("SELECT * FROM t", &mut engine, &parser), "No rows found in 't'");
+}
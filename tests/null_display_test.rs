@@ -0,0 +1,65 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use rust_dbms::engine::{QueryEngine, DEFAULT_NULL_STRING};
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+use rust_dbms::storage::MEMORY_PATH;
+
+mod common;
+
+use common::temp_db;
+
+fn engine_with_null_and_empty_text() -> (QueryEngine, Parser) {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE notes (id INTEGER, body TEXT)", &mut engine, &parser);
+    execute_line("INSERT INTO notes VALUES (1, NULL)", &mut engine, &parser);
+    execute_line("INSERT INTO notes VALUES (2, '')", &mut engine, &parser);
+    (engine, parser)
+}
+
+#[test]
+fn test_null_renders_as_null_by_default() {
+    let (mut engine, parser) = engine_with_null_and_empty_text();
+    assert_eq!(engine.null_string(), DEFAULT_NULL_STRING);
+
+    let output = execute_line("SELECT id, body FROM notes", &mut engine, &parser);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines[2], "1 | NULL");
+    assert_eq!(lines[3].trim_end(), "2 |");
+}
+
+#[test]
+fn test_null_string_can_be_changed() {
+    let (mut engine, parser) = engine_with_null_and_empty_text();
+    engine.set_null_string("∅");
+
+    let output = execute_line("SELECT body, id FROM notes", &mut engine, &parser);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines[2], "∅ | 1");
+    assert_eq!(lines[3], " | 2");
+}
+
+#[test]
+fn test_null_string_cli_option() {
+    let db = temp_db("null_string_cli");
+    cargo_bin_cmd!("isenta")
+        .arg("--db")
+        .arg(&db)
+        .arg("CREATE TABLE notes (id INTEGER, body TEXT)")
+        .arg("INSERT INTO notes VALUES (1, NULL)")
+        .assert()
+        .success();
+
+    cargo_bin_cmd!("isenta")
+        .arg("--db")
+        .arg(&db)
+        .arg("--null-string")
+        .arg("<null>")
+        .arg("SELECT * FROM notes")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 | <null>"));
+
+    let _ = std::fs::remove_file(&db);
+}
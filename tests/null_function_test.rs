@@ -0,0 +1,60 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+use rust_dbms::storage::MEMORY_PATH;
+
+fn engine_with_users() -> (QueryEngine, Parser) {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE users (id INTEGER, name TEXT, nickname TEXT, score INTEGER)", &mut engine, &parser);
+    execute_line("INSERT INTO users VALUES (1, 'Robert', 'Bob', 10)", &mut engine, &parser);
+    execute_line("INSERT INTO users VALUES (2, 'Alice', NULL, 0)", &mut engine, &parser);
+    execute_line("INSERT INTO users VALUES (3, NULL, NULL, NULL)", &mut engine, &parser);
+    (engine, parser)
+}
+
+#[test]
+fn test_coalesce_returns_the_first_non_null_argument() {
+    let (mut engine, parser) = engine_with_users();
+    let output = execute_line("SELECT id, COALESCE(nickname, name, 'unknown') AS shown FROM users", &mut engine, &parser);
+    assert_eq!(output, "id | shown\n----------\n1 | Bob\n2 | Alice\n3 | unknown");
+    // Without a default every argument may be NULL
+    let output = execute_line("SELECT COALESCE(nickname, name) AS shown FROM users WHERE id = 3", &mut engine, &parser);
+    assert_eq!(output, "shown\n-----\nNULL");
+    // A literal argument is read as in VALUES, escapes and commas included
+    let output = execute_line("SELECT COALESCE(nickname, 'it\\'s, none') AS shown FROM users WHERE id = 3", &mut engine, &parser);
+    assert_eq!(output, "shown\n-----\nit's, none");
+}
+
+#[test]
+fn test_coalesce_compares_as_its_column_type() {
+    let (mut engine, parser) = engine_with_users();
+    // The fallback 0 takes the INTEGER type of score, so 10 > 9
+    let output = execute_line("SELECT id FROM users WHERE COALESCE(score, 0) > 9", &mut engine, &parser);
+    assert_eq!(output, "id\n--\n1");
+    let output = execute_line("SELECT id FROM users ORDER BY COALESCE(score, -1), id", &mut engine, &parser);
+    assert_eq!(output, "id\n--\n3\n2\n1");
+}
+
+#[test]
+fn test_nullif_returns_null_on_equality() {
+    let (mut engine, parser) = engine_with_users();
+    let output = execute_line("SELECT id, NULLIF(score, 0) AS score FROM users", &mut engine, &parser);
+    assert_eq!(output, "id | score\n----------\n1 | 10\n2 | NULL\n3 | NULL");
+    let output = execute_line("SELECT id FROM users WHERE NULLIF(name, 'Robert') IS NULL", &mut engine, &parser);
+    assert_eq!(output, "id\n--\n1\n3");
+    // The two are combined to fall back when a value is a placeholder
+    let output = execute_line("SELECT COALESCE(NULLIF(score, 0), -1) AS score FROM users", &mut engine, &parser);
+    assert_eq!(output, "score\n-----\n10\n-1\n-1");
+}
+
+#[test]
+fn test_invalid_calls_are_rejected() {
+    let (mut engine, parser) = engine_with_users();
+    let output = execute_line("SELECT NULLIF(score) FROM users", &mut engine, &parser);
+    assert_eq!(output, "Error: NULLIF takes exactly two arguments");
+    let output = execute_line("SELECT COALESCE() FROM users", &mut engine, &parser);
+    assert_eq!(output, "Error: COALESCE has an empty argument");
+    let output = execute_line("SELECT COALESCE(nick, name) FROM users", &mut engine, &parser);
+    assert_eq!(output, "Error: Column 'nick' not found in table 'users'");
+}
@@ -0,0 +1,64 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{Command, Parser};
+
+mod common;
+
+use common::temp_db;
+
+#[test]
+fn test_null_keyword_is_parsed_as_null() {
+    let parser = Parser::new();
+    assert_eq!(
+        parser.parse("INSERT INTO t VALUES (1, NULL, 'NULL', '')"),
+        Command::Insert {
+            table: "t".to_string(),
+            columns: None,
+            values: vec![vec![Some("1".to_string()), None, Some("NULL".to_string()), Some(String::new())]],
+            returning: None,
+        }
+    );
+}
+
+#[test]
+fn test_null_is_distinct_from_empty_string() {
+    let path = temp_db("null_literal");
+    let mut engine = QueryEngine::with_database(&path);
+    let parser = Parser::new();
+
+    execute_line("CREATE TABLE people (id INTEGER, nickname TEXT)", &mut engine, &parser);
+    execute_line("INSERT INTO people VALUES (1, NULL)", &mut engine, &parser);
+    execute_line("INSERT INTO people VALUES (2, '')", &mut engine, &parser);
+    execute_line("INSERT INTO people VALUES (3, 'Bob')", &mut engine, &parser);
+
+    // The distinction survives a reload from disk
+    drop(engine);
+    let mut engine = QueryEngine::with_database(&path);
+
+    let output = execute_line("SELECT id FROM people WHERE nickname IS NULL", &mut engine, &parser);
+    assert_eq!(output, "id\n--\n1");
+
+    let output = execute_line("SELECT id FROM people WHERE nickname IS NOT NULL", &mut engine, &parser);
+    assert_eq!(output, "id\n--\n2\n3");
+
+    let output = execute_line("SELECT id FROM people WHERE nickname = ''", &mut engine, &parser);
+    assert_eq!(output, "id\n--\n2");
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_equals_null_suggests_is_null() {
+    let path = temp_db("null_comparison");
+    let mut engine = QueryEngine::with_database(&path);
+    let parser = Parser::new();
+
+    execute_line("CREATE TABLE people (id INTEGER, nickname TEXT)", &mut engine, &parser);
+    execute_line("INSERT INTO people VALUES (1, NULL)", &mut engine, &parser);
+
+    let output = execute_line("SELECT id FROM people WHERE nickname = NULL", &mut engine, &parser);
+    assert!(output.starts_with("Error:"), "{}", output);
+    assert!(output.contains("nickname IS NULL"), "{}", output);
+
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,53 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::error::IsentaError;
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("isenta_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    path
+}
+
+#[test]
+fn test_opening_a_directory_returns_an_error() {
+    let dir = temp_dir("open_dir");
+
+    match QueryEngine::open(dir.to_str().unwrap()) {
+        Err(IsentaError::Io(message)) => assert!(message.contains("is a directory"), "{}", message),
+        Err(e) => panic!("unexpected error: {:?}", e),
+        Ok(_) => panic!("opening a directory should fail"),
+    }
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_cli_reports_unopenable_database_without_panicking() {
+    let dir = temp_dir("open_dir_cli");
+
+    let mut cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&dir).arg("SHOW TABLES");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("is a directory"))
+        .stderr(predicate::str::contains("panicked").not());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_opening_a_file_in_a_missing_directory_returns_an_error() {
+    let dir = temp_dir("open_missing_parent");
+    let path = dir.join("missing").join("data.db");
+
+    match QueryEngine::open(path.to_str().unwrap()) {
+        Err(IsentaError::Io(_)) => {}
+        Err(e) => panic!("unexpected error: {:?}", e),
+        Ok(_) => panic!("opening a file in a missing directory should fail"),
+    }
+    assert!(!path.exists());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
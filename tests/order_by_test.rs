@@ -0,0 +1,121 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{parse_to_ast, Command, OrderBy, Parser};
+use rust_dbms::storage::MEMORY_PATH;
+
+fn engine_with_products() -> (QueryEngine, Parser) {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE products (name TEXT, category TEXT, price INTEGER)", &mut engine, &parser);
+    for (name, category, price) in [
+        ("lamp", "'home'", "25"),
+        ("novel", "'books'", "12"),
+        ("chair", "'home'", "80"),
+        ("atlas", "'books'", "40"),
+        ("gift card", "NULL", "9"),
+        ("rug", "'home'", "100"),
+    ] {
+        execute_line(&format!("INSERT INTO products VALUES ('{}', {}, {})", name, category, price), &mut engine, &parser);
+    }
+    (engine, parser)
+}
+
+/// The values of the first result column.
+fn first_column(output: &str) -> Vec<&str> {
+    output.lines().skip(2).map(|line| line.split(" | ").next().unwrap()).collect()
+}
+
+#[test]
+fn test_parse_order_by_list() {
+    let Command::Select { order_by, where_clause, .. } =
+        parse_to_ast("SELECT * FROM products WHERE name != 'x ORDER BY y' ORDER BY category ASC, price desc, name")
+    else {
+        panic!("not a SELECT");
+    };
+    assert!(where_clause.is_some());
+    assert_eq!(
+        order_by,
+        vec![
+            OrderBy { column: "category".to_string(), ascending: true, nulls_first: None },
+            OrderBy { column: "price".to_string(), ascending: false, nulls_first: None },
+            OrderBy { column: "name".to_string(), ascending: true, nulls_first: None },
+        ]
+    );
+    assert!(matches!(parse_to_ast("SELECT * FROM products ORDER BY price SIDEWAYS"), Command::Unknown(_)));
+}
+
+#[test]
+fn test_secondary_key_breaks_ties() {
+    let (mut engine, parser) = engine_with_products();
+    let output = execute_line("SELECT name FROM products ORDER BY category ASC, price DESC", &mut engine, &parser);
+    // NULL sorts first; within each category the higher price comes first
+    assert_eq!(first_column(&output), vec!["gift card", "atlas", "novel", "rug", "chair", "lamp"]);
+}
+
+#[test]
+fn test_integer_keys_sort_numerically() {
+    let (mut engine, parser) = engine_with_products();
+    let output = execute_line("SELECT price, name FROM products WHERE price > 10 ORDER BY price", &mut engine, &parser);
+    assert_eq!(first_column(&output), vec!["12", "25", "40", "80", "100"]);
+}
+
+#[test]
+fn test_ties_on_every_key_keep_table_order() {
+    let (mut engine, parser) = engine_with_products();
+    let output = execute_line("SELECT name FROM products WHERE category = 'home' ORDER BY category DESC", &mut engine, &parser);
+    assert_eq!(first_column(&output), vec!["lamp", "chair", "rug"]);
+}
+
+#[test]
+fn test_order_by_unknown_column() {
+    let (mut engine, parser) = engine_with_products();
+    let output = execute_line("SELECT name FROM products ORDER BY weight", &mut engine, &parser);
+    assert_eq!(output, "Error: Column 'weight' not found in table 'products'");
+}
+
+#[test]
+fn test_insert_select_keeps_the_order() {
+    let (mut engine, parser) = engine_with_products();
+    execute_line("CREATE TABLE cheapest (name TEXT, category TEXT, price INTEGER)", &mut engine, &parser);
+    execute_line("INSERT INTO cheapest SELECT * FROM products ORDER BY price", &mut engine, &parser);
+    let output = execute_line("SELECT name FROM cheapest", &mut engine, &parser);
+    assert_eq!(first_column(&output), vec!["gift card", "novel", "lamp", "atlas", "chair", "rug"]);
+}
+
+#[test]
+fn test_parse_nulls_placement() {
+    let Command::Select { order_by, .. } =
+        parse_to_ast("SELECT * FROM products ORDER BY category DESC NULLS FIRST, price nulls last, name ASC")
+    else {
+        panic!("not a SELECT");
+    };
+    assert_eq!(
+        order_by,
+        vec![
+            OrderBy { column: "category".to_string(), ascending: false, nulls_first: Some(true) },
+            OrderBy { column: "price".to_string(), ascending: true, nulls_first: Some(false) },
+            OrderBy { column: "name".to_string(), ascending: true, nulls_first: None },
+        ]
+    );
+    assert!(matches!(parse_to_ast("SELECT * FROM products ORDER BY price NULLS MIDDLE"), Command::Unknown(_)));
+}
+
+#[test]
+fn test_nulls_last_when_ascending() {
+    let (mut engine, parser) = engine_with_products();
+    let output = execute_line("SELECT name FROM products ORDER BY category ASC NULLS LAST, price", &mut engine, &parser);
+    assert_eq!(first_column(&output), vec!["novel", "atlas", "lamp", "chair", "rug", "gift card"]);
+}
+
+#[test]
+fn test_nulls_first_when_descending() {
+    let (mut engine, parser) = engine_with_products();
+    let output = execute_line("SELECT name FROM products ORDER BY category DESC NULLS FIRST, price", &mut engine, &parser);
+    assert_eq!(first_column(&output), vec!["gift card", "lamp", "chair", "rug", "novel", "atlas"]);
+
+    // Without it, NULLs still come first when descending
+    let output = execute_line("SELECT name FROM products ORDER BY category DESC, price", &mut engine, &parser);
+    assert_eq!(first_column(&output), vec!["gift card", "lamp", "chair", "rug", "novel", "atlas"]);
+    let output = execute_line("SELECT name FROM products ORDER BY category DESC NULLS LAST, price", &mut engine, &parser);
+    assert_eq!(first_column(&output), vec!["lamp", "chair", "rug", "novel", "atlas", "gift card"]);
+}
@@ -0,0 +1,32 @@
+use rust_dbms::database::Database;
+use rust_dbms::log;
+use rust_dbms::storage::{MEMORY_PATH, PAGE_SIZE};
+
+mod common;
+
+use common::temp_db;
+
+#[test]
+fn test_first_allocation_after_initialization_is_page_1() {
+    let path = temp_db("page_allocation_first");
+    let mut db = Database::new(&path).unwrap();
+    assert_eq!(db.allocate_page().unwrap().id, 1);
+    assert_eq!(db.allocate_page().unwrap().id, 2);
+    drop(db);
+    let _ = std::fs::remove_file(&path);
+
+    let mut db = Database::new(MEMORY_PATH).unwrap();
+    assert_eq!(db.allocate_page().unwrap().id, 1);
+}
+
+#[test]
+fn test_short_file_never_hands_out_the_header_page() {
+    let path = temp_db("page_allocation_short");
+    std::fs::write(&path, vec![0u8; 100]).unwrap();
+    let mut db = Database::with_logger(&path, log::quiet()).unwrap();
+    // The 100 bytes would round down to page 0
+    assert_eq!(db.allocate_page().unwrap().id, 1);
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), 2 * PAGE_SIZE as u64);
+    drop(db);
+    let _ = std::fs::remove_file(&path);
+}
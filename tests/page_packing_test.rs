@@ -0,0 +1,66 @@
+use rust_dbms::database::Database;
+use rust_dbms::engine::{Row, Table};
+use rust_dbms::parser::Column;
+use rust_dbms::storage::PAGE_SIZE;
+
+mod common;
+
+use common::temp_db;
+
+fn text_row(rowid: u64, fill: char, len: usize) -> Row {
+    Row { rowid, values: vec![Some(fill.to_string().repeat(len))] }
+}
+
+/// A table whose rows need three data pages when packed strictly in order
+/// (large, large + small, small), but only two when the first small row may
+/// fill the gap after the first large row.
+fn blobs_table() -> Table {
+    Table {
+        name: "blobs".to_string(),
+        columns: vec![Column::new("body", "TEXT")],
+        rows: vec![text_row(1, 'a', 2490), text_row(2, 'b', 2490), text_row(3, 'c', 1495), text_row(4, 'd', 1495)],
+        created_at: 0,
+        updated_at: 0,
+        next_rowid: 5,
+        change_count: 0,
+    }
+}
+
+/// Saves `blobs_table()`, returning how many pages the file has and the
+/// first character of each row as loaded back.
+fn save_and_reload(path: &str) -> (u64, String) {
+    {
+        let mut db = Database::new(path).unwrap();
+        db.save_table(&blobs_table(), true).unwrap();
+    }
+    let pages = std::fs::metadata(path).unwrap().len() / PAGE_SIZE as u64;
+
+    let mut db = Database::new(path).unwrap();
+    let catalog = db.load_catalog().unwrap();
+    let loaded = catalog.find_table("blobs").unwrap();
+    let order = loaded.rows.iter().map(|row| row.values[0].as_ref().unwrap().chars().next().unwrap()).collect();
+    (pages, order)
+}
+
+#[test]
+fn test_smaller_rows_fill_gaps_left_by_large_rows() {
+    let path = temp_db("page_packing");
+    // Header page, schema page and two data pages, with the rows loaded in their original order
+    assert_eq!(save_and_reload(&path), (4, "abcd".to_string()));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_files_without_rowids_are_upgraded_before_packing() {
+    let path = temp_db("page_packing_old_version");
+    drop(Database::new(&path).unwrap());
+    // Mark the still empty file as version 4, which predates rowids
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes[8..12].copy_from_slice(&4u32.to_le_bytes());
+    std::fs::write(&path, bytes).unwrap();
+
+    // The rows are stored with rowids, which restore their order
+    assert_eq!(save_and_reload(&path), (4, "abcd".to_string()));
+    assert_eq!(std::fs::read(&path).unwrap()[8..12], 5u32.to_le_bytes());
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,82 @@
+use rust_dbms::database::Database;
+use rust_dbms::engine::{Row, Table};
+use rust_dbms::error::IsentaError;
+use rust_dbms::parser::Column;
+use rust_dbms::storage::PAGE_SIZE;
+
+mod common;
+
+use common::temp_db;
+
+/// Saves a one-column table, giving a header page (0), a schema page (1) and
+/// a data page (2).
+fn database_with_table(name: &str) -> String {
+    let path = temp_db(name);
+    let table = Table {
+        name: "people".to_string(),
+        columns: vec![Column::new("id", "INTEGER")],
+        rows: (1..=3).map(|id| Row { rowid: id, values: vec![Some(id.to_string())] }).collect(),
+        created_at: 0,
+        updated_at: 0,
+        next_rowid: 4,
+        change_count: 0,
+    };
+    let mut db = Database::new(&path).unwrap();
+    db.save_table(&table, true).unwrap();
+    path
+}
+
+fn patch(path: &str, offset: usize, bytes: &[u8]) {
+    let mut data = std::fs::read(path).unwrap();
+    data[offset..offset + bytes.len()].copy_from_slice(bytes);
+    std::fs::write(path, data).unwrap();
+}
+
+#[test]
+fn test_every_page_is_tagged_with_its_type() {
+    let path = database_with_table("page_type_tags");
+    let data = std::fs::read(&path).unwrap();
+    let tags: Vec<u8> = (1..=3).map(|page| data[page * PAGE_SIZE - 1]).collect();
+    // Header, schema and data pages
+    assert_eq!(tags, vec![1, 2, 3]);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_data_page_is_not_read_as_a_schema_page() {
+    let path = database_with_table("page_type_schema_root");
+    // Point the schema root (header offset 12) at the data page
+    patch(&path, 12, &2u64.to_le_bytes());
+
+    let mut db = Database::new(&path).unwrap();
+    let catalog = db.load_catalog().unwrap();
+    assert!(catalog.get_all_tables().is_empty());
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_schema_page_is_not_read_as_a_data_page() {
+    let path = database_with_table("page_type_data_pointer");
+    // The data page ID follows the name (4 + 6 bytes), the column count (4)
+    // and the column "id INTEGER" (4 + 2 + 4 + 7, then 1 byte of constraint
+    // flags); point it at the schema page
+    patch(&path, PAGE_SIZE + 32, &1u64.to_le_bytes());
+
+    let mut db = Database::new(&path).unwrap();
+    let catalog = db.load_catalog().unwrap();
+    let table = catalog.find_table("people").unwrap();
+    assert!(table.rows.is_empty());
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_header_page_with_wrong_tag_is_rejected() {
+    let path = database_with_table("page_type_header");
+    patch(&path, PAGE_SIZE - 1, &[3]);
+
+    match Database::new(&path) {
+        Err(IsentaError::Corruption(message)) => assert!(message.contains("page 0 is a data page"), "{}", message),
+        other => panic!("expected a corruption error, got {:?}", other.err()),
+    }
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,108 @@
+use rust_dbms::parser::{parse_to_ast, Column, Command, SelectItem, WhereClause, WhereExpr};
+
+fn column(name: &str, data_type: &str) -> Column {
+    Column::new(name, data_type)
+}
+
+fn condition(column: &str, operator: &str, value: Option<&str>, quoted: bool) -> WhereExpr {
+    WhereExpr::Condition(WhereClause {
+        column: column.to_string(),
+        operator: operator.to_string(),
+        value: value.map(str::to_string),
+        quoted,
+    })
+}
+
+#[test]
+fn test_parse_create_table() {
+    assert_eq!(
+        parse_to_ast("CREATE TABLE USERS (ID INTEGER, NAME text, NOTE, PRIMARY KEY (ID))"),
+        Command::CreateTable {
+            name: "USERS".to_string(),
+            columns: vec![column("ID", "INTEGER"), column("NAME", "TEXT"), column("NOTE", "TEXT")],
+        }
+    );
+}
+
+#[test]
+fn test_parse_insert() {
+    assert_eq!(
+        parse_to_ast("INSERT INTO users VALUES (1, 'Alice', NULL, 'NULL')"),
+        Command::Insert {
+            table: "users".to_string(),
+            columns: None,
+            values: vec![vec![Some("1".to_string()), Some("Alice".to_string()), None, Some("NULL".to_string())]],
+            returning: None,
+        }
+    );
+}
+
+#[test]
+fn test_parse_select_projection() {
+    assert_eq!(
+        parse_to_ast("SELECT name, *, 'x', 42, NULL FROM users"),
+        Command::Select {
+            table: "users".to_string(),
+            columns: vec![
+                SelectItem::Column("name".to_string()),
+                SelectItem::Wildcard,
+                SelectItem::Literal(Some("x".to_string())),
+                SelectItem::Literal(Some("42".to_string())),
+                SelectItem::Literal(None),
+            ],
+            where_clause: None,
+            group_by: None,
+            order_by: Vec::new(),
+            limit: None,
+            offset: None,
+        }
+    );
+}
+
+#[test]
+fn test_parse_select_where_operators() {
+    let cases = [
+        ("age >= 18", condition("age", ">=", Some("18"), false)),
+        ("age != 18", condition("age", "!=", Some("18"), false)),
+        ("name LIKE 'A%'", condition("name", "LIKE", Some("A%"), true)),
+        ("name NOT LIKE 'A%'", condition("name", "NOT LIKE", Some("A%"), true)),
+        ("name IS NULL", condition("name", "IS NULL", None, false)),
+        ("name IS NOT NULL", condition("name", "IS NOT NULL", None, false)),
+    ];
+    for (where_sql, expected) in cases {
+        match parse_to_ast(&format!("SELECT * FROM users WHERE {}", where_sql)) {
+            Command::Select { where_clause, .. } => assert_eq!(where_clause, Some(expected), "{}", where_sql),
+            other => panic!("unexpected command for {}: {:?}", where_sql, other),
+        }
+    }
+}
+
+#[test]
+fn test_parse_update() {
+    assert_eq!(
+        parse_to_ast("UPDATE users SET name = 'Bob', age = NULL WHERE id = 1 LIMIT 2;"),
+        Command::Update {
+            table: "users".to_string(),
+            assignments: vec![("name".to_string(), Some("Bob".to_string())), ("age".to_string(), None)],
+            where_clause: Some(condition("id", "=", Some("1"), false)),
+            limit: Some(2),
+        }
+    );
+}
+
+#[test]
+fn test_parse_table_commands() {
+    assert_eq!(parse_to_ast("TRUNCATE TABLE USERS"), Command::Truncate { table: "USERS".to_string() });
+    assert_eq!(
+        parse_to_ast("GET USERS AS JSON"),
+        Command::Get { table: "USERS".to_string(), format: "JSON".to_string() }
+    );
+    assert_eq!(parse_to_ast("INSPECT USERS"), Command::InspectTable { name: "USERS".to_string() });
+    assert_eq!(parse_to_ast("show tables"), Command::ShowTables { pattern: None });
+}
+
+#[test]
+fn test_parse_unknown_statements() {
+    assert_eq!(parse_to_ast("EXPLODE users"), Command::Unknown("EXPLODE users".to_string()));
+    assert!(matches!(parse_to_ast("UPDATE users SET name = 'x' LIMIT -1"), Command::Unknown(_)));
+}
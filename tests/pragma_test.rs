@@ -0,0 +1,125 @@
+use rust_dbms::engine::{ColumnCountMode, QueryEngine};
+use rust_dbms::execute_line;
+use rust_dbms::parser::{Command, Parser};
+use rust_dbms::storage::MEMORY_PATH;
+
+#[test]
+fn test_parse_pragma() {
+    let parser = Parser::new();
+    assert_eq!(
+        parser.parse("PRAGMA Strict = off"),
+        Command::Pragma { name: "strict".to_string(), value: Some("off".to_string()) }
+    );
+    assert_eq!(
+        parser.parse("pragma null_string = '<null>';"),
+        Command::Pragma { name: "null_string".to_string(), value: Some("<null>".to_string()) }
+    );
+    assert_eq!(parser.parse("PRAGMA timing"), Command::Pragma { name: "timing".to_string(), value: None });
+}
+
+#[test]
+fn test_pragmas_change_settings() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+
+    assert_eq!(execute_line("PRAGMA strict", &mut engine, &parser), "strict = on");
+    assert_eq!(execute_line("PRAGMA strict = off", &mut engine, &parser), "strict = off");
+    assert_eq!(engine.column_count_mode(), ColumnCountMode::Lenient);
+
+    assert_eq!(execute_line("PRAGMA null_string = '-'", &mut engine, &parser), "null_string = '-'");
+    assert_eq!(engine.null_string(), "-");
+
+    assert_eq!(execute_line("PRAGMA timing = on", &mut engine, &parser).lines().next(), Some("timing = on"));
+    assert!(engine.timing());
+}
+
+#[test]
+fn test_timing_is_reported_after_each_statement() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("PRAGMA timing = on", &mut engine, &parser);
+
+    let output = execute_line("SHOW TABLES", &mut engine, &parser);
+    let last = output.lines().last().unwrap();
+    assert!(last.starts_with("Time: ") && last.ends_with(" ms"), "{}", output);
+
+    execute_line("PRAGMA timing = off", &mut engine, &parser);
+    assert_eq!(execute_line("SHOW TABLES", &mut engine, &parser), "No tables in database");
+}
+
+#[test]
+fn test_invalid_pragmas_are_errors() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+
+    let output = execute_line("PRAGMA cache_size = 10", &mut engine, &parser);
+    assert!(output.starts_with("Error: Unknown pragma 'cache_size'"), "{}", output);
+
+    let output = execute_line("PRAGMA strict = maybe", &mut engine, &parser);
+    assert!(output.contains("expected on or off"), "{}", output);
+    assert_eq!(engine.column_count_mode(), ColumnCountMode::Strict);
+}
+
+#[test]
+fn test_short_inserts_are_rejected_unless_relaxed() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE notes (id INTEGER, body TEXT, tag TEXT)", &mut engine, &parser);
+
+    let output = execute_line("INSERT INTO notes VALUES (1)", &mut engine, &parser);
+    assert_eq!(output, "Error: Column count mismatch: expected 3, got 1");
+
+    assert_eq!(execute_line("PRAGMA relaxed_insert = on", &mut engine, &parser), "relaxed_insert = on");
+    assert!(engine.relaxed_insert());
+    execute_line("INSERT INTO notes VALUES (1)", &mut engine, &parser);
+    execute_line("INSERT INTO notes VALUES (2, 'two')", &mut engine, &parser);
+    let output = execute_line("SELECT * FROM notes", &mut engine, &parser);
+    assert_eq!(output, "id | body | tag\n---------------\n1 | NULL | NULL\n2 | two | NULL");
+
+    // Extra values are still an error
+    let output = execute_line("INSERT INTO notes VALUES (3, 'a', 'b', 'c')", &mut engine, &parser);
+    assert_eq!(output, "Error: Column count mismatch: expected 3, got 4");
+
+    execute_line("PRAGMA relaxed_insert = off", &mut engine, &parser);
+    let output = execute_line("INSERT INTO notes VALUES (4)", &mut engine, &parser);
+    assert!(output.starts_with("Error: Column count mismatch"), "{}", output);
+}
+
+#[test]
+fn test_relaxed_padding_still_checks_not_null() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE people (id INTEGER, name TEXT NOT NULL)", &mut engine, &parser);
+    engine.set_relaxed_insert(true);
+
+    let output = execute_line("INSERT INTO people VALUES (1)", &mut engine, &parser);
+    assert!(output.starts_with("Error:") && output.contains("NOT NULL"), "{}", output);
+    assert_eq!(execute_line("SELECT * FROM people", &mut engine, &parser), "No rows found in 'people'");
+}
+
+#[test]
+fn test_max_rows_caps_rendered_rows_with_a_notice() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE numbers (n INTEGER)", &mut engine, &parser);
+    for n in 1..=5 {
+        execute_line(&format!("INSERT INTO numbers VALUES ({})", n), &mut engine, &parser);
+    }
+
+    assert_eq!(execute_line("PRAGMA max_rows", &mut engine, &parser), "max_rows = off");
+    assert_eq!(execute_line("PRAGMA max_rows = 2", &mut engine, &parser), "max_rows = 2");
+    let output = execute_line("SELECT n FROM numbers", &mut engine, &parser);
+    assert_eq!(output, "n\n-\n1\n2\n... (3 more rows not shown)");
+
+    // A result within the cap shows no notice
+    let output = execute_line("SELECT n FROM numbers WHERE n <= 2", &mut engine, &parser);
+    assert_eq!(output, "n\n-\n1\n2");
+    // The cap only affects rendering
+    let output = execute_line("SELECT COUNT(*) FROM numbers", &mut engine, &parser);
+    assert_eq!(output.lines().last(), Some("5"));
+
+    assert_eq!(execute_line("PRAGMA max_rows = off", &mut engine, &parser), "max_rows = off");
+    assert_eq!(execute_line("SELECT n FROM numbers", &mut engine, &parser).lines().count(), 7);
+    let output = execute_line("PRAGMA max_rows = many", &mut engine, &parser);
+    assert!(output.starts_with("Error: Invalid value 'many'"), "{}", output);
+}
@@ -0,0 +1,89 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+use rust_dbms::storage::PAGE_SIZE;
+
+mod common;
+
+use common::temp_db;
+
+/// Inserts rows of about 200 bytes each, roughly 19 to a data page.
+fn insert_rows(engine: &mut QueryEngine, table: &str, ids: std::ops::Range<u32>) {
+    for id in ids {
+        let values = vec![Some(id.to_string()), Some(format!("{:0>200}", id))];
+        engine.execute_insert(table.to_string(), values).unwrap();
+    }
+}
+
+fn page_count(engine: &mut QueryEngine) -> u64 {
+    engine.file_info().unwrap().page_count
+}
+
+/// The type tags of the pages from `first` to `last`.
+fn page_tags(path: &str, first: usize, last: usize) -> Vec<u8> {
+    let data = std::fs::read(path).unwrap();
+    (first..=last).map(|page| data[(page + 1) * PAGE_SIZE - 1]).collect()
+}
+
+#[test]
+fn test_preallocated_pages_are_filled_in_order() {
+    let path = temp_db("preallocation_fill");
+    let parser = Parser::new();
+    {
+        let mut engine = QueryEngine::with_database(&path);
+        assert_eq!(execute_line("PRAGMA preallocate_pages = 6", &mut engine, &parser), "preallocate_pages = 6");
+        execute_line("CREATE TABLE logs (id INTEGER, message TEXT)", &mut engine, &parser);
+        // Header, schema page and six data pages
+        assert_eq!(page_count(&mut engine), 8);
+
+        execute_line("CREATE TABLE other (id INTEGER, message TEXT)", &mut engine, &parser);
+        insert_rows(&mut engine, "other", 0..1);
+        let pages = page_count(&mut engine);
+
+        // The rows spill onto the preallocated pages, not onto the end of the file
+        insert_rows(&mut engine, "logs", 0..50);
+        assert_eq!(page_count(&mut engine), pages);
+        engine.close().unwrap();
+    }
+    // Three data pages in use, then three still free
+    assert_eq!(page_tags(&path, 2, 7), vec![3, 3, 3, 4, 4, 4]);
+
+    let mut engine = QueryEngine::with_database(&path);
+    let output = execute_line("SELECT COUNT(*) FROM logs", &mut engine, &parser);
+    assert_eq!(output.lines().last(), Some("50"));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_rewrites_reuse_the_existing_data_pages() {
+    let path = temp_db("preallocation_reuse");
+    let parser = Parser::new();
+    let mut engine = QueryEngine::with_database(&path);
+    execute_line("CREATE TABLE logs (id INTEGER, message TEXT)", &mut engine, &parser);
+    insert_rows(&mut engine, "logs", 0..50);
+    let pages = page_count(&mut engine);
+
+    // Every insert rewrites the table's chain in place
+    insert_rows(&mut engine, "logs", 50..52);
+    assert_eq!(page_count(&mut engine), pages);
+
+    // Pages a shrunk table no longer needs are freed and taken back as it grows
+    execute_line("TRUNCATE TABLE logs", &mut engine, &parser);
+    // The first freed pages also start the free-page list
+    assert_eq!(page_count(&mut engine), pages + 1);
+    insert_rows(&mut engine, "logs", 0..50);
+    assert_eq!(page_count(&mut engine), pages + 1);
+    let output = execute_line("SELECT COUNT(*) FROM logs", &mut engine, &parser);
+    assert_eq!(output.lines().last(), Some("50"));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_preallocate_pages_must_be_positive() {
+    let mut engine = QueryEngine::with_database(":memory:");
+    let parser = Parser::new();
+    assert_eq!(execute_line("PRAGMA preallocate_pages", &mut engine, &parser), "preallocate_pages = 1");
+    let output = execute_line("PRAGMA preallocate_pages = 0", &mut engine, &parser);
+    assert!(output.starts_with("Error: Invalid value '0'"), "{}", output);
+    assert_eq!(engine.data_page_preallocation(), 1);
+}
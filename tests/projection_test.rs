@@ -0,0 +1,59 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{Command, Parser, SelectItem};
+
+mod common;
+
+use common::temp_db;
+
+#[test]
+fn test_projection_items_are_parsed() {
+    let parser = Parser::new();
+    match parser.parse("SELECT id, id, 'label', 42, NULL FROM t") {
+        Command::Select { columns, .. } => assert_eq!(
+            columns,
+            vec![
+                SelectItem::Column("id".to_string()),
+                SelectItem::Column("id".to_string()),
+                SelectItem::Literal(Some("label".to_string())),
+                SelectItem::Literal(Some("42".to_string())),
+                SelectItem::Literal(None),
+            ]
+        ),
+        other => panic!("unexpected command: {:?}", other),
+    }
+}
+
+#[test]
+fn test_select_repeated_and_constant_columns() {
+    let path = temp_db("projection");
+    let mut engine = QueryEngine::with_database(&path);
+    let parser = Parser::new();
+
+    execute_line("CREATE TABLE items (id INTEGER, name TEXT)", &mut engine, &parser);
+    execute_line("INSERT INTO items VALUES (1, 'Pen')", &mut engine, &parser);
+    execute_line("INSERT INTO items VALUES (2, 'Ink')", &mut engine, &parser);
+
+    let output = execute_line("SELECT id, id, 'label' FROM items", &mut engine, &parser);
+    assert_eq!(output, "id | id | label\n---------------\n1 | 1 | label\n2 | 2 | label");
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_wildcard_expands_in_place_next_to_other_columns() {
+    let path = temp_db("projection_wildcard");
+    let mut engine = QueryEngine::with_database(&path);
+    let parser = Parser::new();
+
+    execute_line("CREATE TABLE items (id INTEGER, name TEXT)", &mut engine, &parser);
+    execute_line("INSERT INTO items VALUES (1, 'Pen')", &mut engine, &parser);
+
+    let output = execute_line("SELECT *, id FROM items", &mut engine, &parser);
+    assert_eq!(output, "id | name | id\n--------------\n1 | Pen | 1");
+
+    let output = execute_line("SELECT 'x', * FROM items", &mut engine, &parser);
+    assert_eq!(output, "x | id | name\n-------------\nx | 1 | Pen");
+
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,46 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{Parser, SelectItem};
+
+mod common;
+
+use common::temp_db;
+
+#[test]
+fn test_result_rows_are_accessible_by_column_name() {
+    let path = temp_db("query_result");
+    let mut engine = QueryEngine::with_database(&path);
+    let parser = Parser::new();
+
+    execute_line("CREATE TABLE products (id INTEGER, name TEXT, price TEXT)", &mut engine, &parser);
+    execute_line("INSERT INTO products VALUES (1, 'Pen', '1.25')", &mut engine, &parser);
+    execute_line("INSERT INTO products VALUES (2, NULL, 'n/a')", &mut engine, &parser);
+
+    let result = engine
+        .execute_select(
+            "products".to_string(),
+            vec![
+                SelectItem::Column("price".to_string()),
+                SelectItem::Column("id".to_string()),
+                SelectItem::Column("name".to_string()),
+            ],
+            None,
+        )
+        .unwrap();
+    assert_eq!(result.columns(), ["price", "id", "name"]);
+
+    let rows: Vec<_> = result.rows().collect();
+    assert_eq!(rows.len(), 2);
+
+    assert_eq!(rows[0].get("name"), Some("Pen"));
+    assert_eq!(rows[0].get("NAME"), Some("Pen"));
+    assert_eq!(rows[0].get_int("id"), Some(1));
+    assert_eq!(rows[0].get_float("price"), Some(1.25));
+
+    // NULLs, unparseable values and unknown columns all come back as None
+    assert_eq!(rows[1].get("name"), None);
+    assert_eq!(rows[1].get_float("price"), None);
+    assert_eq!(rows[1].get("missing"), None);
+
+    let _ = std::fs::remove_file(&path);
+}
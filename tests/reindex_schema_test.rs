@@ -0,0 +1,68 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{parse_to_ast, Command, Parser};
+use rust_dbms::storage::PAGE_SIZE;
+
+mod common;
+
+use common::temp_db;
+
+fn patch(path: &str, offset: usize, bytes: &[u8]) {
+    let mut data = std::fs::read(path).unwrap();
+    data[offset..offset + bytes.len()].copy_from_slice(bytes);
+    std::fs::write(path, data).unwrap();
+}
+
+/// Creates three tables with a row each: schema pages 1, 3 and 5.
+fn database_with_tables(name: &str) -> String {
+    let path = temp_db(name);
+    let mut engine = QueryEngine::with_database(&path);
+    let parser = Parser::new();
+    for table in ["a", "b", "c"] {
+        execute_line(&format!("CREATE TABLE {} (id INTEGER)", table), &mut engine, &parser);
+        execute_line(&format!("INSERT INTO {} VALUES (1)", table), &mut engine, &parser);
+    }
+    path
+}
+
+#[test]
+fn test_parse_reindex_schema() {
+    assert_eq!(parse_to_ast("reindex  schema;"), Command::ReindexSchema);
+    assert!(matches!(parse_to_ast("REINDEX SCHEMA now"), Command::Unknown(_)));
+}
+
+#[test]
+fn test_reindex_relinks_tables_lost_from_the_chain() {
+    let path = database_with_tables("reindex_schema");
+    // Point the schema root (header offset 12) past the first table and
+    // claim a single table, losing "a" and "c"
+    patch(&path, 12, &3u64.to_le_bytes());
+    patch(&path, 20, &1u32.to_le_bytes());
+
+    let parser = Parser::new();
+    let mut engine = QueryEngine::with_database(&path);
+    assert_eq!(execute_line("SHOW TABLES", &mut engine, &parser), "Tables:\n- b");
+
+    assert_eq!(execute_line("REINDEX SCHEMA", &mut engine, &parser), "Schema chain rebuilt: 3 tables relinked");
+    assert_eq!(execute_line("SHOW TABLES", &mut engine, &parser), "Tables:\n- a\n- b\n- c");
+    assert_eq!(execute_line("SELECT id FROM c", &mut engine, &parser), "id\n--\n1");
+    drop(engine);
+
+    // The repaired chain is stored in the file
+    let mut engine = QueryEngine::with_database(&path);
+    assert_eq!(execute_line("SHOW TABLES", &mut engine, &parser), "Tables:\n- a\n- b\n- c");
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_reindex_ignores_pages_that_are_not_schema_pages() {
+    let path = database_with_tables("reindex_schema_tags");
+    // Retag table "b"'s schema page as a free page
+    patch(&path, 4 * PAGE_SIZE - 1, &[4]);
+
+    let parser = Parser::new();
+    let mut engine = QueryEngine::with_database(&path);
+    assert_eq!(execute_line("REINDEX SCHEMA", &mut engine, &parser), "Schema chain rebuilt: 2 tables relinked");
+    assert_eq!(execute_line("SHOW TABLES", &mut engine, &parser), "Tables:\n- a\n- c");
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,85 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+
+mod common;
+
+use common::temp_db;
+
+#[test]
+fn test_renamed_column_keeps_its_constraints_and_values() {
+    let path = temp_db("rename_column");
+    let parser = Parser::new();
+    {
+        let mut engine = QueryEngine::with_database(&path);
+        execute_line("CREATE TABLE users (id INTEGER NOT NULL, name TEXT DEFAULT 'anon')", &mut engine, &parser);
+        execute_line("INSERT INTO users VALUES (2, 'bob')", &mut engine, &parser);
+        execute_line("INSERT INTO users VALUES (1, 'alice')", &mut engine, &parser);
+
+        let output = execute_line("ALTER TABLE users RENAME COLUMN id TO user_id", &mut engine, &parser);
+        assert_eq!(output, "Column 'id' of 'users' renamed to 'user_id'");
+        engine.close().unwrap();
+    }
+
+    // The rename is persisted, and the renamed column is used as before
+    let mut engine = QueryEngine::with_database(&path);
+    let output = execute_line("SELECT name FROM users WHERE user_id >= 1 ORDER BY user_id", &mut engine, &parser);
+    assert_eq!(output, "name\n----\nalice\nbob");
+    let output = execute_line("INSERT INTO users (name) VALUES ('carol')", &mut engine, &parser);
+    assert!(output.contains("NOT NULL"), "{}", output);
+    execute_line("INSERT INTO users (user_id) VALUES (3)", &mut engine, &parser);
+    let output = execute_line("SELECT name FROM users WHERE user_id = 3", &mut engine, &parser);
+    assert_eq!(output.lines().last(), Some("anon"));
+
+    let output = execute_line("SELECT id FROM users", &mut engine, &parser);
+    assert!(output.starts_with("Error:"), "{}", output);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_renamed_table_is_found_under_its_new_name() {
+    let path = temp_db("rename_table");
+    let parser = Parser::new();
+    {
+        let mut engine = QueryEngine::with_database(&path);
+        execute_line("CREATE TABLE first (id INTEGER)", &mut engine, &parser);
+        execute_line("CREATE TABLE second (id INTEGER)", &mut engine, &parser);
+        execute_line("INSERT INTO first VALUES (1)", &mut engine, &parser);
+
+        let output = execute_line("ALTER TABLE first RENAME TO primary_table", &mut engine, &parser);
+        assert_eq!(output, "Table 'first' renamed to 'primary_table'");
+        execute_line("INSERT INTO primary_table VALUES (2)", &mut engine, &parser);
+        engine.close().unwrap();
+    }
+
+    let mut engine = QueryEngine::with_database(&path);
+    assert_eq!(execute_line("SHOW TABLES", &mut engine, &parser), "Tables:\n- primary_table\n- second");
+    let output = execute_line("SELECT COUNT(*) FROM primary_table", &mut engine, &parser);
+    assert_eq!(output.lines().last(), Some("2"));
+    let output = execute_line("SELECT * FROM first", &mut engine, &parser);
+    assert!(output.starts_with("Error:"), "{}", output);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_rename_rejects_ambiguous_names() {
+    let mut engine = QueryEngine::with_database(":memory:");
+    let parser = Parser::new();
+    execute_line("CREATE TABLE a (id INTEGER, name TEXT)", &mut engine, &parser);
+    execute_line("CREATE TABLE b (id INTEGER)", &mut engine, &parser);
+
+    let output = execute_line("ALTER TABLE a RENAME TO B", &mut engine, &parser);
+    assert!(output.starts_with("Error:") && output.contains("already exists"), "{}", output);
+    let output = execute_line("ALTER TABLE a RENAME COLUMN name TO ID", &mut engine, &parser);
+    assert_eq!(output, "Error: Column 'ID' already exists in table 'a'");
+    let output = execute_line("ALTER TABLE a RENAME COLUMN name TO _rowid", &mut engine, &parser);
+    assert!(output.starts_with("Error:"), "{}", output);
+    let output = execute_line("ALTER TABLE a RENAME COLUMN missing TO other", &mut engine, &parser);
+    assert!(output.starts_with("Error:"), "{}", output);
+
+    // Changing only the case of a name is not a clash
+    let output = execute_line("ALTER TABLE a RENAME COLUMN name TO Name", &mut engine, &parser);
+    assert_eq!(output, "Column 'name' of 'a' renamed to 'Name'");
+    let output = execute_line("ALTER TABLE a RENAME TO A", &mut engine, &parser);
+    assert_eq!(output, "Table 'a' renamed to 'A'");
+}
@@ -0,0 +1,48 @@
+use rust_dbms::database::Database;
+use rust_dbms::engine::{Row, Table};
+use rust_dbms::parser::Column;
+
+mod common;
+
+use common::temp_db;
+
+fn row(id: &str, name: &str) -> Row {
+    Row { rowid: id.parse().unwrap(), values: vec![Some(id.to_string()), Some(name.to_string())] }
+}
+
+#[test]
+fn test_corrupt_row_is_skipped_and_later_rows_still_load() {
+    let path = temp_db("row_decoding");
+    let table = Table {
+        name: "people".to_string(),
+        columns: vec![
+            Column::new("id", "INTEGER"),
+            Column::new("name", "TEXT"),
+        ],
+        rows: vec![row("1", "alpha"), row("2", "BROKEN"), row("3", "gamma")],
+        created_at: 0,
+        updated_at: 0,
+        next_rowid: 1,
+        change_count: 0,
+    };
+
+    {
+        let mut db = Database::new(&path).unwrap();
+        db.save_table(&table, true).unwrap();
+    }
+
+    // Turn the middle row's text into a reference to a dictionary entry
+    // that doesn't exist; the tag byte precedes the 4-byte length
+    let mut bytes = std::fs::read(&path).unwrap();
+    let pos = bytes.windows(6).position(|w| w == b"BROKEN").unwrap();
+    bytes[pos - 5] = 3;
+    std::fs::write(&path, bytes).unwrap();
+
+    let mut db = Database::new(&path).unwrap();
+    let catalog = db.load_catalog().unwrap();
+    let loaded = catalog.find_table("people").unwrap();
+    let ids: Vec<&str> = loaded.rows.iter().map(|r| r.values[0].as_deref().unwrap()).collect();
+    assert_eq!(ids, vec!["1", "3"]);
+
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,93 @@
+use rust_dbms::database::Database;
+use rust_dbms::engine::{QueryEngine, Row, Table};
+use rust_dbms::execute_line;
+use rust_dbms::parser::{Column, Parser};
+
+mod common;
+
+use common::temp_db;
+
+/// Saves a table `items` holding a row with each of `rowids`, its `id`
+/// the row's position, with its rowid counter at `next_rowid`.
+fn database_with_rowids(name: &str, rowids: &[u64], next_rowid: u64) -> String {
+    let path = temp_db(name);
+    let table = Table {
+        name: "items".to_string(),
+        columns: vec![Column::new("id", "INTEGER")],
+        rows: (1..).zip(rowids).map(|(id, &rowid)| Row { rowid, values: vec![Some(id.to_string())] }).collect(),
+        created_at: 0,
+        updated_at: 0,
+        next_rowid,
+        change_count: 0,
+    };
+    let mut db = Database::new(&path).unwrap();
+    db.save_table(&table, true).unwrap();
+    path
+}
+
+#[test]
+fn test_counter_is_bumped_past_stored_rowids() {
+    let path = database_with_rowids("rowid_counter_bump", &[1, 5], 2);
+    let parser = Parser::new();
+    let mut engine = QueryEngine::with_database(&path);
+    assert_eq!(execute_line("INSERT INTO items VALUES (0)", &mut engine, &parser), "Inserted 1 row into 'items'");
+    let output = execute_line("SELECT _rowid, id FROM items", &mut engine, &parser);
+    assert_eq!(output, "_rowid | id\n-----------\n1 | 1\n5 | 2\n6 | 0");
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_exhausted_counter_is_an_error() {
+    let path = database_with_rowids("rowid_counter_exhausted", &[u64::MAX - 2], u64::MAX - 1);
+    let parser = Parser::new();
+    let mut engine = QueryEngine::with_database(&path);
+    assert_eq!(execute_line("INSERT INTO items VALUES (7)", &mut engine, &parser), "Inserted 1 row into 'items'");
+
+    // The last rowid would leave nothing for the counter to move on to
+    let exhausted = "Error: auto-increment counter exhausted for table 'items'";
+    assert_eq!(execute_line("INSERT INTO items VALUES (2)", &mut engine, &parser), exhausted);
+    assert_eq!(execute_line("INSERT INTO items SELECT id FROM items", &mut engine, &parser), exhausted);
+    assert_eq!(execute_line("SELECT id FROM items", &mut engine, &parser), "id\n--\n1\n7");
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_insert_select_can_take_the_last_rowids() {
+    let path = database_with_rowids("rowid_counter_last", &[5], u64::MAX - 1);
+    let parser = Parser::new();
+    let mut engine = QueryEngine::with_database(&path);
+    // The copied row takes rowid u64::MAX - 1, leaving the counter at u64::MAX
+    assert_eq!(execute_line("INSERT INTO items SELECT id FROM items", &mut engine, &parser), "Inserted 1 rows into 'items'");
+    let output = execute_line("SELECT _rowid, id FROM items", &mut engine, &parser);
+    assert_eq!(output, format!("_rowid | id\n-----------\n5 | 1\n{} | 1", u64::MAX - 1));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_counter_is_bumped_past_an_inserted_rowid() {
+    let path = database_with_rowids("rowid_counter_given", &[1], 2);
+    let parser = Parser::new();
+    {
+        let mut engine = QueryEngine::with_database(&path);
+        assert_eq!(
+            execute_line("INSERT INTO items (_rowid, id) VALUES (100, 2)", &mut engine, &parser),
+            "Inserted 1 row into 'items'"
+        );
+        assert_eq!(execute_line("INSERT INTO items VALUES (3)", &mut engine, &parser), "Inserted 1 row into 'items'");
+        // A rowid below the counter is taken without moving it back
+        assert_eq!(
+            execute_line("INSERT INTO items (id, _rowid) VALUES (4, 50), (5, NULL)", &mut engine, &parser),
+            "Inserted 2 rows into 'items'"
+        );
+        let output = execute_line("INSERT INTO items (_rowid, id) VALUES (50, 6)", &mut engine, &parser);
+        assert_eq!(output, "Error: rowid 50 is already in use in table 'items'");
+        let output = execute_line("INSERT INTO items (_rowid, id) VALUES (0, 6)", &mut engine, &parser);
+        assert_eq!(output, "Error: Invalid value for column '_rowid': '0' is not a valid rowid");
+    }
+    let mut engine = QueryEngine::with_database(&path);
+    let output = execute_line("INSERT INTO items VALUES (7) RETURNING _rowid", &mut engine, &parser);
+    assert_eq!(output, "_rowid\n------\n103");
+    let output = execute_line("SELECT _rowid, id FROM items", &mut engine, &parser);
+    assert_eq!(output, "_rowid | id\n-----------\n1 | 1\n50 | 4\n100 | 2\n101 | 3\n102 | 5\n103 | 7");
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,138 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{Parser, SelectItem};
+use rust_dbms::storage::PAGE_SIZE;
+
+mod common;
+
+use common::temp_db;
+
+#[test]
+fn test_rowid_is_selectable_and_filterable() {
+    let path = temp_db("rowid_select");
+    let parser = Parser::new();
+    {
+        let mut engine = QueryEngine::with_database(&path);
+        execute_line("CREATE TABLE items (name TEXT)", &mut engine, &parser);
+        for name in ["a", "b", "c"] {
+            execute_line(&format!("INSERT INTO items VALUES ('{}')", name), &mut engine, &parser);
+        }
+    }
+
+    // Rowids survive a reload and aren't part of *
+    let mut engine = QueryEngine::with_database(&path);
+    assert_eq!(execute_line("SELECT * FROM items WHERE _rowid = 2", &mut engine, &parser), "name\n----\nb");
+    assert_eq!(
+        execute_line("SELECT _rowid, name FROM items WHERE _rowid >= 2", &mut engine, &parser),
+        "_rowid | name\n-------------\n2 | b\n3 | c"
+    );
+
+    execute_line("UPDATE items SET name = 'z' WHERE _rowid = 1", &mut engine, &parser);
+    let result = engine.execute_select("items".to_string(), vec![SelectItem::Wildcard], None).unwrap();
+    let rows: Vec<(u64, &str)> = result.rows().map(|row| (row.rowid(), row.get("name").unwrap())).collect();
+    assert_eq!(rows, vec![(1, "z"), (2, "b"), (3, "c")]);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_rowids_are_not_reused() {
+    let path = temp_db("rowid_reuse");
+    let parser = Parser::new();
+    {
+        let mut engine = QueryEngine::with_database(&path);
+        execute_line("CREATE TABLE items (name TEXT)", &mut engine, &parser);
+        execute_line("INSERT INTO items VALUES ('a')", &mut engine, &parser);
+        execute_line("INSERT INTO items VALUES ('b')", &mut engine, &parser);
+        execute_line("TRUNCATE TABLE items", &mut engine, &parser);
+    }
+
+    let mut engine = QueryEngine::with_database(&path);
+    execute_line("INSERT INTO items VALUES ('c')", &mut engine, &parser);
+    assert_eq!(execute_line("SELECT _rowid FROM items", &mut engine, &parser), "_rowid\n------\n3");
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_rows_reload_in_insertion_order() {
+    let path = temp_db("rowid_order");
+    let parser = Parser::new();
+    {
+        let mut engine = QueryEngine::with_database(&path);
+        execute_line("CREATE TABLE blobs (id INTEGER, body TEXT)", &mut engine, &parser);
+        // Page packing stores the third row next to the first one
+        for (id, fill, len) in [(1, 'a', 2490), (2, 'b', 2490), (3, 'c', 1495), (4, 'd', 1495)] {
+            let body = fill.to_string().repeat(len);
+            execute_line(&format!("INSERT INTO blobs VALUES ({}, '{}')", id, body), &mut engine, &parser);
+        }
+    }
+
+    let mut engine = QueryEngine::with_database(&path);
+    assert_eq!(execute_line("SELECT id FROM blobs", &mut engine, &parser), "id\n--\n1\n2\n3\n4");
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_real_column_named_rowid_takes_precedence() {
+    let path = temp_db("rowid_shadowed");
+    let parser = Parser::new();
+    let mut engine = QueryEngine::with_database(&path);
+
+    execute_line("CREATE TABLE t (_rowid INTEGER)", &mut engine, &parser);
+    execute_line("INSERT INTO t VALUES (42)", &mut engine, &parser);
+    assert_eq!(execute_line("SELECT _rowid FROM t", &mut engine, &parser), "_rowid\n------\n42");
+
+    let _ = std::fs::remove_file(&path);
+}
+
+/// Writes a version 4 file, which predates rowids, holding a table
+/// `items (name TEXT)` with a row for each of `names`.
+fn write_version_4_file(path: &str, names: &[&str]) {
+    let page = |data: Vec<u8>| {
+        let mut page = vec![0u8; PAGE_SIZE];
+        page[..data.len()].copy_from_slice(&data);
+        page
+    };
+    let text = |value: &str| [&(value.len() as u32).to_le_bytes()[..], value.as_bytes()].concat();
+
+    // Magic number, version, schema root and table count
+    let header = [&0x4953454E54414442u64.to_le_bytes()[..], &4u32.to_le_bytes(), &1u64.to_le_bytes(), &1u32.to_le_bytes()].concat();
+    // Name, column count, each column's name and type, then the first data page and the next schema page
+    let schema = [text("items"), 1u32.to_le_bytes().to_vec(), text("name"), text("TEXT"), 2u64.to_le_bytes().to_vec(), 0u64.to_le_bytes().to_vec()].concat();
+    // Row count and an empty dictionary, each row's length and its TEXT value, then no next page
+    let mut data = [(names.len() as u32).to_le_bytes(), 0u32.to_le_bytes()].concat();
+    for name in names {
+        let value = [&[2u8][..], &text(name)].concat();
+        data.extend_from_slice(&(value.len() as u16).to_le_bytes());
+        data.extend_from_slice(&value);
+    }
+    data.extend_from_slice(&0u64.to_le_bytes());
+
+    std::fs::write(path, [page(header), page(schema), page(data)].concat()).unwrap();
+}
+
+#[test]
+fn test_files_without_rowids_keep_them_once_written() {
+    let path = temp_db("rowid_old_version");
+    write_version_4_file(&path, &["a", "b", "c"]);
+    let parser = Parser::new();
+    {
+        let mut engine = QueryEngine::with_database(&path);
+        let output = execute_line("SELECT _rowid, name FROM items", &mut engine, &parser);
+        assert_eq!(output, "_rowid | name\n-------------\n1 | a\n2 | b\n3 | c");
+        assert_eq!(execute_line("DELETE FROM items WHERE name = 'a'", &mut engine, &parser), "Deleted 1 rows from 'items'");
+    }
+
+    // The first write stores the rowids, so the rows after the deleted one keep theirs
+    let bytes = std::fs::read(&path).unwrap();
+    assert_eq!(bytes[8..12], 5u32.to_le_bytes());
+    let mut engine = QueryEngine::with_database(&path);
+    let output = execute_line("SELECT _rowid, name FROM items", &mut engine, &parser);
+    assert_eq!(output, "_rowid | name\n-------------\n2 | b\n3 | c");
+    execute_line("INSERT INTO items VALUES ('d')", &mut engine, &parser);
+    assert_eq!(execute_line("SELECT _rowid FROM items WHERE name = 'd'", &mut engine, &parser), "_rowid\n------\n4");
+
+    let _ = std::fs::remove_file(&path);
+}
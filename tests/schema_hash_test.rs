@@ -0,0 +1,51 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+use rust_dbms::storage::MEMORY_PATH;
+
+fn hash_of(create: &str, inserts: &[&str]) -> u64 {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line(create, &mut engine, &parser);
+    for insert in inserts {
+        execute_line(insert, &mut engine, &parser);
+    }
+    engine.get_all_tables()[0].schema_hash()
+}
+
+#[test]
+fn test_schema_ddl_is_canonical() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE Items (ID integer not null, Label TEXT DEFAULT 'x', qty INTEGER default 0)", &mut engine, &parser);
+
+    let table = &engine.get_all_tables()[0];
+    assert_eq!(
+        table.schema_ddl(),
+        "CREATE TABLE items (id INTEGER NOT NULL, label TEXT DEFAULT 'x', qty INTEGER DEFAULT 0)"
+    );
+    // Fixed across runs and builds
+    assert_eq!(table.schema_hash(), 0x7a99_9c55_adb1_6a2e);
+}
+
+#[test]
+fn test_schema_hash_ignores_data_but_not_schema() {
+    let base = hash_of("CREATE TABLE t (id INTEGER, name TEXT)", &[]);
+
+    assert_eq!(hash_of("create table T (ID INTEGER, Name text)", &["INSERT INTO t VALUES (1, 'a')"]), base);
+    assert_ne!(hash_of("CREATE TABLE t (name TEXT, id INTEGER)", &[]), base);
+    assert_ne!(hash_of("CREATE TABLE t (id FLOAT, name TEXT)", &[]), base);
+    assert_ne!(hash_of("CREATE TABLE t (id INTEGER NOT NULL, name TEXT)", &[]), base);
+    assert_ne!(hash_of("CREATE TABLE t (id INTEGER, name TEXT DEFAULT 'x')", &[]), base);
+}
+
+#[test]
+fn test_inspect_shows_schema_hash() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE t (id INTEGER)", &mut engine, &parser);
+
+    let expected = format!("Schema hash: {:016x}", engine.get_all_tables()[0].schema_hash());
+    let output = execute_line("INSPECT t", &mut engine, &parser);
+    assert!(output.ends_with(&expected), "{}", output);
+}
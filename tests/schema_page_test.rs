@@ -0,0 +1,73 @@
+use rust_dbms::database::Database;
+use rust_dbms::engine::{Row, Table};
+use rust_dbms::error::IsentaError;
+use rust_dbms::parser::Column;
+use rust_dbms::storage::PAGE_SIZE;
+
+mod common;
+
+use common::temp_db;
+
+fn table(name: &str) -> Table {
+    Table {
+        name: name.to_string(),
+        columns: vec![Column::new("id", "INTEGER")],
+        rows: vec![Row { rowid: 1, values: vec![Some("1".to_string())] }],
+        created_at: 0,
+        updated_at: 0,
+        next_rowid: 2,
+        change_count: 0,
+    }
+}
+
+/// Saves a table, then overwrites the name length on its schema page, the
+/// first page after the header.
+fn database_with_name_length(name: &str, name_len: u32) -> String {
+    let path = temp_db(name);
+    {
+        let mut db = Database::new(&path).unwrap();
+        db.save_table(&table("people"), true).unwrap();
+    }
+
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes[PAGE_SIZE..PAGE_SIZE + 4].copy_from_slice(&name_len.to_le_bytes());
+    std::fs::write(&path, bytes).unwrap();
+    path
+}
+
+fn assert_schema_page_rejected(path: &str) {
+    // Loading skips the page instead of reading a bogus table
+    let mut db = Database::new(path).unwrap();
+    let catalog = db.load_catalog().unwrap();
+    assert!(catalog.find_table("people").is_none());
+
+    // Looking the table up for an update rejects the same page as corrupt
+    let err = db.update_table_data(&table("people")).unwrap_err();
+    assert!(matches!(err, IsentaError::Corruption(_)), "{:?}", err);
+}
+
+#[test]
+fn test_zero_length_table_name_is_rejected_on_every_path() {
+    let path = database_with_name_length("schema_zero_name", 0);
+    assert_schema_page_rejected(&path);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_overlong_table_name_is_rejected_on_every_path() {
+    let path = database_with_name_length("schema_long_name", 256);
+    assert_schema_page_rejected(&path);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_saving_an_overlong_table_name_fails() {
+    let path = temp_db("schema_save_long_name");
+    let mut db = Database::new(&path).unwrap();
+
+    let err = db.save_table(&table(&"t".repeat(256)), true).unwrap_err();
+    assert!(matches!(err, IsentaError::Constraint(_)), "{:?}", err);
+    db.save_table(&table(&"t".repeat(255)), true).unwrap();
+
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,50 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+use rust_dbms::storage::MEMORY_PATH;
+
+fn create_statement(name: &str, columns: usize) -> String {
+    let columns: Vec<String> = (0..columns).map(|i| format!("column_{} INTEGER", i)).collect();
+    format!("CREATE TABLE {} ({})", name, columns.join(", "))
+}
+
+#[test]
+fn test_oversized_schema_is_rejected_up_front() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    let output = execute_line(&create_statement("wide", 300), &mut engine, &parser);
+    // The name, the column count, 300 columns of 4 + 8..10 + 4 + 7 + 1 bytes
+    // and 48 bytes of page IDs and bookkeeping fields
+    assert_eq!(output, "Error: schema too large to fit in one page (7750 bytes, max 4095)");
+
+    // Nothing was created, in the catalog or the file
+    assert!(engine.get_table_schema("wide").is_none());
+    assert_eq!(execute_line("SHOW TABLES", &mut engine, &parser), "No tables in database");
+    let output = execute_line(&create_statement("wide", 100), &mut engine, &parser);
+    assert_eq!(output, "Table 'wide' created successfully");
+}
+
+#[test]
+fn test_renames_that_overflow_the_schema_page_are_rejected() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line(&create_statement("wide", 150), &mut engine, &parser);
+    let long_name = "c".repeat(1000);
+    let output = execute_line(&format!("ALTER TABLE wide RENAME COLUMN column_0 TO {}", long_name), &mut engine, &parser);
+    assert!(output.starts_with("Error: schema too large to fit in one page ("), "{}", output);
+    assert_eq!(engine.get_table_schema("wide").unwrap().columns[0].name, "column_0");
+}
+
+#[test]
+fn test_a_schema_that_exactly_fills_the_page_is_stored() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    // 4 + 1 bytes of name, 4 of column count, 4 + n + 4 + 7 + 1 for the
+    // column and 48 of fields make 73 + n bytes, of the 4095 available
+    let output = execute_line(&format!("CREATE TABLE t ({} INTEGER)", "c".repeat(4023)), &mut engine, &parser);
+    assert_eq!(output, "Error: schema too large to fit in one page (4096 bytes, max 4095)");
+    let output = execute_line(&format!("CREATE TABLE t ({} INTEGER)", "c".repeat(4022)), &mut engine, &parser);
+    assert_eq!(output, "Table 't' created successfully");
+    execute_line("INSERT INTO t VALUES (1)", &mut engine, &parser);
+    assert_eq!(execute_line("SELECT COUNT(*) FROM t", &mut engine, &parser).lines().last(), Some("1"));
+}
@@ -0,0 +1,133 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{Command, Limit, Parser, SelectItem};
+use rust_dbms::storage::MEMORY_PATH;
+
+fn engine_with_numbers(count: usize) -> (QueryEngine, Parser) {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE numbers (n INTEGER)", &mut engine, &parser);
+    for n in 1..=count {
+        execute_line(&format!("INSERT INTO numbers VALUES ({})", n), &mut engine, &parser);
+    }
+    (engine, parser)
+}
+
+fn limit_of(sql: &str) -> Option<Limit> {
+    match Parser::new().parse(sql) {
+        Command::Select { limit, .. } | Command::CrossJoin { limit, .. } => limit,
+        other => panic!("expected a SELECT, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_top_and_limit() {
+    assert_eq!(limit_of("SELECT * FROM numbers"), None);
+    assert_eq!(limit_of("SELECT TOP 10 * FROM numbers"), Some(Limit::Rows(10)));
+    assert_eq!(limit_of("select top 10 percent n FROM numbers"), Some(Limit::Percent(10)));
+    assert_eq!(limit_of("SELECT * FROM numbers ORDER BY n LIMIT 3"), Some(Limit::Rows(3)));
+    assert_eq!(limit_of("SELECT * FROM numbers WHERE n > 2 LIMIT 25%"), Some(Limit::Percent(25)));
+    assert_eq!(limit_of("SELECT * FROM a, b LIMIT 1"), Some(Limit::Rows(1)));
+    // TOP without a number is still a column
+    assert!(matches!(
+        Parser::new().parse("SELECT top FROM t"),
+        Command::Select { limit: None, columns, .. } if columns.len() == 1
+    ));
+}
+
+#[test]
+fn test_invalid_limits_are_rejected() {
+    let parser = Parser::new();
+    for sql in [
+        "SELECT * FROM numbers LIMIT x",
+        "SELECT * FROM numbers LIMIT 101%",
+        "SELECT TOP 200 PERCENT * FROM numbers",
+        "SELECT TOP 2 * FROM numbers LIMIT 3",
+        "CREATE TABLE copy AS SELECT * FROM numbers LIMIT 3",
+        "INSERT INTO copy SELECT TOP 3 * FROM numbers",
+        "INSERT INTO copy SELECT * FROM numbers OFFSET 3",
+    ] {
+        assert!(matches!(parser.parse(sql), Command::Unknown(_)), "{}", sql);
+    }
+}
+
+#[test]
+fn test_top_returns_the_first_rows() {
+    let (mut engine, parser) = engine_with_numbers(12);
+    let output = execute_line("SELECT TOP 10 * FROM numbers", &mut engine, &parser);
+    assert_eq!(output, "n\n-\n1\n2\n3\n4\n5\n6\n7\n8\n9\n10");
+    // The limit applies after sorting
+    let output = execute_line("SELECT TOP 2 n FROM numbers ORDER BY n DESC", &mut engine, &parser);
+    assert_eq!(output, "n\n-\n12\n11");
+    let output = execute_line("SELECT n FROM numbers WHERE n > 5 ORDER BY n DESC LIMIT 2", &mut engine, &parser);
+    assert_eq!(output, "n\n-\n12\n11");
+}
+
+#[test]
+fn test_percentage_limit_is_rounded() {
+    let (mut engine, parser) = engine_with_numbers(12);
+    // 25% of 12 rows is 3
+    let output = execute_line("SELECT n FROM numbers LIMIT 25%", &mut engine, &parser);
+    assert_eq!(output, "n\n-\n1\n2\n3");
+    // 10% of the 7 rows matched is 0.7, rounded to 1
+    let output = execute_line("SELECT TOP 10 PERCENT n FROM numbers WHERE n > 5", &mut engine, &parser);
+    assert_eq!(output, "n\n-\n6");
+    // 50% of the 7 rows is 3.5, rounded up
+    let output = execute_line("SELECT n FROM numbers WHERE n > 5 LIMIT 50%", &mut engine, &parser);
+    assert_eq!(output, "n\n-\n6\n7\n8\n9");
+    let output = execute_line("SELECT n FROM numbers LIMIT 0%", &mut engine, &parser);
+    assert_eq!(output, "n\n-");
+}
+
+#[test]
+fn test_limit_rows() {
+    assert_eq!(Limit::Rows(5).rows(3), 3);
+    assert_eq!(Limit::Rows(5).rows(8), 5);
+    assert_eq!(Limit::Percent(100).rows(7), 7);
+    assert_eq!(Limit::Percent(33).rows(10), 3);
+}
+
+#[test]
+fn test_negative_or_non_numeric_counts_are_rejected() {
+    let (mut engine, parser) = engine_with_numbers(3);
+    let output = execute_line("SELECT * FROM numbers LIMIT -1", &mut engine, &parser);
+    assert!(output.starts_with("Unknown command: Invalid LIMIT: '-1' is not a non-negative integer"), "{}", output);
+    let output = execute_line("SELECT * FROM numbers OFFSET -2", &mut engine, &parser);
+    assert!(output.starts_with("Unknown command: Invalid OFFSET: '-2' is not a non-negative integer"), "{}", output);
+    let output = execute_line("SELECT * FROM numbers LIMIT 1 OFFSET x", &mut engine, &parser);
+    assert!(output.starts_with("Unknown command: Invalid OFFSET: 'x' is not a non-negative integer"), "{}", output);
+}
+
+#[test]
+fn test_offset_skips_rows() {
+    let (mut engine, parser) = engine_with_numbers(5);
+    assert!(matches!(
+        parser.parse("SELECT * FROM numbers LIMIT 2 OFFSET 1"),
+        Command::Select { limit: Some(Limit::Rows(2)), offset: Some(1), .. }
+    ));
+    // Without a LIMIT the rest of the rows are returned
+    let output = execute_line("SELECT n FROM numbers OFFSET 3", &mut engine, &parser);
+    assert_eq!(output, "n\n-\n4\n5");
+    let output = execute_line("SELECT n FROM numbers ORDER BY n DESC LIMIT 2 OFFSET 1", &mut engine, &parser);
+    assert_eq!(output, "n\n-\n4\n3");
+    let output = execute_line("SELECT TOP 1 n FROM numbers WHERE n > 1 OFFSET 2", &mut engine, &parser);
+    assert_eq!(output, "n\n-\n4");
+    let output = execute_line("SELECT n FROM numbers OFFSET 9", &mut engine, &parser);
+    assert_eq!(output, "n\n-");
+}
+
+#[test]
+fn test_limit_zero_and_offset_past_the_end_return_only_the_header() {
+    let (mut engine, parser) = engine_with_numbers(3);
+    assert_eq!(execute_line("SELECT n FROM numbers LIMIT 0", &mut engine, &parser), "n\n-");
+    assert_eq!(execute_line("SELECT n AS number FROM numbers LIMIT 2 OFFSET 10", &mut engine, &parser), "number\n------");
+    // A query that matches nothing still says so
+    assert_eq!(execute_line("SELECT n FROM numbers WHERE n > 99 LIMIT 0", &mut engine, &parser), "No rows found in 'numbers'");
+
+    // The result keeps its columns
+    let mut result = engine.execute_select("numbers".to_string(), vec![SelectItem::Wildcard], None).unwrap();
+    result.offset(10);
+    result.limit(Limit::Rows(0));
+    assert!(result.is_empty());
+    assert_eq!(result.columns(), ["n"]);
+}
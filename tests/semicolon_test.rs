@@ -0,0 +1,56 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{Command, Parser, WhereExpr};
+use rust_dbms::storage::MEMORY_PATH;
+
+#[test]
+fn test_trailing_semicolon_is_ignored_for_every_command() {
+    let parser = Parser::new();
+    let statements = [
+        "CREATE TABLE t (id INTEGER, name TEXT)",
+        "INSERT INTO t VALUES (1, 'a')",
+        "SELECT * FROM t",
+        "SELECT name FROM t WHERE id = 1",
+        "UPDATE t SET name = 'b' WHERE id = 1",
+        "TRUNCATE TABLE t",
+        "GET t AS JSON",
+        "SHOW TABLES",
+        "INSPECT t",
+    ];
+
+    for statement in statements {
+        let expected = parser.parse(statement);
+        assert!(!matches!(expected, Command::Unknown(_)), "{}", statement);
+        assert_eq!(parser.parse(&format!("{};", statement)), expected, "{}", statement);
+        assert_eq!(parser.parse(&format!("{} ;  ", statement)), expected, "{}", statement);
+    }
+}
+
+#[test]
+fn test_semicolon_inside_quotes_is_kept() {
+    let parser = Parser::new();
+    assert_eq!(
+        parser.parse("INSERT INTO t VALUES (1, 'a;');"),
+        Command::Insert {
+            table: "t".to_string(),
+            columns: None,
+            values: vec![vec![Some("1".to_string()), Some("a;".to_string())]],
+            returning: None,
+        }
+    );
+
+    match parser.parse("SELECT * FROM t WHERE name = 'x;'") {
+        Command::Select { where_clause: Some(WhereExpr::Condition(clause)), .. } => assert_eq!(clause.value.as_deref(), Some("x;")),
+        other => panic!("unexpected command: {:?}", other),
+    }
+}
+
+#[test]
+fn test_statements_with_semicolons_execute() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+
+    execute_line("CREATE TABLE t (id INTEGER, name TEXT);", &mut engine, &parser);
+    execute_line("INSERT INTO t VALUES (1, 'a');", &mut engine, &parser);
+    assert_eq!(execute_line("SELECT name FROM t;", &mut engine, &parser), "name\n----\na");
+}
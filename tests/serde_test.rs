@@ -0,0 +1,44 @@
+#![cfg(feature = "serde")]
+
+use rust_dbms::engine::{QueryEngine, Table};
+use rust_dbms::execute_line;
+use rust_dbms::parser::{Parser, SelectItem};
+use rust_dbms::storage::MEMORY_PATH;
+
+fn engine_with_rows() -> QueryEngine {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE users (id INTEGER NOT NULL, name TEXT DEFAULT 'n/a')", &mut engine, &parser);
+    execute_line("INSERT INTO users VALUES (1, 'Alice')", &mut engine, &parser);
+    execute_line("INSERT INTO users VALUES (2, NULL)", &mut engine, &parser);
+    engine
+}
+
+#[test]
+fn test_table_round_trips_through_json() {
+    let engine = engine_with_rows();
+    let table = engine.get_all_tables()[0].clone();
+
+    let json = serde_json::to_string(&table).unwrap();
+    let restored: Table = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.name, table.name);
+    assert_eq!(restored.columns, table.columns);
+    assert_eq!(restored.rows[1].values, vec![Some("2".to_string()), None]);
+
+    // The built-in JSON output matches serde's
+    let mut engine = engine;
+    let output = execute_line("GET users AS JSON", &mut engine, &Parser::new());
+    assert_eq!(output, serde_json::to_string_pretty(&table).unwrap());
+}
+
+#[test]
+fn test_result_rows_serialize_as_objects() {
+    let engine = engine_with_rows();
+    let result = engine.execute_select("users".to_string(), vec![SelectItem::Wildcard], None).unwrap();
+
+    let rows: Vec<_> = result.rows().collect();
+    assert_eq!(
+        serde_json::to_string(&rows).unwrap(),
+        r#"[{"id":"1","name":"Alice"},{"id":"2","name":null}]"#
+    );
+}
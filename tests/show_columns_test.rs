@@ -0,0 +1,45 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{Command, Parser};
+use rust_dbms::storage::MEMORY_PATH;
+
+#[test]
+fn test_parse_show_columns() {
+    let parser = Parser::new();
+    assert_eq!(parser.parse("show columns from users;"), Command::ShowColumns { table: "users".to_string() });
+    assert!(matches!(parser.parse("SHOW COLUMNS users"), Command::Unknown(_)));
+    assert!(matches!(parser.parse("SHOW COLUMNS FROM a b"), Command::Unknown(_)));
+}
+
+#[test]
+fn test_show_columns_lists_one_row_per_column() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line(
+        "CREATE TABLE users (id INTEGER NOT NULL, name VARCHAR(20) DEFAULT 'anon', score FLOAT DEFAULT 0)",
+        &mut engine,
+        &parser,
+    );
+
+    let result = engine.show_columns("users").unwrap();
+    assert_eq!(result.columns(), ["name", "type", "nullable", "key", "default"]);
+    let rows: Vec<Vec<Option<&str>>> =
+        result.rows().map(|row| row.values().iter().map(|v| v.as_deref()).collect()).collect();
+    assert_eq!(rows[0], [Some("id"), Some("INTEGER"), Some("NO"), None, None]);
+    assert_eq!(rows[1], [Some("name"), Some("VARCHAR(20)"), Some("YES"), None, Some("anon")]);
+    assert_eq!(rows[2], [Some("score"), Some("FLOAT"), Some("YES"), None, Some("0")]);
+
+    let output = execute_line("SHOW COLUMNS FROM users", &mut engine, &parser);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines[0], "name | type | nullable | key | default");
+    assert_eq!(lines[2], "id | INTEGER | NO | NULL | NULL");
+    assert_eq!(lines.len(), 5);
+}
+
+#[test]
+fn test_show_columns_of_a_missing_table() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    let output = execute_line("SHOW COLUMNS FROM nowhere", &mut engine, &parser);
+    assert!(output.starts_with("Error:") && output.contains("nowhere"), "{}", output);
+}
@@ -0,0 +1,42 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{parse_to_ast, Command, Parser};
+use rust_dbms::storage::MEMORY_PATH;
+
+fn engine_with_tables() -> (QueryEngine, Parser) {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    for table in ["users", "user_roles", "orders", "Userdata"] {
+        execute_line(&format!("CREATE TABLE {} (id INTEGER)", table), &mut engine, &parser);
+    }
+    (engine, parser)
+}
+
+#[test]
+fn test_parse_show_tables_like() {
+    assert_eq!(
+        parse_to_ast("show tables like 'user%';"),
+        Command::ShowTables { pattern: Some("user%".to_string()) }
+    );
+    assert!(matches!(parse_to_ast("SHOW TABLES LIKE user%"), Command::Unknown(_)));
+    assert!(matches!(parse_to_ast("SHOW TABLES WHERE x"), Command::Unknown(_)));
+}
+
+#[test]
+fn test_show_tables_filters_by_prefix() {
+    let (mut engine, parser) = engine_with_tables();
+    let output = execute_line("SHOW TABLES LIKE 'user%'", &mut engine, &parser);
+    assert_eq!(output, "Tables:\n- users\n- user_roles\n- Userdata");
+    // `_` matches any one character
+    let output = execute_line("SHOW TABLES LIKE 'user_'", &mut engine, &parser);
+    assert_eq!(output, "Tables:\n- users");
+    let output = execute_line("SHOW TABLES", &mut engine, &parser);
+    assert_eq!(output, "Tables:\n- users\n- user_roles\n- orders\n- Userdata");
+}
+
+#[test]
+fn test_show_tables_without_a_match() {
+    let (mut engine, parser) = engine_with_tables();
+    let output = execute_line("SHOW TABLES LIKE 'invoice%'", &mut engine, &parser);
+    assert_eq!(output, "No tables matching 'invoice%'");
+}
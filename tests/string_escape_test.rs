@@ -0,0 +1,60 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{is_complete_statement, Command, Parser};
+
+mod common;
+
+use common::temp_db;
+
+#[test]
+fn test_escapes_are_decoded_in_single_quotes() {
+    let parser = Parser::new();
+    match parser.parse(r"INSERT INTO notes VALUES (1, 'one\ntwo\tit\'s \\ C:\data')") {
+        Command::Insert { values, .. } => {
+            assert_eq!(values[0][1].as_deref(), Some("one\ntwo\tit's \\ C:\\data"));
+        }
+        other => panic!("{:?}", other),
+    }
+    // An escaped quote doesn't end the value
+    assert!(!is_complete_statement(r"INSERT INTO notes VALUES (1, 'it\');"));
+    assert!(is_complete_statement(r"INSERT INTO notes VALUES (1, 'it\'s');"));
+}
+
+#[test]
+fn test_newline_round_trips_through_storage() {
+    let path = temp_db("escape_round_trip");
+    let parser = Parser::new();
+    {
+        let mut engine = QueryEngine::with_database(&path);
+        execute_line("CREATE TABLE notes (id INTEGER, body TEXT)", &mut engine, &parser);
+        execute_line(r"INSERT INTO notes VALUES (1, 'first line\nsecond line')", &mut engine, &parser);
+        execute_line(r"INSERT INTO notes VALUES (2, 'tab\there')", &mut engine, &parser);
+        engine.close().unwrap();
+    }
+
+    let mut engine = QueryEngine::with_database(&path);
+    let table = engine.get_table_schema("notes").unwrap();
+    assert_eq!(table.rows[0].values[1].as_deref(), Some("first line\nsecond line"));
+
+    // Displayed with its escapes, one row per line
+    let output = execute_line("SELECT body FROM notes", &mut engine, &parser);
+    assert_eq!(output, "body\n----\nfirst line\\nsecond line\ntab\\there");
+    let output = execute_line(r"SELECT id FROM notes WHERE body = 'tab\there'", &mut engine, &parser);
+    assert_eq!(output, "id\n--\n2");
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_export_quotes_values_with_newlines() {
+    let csv = temp_db("escape_export").replace(".db", ".csv");
+    let mut engine = QueryEngine::with_database(":memory:");
+    let parser = Parser::new();
+    execute_line("CREATE TABLE notes (id INTEGER, body TEXT)", &mut engine, &parser);
+    execute_line(r"INSERT INTO notes VALUES (1, 'say \'hi\'\nthen go')", &mut engine, &parser);
+
+    let output = execute_line(&format!("EXPORT notes TO '{}'", csv), &mut engine, &parser);
+    assert_eq!(output, format!("Exported 1 rows from 'notes' to '{}'", csv));
+    let written = std::fs::read_to_string(&csv).unwrap();
+    assert_eq!(written, "id,body\n1,\"say 'hi'\nthen go\"\n");
+    let _ = std::fs::remove_file(&csv);
+}
@@ -0,0 +1,81 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+use rust_dbms::storage::MEMORY_PATH;
+
+fn engine_with_people() -> (QueryEngine, Parser) {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE people (id INTEGER, name TEXT, bio TEXT)", &mut engine, &parser);
+    execute_line("INSERT INTO people VALUES (1, 'Bob', '  likes tea ')", &mut engine, &parser);
+    execute_line("INSERT INTO people VALUES (2, 'alice', 'Zoë')", &mut engine, &parser);
+    execute_line("INSERT INTO people VALUES (3, 'BOB', NULL)", &mut engine, &parser);
+    (engine, parser)
+}
+
+#[test]
+fn test_upper_and_lower() {
+    let (mut engine, parser) = engine_with_people();
+    let output = execute_line("SELECT id FROM people WHERE LOWER(name) = 'bob'", &mut engine, &parser);
+    assert_eq!(output, "id\n--\n1\n3");
+    let output = execute_line("SELECT UPPER(name), lower(bio) AS bio FROM people WHERE id = 2", &mut engine, &parser);
+    assert_eq!(output, "UPPER(name) | bio\n-----------------\nALICE | zoë");
+}
+
+#[test]
+fn test_length_counts_characters_and_compares_as_a_number() {
+    let (mut engine, parser) = engine_with_people();
+    let output = execute_line("SELECT id, LENGTH(bio) AS n FROM people", &mut engine, &parser);
+    assert_eq!(output, "id | n\n------\n1 | 12\n2 | 3\n3 | NULL");
+    // 12 > 9 as a number, though not as text
+    let output = execute_line("SELECT id FROM people WHERE LENGTH(bio) > 9", &mut engine, &parser);
+    assert_eq!(output, "id\n--\n1");
+    let output = execute_line("SELECT name FROM people ORDER BY LENGTH(name) DESC, id", &mut engine, &parser);
+    assert_eq!(output, "name\n----\nalice\nBob\nBOB");
+}
+
+#[test]
+fn test_trim() {
+    let (mut engine, parser) = engine_with_people();
+    let output = execute_line("SELECT id FROM people WHERE TRIM(bio) = 'likes tea'", &mut engine, &parser);
+    assert_eq!(output, "id\n--\n1");
+    let output = execute_line("SELECT LENGTH(TRIM(bio)) AS n FROM people WHERE id = 1", &mut engine, &parser);
+    assert_eq!(output, "n\n-\n9");
+}
+
+#[test]
+fn test_substr() {
+    let (mut engine, parser) = engine_with_people();
+    let output = execute_line(
+        "SELECT SUBSTR(name, 2) AS a, SUBSTR(name, 1, 3) AS b, SUBSTR(name, -2) AS c, SUBSTR(name, 9) AS d FROM people WHERE id = 2",
+        &mut engine,
+        &parser,
+    );
+    assert_eq!(output, "a | b | c | d\n-------------\nlice | ali | ce |");
+    let output = execute_line("SELECT id FROM people WHERE SUBSTR(bio, 1, 2) = 'Zo'", &mut engine, &parser);
+    assert_eq!(output, "id\n--\n2");
+}
+
+#[test]
+fn test_functions_propagate_null() {
+    let (mut engine, parser) = engine_with_people();
+    let output = execute_line(
+        "SELECT UPPER(bio) AS u, LOWER(bio) AS l, TRIM(bio) AS t, SUBSTR(bio, 1, 1) AS s FROM people WHERE id = 3",
+        &mut engine,
+        &parser,
+    );
+    assert_eq!(output, "u | l | t | s\n-------------\nNULL | NULL | NULL | NULL");
+    let output = execute_line("SELECT id FROM people WHERE UPPER(bio) IS NULL", &mut engine, &parser);
+    assert_eq!(output, "id\n--\n3");
+}
+
+#[test]
+fn test_invalid_function_calls_are_rejected() {
+    let (mut engine, parser) = engine_with_people();
+    let output = execute_line("SELECT UPPER(name, bio) FROM people", &mut engine, &parser);
+    assert!(output.contains("UPPER takes exactly one column"), "{}", output);
+    let output = execute_line("SELECT SUBSTR(name, x) FROM people", &mut engine, &parser);
+    assert!(output.contains("SUBSTR start 'x' is not an integer"), "{}", output);
+    let output = execute_line("SELECT id FROM people WHERE LOWER(nickname) = 'bob'", &mut engine, &parser);
+    assert!(output.starts_with("Error:") && output.contains("nickname"), "{}", output);
+}
@@ -0,0 +1,97 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::error::IsentaError;
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+use rust_dbms::storage::MEMORY_PATH;
+
+mod common;
+
+use common::temp_db;
+
+fn ids(engine: &mut QueryEngine, parser: &Parser) -> String {
+    execute_line("SELECT id FROM items", engine, parser)
+}
+
+#[test]
+fn test_a_transaction_locks_the_tables_it_writes() {
+    let path = temp_db("table_lock");
+    let parser = Parser::new();
+    let mut first = QueryEngine::with_database(&path);
+    execute_line("CREATE TABLE items (id INTEGER)", &mut first, &parser);
+    execute_line("CREATE TABLE other (id INTEGER)", &mut first, &parser);
+    execute_line("INSERT INTO items VALUES (0)", &mut first, &parser);
+    let mut second = QueryEngine::with_database(&path);
+
+    let mut tx = first.begin().unwrap();
+    assert_eq!(execute_line("INSERT INTO items VALUES (1)", &mut tx, &parser), "Inserted 1 row into 'items'");
+
+    // The other connection can neither write the table nor see the change
+    for sql in ["INSERT INTO items VALUES (2)", "DELETE FROM items", "UPDATE items SET id = 3", "ALTER TABLE items RENAME TO things"] {
+        assert_eq!(execute_line(sql, &mut second, &parser), "Error: table 'items' is locked by another transaction", "{}", sql);
+    }
+    assert_eq!(ids(&mut second, &parser), "id\n--\n0");
+    // Tables the transaction hasn't written stay writable
+    assert_eq!(execute_line("INSERT INTO other VALUES (1)", &mut second, &parser), "Inserted 1 row into 'other'");
+    // The transaction itself can keep writing
+    assert_eq!(execute_line("INSERT INTO items VALUES (4)", &mut tx, &parser), "Inserted 1 row into 'items'");
+    tx.commit().unwrap();
+
+    // Once committed the lock is gone; the other connection picks up the
+    // committed rows when its write finds the table changed
+    assert_eq!(
+        execute_line("INSERT INTO items VALUES (2)", &mut second, &parser),
+        "Error: table 'items' changed concurrently, retry"
+    );
+    assert_eq!(execute_line("INSERT INTO items VALUES (2)", &mut second, &parser), "Inserted 1 row into 'items'");
+    assert_eq!(ids(&mut second, &parser), "id\n--\n0\n1\n4\n2");
+}
+
+#[test]
+fn test_creating_a_table_is_locked_until_the_transaction_ends() {
+    let path = temp_db("table_lock_create");
+    let parser = Parser::new();
+    let mut first = QueryEngine::with_database(&path);
+    let mut second = QueryEngine::with_database(&path);
+
+    let mut tx = first.begin().unwrap();
+    execute_line("CREATE TABLE items (id INTEGER)", &mut tx, &parser);
+    assert_eq!(
+        second.execute_create_table("items".to_string(), Vec::new()),
+        Err(IsentaError::TableLocked("items".to_string()))
+    );
+    assert!(second.get_table_schema("items").is_none());
+    tx.rollback().unwrap();
+
+    // Rolling back releases the lock
+    assert_eq!(execute_line("CREATE TABLE items (id INTEGER)", &mut second, &parser), "Table 'items' created successfully");
+}
+
+#[test]
+fn test_dropping_a_transaction_releases_its_locks() {
+    let path = temp_db("table_lock_drop");
+    let parser = Parser::new();
+    let mut first = QueryEngine::with_database(&path);
+    execute_line("CREATE TABLE items (id INTEGER)", &mut first, &parser);
+    let mut second = QueryEngine::with_database(&path);
+
+    {
+        let mut tx = first.begin().unwrap();
+        execute_line("INSERT INTO items VALUES (1)", &mut tx, &parser);
+        assert!(execute_line("INSERT INTO items VALUES (2)", &mut second, &parser).contains("locked"));
+    }
+    assert_eq!(execute_line("INSERT INTO items VALUES (2)", &mut second, &parser), "Inserted 1 row into 'items'");
+    assert_eq!(ids(&mut second, &parser), "id\n--\n2");
+}
+
+#[test]
+fn test_in_memory_databases_are_not_shared() {
+    let parser = Parser::new();
+    let mut first = QueryEngine::with_database(MEMORY_PATH);
+    let mut second = QueryEngine::with_database(MEMORY_PATH);
+    execute_line("CREATE TABLE items (id INTEGER)", &mut first, &parser);
+    execute_line("CREATE TABLE items (id INTEGER)", &mut second, &parser);
+
+    let mut tx = first.begin().unwrap();
+    execute_line("INSERT INTO items VALUES (1)", &mut tx, &parser);
+    assert_eq!(execute_line("INSERT INTO items VALUES (2)", &mut second, &parser), "Inserted 1 row into 'items'");
+}
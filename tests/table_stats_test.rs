@@ -0,0 +1,64 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{Command, Parser};
+use rust_dbms::storage::MEMORY_PATH;
+
+/// A log table of 50 rows of about 200 bytes each, roughly 19 to a data
+/// page, in page 1 of the file.
+fn log_table() -> (QueryEngine, Parser) {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE logs (id INTEGER, message TEXT)", &mut engine, &parser);
+    for id in 1..=50 {
+        let values = vec![Some(id.to_string()), Some(format!("{:0>200}", id))];
+        engine.execute_insert("logs".to_string(), values).unwrap();
+    }
+    (engine, parser)
+}
+
+#[test]
+fn test_parse_table_stats() {
+    let parser = Parser::new();
+    assert_eq!(parser.parse("table stats logs;"), Command::TableStats { table: "logs".to_string() });
+    assert!(matches!(parser.parse("TABLE STATS a b"), Command::Unknown(_)));
+}
+
+#[test]
+fn test_stats_count_pages_rows_and_space() {
+    let (mut engine, _) = log_table();
+    let stats = engine.table_stats("logs").unwrap();
+    assert_eq!(stats.schema_page, 1);
+    assert_eq!(stats.data_pages, 3);
+    assert_eq!(stats.rows, 50);
+    assert!(stats.used_bytes > 50 * 200 && stats.used_bytes < stats.capacity_bytes);
+    assert_eq!(stats.capacity_bytes - stats.used_bytes, stats.wasted_bytes());
+    assert!((200..260).contains(&stats.average_row_size()), "{:?}", stats);
+}
+
+#[test]
+fn test_deleted_rows_show_up_as_wasted_space() {
+    let (mut engine, parser) = log_table();
+    let before = engine.table_stats("logs").unwrap();
+    execute_line("DELETE FROM logs WHERE id <= 10", &mut engine, &parser);
+
+    let after = engine.table_stats("logs").unwrap();
+    assert_eq!(after.data_pages, before.data_pages);
+    assert_eq!(after.rows, 40);
+    assert!(after.wasted_bytes() >= before.wasted_bytes() + 10 * 200);
+}
+
+#[test]
+fn test_table_stats_command() {
+    let (mut engine, parser) = log_table();
+    execute_line("CREATE TABLE empty (id INTEGER)", &mut engine, &parser);
+    let output = execute_line("TABLE STATS empty", &mut engine, &parser);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines[..5], ["Table: empty", "Schema page: 5", "Data pages: 1", "Rows: 0", "Average row size: 0 bytes"]);
+
+    let output = execute_line("TABLE STATS logs", &mut engine, &parser);
+    assert!(output.contains("Data pages: 3\nRows: 50\n"), "{}", output);
+    assert!(output.lines().last().unwrap().starts_with("Wasted space: "), "{}", output);
+
+    let output = execute_line("TABLE STATS nowhere", &mut engine, &parser);
+    assert!(output.starts_with("Error:"), "{}", output);
+}
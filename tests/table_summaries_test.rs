@@ -0,0 +1,22 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+use rust_dbms::storage::MEMORY_PATH;
+
+#[test]
+fn test_table_summaries_list_names_and_row_counts() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    assert!(engine.table_summaries().is_empty());
+
+    execute_line("CREATE TABLE orders (id INTEGER)", &mut engine, &parser);
+    execute_line("CREATE TABLE customers (id INTEGER)", &mut engine, &parser);
+    for id in 0..3 {
+        execute_line(&format!("INSERT INTO orders VALUES ({})", id), &mut engine, &parser);
+    }
+
+    assert_eq!(engine.table_summaries(), vec![("orders".to_string(), 3), ("customers".to_string(), 0)]);
+
+    execute_line("TRUNCATE TABLE orders", &mut engine, &parser);
+    assert_eq!(engine.table_summaries()[0], ("orders".to_string(), 0));
+}
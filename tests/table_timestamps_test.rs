@@ -0,0 +1,86 @@
+use rust_dbms::database::Database;
+use rust_dbms::engine::{QueryEngine, Table};
+use rust_dbms::execute_line;
+use rust_dbms::parser::{Column, Parser};
+
+mod common;
+
+use common::temp_db;
+
+fn stored_table(path: &str) -> Table {
+    let mut db = Database::new(path).unwrap();
+    let catalog = db.load_catalog().unwrap();
+    catalog.get_all_tables()[0].clone()
+}
+
+fn save_table_with_times(path: &str, created_at: u64, updated_at: u64) {
+    let table = Table {
+        name: "events".to_string(),
+        columns: vec![Column::new("id", "INTEGER")],
+        rows: Vec::new(),
+        created_at,
+        updated_at,
+        next_rowid: 1,
+        change_count: 0,
+    };
+    let mut db = Database::new(path).unwrap();
+    db.save_table(&table, true).unwrap();
+}
+
+#[test]
+fn test_timestamps_are_set_on_create_and_persisted() {
+    let path = temp_db("timestamps_create");
+    {
+        let mut engine = QueryEngine::with_database(&path);
+        execute_line("CREATE TABLE events (id INTEGER)", &mut engine, &Parser::new());
+    }
+
+    let table = stored_table(&path);
+    assert!(table.created_at > 0);
+    assert_eq!(table.created_at, table.updated_at);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_mutation_updates_only_the_modification_time() {
+    let path = temp_db("timestamps_mutation");
+    save_table_with_times(&path, 100, 200);
+    assert_eq!((stored_table(&path).created_at, stored_table(&path).updated_at), (100, 200));
+
+    {
+        let mut engine = QueryEngine::with_database(&path);
+        let parser = Parser::new();
+        execute_line("INSERT INTO events VALUES (1)", &mut engine, &parser);
+
+        let output = execute_line("INSPECT events", &mut engine, &parser);
+        assert!(output.contains("Created: 100 (Unix time)"), "{}", output);
+        assert!(!output.contains("Updated: 200"), "{}", output);
+    }
+
+    let table = stored_table(&path);
+    assert_eq!(table.created_at, 100);
+    assert!(table.updated_at > 200);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_files_without_timestamps_read_as_zero() {
+    let path = temp_db("timestamps_old_version");
+    save_table_with_times(&path, 100, 200);
+
+    // Mark the file as version 3, which predates the timestamps
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes[8..12].copy_from_slice(&3u32.to_le_bytes());
+    std::fs::write(&path, bytes).unwrap();
+
+    let table = stored_table(&path);
+    assert_eq!((table.created_at, table.updated_at), (0, 0));
+
+    let mut engine = QueryEngine::with_database(&path);
+    let output = execute_line("INSPECT events", &mut engine, &Parser::new());
+    assert!(output.contains("Created: unknown"), "{}", output);
+
+    let _ = std::fs::remove_file(&path);
+}
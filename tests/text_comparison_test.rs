@@ -0,0 +1,47 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+
+fn engine_with_names() -> (QueryEngine, Parser) {
+    let mut engine = QueryEngine::with_database(":memory:");
+    let parser = Parser::new();
+    execute_line("CREATE TABLE people (id INTEGER, name TEXT)", &mut engine, &parser);
+    for (id, name) in ["alice", "Bob", "Mallory", "mike", "Oscar", "zoe"].iter().enumerate() {
+        execute_line(&format!("INSERT INTO people VALUES ({}, '{}')", id + 1, name), &mut engine, &parser);
+    }
+    (engine, parser)
+}
+
+/// The names a query returns, in order.
+fn names(output: &str) -> Vec<&str> {
+    output.lines().skip(2).collect()
+}
+
+#[test]
+fn test_greater_or_equal_returns_the_back_half_of_the_alphabet() {
+    let (mut engine, parser) = engine_with_names();
+    let output = execute_line("SELECT name FROM people WHERE name >= 'M'", &mut engine, &parser);
+    assert_eq!(names(&output), vec!["Mallory", "mike", "Oscar", "zoe"]);
+}
+
+#[test]
+fn test_ordering_operators_ignore_case() {
+    let (mut engine, parser) = engine_with_names();
+    let output = execute_line("SELECT name FROM people WHERE name < 'm'", &mut engine, &parser);
+    assert_eq!(names(&output), vec!["alice", "Bob"]);
+
+    let output = execute_line("SELECT name FROM people WHERE name > 'MIKE'", &mut engine, &parser);
+    assert_eq!(names(&output), vec!["Oscar", "zoe"]);
+
+    // A value equal but for case is both <= and >=, as it is =
+    let output = execute_line("SELECT name FROM people WHERE name <= 'OSCAR' AND name >= 'oscar'", &mut engine, &parser);
+    assert_eq!(names(&output), vec!["Oscar"]);
+}
+
+#[test]
+fn test_ordering_operators_never_match_null() {
+    let (mut engine, parser) = engine_with_names();
+    execute_line("INSERT INTO people VALUES (7, NULL)", &mut engine, &parser);
+    let output = execute_line("SELECT id FROM people WHERE name > ''", &mut engine, &parser);
+    assert_eq!(names(&output).len(), 6);
+}
@@ -0,0 +1,71 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{Column, Command, Parser};
+use rust_dbms::storage::MEMORY_PATH;
+
+mod common;
+
+use common::temp_db;
+
+#[test]
+fn test_parse_declared_lengths() {
+    let Command::CreateTable { columns, .. } =
+        Parser::new().parse("CREATE TABLE t (code varchar(5), note TEXT (20) NOT NULL, body TEXT, n INTEGER)")
+    else {
+        panic!("not a CREATE TABLE");
+    };
+    let lengths: Vec<Option<usize>> = columns.iter().map(Column::max_length).collect();
+    assert_eq!(lengths, vec![Some(5), Some(20), None, None]);
+    assert_eq!(columns[1].data_type, "TEXT(20)");
+    assert!(columns[1].not_null);
+}
+
+#[test]
+fn test_values_over_the_length_are_rejected() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE people (name VARCHAR(5), bio TEXT)", &mut engine, &parser);
+
+    // Lengths count characters, not bytes
+    assert_eq!(execute_line("INSERT INTO people VALUES ('Zoë', 'x')", &mut engine, &parser), "Inserted 1 row into 'people'");
+    assert_eq!(execute_line("INSERT INTO people VALUES ('Alice', NULL)", &mut engine, &parser), "Inserted 1 row into 'people'");
+    let long_bio = format!("INSERT INTO people VALUES ('Bob', '{}')", "x".repeat(1000));
+    assert_eq!(execute_line(&long_bio, &mut engine, &parser), "Inserted 1 row into 'people'");
+
+    assert_eq!(
+        execute_line("INSERT INTO people VALUES ('Alexandra', 'x')", &mut engine, &parser),
+        "Error: value exceeds max length 5 for column 'name'"
+    );
+    assert_eq!(
+        execute_line("UPDATE people SET name = 'Bartholomew' WHERE name = 'Bob'", &mut engine, &parser),
+        "Error: value exceeds max length 5 for column 'name'"
+    );
+    let output = execute_line("SELECT COUNT(*) FROM people WHERE name = 'Bob'", &mut engine, &parser);
+    assert_eq!(output.lines().last(), Some("1"));
+}
+
+#[test]
+fn test_lengths_are_checked_at_create_time() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    let output = execute_line("CREATE TABLE t (code CHAR(2) DEFAULT 'abc')", &mut engine, &parser);
+    assert_eq!(output, "Error: Invalid default for column 'code': value exceeds max length 2 for column 'code'");
+    let output = execute_line("CREATE TABLE t (code VARCHAR(two))", &mut engine, &parser);
+    assert!(output.starts_with("Error: Invalid length in type VARCHAR(TWO)"), "{}", output);
+}
+
+#[test]
+fn test_lengths_survive_reopening() {
+    let path = temp_db("text_length_reopen");
+    let parser = Parser::new();
+    {
+        let mut engine = QueryEngine::with_database(&path);
+        execute_line("CREATE TABLE codes (code VARCHAR(3))", &mut engine, &parser);
+    }
+    let mut engine = QueryEngine::with_database(&path);
+    assert_eq!(
+        execute_line("INSERT INTO codes VALUES ('abcd')", &mut engine, &parser),
+        "Error: value exceeds max length 3 for column 'code'"
+    );
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,92 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+use rust_dbms::storage::{Durability, MEMORY_PATH};
+
+mod common;
+
+use common::temp_db;
+
+fn count(engine: &mut QueryEngine, parser: &Parser) -> String {
+    execute_line("SELECT COUNT(*) FROM items", engine, parser).lines().last().unwrap().to_string()
+}
+
+#[test]
+fn test_dropping_the_guard_discards_its_changes() {
+    let path = temp_db("transaction_drop");
+    let parser = Parser::new();
+    let mut engine = QueryEngine::with_database(&path);
+    execute_line("CREATE TABLE items (id INTEGER)", &mut engine, &parser);
+    execute_line("INSERT INTO items VALUES (1)", &mut engine, &parser);
+
+    {
+        let mut tx = engine.begin().unwrap();
+        execute_line("INSERT INTO items VALUES (2)", &mut tx, &parser);
+        execute_line("CREATE TABLE scratch (id INTEGER)", &mut tx, &parser);
+        // Visible inside the transaction, but not in the file
+        assert_eq!(count(&mut tx, &parser), "2");
+        let mut reader = QueryEngine::with_database(&path);
+        assert_eq!(count(&mut reader, &parser), "1");
+    }
+
+    assert_eq!(count(&mut engine, &parser), "1");
+    assert!(engine.get_table_schema("scratch").is_none());
+    // The engine carries on normally afterwards
+    execute_line("INSERT INTO items VALUES (3)", &mut engine, &parser);
+    drop(engine);
+    let mut engine = QueryEngine::with_database(&path);
+    assert_eq!(execute_line("SELECT id FROM items", &mut engine, &parser), "id\n--\n1\n3");
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_commit_writes_the_changes() {
+    let path = temp_db("transaction_commit");
+    let parser = Parser::new();
+    {
+        let mut engine = QueryEngine::with_database(&path);
+        execute_line("CREATE TABLE items (id INTEGER)", &mut engine, &parser);
+        let mut tx = engine.begin().unwrap();
+        execute_line("INSERT INTO items VALUES (1)", &mut tx, &parser);
+        execute_line("INSERT INTO items VALUES (2)", &mut tx, &parser);
+        tx.commit().unwrap();
+
+        let mut reader = QueryEngine::with_database(&path);
+        assert_eq!(count(&mut reader, &parser), "2");
+    }
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_rollback_in_memory_and_with_deferred_durability() {
+    let parser = Parser::new();
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    execute_line("CREATE TABLE items (id INTEGER)", &mut engine, &parser);
+    let mut tx = engine.begin().unwrap();
+    execute_line("INSERT INTO items VALUES (1)", &mut tx, &parser);
+    tx.rollback().unwrap();
+    assert_eq!(count(&mut engine, &parser), "0");
+
+    // Pages pending before the transaction are kept by its rollback
+    let path = temp_db("transaction_deferred");
+    let mut engine = QueryEngine::with_database(&path);
+    engine.set_durability(Durability::Deferred).unwrap();
+    execute_line("CREATE TABLE items (id INTEGER)", &mut engine, &parser);
+    execute_line("INSERT INTO items VALUES (1)", &mut engine, &parser);
+    let mut tx = engine.begin().unwrap();
+    execute_line("DELETE FROM items", &mut tx, &parser);
+    tx.flush().unwrap();
+    drop(tx);
+    assert_eq!(count(&mut engine, &parser), "1");
+    engine.close().unwrap();
+    let mut engine = QueryEngine::with_database(&path);
+    assert_eq!(count(&mut engine, &parser), "1");
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_only_one_transaction_at_a_time() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let mut tx = engine.begin().unwrap();
+    assert_eq!(tx.begin().err().unwrap().to_string(), "A transaction is already open");
+}
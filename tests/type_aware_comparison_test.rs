@@ -1,27 +1,32 @@
-use assert_cmd::Command;
+use assert_cmd::cargo::cargo_bin_cmd;
 use predicates::prelude::*;
 
+mod common;
+
+use common::temp_db;
+
 #[test]
 fn test_integer_comparisons() {
-    let mut cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("CREATE TABLE test_int (id INTEGER, value INTEGER)");
+    let db = temp_db("integer_comparisons");
+    let mut cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("CREATE TABLE test_int (id INTEGER, value INTEGER)");
     cmd.assert().success();
 
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("INSERT INTO test_int VALUES (1, 10)");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("INSERT INTO test_int VALUES (1, 10)");
     cmd.assert().success();
 
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("INSERT INTO test_int VALUES (2, 20)");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("INSERT INTO test_int VALUES (2, 20)");
     cmd.assert().success();
     
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("INSERT INTO test_int VALUES (3, 30)");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("INSERT INTO test_int VALUES (3, 30)");
     cmd.assert().success();
 
     // Test >
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("SELECT id FROM test_int WHERE value > 15");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("SELECT id FROM test_int WHERE value > 15");
     cmd.assert()
         .success()
         .stdout(predicate::str::contains("2"))
@@ -29,8 +34,8 @@ fn test_integer_comparisons() {
         .stdout(predicate::str::contains("1").not());
         
     // Test <
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("SELECT id FROM test_int WHERE value < 25");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("SELECT id FROM test_int WHERE value < 25");
     cmd.assert()
         .success()
         .stdout(predicate::str::contains("1"))
@@ -38,8 +43,8 @@ fn test_integer_comparisons() {
         .stdout(predicate::str::contains("3").not());
 
     // Test >=
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("SELECT id FROM test_int WHERE value >= 20");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("SELECT id FROM test_int WHERE value >= 20");
     cmd.assert()
         .success()
         .stdout(predicate::str::contains("2"))
@@ -47,42 +52,88 @@ fn test_integer_comparisons() {
         .stdout(predicate::str::contains("1").not());
 
     // Test <=
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("SELECT id FROM test_int WHERE value <= 20");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("SELECT id FROM test_int WHERE value <= 20");
     cmd.assert()
         .success()
         .stdout(predicate::str::contains("1"))
         .stdout(predicate::str::contains("2"))
         .stdout(predicate::str::contains("3").not());
+
+    let _ = std::fs::remove_file(&db);
 }
 
 #[test]
 fn test_text_comparisons() {
-    let mut cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("CREATE TABLE test_text (id INTEGER, name TEXT)");
+    let db = temp_db("text_comparisons");
+    let mut cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("CREATE TABLE test_text (id INTEGER, name TEXT)");
     cmd.assert().success();
 
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("INSERT INTO test_text VALUES (1, 'apple')");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("INSERT INTO test_text VALUES (1, 'apple')");
     cmd.assert().success();
 
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("INSERT INTO test_text VALUES (2, 'banana')");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("INSERT INTO test_text VALUES (2, 'banana')");
     cmd.assert().success();
 
     // Test =
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("SELECT id FROM test_text WHERE name = 'apple'");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("SELECT id FROM test_text WHERE name = 'apple'");
     cmd.assert()
         .success()
         .stdout(predicate::str::contains("1"))
         .stdout(predicate::str::contains("2").not());
 
     // Test !=
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("SELECT id FROM test_text WHERE name != 'apple'");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("SELECT id FROM test_text WHERE name != 'apple'");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("2"))
+        .stdout(predicate::str::contains("1").not());
+
+    let _ = std::fs::remove_file(&db);
+}
+
+#[test]
+fn test_int_column_range_predicates() {
+    let db = temp_db("int_range_predicates");
+    let mut cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db")
+        .arg(&db)
+        .arg("CREATE TABLE readings (id INTEGER, value INT)")
+        .arg("INSERT INTO readings VALUES (1, 9)")
+        .arg("INSERT INTO readings VALUES (2, 15)")
+        .arg("INSERT INTO readings VALUES (3, 100)");
+    cmd.assert().success();
+
+    // Values are read back from integer-encoded storage in a new process, so
+    // "100" must compare above "15" numerically rather than below it as text
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("SELECT id FROM readings WHERE value > 9 AND value < 100");
     cmd.assert()
         .success()
         .stdout(predicate::str::contains("2"))
+        .stdout(predicate::str::contains("1").not())
+        .stdout(predicate::str::contains("3").not());
+
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("SELECT id FROM readings WHERE value >= 15");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("2"))
+        .stdout(predicate::str::contains("3"))
         .stdout(predicate::str::contains("1").not());
+
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("SELECT id FROM readings WHERE value <= 15");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("1"))
+        .stdout(predicate::str::contains("2"))
+        .stdout(predicate::str::contains("3").not());
+
+    let _ = std::fs::remove_file(&db);
 }
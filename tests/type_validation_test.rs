@@ -0,0 +1,53 @@
+use rust_dbms::engine::{validate_value, QueryEngine};
+use rust_dbms::execute_line;
+use rust_dbms::parser::{Parser, SelectItem};
+
+mod common;
+
+use common::temp_db;
+
+#[test]
+fn test_validate_value_per_type() {
+    assert!(validate_value(Some("42"), "INTEGER").is_ok());
+    assert!(validate_value(Some("4.2"), "INT").is_err());
+    assert!(validate_value(Some("-0.5"), "FLOAT").is_ok());
+    assert!(validate_value(Some("abc"), "FLOAT").is_err());
+    assert!(validate_value(Some("TRUE"), "BOOLEAN").is_ok());
+    assert!(validate_value(Some("yes"), "BOOLEAN").is_err());
+    assert!(validate_value(Some("anything"), "TEXT").is_ok());
+    assert!(validate_value(None, "INTEGER").is_ok());
+}
+
+#[test]
+fn test_insert_and_update_reject_invalid_values() {
+    let path = temp_db("type_validation");
+    let mut engine = QueryEngine::with_database(&path);
+    let parser = Parser::new();
+
+    execute_line("CREATE TABLE readings (id INTEGER, value FLOAT, valid BOOLEAN)", &mut engine, &parser);
+    assert_eq!(
+        execute_line("INSERT INTO readings VALUES (1, 2.5, true)", &mut engine, &parser),
+        "Inserted 1 row into 'readings'"
+    );
+
+    let rejected = [
+        ("INSERT INTO readings VALUES (2, 'high', true)", "'high' is not a valid FLOAT"),
+        ("INSERT INTO readings VALUES (3, 1.0, 'maybe')", "'maybe' is not a valid BOOLEAN"),
+        ("UPDATE readings SET value = 'low' WHERE id = 1", "'low' is not a valid FLOAT"),
+        ("UPDATE readings SET valid = 'nope'", "'nope' is not a valid BOOLEAN"),
+    ];
+    for (statement, message) in rejected {
+        let output = execute_line(statement, &mut engine, &parser);
+        assert!(output.starts_with("Error: Invalid value for column"), "{}", output);
+        assert!(output.ends_with(message), "{}", output);
+    }
+
+    // Nothing was changed by the rejected statements
+    let result = engine.execute_select("readings".to_string(), vec![SelectItem::Wildcard], None).unwrap();
+    assert_eq!(result.len(), 1);
+    let row = result.rows().next().unwrap();
+    assert_eq!(row.get_float("value"), Some(2.5));
+    assert_eq!(row.get("valid"), Some("true"));
+
+    let _ = std::fs::remove_file(&path);
+}
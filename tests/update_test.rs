@@ -0,0 +1,100 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{Command, Parser, WhereClause, WhereExpr};
+use rust_dbms::storage::MEMORY_PATH;
+
+#[test]
+fn test_set_list_is_parsed_into_assignments() {
+    let parser = Parser::new();
+    assert_eq!(
+        parser.parse("UPDATE t SET a = 1, b = 'x, y = z', c = NULL WHERE id = 3"),
+        Command::Update {
+            table: "t".to_string(),
+            assignments: vec![
+                ("a".to_string(), Some("1".to_string())),
+                ("b".to_string(), Some("x, y = z".to_string())),
+                ("c".to_string(), None),
+            ],
+            where_clause: Some(WhereExpr::Condition(WhereClause {
+                column: "id".to_string(),
+                operator: "=".to_string(),
+                value: Some("3".to_string()),
+                quoted: false,
+            })),
+            limit: None,
+        }
+    );
+}
+
+#[test]
+fn test_update_two_columns() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+
+    execute_line("CREATE TABLE users (id INTEGER, name TEXT, age INTEGER)", &mut engine, &parser);
+    execute_line("INSERT INTO users VALUES (1, 'Alice', 30)", &mut engine, &parser);
+    execute_line("INSERT INTO users VALUES (2, 'Bob', 40)", &mut engine, &parser);
+
+    let output = execute_line("UPDATE users SET name = 'Alicia', age = 31 WHERE id = 1", &mut engine, &parser);
+    assert_eq!(output, "Updated 1 rows in 'users'");
+
+    let output = execute_line("SELECT name, age FROM users", &mut engine, &parser);
+    assert_eq!(output, "name | age\n----------\nAlicia | 31\nBob | 40");
+}
+
+#[test]
+fn test_invalid_assignment_leaves_rows_unchanged() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+
+    execute_line("CREATE TABLE users (id INTEGER, name TEXT, age INTEGER)", &mut engine, &parser);
+    execute_line("INSERT INTO users VALUES (1, 'Alice', 30)", &mut engine, &parser);
+
+    let output = execute_line("UPDATE users SET name = 'Alicia', age = 'old'", &mut engine, &parser);
+    assert!(output.starts_with("Error: Invalid value for column"), "{}", output);
+
+    let output = execute_line("UPDATE users SET age = 1, age = 2", &mut engine, &parser);
+    assert!(output.contains("assigned more than once"), "{}", output);
+
+    let output = execute_line("SELECT name, age FROM users", &mut engine, &parser);
+    assert_eq!(output, "name | age\n----------\nAlice | 30");
+}
+
+#[test]
+fn test_update_limit_caps_affected_rows() {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+
+    execute_line("CREATE TABLE jobs (id INTEGER, status TEXT)", &mut engine, &parser);
+    for id in 1..=5 {
+        execute_line(&format!("INSERT INTO jobs VALUES ({}, 'old')", id), &mut engine, &parser);
+    }
+
+    let output = execute_line("UPDATE jobs SET status = 'done' WHERE status = 'old' LIMIT 2", &mut engine, &parser);
+    assert_eq!(output, "Updated 2 rows in 'jobs'");
+
+    // Fewer matches than the limit reports the actual count
+    let output = execute_line("UPDATE jobs SET status = 'done' WHERE status = 'old' LIMIT 10", &mut engine, &parser);
+    assert_eq!(output, "Updated 3 rows in 'jobs'");
+
+    let output = execute_line("UPDATE jobs SET status = 'new' LIMIT 0", &mut engine, &parser);
+    assert_eq!(output, "Updated 0 rows in 'jobs'");
+}
+
+#[test]
+fn test_update_limit_parsing() {
+    let parser = Parser::new();
+
+    match parser.parse("UPDATE t SET note = 'no LIMIT 5'") {
+        Command::Update { assignments, limit, .. } => {
+            assert_eq!(assignments, vec![("note".to_string(), Some("no LIMIT 5".to_string()))]);
+            assert_eq!(limit, None);
+        }
+        other => panic!("unexpected command: {:?}", other),
+    }
+
+    match parser.parse("UPDATE t SET a = 1 LIMIT -1") {
+        Command::Unknown(message) => assert!(message.starts_with("Invalid LIMIT"), "{}", message),
+        other => panic!("unexpected command: {:?}", other),
+    }
+}
@@ -0,0 +1,95 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{parse_to_ast, Command, Parser};
+use std::cell::Cell;
+use std::rc::Rc;
+
+mod common;
+
+use common::temp_db;
+
+fn engine_with_items(path: &str) -> (QueryEngine, Parser) {
+    let mut engine = QueryEngine::with_database(path);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE items (id INTEGER NOT NULL, name TEXT)", &mut engine, &parser);
+    execute_line("INSERT INTO items VALUES (1, 'tea')", &mut engine, &parser);
+    (engine, parser)
+}
+
+#[test]
+fn test_parse_validate() {
+    match parse_to_ast("validate INSERT INTO items VALUES (1, 'x');") {
+        Command::Validate(command) => assert!(matches!(*command, Command::Insert { .. })),
+        other => panic!("expected VALIDATE, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_invalid_insert_is_reported_and_changes_nothing() {
+    let path = temp_db("validate_insert");
+    let (mut engine, parser) = engine_with_items(&path);
+    let file_before = std::fs::read(&path).unwrap();
+
+    let output = execute_line("VALIDATE INSERT INTO items VALUES ('x', 'cake')", &mut engine, &parser);
+    assert_eq!(output, "Error: Invalid value for column 'id': 'x' is not a valid INTEGER");
+    let output = execute_line("VALIDATE INSERT INTO items (name) VALUES ('cake')", &mut engine, &parser);
+    assert!(output.starts_with("Error:") && output.contains("id"), "{}", output);
+    assert_eq!(execute_line("VALIDATE INSERT INTO items VALUES (2, 'cake')", &mut engine, &parser), "Statement is valid");
+
+    assert_eq!(execute_line("SELECT * FROM items", &mut engine, &parser), "id | name\n---------\n1 | tea");
+    assert_eq!(std::fs::read(&path).unwrap(), file_before);
+    // The rowid a validated insert took is handed out again
+    execute_line("INSERT INTO items VALUES (2, 'cake')", &mut engine, &parser);
+    assert_eq!(execute_line("SELECT _rowid FROM items WHERE id = 2", &mut engine, &parser), "_rowid\n------\n2");
+}
+
+#[test]
+fn test_validated_schema_changes_are_not_applied() {
+    let path = temp_db("validate_schema");
+    let (mut engine, parser) = engine_with_items(&path);
+    let changes = Rc::new(Cell::new(0));
+    let counter = Rc::clone(&changes);
+    engine.set_on_change(Box::new(move |_| counter.set(counter.get() + 1)));
+
+    assert_eq!(execute_line("VALIDATE CREATE TABLE other (id INTEGER)", &mut engine, &parser), "Statement is valid");
+    assert_eq!(
+        execute_line("VALIDATE CREATE TABLE items (id INTEGER)", &mut engine, &parser),
+        "Error: Table 'items' already exists"
+    );
+    assert_eq!(execute_line("VALIDATE DROP TABLE items", &mut engine, &parser), "Statement is valid");
+    assert_eq!(execute_line("VALIDATE DELETE FROM items", &mut engine, &parser), "Statement is valid");
+    assert_eq!(
+        execute_line("VALIDATE UPDATE items SET price = 1", &mut engine, &parser),
+        "Error: Column 'price' not found in table 'items'"
+    );
+
+    assert!(engine.get_table_schema("other").is_none());
+    assert_eq!(execute_line("SELECT * FROM items", &mut engine, &parser), "id | name\n---------\n1 | tea");
+    assert_eq!(changes.get(), 0);
+    drop(engine);
+
+    let mut engine = QueryEngine::with_database(&path);
+    assert!(engine.get_table_schema("other").is_none());
+    assert_eq!(execute_line("SELECT * FROM items", &mut engine, &parser), "id | name\n---------\n1 | tea");
+}
+
+#[test]
+fn test_statements_with_outside_effects_are_refused() {
+    let path = temp_db("validate_refused");
+    let (mut engine, parser) = engine_with_items(&path);
+    let refused = "Error: VALIDATE only checks statements that create, read or change tables";
+    assert_eq!(execute_line("VALIDATE EXPORT items TO 'items.csv'", &mut engine, &parser), refused);
+    assert_eq!(execute_line("VALIDATE PRAGMA null_string = '-'", &mut engine, &parser), refused);
+    assert!(execute_line("VALIDATE FROB items", &mut engine, &parser).starts_with("Unknown command: FROB items"));
+}
+
+#[test]
+fn test_validate_inside_a_transaction_fails() {
+    let path = temp_db("validate_transaction");
+    let (mut engine, parser) = engine_with_items(&path);
+    let mut tx = engine.begin().unwrap();
+    assert_eq!(
+        execute_line("VALIDATE INSERT INTO items VALUES (2, 'cake')", &mut tx, &parser),
+        "Error: A transaction is already open"
+    );
+}
@@ -0,0 +1,48 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use rust_dbms::database::DB_VERSION;
+
+mod common;
+
+use common::temp_db;
+
+#[test]
+fn test_version_flag() {
+    cargo_bin_cmd!("isenta")
+        .arg("--version")
+        .assert()
+        .success()
+        .stdout(format!("IsentaDB v{}\nDatabase file format version {}\n", env!("CARGO_PKG_VERSION"), DB_VERSION));
+}
+
+#[test]
+fn test_info_reports_the_file_header() {
+    let db = temp_db("info_command");
+    cargo_bin_cmd!("isenta")
+        .arg("--db")
+        .arg(&db)
+        .arg("CREATE TABLE a (id INTEGER)")
+        .arg("CREATE TABLE b (id INTEGER)")
+        .assert()
+        .success();
+
+    // Header, plus a schema and a data page per table
+    cargo_bin_cmd!("isenta").arg("info").arg(&db).assert().success().stdout(format!(
+        "File: {}\nMagic number: 0x4953454E54414442\nFormat version: {}\nTables: 2\nPages: 5\n",
+        db, DB_VERSION
+    ));
+
+    let _ = std::fs::remove_file(&db);
+}
+
+#[test]
+fn test_info_does_not_create_a_missing_file() {
+    let db = temp_db("info_missing");
+    cargo_bin_cmd!("isenta")
+        .arg("info")
+        .arg(&db)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is not a database file"));
+    assert!(!std::path::Path::new(&db).exists());
+}
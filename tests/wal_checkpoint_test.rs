@@ -0,0 +1,137 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::error::IsentaError;
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+use rust_dbms::storage::{Durability, Page, StorageEngine, PAGE_SIZE};
+use rust_dbms::wal::{WalRecord, WriteAheadLog};
+use std::time::Duration;
+
+mod common;
+
+fn temp_wal(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("isenta_{}_{}.wal", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path.to_string_lossy().into_owned()
+}
+
+fn record(page_id: u64) -> WalRecord {
+    WalRecord { page_id, offset: 0, length: 4, data: vec![1, 2, 3, 4] }
+}
+
+#[test]
+fn test_checkpoints_keep_the_wal_bounded() {
+    let path = temp_wal("wal_bounded");
+    let mut wal = WriteAheadLog::new(&path);
+    for page_id in 0..1000 {
+        wal.append(&record(page_id)).unwrap();
+        if wal.size().unwrap() >= 4096 {
+            wal.checkpoint(|| Ok(())).unwrap();
+        }
+        assert!(wal.size().unwrap() < 4096);
+    }
+    // Records appended after a checkpoint are still read back
+    wal.checkpoint(|| Ok(())).unwrap();
+    wal.append(&record(7)).unwrap();
+    assert_eq!(WriteAheadLog::new(&path).read_all().iter().map(|r| r.page_id).collect::<Vec<_>>(), vec![7]);
+}
+
+#[test]
+fn test_the_wal_is_kept_when_the_database_sync_fails() {
+    let path = temp_wal("wal_sync_failed");
+    let mut wal = WriteAheadLog::new(&path);
+    wal.append(&record(1)).unwrap();
+    wal.append(&record(2)).unwrap();
+
+    let result = wal.checkpoint(|| Err(IsentaError::Io("disk full".to_string())));
+    assert_eq!(result, Err(IsentaError::Io("disk full".to_string())));
+    assert_eq!(WriteAheadLog::new(&path).read_all().len(), 2);
+}
+
+fn temp_db(name: &str) -> String {
+    let path = common::temp_db(name);
+    let _ = std::fs::remove_file(format!("{}-wal", path));
+    path
+}
+
+fn wal_len(db_path: &str) -> u64 {
+    std::fs::metadata(format!("{}-wal", db_path)).map_or(0, |metadata| metadata.len())
+}
+
+#[test]
+fn test_writes_checkpoint_the_wal_once_it_is_large() {
+    let path = temp_db("wal_autocheckpoint");
+    let parser = Parser::new();
+    let mut engine = QueryEngine::with_database(&path);
+    engine.set_durability(Durability::Wal).unwrap();
+    assert_eq!(execute_line("PRAGMA wal_autocheckpoint = 8", &mut engine, &parser), "wal_autocheckpoint = 8");
+
+    execute_line("CREATE TABLE log (id INTEGER, message TEXT)", &mut engine, &parser);
+    let limit = 8 * (24 + PAGE_SIZE as u64);
+    let mut largest = 0;
+    for i in 0..300 {
+        execute_line(&format!("INSERT INTO log VALUES ({}, 'entry {}')", i, i), &mut engine, &parser);
+        largest = largest.max(wal_len(&path));
+        assert!(wal_len(&path) < limit, "WAL grew to {} bytes", wal_len(&path));
+    }
+    assert!(largest > 0);
+
+    // Closing checkpoints the rest and removes the log
+    engine.close().unwrap();
+    assert!(!std::path::Path::new(&format!("{}-wal", path)).exists());
+    let mut engine = QueryEngine::with_database(&path);
+    assert_eq!(execute_line("SELECT COUNT(*) FROM log", &mut engine, &parser), "count\n-----\n300");
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_writes_checkpoint_the_wal_once_the_interval_passes() {
+    let path = temp_db("wal_interval");
+    let mut storage = StorageEngine::new(&path).unwrap();
+    storage.set_durability(Durability::Wal).unwrap();
+    storage.set_wal_autocheckpoint(None);
+
+    storage.write_page(&Page::new(0)).unwrap();
+    assert_eq!(wal_len(&path), 24 + PAGE_SIZE as u64);
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), 0);
+
+    storage.set_wal_checkpoint_interval(Some(Duration::ZERO));
+    storage.write_page(&Page::new(1)).unwrap();
+    assert_eq!(wal_len(&path), 0);
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), 2 * PAGE_SIZE as u64);
+
+    drop(storage);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_the_wal_is_replayed_after_a_crash() {
+    let path = temp_db("wal_replay");
+    let parser = Parser::new();
+    let mut engine = QueryEngine::with_database(&path);
+    engine.set_durability(Durability::Wal).unwrap();
+    engine.set_wal_autocheckpoint(None);
+    execute_line("CREATE TABLE log (id INTEGER)", &mut engine, &parser);
+    for i in 1..=12 {
+        execute_line(&format!("INSERT INTO log VALUES ({})", i), &mut engine, &parser);
+    }
+    // Simulate a crash: the engine never gets to checkpoint its log
+    std::mem::forget(engine);
+
+    let mut engine = QueryEngine::with_database(&path);
+    assert_eq!(execute_line("SELECT COUNT(*) FROM log", &mut engine, &parser), "count\n-----\n12");
+    assert_eq!(wal_len(&path), 0);
+    drop(engine);
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(format!("{}-wal", path));
+}
+
+#[test]
+fn test_wal_pragma_values() {
+    let mut engine = QueryEngine::with_database(":memory:");
+    assert_eq!(engine.pragma("wal_autocheckpoint", None).unwrap(), "1000");
+    assert_eq!(engine.pragma("wal_autocheckpoint", Some("off")).unwrap(), "off");
+    assert!(engine.pragma("wal_autocheckpoint", Some("big")).is_err());
+    assert_eq!(engine.pragma("wal_checkpoint_interval", None).unwrap(), "off");
+    assert_eq!(engine.pragma("wal_checkpoint_interval", Some("30")).unwrap(), "30");
+    assert_eq!(engine.wal_checkpoint_interval(), Some(Duration::from_secs(30)));
+}
@@ -1,62 +1,72 @@
-use assert_cmd::Command;
+use assert_cmd::cargo::cargo_bin_cmd;
 use predicates::prelude::*;
 
+mod common;
+
+use common::temp_db;
+
 #[test]
 fn test_select_with_not_equals_clause() {
-    let mut cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("CREATE TABLE test_neq (id INT, name TEXT)");
+    let db = temp_db("select_with_not_equals_clause");
+    let mut cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("CREATE TABLE test_neq (id INT, name TEXT)");
     cmd.assert().success();
 
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("INSERT INTO test_neq VALUES (1, 'Alice')");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("INSERT INTO test_neq VALUES (1, 'Alice')");
     cmd.assert().success();
 
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("INSERT INTO test_neq VALUES (2, 'Bob')");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("INSERT INTO test_neq VALUES (2, 'Bob')");
     cmd.assert().success();
 
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("INSERT INTO test_neq VALUES (3, 'Charlie')");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("INSERT INTO test_neq VALUES (3, 'Charlie')");
     cmd.assert().success();
 
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("SELECT * FROM test_neq WHERE name != 'Bob'");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("SELECT * FROM test_neq WHERE name != 'Bob'");
     cmd.assert()
         .success()
         .stdout(predicate::str::contains("1 | Alice"))
         .stdout(predicate::str::contains("3 | Charlie"))
         .stdout(predicate::str::contains("Bob").not());
+
+    let _ = std::fs::remove_file(&db);
 }
 
 #[test]
 fn test_update_with_not_equals_clause() {
-    let mut cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("CREATE TABLE test_update_neq (id INT, name TEXT)");
+    let db = temp_db("update_with_not_equals_clause");
+    let mut cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("CREATE TABLE test_update_neq (id INT, name TEXT)");
     cmd.assert().success();
 
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("INSERT INTO test_update_neq VALUES (1, 'One')");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("INSERT INTO test_update_neq VALUES (1, 'One')");
     cmd.assert().success();
 
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("INSERT INTO test_update_neq VALUES (2, 'Two')");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("INSERT INTO test_update_neq VALUES (2, 'Two')");
     cmd.assert().success();
 
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("UPDATE test_update_neq SET name = 'Changed' WHERE id != 2");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("UPDATE test_update_neq SET name = 'Changed' WHERE id != 2");
     cmd.assert()
         .success()
         .stdout(predicate::str::contains("Updated 1 rows in 'test_update_neq'"));
 
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("SELECT name FROM test_update_neq WHERE id = 1");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("SELECT name FROM test_update_neq WHERE id = 1");
     cmd.assert()
         .success()
         .stdout(predicate::str::contains("Changed"));
     
-    cmd = Command::cargo_bin("isenta").unwrap();
-    cmd.arg("SELECT name FROM test_update_neq WHERE id = 2");
+    cmd = cargo_bin_cmd!("isenta");
+    cmd.arg("--db").arg(&db).arg("SELECT name FROM test_update_neq WHERE id = 2");
     cmd.assert()
         .success()
         .stdout(predicate::str::contains("Two"));
+
+    let _ = std::fs::remove_file(&db);
 }
@@ -0,0 +1,141 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::{parse_to_ast, Command, Parser, SelectItem, WhereClause, WhereExpr};
+use rust_dbms::storage::MEMORY_PATH;
+
+fn condition(column: &str, operator: &str, value: &str, quoted: bool) -> Box<WhereExpr> {
+    Box::new(WhereExpr::Condition(WhereClause {
+        column: column.to_string(),
+        operator: operator.to_string(),
+        value: Some(value.to_string()),
+        quoted,
+    }))
+}
+
+fn where_of(command: Command) -> Option<WhereExpr> {
+    match command {
+        Command::Select { where_clause, .. } | Command::Update { where_clause, .. } => where_clause,
+        other => panic!("unexpected command: {:?}", other),
+    }
+}
+
+/// A table of ids 1 to 6, with city 'Oslo' for even ids and 'Rome' for odd ones.
+fn engine_with_people() -> (QueryEngine, Parser) {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE people (id INTEGER, city TEXT, seen INTEGER)", &mut engine, &parser);
+    for id in 1..=6 {
+        let city = if id % 2 == 0 { "Oslo" } else { "Rome" };
+        execute_line(&format!("INSERT INTO people VALUES ({}, '{}', 0)", id, city), &mut engine, &parser);
+    }
+    (engine, parser)
+}
+
+fn selected_ids(engine: &QueryEngine, where_clause: Option<WhereExpr>) -> Vec<i64> {
+    let result = engine.execute_select("people".to_string(), vec![SelectItem::Wildcard], where_clause).unwrap();
+    result.rows().map(|row| row.get_int("id").unwrap()).collect()
+}
+
+#[test]
+fn test_and_binds_tighter_than_or() {
+    let parser = Parser::new();
+    assert_eq!(
+        where_of(parser.parse("SELECT * FROM t WHERE a = 1 OR b = 2 AND c = 3")),
+        Some(WhereExpr::Or(
+            condition("a", "=", "1", false),
+            Box::new(WhereExpr::And(condition("b", "=", "2", false), condition("c", "=", "3", false))),
+        ))
+    );
+}
+
+#[test]
+fn test_parentheses_group_conditions() {
+    let parser = Parser::new();
+    assert_eq!(
+        where_of(parser.parse("UPDATE t SET x = 1 WHERE (a = 1 or b = 2) and c = 3")),
+        Some(WhereExpr::And(
+            Box::new(WhereExpr::Or(condition("a", "=", "1", false), condition("b", "=", "2", false))),
+            condition("c", "=", "3", false),
+        ))
+    );
+}
+
+#[test]
+fn test_keywords_inside_quotes_are_part_of_the_value() {
+    let parser = Parser::new();
+    assert_eq!(
+        where_of(parser.parse("SELECT * FROM t WHERE name = 'salt and pepper'")),
+        Some(*condition("name", "=", "salt and pepper", true))
+    );
+}
+
+#[test]
+fn test_compound_where_matches_the_same_rows_in_select_update_and_delete() {
+    let (mut engine, parser) = engine_with_people();
+    let where_sql = "(city = 'Oslo' AND id > 2) OR id = 1";
+
+    let expr = where_of(parser.parse(&format!("SELECT * FROM people WHERE {}", where_sql)));
+    assert_eq!(selected_ids(&engine, expr), vec![1, 4, 6]);
+
+    let output = execute_line(&format!("UPDATE people SET seen = 1 WHERE {}", where_sql), &mut engine, &parser);
+    assert!(output.contains("Updated 3 rows"), "{}", output);
+
+    let expr = where_of(parser.parse("SELECT * FROM people WHERE seen = 1"));
+    assert_eq!(selected_ids(&engine, expr), vec![1, 4, 6]);
+
+    let output = execute_line(&format!("DELETE FROM people WHERE {}", where_sql), &mut engine, &parser);
+    assert_eq!(output, "Deleted 3 rows from 'people'");
+    assert_eq!(selected_ids(&engine, None), vec![2, 3, 5]);
+}
+
+#[test]
+fn test_every_condition_is_validated() {
+    let (mut engine, parser) = engine_with_people();
+
+    let output = execute_line("SELECT * FROM people WHERE id = 1 OR missing = 2", &mut engine, &parser);
+    assert!(output.contains("Column 'missing' not found"), "{}", output);
+
+    let output = execute_line("UPDATE people SET seen = 1 WHERE city = 'Oslo' AND id = 'abc'", &mut engine, &parser);
+    assert!(output.contains("cannot compare INTEGER column 'id'"), "{}", output);
+}
+
+#[test]
+fn test_operators_inside_quotes_are_part_of_the_value() {
+    let expr = where_of(parse_to_ast("SELECT * FROM notes WHERE note = 'a>b'"));
+    assert_eq!(expr, Some(*condition("note", "=", "a>b", true)));
+    let expr = where_of(parse_to_ast("SELECT * FROM notes WHERE note != 'x <= y'"));
+    assert_eq!(expr, Some(*condition("note", "!=", "x <= y", true)));
+    let expr = where_of(parse_to_ast("SELECT * FROM notes WHERE note = 'we like it'"));
+    assert_eq!(expr, Some(*condition("note", "=", "we like it", true)));
+
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE notes (id INTEGER, note TEXT)", &mut engine, &parser);
+    execute_line("INSERT INTO notes VALUES (1, 'a>b')", &mut engine, &parser);
+    execute_line("INSERT INTO notes VALUES (2, 'b')", &mut engine, &parser);
+    let output = execute_line("SELECT id FROM notes WHERE note = 'a>b'", &mut engine, &parser);
+    assert_eq!(output, "id\n--\n1");
+}
+
+#[test]
+fn test_values_with_trailing_tokens_are_rejected() {
+    let (mut engine, parser) = engine_with_people();
+    for statement in [
+        "DELETE FROM people WHERE city != 'Rome' garbage",
+        "DELETE FROM people WHERE city = 'Rome' 'Oslo'",
+        "DELETE FROM people WHERE id > 1 2",
+        "UPDATE people SET seen = 1 WHERE city = 'Rome' LIMIT",
+        "SELECT * FROM people WHERE city = 'Rome",
+    ] {
+        match parse_to_ast(statement) {
+            Command::Unknown(message) => assert!(message.starts_with("Invalid WHERE clause: "), "{}", message),
+            other => panic!("expected an invalid WHERE clause for {}, got {:?}", statement, other),
+        }
+    }
+    let output = execute_line("DELETE FROM people WHERE city != 'Rome' garbage", &mut engine, &parser);
+    assert!(output.starts_with("Unknown command: Invalid WHERE clause: city != 'Rome' garbage"), "{}", output);
+    assert_eq!(selected_ids(&engine, None), vec![1, 2, 3, 4, 5, 6]);
+
+    // Escaped quotes and spaces inside a literal are still one value
+    assert!(matches!(parse_to_ast(r"DELETE FROM people WHERE city = 'it\'s a city'"), Command::Delete { where_clause: Some(_), .. }));
+}
@@ -0,0 +1,45 @@
+use rust_dbms::engine::QueryEngine;
+use rust_dbms::execute_line;
+use rust_dbms::parser::Parser;
+use rust_dbms::storage::MEMORY_PATH;
+
+fn engine_with_people() -> (QueryEngine, Parser) {
+    let mut engine = QueryEngine::with_database(MEMORY_PATH);
+    let parser = Parser::new();
+    execute_line("CREATE TABLE people (name TEXT, age INTEGER)", &mut engine, &parser);
+    execute_line("INSERT INTO people VALUES ('Ann', 30)", &mut engine, &parser);
+    execute_line("INSERT INTO people VALUES ('42', 42)", &mut engine, &parser);
+    (engine, parser)
+}
+
+#[test]
+fn test_integer_column_compared_with_text() {
+    let (mut engine, parser) = engine_with_people();
+
+    let output = execute_line("SELECT * FROM people WHERE age = 'abc'", &mut engine, &parser);
+    assert_eq!(output, "Error: cannot compare INTEGER column 'age' with text value 'abc'");
+
+    let output = execute_line("UPDATE people SET name = 'x' WHERE age > abc", &mut engine, &parser);
+    assert_eq!(output, "Error: cannot compare INTEGER column 'age' with text value 'abc'");
+
+    // A quoted number is still a valid integer
+    let output = execute_line("SELECT name FROM people WHERE age = '30'", &mut engine, &parser);
+    assert_eq!(output, "name\n----\nAnn");
+}
+
+#[test]
+fn test_text_column_compared_with_number() {
+    let (mut engine, parser) = engine_with_people();
+
+    // Any literal is valid text, so an unquoted number compares as text
+    let output = execute_line("SELECT age FROM people WHERE name = 42", &mut engine, &parser);
+    assert_eq!(output, "age\n---\n42");
+    let output = execute_line("SELECT age FROM people WHERE name != 4.5", &mut engine, &parser);
+    assert_eq!(output, "age\n---\n30\n42");
+
+    // Quoting compares the same way, and patterns are always text
+    let output = execute_line("SELECT age FROM people WHERE name = '42'", &mut engine, &parser);
+    assert_eq!(output, "age\n---\n42");
+    let output = execute_line("SELECT age FROM people WHERE name LIKE 4%", &mut engine, &parser);
+    assert_eq!(output, "age\n---\n42");
+}